@@ -2,7 +2,17 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use clap::builder::BoolishValueParser;
 use clap::{Args, Subcommand};
-use serde::Serialize;
+use elf_notes::{
+    ELFOSABI_GNU, ELFOSABI_SYSV, EM_AARCH64, EM_X86_64, KernelVersion, StripReport,
+    abi_tag_os_name, elf_abi_info, elf64_le_machine, osabi_name, resolve_objcopy_path,
+    strip_gnu_property_notes_under,
+};
+use runner_util::{
+    BinfmtEntry, FEX_BINFMT_PATH, MountEntry, hash_file, is_sensitive_env_key, mount_for_path,
+    parse_binfmt_entry, parse_mountinfo, signal_name,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::ffi::OsString;
 use std::fs::File;
 use std::io::Read;
@@ -11,11 +21,15 @@ use std::os::fd::{FromRawFd, IntoRawFd, OwnedFd, RawFd};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Duration;
 
 #[cfg(feature = "squashfs-ng")]
 use std::collections::HashMap;
 
+mod squashfs;
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None, subcommand_precedence_over_arg = true)]
 struct Cli {
@@ -34,6 +48,26 @@ enum Commands {
     /// Run probes inside the guest (evidence-first)
     Probe(ProbeArgs),
 
+    /// Re-run a previous `run` from its `inputs.json` (evidence-first)
+    Replay(ReplayArgs),
+
+    /// Extract (and optionally strip) an AppImage into the cache, then print its
+    /// `squashfs-root` path on stdout
+    ExtractOnly(ExtractOnlyArgs),
+
+    /// Inventory the evidence artifacts in an out dir and flag missing ones
+    ListArtifacts(ListArtifactsArgs),
+
+    /// List every section (name, type, size) of an ELF64 file, e.g. one FEX still rejects
+    ElfSections(ElfSectionsArgs),
+
+    /// Print the embedded SquashFS superblock (version, compression, sizes) without extracting
+    SquashfsInfo(SquashfsInfoArgs),
+
+    /// Print the JSON Schema of the JSON artifacts this tool writes, for validating them in CI
+    #[command(hide = true)]
+    EmitSchema(EmitSchemaArgs),
+
     /// Internal: host-side PC/SC bridge (vsock -> pcscd unix socket)
     #[command(hide = true)]
     PcscHost(PcscHostArgs),
@@ -62,6 +96,11 @@ struct CommonGuestOpts {
     #[arg(long, default_value = "auto", value_enum)]
     fex_profile: FexProfile,
 
+    /// Directory of FEX host thunk libraries (`*-host.so`), passed to the guest as
+    /// `FEX_THUNKHOSTLIBS`. Reports list the libraries found there as `fex_thunks`.
+    #[arg(long)]
+    fex_thunk_dir: Option<PathBuf>,
+
     /// Path to the muvm binary to execute.
     #[arg(long, default_value = "muvm")]
     muvm_path: PathBuf,
@@ -86,6 +125,19 @@ struct CommonGuestOpts {
     #[arg(long)]
     guest_pre: Option<String>,
 
+    /// Guest directory to run the AppImage (or probe) from, created if missing.
+    ///
+    /// Without it the guest inherits whatever cwd muvm starts in.
+    #[arg(long, value_name = "PATH")]
+    guest_cwd: Option<String>,
+
+    /// Trace the guest prelude (`--guest-pre` and the pcsc bridge setup) with `set -x`.
+    ///
+    /// The xtrace goes to `guest-pre.log` in the out dir, separate from the app's output,
+    /// so a prelude line that aborts the run can be pinpointed. Not available in legacy mode.
+    #[arg(long, default_value_t = false)]
+    guest_shell_trace: bool,
+
     /// Enable a best-effort PC/SC bridge so x86_64 apps can talk to host pcscd without USB passthrough.
     ///
     /// This sets `PCSCLITE_CSOCK_NAME` inside the guest and spawns a guest-side unix socket proxy
@@ -106,6 +158,204 @@ struct CommonGuestOpts {
     /// We default to a user-writable location so this works without `--privileged`.
     #[arg(long, default_value = "/tmp/pcscd.comm")]
     pcsc_guest_socket: PathBuf,
+
+    /// Have the guest side of the PC/SC bridge log per-connection throughput (bytes/sec each
+    /// direction) every N seconds, to tell a slow bridge from a slow card.
+    ///
+    /// Lines go to the bridge log (`/tmp/pcsc-guest.log` in the guest), next to the byte
+    /// totals each connection logs when it closes.
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "pcsc_bridge",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pcsc_throughput_interval_seconds: Option<u64>,
+
+    /// Rows of the PTY muvm runs under (also exported as `LINES` to muvm).
+    #[arg(long, default_value_t = 24)]
+    pty_rows: u16,
+
+    /// Columns of the PTY muvm runs under (also exported as `COLUMNS` to muvm).
+    #[arg(long, default_value_t = 120)]
+    pty_cols: u16,
+
+    /// Host environment muvm is started with.
+    ///
+    /// - `inherit`: the ambient environment of this process.
+    /// - `clean`: only a curated allowlist (PATH, HOME, display/session vars, locale).
+    ///
+    /// Guest variables still come from `--env` either way.
+    #[arg(long, default_value = "inherit", value_enum)]
+    host_env: HostEnvPolicy,
+
+    /// Host variable to forward to muvm (repeatable); added to the `--host-env=clean`
+    /// allowlist, or the only ones forwarded with `--clear-env`.
+    #[arg(long, value_name = "KEY")]
+    env_passthrough: Vec<String>,
+
+    /// Start muvm from an empty environment plus `--env-passthrough` (and `--env` for the
+    /// guest), so the guest env doesn't depend on the host session.
+    #[arg(long, default_value_t = false, conflicts_with = "host_env")]
+    clear_env: bool,
+
+    /// Env key whose value is written as `***` in the out dir's artifacts (repeatable), on
+    /// top of keys containing TOKEN, SECRET, PASSWORD or KEY. The guest still gets the value.
+    #[arg(long, value_name = "KEY")]
+    redact: Vec<String>,
+
+    /// When `--timeout-seconds` fires, screenshot the guest display (grim, import or xwd,
+    /// whichever the guest has) into the out dir before killing muvm.
+    #[arg(long, default_value_t = false)]
+    capture_screenshot_on_timeout: bool,
+
+    /// Copy the guest's `/proc/self/mountinfo` into `guest-mountinfo.txt` in the out dir
+    /// before anything else runs, and report which filesystems back the extract/out dirs.
+    #[arg(long, default_value_t = false)]
+    dump_guest_mounts: bool,
+}
+
+impl CommonGuestOpts {
+    fn pty_settings(&self) -> PtySettings {
+        PtySettings {
+            rows: self.pty_rows,
+            cols: self.pty_cols,
+            host_env: if self.clear_env {
+                HostEnvPolicy::Empty
+            } else {
+                self.host_env
+            },
+            env_passthrough: self.env_passthrough.clone(),
+            stdin: None,
+            screenshot_on_timeout: self.capture_screenshot_on_timeout,
+            screenshot_dir: None,
+        }
+    }
+}
+
+#[derive(
+    clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+enum HostEnvPolicy {
+    Inherit,
+    Clean,
+    /// Only `--env-passthrough`; set by `--clear-env`.
+    #[value(skip)]
+    Empty,
+}
+
+/// Host variables kept by `--host-env=clean`; muvm needs these to reach the display and session.
+const CLEAN_HOST_ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "LC_ALL",
+    "XDG_RUNTIME_DIR",
+    "WAYLAND_DISPLAY",
+    "DISPLAY",
+    "XAUTHORITY",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "PULSE_SERVER",
+];
+
+/// How muvm is spawned under the capture PTY.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+struct PtySettings {
+    rows: u16,
+    cols: u16,
+    host_env: HostEnvPolicy,
+    #[serde(default)]
+    env_passthrough: Vec<String>,
+    /// Input written to the PTY after spawning (`--stdin-file` / `--stdin-string`).
+    #[serde(default)]
+    stdin: Option<PtyStdin>,
+    #[serde(default)]
+    screenshot_on_timeout: bool,
+    /// Out dir for the timeout screenshot; set per run when `screenshot_on_timeout`.
+    #[serde(skip)]
+    screenshot_dir: Option<PathBuf>,
+}
+
+impl Default for PtySettings {
+    fn default() -> Self {
+        Self {
+            rows: 24,
+            cols: 120,
+            host_env: HostEnvPolicy::Inherit,
+            env_passthrough: Vec::new(),
+            stdin: None,
+            screenshot_on_timeout: false,
+            screenshot_dir: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum StdinSource {
+    File(PathBuf),
+    String(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+struct PtyStdin {
+    source: StdinSource,
+    /// Send EOF (newline + VEOF) once the input is written.
+    eof: bool,
+}
+
+impl PtyStdin {
+    fn bytes(&self) -> Result<Vec<u8>> {
+        match &self.source {
+            StdinSource::File(path) => {
+                std::fs::read(path).with_context(|| format!("read stdin file {}", path.display()))
+            }
+            StdinSource::String(s) => Ok(s.as_bytes().to_vec()),
+        }
+    }
+}
+
+/// What happened to `--stdin-file` / `--stdin-string` input.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+struct StdinReport {
+    bytes: u64,
+    /// All bytes were written to the PTY.
+    fed: bool,
+    eof_sent: bool,
+    error: Option<String>,
+}
+
+impl PtySettings {
+    /// These settings with the timeout screenshot (if enabled) going to `out_dir`.
+    fn with_out_dir(mut self, out_dir: &Path) -> Self {
+        if self.screenshot_on_timeout {
+            self.screenshot_dir = Some(out_dir.to_path_buf());
+        }
+        self
+    }
+
+    /// Host variables muvm is started with, or `None` when it inherits everything.
+    fn host_env_passthrough(&self) -> Option<Vec<String>> {
+        let allowlist: &[&str] = match self.host_env {
+            HostEnvPolicy::Inherit => return None,
+            HostEnvPolicy::Clean => CLEAN_HOST_ENV_ALLOWLIST,
+            HostEnvPolicy::Empty => &[],
+        };
+        let mut keys: Vec<String> = allowlist
+            .iter()
+            .map(|k| k.to_string())
+            .chain(self.env_passthrough.iter().cloned())
+            .filter(|k| std::env::var_os(k).is_some())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        Some(keys)
+    }
 }
 
 #[derive(Args, Clone, Debug)]
@@ -117,6 +367,10 @@ struct PcscHostArgs {
     /// Host pcscd unix socket to connect to
     #[arg(long, default_value = "/run/pcscd/pcscd.comm")]
     pcsc_socket: PathBuf,
+
+    /// Log each connection's throughput (bytes/sec each direction) every N seconds
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    throughput_interval_seconds: Option<u64>,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -128,6 +382,10 @@ struct PcscGuestArgs {
     /// Path for the guest unix socket to create for pcsc-lite clients
     #[arg(long, default_value = "/tmp/pcscd.comm")]
     listen: PathBuf,
+
+    /// Log each connection's throughput (bytes/sec each direction) every N seconds
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    throughput_interval_seconds: Option<u64>,
 }
 
 #[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
@@ -159,11 +417,47 @@ struct ExtractionOpts {
 
     /// How to extract the embedded SquashFS filesystem.
     ///
-    /// - `auto` (default): use `squashfs-ng` if compiled in, otherwise `unsquashfs`.
+    /// - `auto` (default): use `squashfs-ng` if compiled in, otherwise `unsquashfs`, or
+    ///   `builtin` when no `unsquashfs` is installed.
     /// - `unsquashfs`: spawn the external `unsquashfs` binary.
     /// - `squashfs-ng`: extract using the `squashfs-ng` Rust crate (requires the Cargo feature).
+    /// - `builtin`: the runner's own (slow) SquashFS reader; gzip, lzma, xz, lz4 and zstd only.
     #[arg(long, default_value = "auto", value_enum)]
     extract_with: ExtractWith,
+
+    /// Keep temporary extraction artifacts instead of deleting them after a successful extraction.
+    ///
+    /// Today this only affects the `squashfs-ng` backend, which copies the embedded SquashFS
+    /// payload to `embedded.squashfs` in the cache dir before extracting it. The copy is as
+    /// large as the AppImage's payload; keeping it only saves the copy when the cache is
+    /// re-extracted. Without this, a copy left by an earlier run is also removed when the
+    /// cached extraction is reused.
+    #[arg(long, default_value_t = false)]
+    keep: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum AppArch {
+    Auto,
+    #[value(name = "x86_64")]
+    X86_64,
+    Aarch64,
+}
+
+#[derive(Args, Clone, Debug)]
+struct ArchOpts {
+    /// Architecture of the AppImage payload.
+    ///
+    /// - `auto` (default): detect from the AppRun ELF (or the first ELF under `usr/bin` when
+    ///   AppRun is a script).
+    /// - `x86_64`: always run under FEX (`--emu=fex`).
+    /// - `aarch64`: run natively (no `--emu=fex`, no FEX images).
+    #[arg(long, default_value = "auto", value_enum)]
+    arch: AppArch,
+
+    /// Keep `--emu=fex` even when the payload is aarch64.
+    #[arg(long, default_value_t = false)]
+    force_fex: bool,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -177,6 +471,18 @@ struct RunArgs {
     #[command(flatten)]
     extraction: ExtractionOpts,
 
+    #[command(flatten)]
+    arch: ArchOpts,
+
+    /// Enable FEX's own logging and capture it into `fex.log` in the out dir.
+    ///
+    /// Sets `FEX_SILENTLOG=0` and points `FEX_OUTPUTLOG` at the out dir (via the guest's
+    /// `/run/muvm-host` view of the host), so FEX diagnostics stay separate from app output.
+    /// FEX has no `Verbose` option; `SilentLog` is what gates its diagnostics.
+    /// Lines mentioning thunks or unsupported features are summarized in `run.report.json`.
+    #[arg(long, default_value_t = false)]
+    fex_verbose: bool,
+
     /// Output directory for evidence artifacts.
     ///
     /// If not provided, defaults to `docs/agent-context/research/<app>/<timestamp>/`.
@@ -187,11 +493,126 @@ struct RunArgs {
     #[arg(long)]
     report: Option<PathBuf>,
 
+    /// Extract (and strip) exactly as `run` would, print the `squashfs-root` path on stdout,
+    /// and exit without launching the AppImage. Same as `extract-only`.
+    #[arg(long, default_value_t = false)]
+    print_extract_dir: bool,
+
+    /// Write this file's contents to the app's stdin (the PTY) after spawning.
+    #[arg(long, conflicts_with = "stdin_string")]
+    stdin_file: Option<PathBuf>,
+
+    /// Write this string to the app's stdin (the PTY) after spawning.
+    #[arg(long)]
+    stdin_string: Option<String>,
+
+    /// Leave stdin open after writing `--stdin-file`/`--stdin-string` instead of sending EOF.
+    #[arg(long, default_value_t = false)]
+    stdin_keep_open: bool,
+
+    /// Re-extract and re-run whenever the AppImage's mtime changes, until interrupted.
+    ///
+    /// Each run writes to its own `run-NNN/` subdir of the out dir.
+    #[arg(long, default_value_t = false, conflicts_with = "print_extract_dir")]
+    watch: bool,
+
+    /// How often `--watch` checks the AppImage's mtime.
+    #[arg(long, default_value_t = 2, requires = "watch")]
+    watch_interval_seconds: u64,
+
+    /// Read more AppImage arguments from this file, one per line, appended after the ones
+    /// given after `--`. Blank lines and lines starting with `#` are skipped; other lines are
+    /// taken verbatim (no quoting or trimming).
+    #[arg(long, value_name = "PATH")]
+    args_file: Option<PathBuf>,
+
     /// Arguments to pass to the AppImage
     #[arg(last = true)]
     args: Vec<String>,
 }
 
+#[derive(Args, Clone, Debug)]
+struct ExtractOnlyArgs {
+    /// Path to the AppImage file
+    appimage: PathBuf,
+
+    #[command(flatten)]
+    extraction: ExtractionOpts,
+}
+
+#[derive(Args, Clone, Debug)]
+struct ReplayArgs {
+    /// Path to an `inputs.json` written by a previous `run`.
+    #[arg(long)]
+    inputs: PathBuf,
+
+    /// Extraction options, used only if the recorded extract dir no longer exists.
+    #[command(flatten)]
+    extraction: ExtractionOpts,
+
+    /// Output directory for evidence artifacts.
+    ///
+    /// If not provided, defaults to `docs/agent-context/research/<app>/<timestamp>/`.
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug)]
+struct ListArtifactsArgs {
+    /// Out dir written by `run`, `replay` or `probe`.
+    #[arg(long)]
+    dir: PathBuf,
+
+    /// Print the inventory as JSON instead of a table.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+struct ElfSectionsArgs {
+    /// ELF file to inspect.
+    #[arg(long)]
+    file: PathBuf,
+}
+
+#[derive(Args, Clone, Debug)]
+struct SquashfsInfoArgs {
+    /// AppImage to inspect.
+    appimage: PathBuf,
+
+    /// Print the superblock as JSON instead of a table.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+struct EmitSchemaArgs {
+    /// Only this artifact's schema; by default all of them, as one object keyed by name.
+    #[arg(value_enum)]
+    artifact: Option<SchemaArtifact>,
+}
+
+/// JSON artifacts with a schema, named after the file (or flag) that produces them.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum SchemaArtifact {
+    /// `inputs.json`
+    Inputs,
+    /// `run.report.json` written by `run` and `replay`
+    RunReport,
+    /// `run.report.json` written by `probe`
+    ProbeReport,
+    /// `run.full.json` written by `run` and `replay`
+    RunFull,
+    /// `run.full.json` written by `probe`
+    ProbeFull,
+    /// `probes.report.json` written by `probe all`
+    ProbesReport,
+    /// `list-artifacts --json`
+    ListArtifacts,
+    /// `squashfs-info --json`
+    SquashfsInfo,
+}
+
 #[derive(Args, Clone, Debug)]
 struct LegacyRunArgs {
     /// Path to the AppImage file (legacy mode)
@@ -203,6 +624,9 @@ struct LegacyRunArgs {
     #[command(flatten)]
     extraction: ExtractionOpts,
 
+    #[command(flatten)]
+    arch: ArchOpts,
+
     /// Write a JSON report (evidence artifact) describing what was executed and what was modified.
     #[arg(long)]
     report: Option<PathBuf>,
@@ -240,6 +664,33 @@ enum ProbeKind {
 
     /// Capture X11 extension opcode mappings (to identify "major code" values)
     X11Opcodes,
+
+    /// Run every probe above in one muvm invocation (one subdir per probe)
+    All {
+        /// Bound each probe's script with the guest's `timeout`, so one hanging tool doesn't
+        /// use up the whole `--timeout-seconds` budget. A probe that hits it is marked
+        /// `timed_out` in `probes.report.json` and the next one starts.
+        #[arg(long, value_name = "SECONDS")]
+        timeout_per_probe: Option<u64>,
+    },
+}
+
+/// Probes covered by `probe all`, in the order they run.
+const ALL_PROBE_KINDS: [ProbeKind; 4] = [
+    ProbeKind::Display,
+    ProbeKind::Gpu,
+    ProbeKind::Devices,
+    ProbeKind::X11Opcodes,
+];
+
+fn probe_name(kind: &ProbeKind) -> &'static str {
+    match kind {
+        ProbeKind::Display => "probe-display",
+        ProbeKind::Gpu => "probe-gpu",
+        ProbeKind::Devices => "probe-devices",
+        ProbeKind::X11Opcodes => "probe-x11-opcodes",
+        ProbeKind::All { .. } => "probe-all",
+    }
 }
 
 #[derive(clap::ValueEnum, Clone, Copy, Debug)]
@@ -247,6 +698,7 @@ enum ExtractWith {
     Auto,
     Unsquashfs,
     SquashfsNg,
+    Builtin,
 }
 
 struct PcscBridgeGuard {
@@ -255,6 +707,7 @@ struct PcscBridgeGuard {
     guest_socket: PathBuf,
     runner_exe: PathBuf,
     host_link_path: Option<PathBuf>,
+    throughput_interval_seconds: Option<u64>,
 }
 
 impl PcscBridgeGuard {
@@ -265,6 +718,7 @@ impl PcscBridgeGuard {
             guest_socket: PathBuf::new(),
             runner_exe: PathBuf::new(),
             host_link_path: None,
+            throughput_interval_seconds: None,
         }
     }
 
@@ -287,11 +741,15 @@ impl PcscBridgeGuard {
         }
 
         let guest_runner = format!("/run/muvm-host{}", self.runner_exe.display());
+        let throughput = self
+            .throughput_interval_seconds
+            .map(|n| format!(" --throughput-interval-seconds {n}"))
+            .unwrap_or_default();
         let prelude = format!(
             r#"# pcsc bridge (guest)
 export PCSCLITE_CSOCK_NAME="{sock}"
 rm -f "$PCSCLITE_CSOCK_NAME" || true
-"{runner}" pcsc-guest --host-port {port} --listen "$PCSCLITE_CSOCK_NAME" >/tmp/pcsc-guest.log 2>&1 &
+"{runner}" pcsc-guest --host-port {port} --listen "$PCSCLITE_CSOCK_NAME"{throughput} >/tmp/pcsc-guest.log 2>&1 &
 for i in $(seq 1 50); do
     [ -S "$PCSCLITE_CSOCK_NAME" ] && break
     sleep 0.05
@@ -394,6 +852,7 @@ fn maybe_enable_pcsc_bridge(
         guest_socket: opts.pcsc_guest_socket.clone(),
         runner_exe,
         host_link_path: Some(link_path),
+        throughput_interval_seconds: opts.pcsc_throughput_interval_seconds,
     })
 }
 
@@ -411,7 +870,11 @@ struct SockAddrVm {
     svm_zero: [u8; 4],
 }
 
-fn pcsc_bridge_host_listen(vsock_port: u32, pcsc_socket: &Path) -> Result<()> {
+fn pcsc_bridge_host_listen(
+    vsock_port: u32,
+    pcsc_socket: &Path,
+    throughput_interval: Option<Duration>,
+) -> Result<()> {
     let listener_fd = vsock_listen(vsock_port)?;
     eprintln!(
         "pcsc-bridge(host): listening on vsock port {vsock_port}, forwarding to {}",
@@ -422,8 +885,13 @@ fn pcsc_bridge_host_listen(vsock_port: u32, pcsc_socket: &Path) -> Result<()> {
         let (client_fd, peer_cid, peer_port) = vsock_accept(listener_fd)?;
         let pcsc_socket = pcsc_socket.to_path_buf();
         std::thread::spawn(move || {
-            if let Err(err) = pcsc_bridge_host_handle(client_fd, peer_cid, peer_port, &pcsc_socket)
-            {
+            if let Err(err) = pcsc_bridge_host_handle(
+                client_fd,
+                peer_cid,
+                peer_port,
+                &pcsc_socket,
+                throughput_interval,
+            ) {
                 eprintln!("pcsc-bridge(host): client error: {err:#}");
             }
         });
@@ -435,6 +903,7 @@ fn pcsc_bridge_host_handle(
     peer_cid: u32,
     peer_port: u32,
     pcsc_socket: &Path,
+    throughput_interval: Option<Duration>,
 ) -> Result<()> {
     eprintln!("pcsc-bridge(host): accepted from cid={peer_cid} port={peer_port}");
 
@@ -442,10 +911,14 @@ fn pcsc_bridge_host_handle(
         .with_context(|| format!("connect to host pcsc socket: {}", pcsc_socket.display()))?;
 
     let client = unsafe { File::from_raw_fd(client_fd.into_raw_fd()) };
-    bidir_copy_unix_file(unix, client)
+    bidir_copy_unix_file(unix, client, "pcsc-bridge(host)", throughput_interval)
 }
 
-fn pcsc_bridge_guest_listen(listen_path: &Path, host_port: u32) -> Result<()> {
+fn pcsc_bridge_guest_listen(
+    listen_path: &Path,
+    host_port: u32,
+    throughput_interval: Option<Duration>,
+) -> Result<()> {
     if let Some(parent) = listen_path.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("create dir {}", parent.display()))?;
@@ -464,7 +937,7 @@ fn pcsc_bridge_guest_listen(listen_path: &Path, host_port: u32) -> Result<()> {
     for stream in listener.incoming() {
         let stream = stream.context("accept guest unix client")?;
         std::thread::spawn(move || {
-            if let Err(err) = pcsc_bridge_guest_handle(stream, host_port) {
+            if let Err(err) = pcsc_bridge_guest_handle(stream, host_port, throughput_interval) {
                 eprintln!("pcsc-bridge(guest): client error: {err:#}");
             }
         });
@@ -472,7 +945,11 @@ fn pcsc_bridge_guest_listen(listen_path: &Path, host_port: u32) -> Result<()> {
     Ok(())
 }
 
-fn pcsc_bridge_guest_handle(unix: std::os::unix::net::UnixStream, host_port: u32) -> Result<()> {
+fn pcsc_bridge_guest_handle(
+    unix: std::os::unix::net::UnixStream,
+    host_port: u32,
+    throughput_interval: Option<Duration>,
+) -> Result<()> {
     eprintln!(
         "pcsc-bridge(guest): accepted unix client, connecting to host vsock port {host_port}"
     );
@@ -480,33 +957,104 @@ fn pcsc_bridge_guest_handle(unix: std::os::unix::net::UnixStream, host_port: u32
         .with_context(|| format!("connect vsock host port {host_port}"))?;
 
     let vsock = unsafe { File::from_raw_fd(vsock_fd.into_raw_fd()) };
-    bidir_copy_unix_file(unix, vsock)
+    bidir_copy_unix_file(unix, vsock, "pcsc-bridge(guest)", throughput_interval)
 }
 
-fn bidir_copy_unix_file(unix: std::os::unix::net::UnixStream, file: File) -> Result<()> {
+/// Copies both ways until each side closes, counting bytes per direction. Logs the totals
+/// when done and, with `throughput_interval`, the bytes/sec of each direction every interval.
+fn bidir_copy_unix_file(
+    unix: std::os::unix::net::UnixStream,
+    file: File,
+    log_prefix: &'static str,
+    throughput_interval: Option<Duration>,
+) -> Result<()> {
     let mut unix_a = unix;
     let mut unix_b = unix_a.try_clone().context("clone unix stream")?;
 
     let mut file_a = file;
     let mut file_b = file_a.try_clone().context("clone vsock fd")?;
 
+    let to_vsock = Arc::new(AtomicU64::new(0));
+    let to_unix = Arc::new(AtomicU64::new(0));
+    let done = Arc::new(AtomicBool::new(false));
+    let started = std::time::Instant::now();
+
+    let reporter = throughput_interval.map(|interval| {
+        let (to_vsock, to_unix, done) = (to_vsock.clone(), to_unix.clone(), done.clone());
+        std::thread::spawn(move || {
+            let mut last = (0, 0);
+            let mut next = std::time::Instant::now() + interval;
+            while !done.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(100));
+                if std::time::Instant::now() < next {
+                    continue;
+                }
+                next += interval;
+                let now = (
+                    to_vsock.load(Ordering::Relaxed),
+                    to_unix.load(Ordering::Relaxed),
+                );
+                let secs = interval.as_secs_f64();
+                eprintln!(
+                    "{log_prefix}: throughput unix->vsock={:.0} B/s vsock->unix={:.0} B/s",
+                    (now.0 - last.0) as f64 / secs,
+                    (now.1 - last.1) as f64 / secs
+                );
+                last = now;
+            }
+        })
+    });
+
+    let counter = to_vsock.clone();
     let t1 = std::thread::spawn(move || -> Result<()> {
-        std::io::copy(&mut unix_a, &mut file_a).context("copy unix->vsock")?;
-        Ok(())
+        copy_counting(&mut unix_a, &mut file_a, &counter).context("copy unix->vsock")
     });
 
+    let counter = to_unix.clone();
     let t2 = std::thread::spawn(move || -> Result<()> {
-        std::io::copy(&mut file_b, &mut unix_b).context("copy vsock->unix")?;
-        Ok(())
+        copy_counting(&mut file_b, &mut unix_b, &counter).context("copy vsock->unix")
     });
 
-    t1.join()
-        .map_err(|_| anyhow::anyhow!("copy thread 1 panicked"))??;
-    t2.join()
-        .map_err(|_| anyhow::anyhow!("copy thread 2 panicked"))??;
+    let r1 = t1
+        .join()
+        .map_err(|_| anyhow::anyhow!("copy thread 1 panicked"));
+    let r2 = t2
+        .join()
+        .map_err(|_| anyhow::anyhow!("copy thread 2 panicked"));
+    done.store(true, Ordering::Relaxed);
+    if let Some(reporter) = reporter {
+        let _ = reporter.join();
+    }
+    eprintln!(
+        "{log_prefix}: connection closed after {:.1}s: unix->vsock={} bytes vsock->unix={} bytes",
+        started.elapsed().as_secs_f64(),
+        to_vsock.load(Ordering::Relaxed),
+        to_unix.load(Ordering::Relaxed)
+    );
+    r1??;
+    r2??;
     Ok(())
 }
 
+/// `std::io::copy`, but bumping `counter` after every write so it can be sampled mid-copy.
+fn copy_counting(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    counter: &AtomicU64,
+) -> std::io::Result<()> {
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buf[..n])?;
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+    }
+}
+
 fn vsock_listen(port: u32) -> Result<RawFd> {
     let fd = unsafe { libc::socket(libc::AF_VSOCK, libc::SOCK_STREAM, 0) };
     if fd < 0 {
@@ -599,38 +1147,107 @@ fn main() -> Result<()> {
     match cli.command {
         Some(Commands::Run(args)) => run_mode(args),
         Some(Commands::Probe(args)) => probe_mode(args),
+        Some(Commands::Replay(args)) => replay_mode(args),
+        Some(Commands::ExtractOnly(args)) => extract_only_mode(args),
+        Some(Commands::ListArtifacts(args)) => list_artifacts_mode(args),
+        Some(Commands::ElfSections(args)) => {
+            print!("{}", elf_notes::format_elf_sections(&args.file)?);
+            Ok(())
+        }
+        Some(Commands::SquashfsInfo(args)) => squashfs_info_mode(args),
+        Some(Commands::EmitSchema(args)) => emit_schema_mode(args),
         Some(Commands::PcscHost(args)) => pcsc_host_mode(args),
         Some(Commands::PcscGuest(args)) => pcsc_guest_mode(args),
         None => legacy_mode(cli.legacy),
     }
 }
 
+impl RunArgs {
+    fn pty_settings(&self) -> Result<PtySettings> {
+        let source = match (&self.stdin_file, &self.stdin_string) {
+            (Some(path), _) => Some(StdinSource::File(
+                path.canonicalize()
+                    .with_context(|| format!("--stdin-file {}", path.display()))?,
+            )),
+            (None, Some(s)) => Some(StdinSource::String(s.clone())),
+            (None, None) => None,
+        };
+        Ok(PtySettings {
+            stdin: source.map(|source| PtyStdin {
+                source,
+                eof: !self.stdin_keep_open,
+            }),
+            ..self.guest.pty_settings()
+        })
+    }
+
+    /// Arguments after `--`, followed by those from `--args-file`.
+    fn app_args(&self) -> Result<Vec<String>> {
+        let mut argv = self.args.clone();
+        if let Some(path) = &self.args_file {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Reading --args-file {}", path.display()))?;
+            argv.extend(
+                text.lines()
+                    .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+        Ok(argv)
+    }
+}
+
 fn pcsc_host_mode(args: PcscHostArgs) -> Result<()> {
-    pcsc_bridge_host_listen(args.port, &args.pcsc_socket)
+    pcsc_bridge_host_listen(
+        args.port,
+        &args.pcsc_socket,
+        args.throughput_interval_seconds.map(Duration::from_secs),
+    )
 }
 
 fn pcsc_guest_mode(args: PcscGuestArgs) -> Result<()> {
-    pcsc_bridge_guest_listen(&args.listen, args.host_port)
+    pcsc_bridge_guest_listen(
+        &args.listen,
+        args.host_port,
+        args.throughput_interval_seconds.map(Duration::from_secs),
+    )
 }
 
 fn legacy_mode(args: LegacyRunArgs) -> Result<()> {
     let Some(appimage) = args.appimage else {
         anyhow::bail!("missing APPIMAGE (try: appimage-runner run <AppImage> ...)");
     };
+    if args.guest.capture_screenshot_on_timeout || args.guest.dump_guest_mounts {
+        anyhow::bail!(
+            "--capture-screenshot-on-timeout and --dump-guest-mounts need an out dir; use `run` instead"
+        );
+    }
 
     let appimage_path = appimage
         .canonicalize()
         .context("Failed to canonicalize AppImage path")?;
     let muvm_path = canonicalize_muvm_path(&args.guest.muvm_path)?;
+    if args.guest.guest_shell_trace {
+        anyhow::bail!("--guest-shell-trace needs an out dir; use `appimage-runner run` instead");
+    }
 
-    validate_muvm_args(&muvm_path, &args.guest.muvm_arg)?;
+    let muvm_help = probe_muvm(&muvm_path)?;
+    validate_muvm_args(&muvm_path, &muvm_help, &args.guest.muvm_arg)?;
 
     println!("Getting offset for: {}", appimage_path.display());
     let offset = get_offset(&appimage_path)?;
     println!("Detected offset: {}", offset);
 
-    let extract_dir = extract_appimage(&appimage_path, offset, args.extraction.extract_with)?;
+    let extract_dir = extract_appimage(
+        &appimage_path,
+        offset,
+        args.extraction.extract_with,
+        args.extraction.keep,
+    )?;
     println!("Extracted to: {}", extract_dir.display());
+    let extraction_backend = extraction_backend(&extract_dir);
+    let squashfs_compression_id = squashfs_compression_id(&appimage_path, offset).ok();
+    print_extraction(&extraction_backend, squashfs_compression_id);
 
     let mut strip_report = StripReport::default();
     if args.extraction.strip_gnu_property {
@@ -643,25 +1260,47 @@ fn legacy_mode(args: LegacyRunArgs) -> Result<()> {
     let (fex_images, fex_rootfs_compat_overlay) =
         prepare_fex_images(&args.guest.fex_image, args.guest.fex_profile)
             .context("Preparing FEX images")?;
+    let input_hashes = InputHashes::of(Some(&appimage_path), &muvm_path, &fex_images)?;
+
+    let arch =
+        decide_arch(&appimage_path, &extract_dir, &args.arch).context("Deciding AppImage arch")?;
+    println!("Arch: {} (emu_fex={})", arch.effective, arch.emu_fex);
+    let abi_compat = abi_compat_report(&extract_dir).context("Checking payload ELF ABI")?;
 
     let pcsc = maybe_enable_pcsc_bridge(&args.guest, None)?;
-    let effective_env = pcsc.apply_env(&args.guest.env);
-    let effective_guest_pre = pcsc.apply_guest_pre(args.guest.guest_pre.as_deref());
+    let mut effective_env = pcsc.apply_env(&args.guest.env);
+    let effective_guest_pre = with_guest_cwd(
+        args.guest.guest_cwd.as_deref(),
+        pcsc.apply_guest_pre(args.guest.guest_pre.as_deref()),
+    );
+
+    if let Some(dir) = args.guest.fex_thunk_dir.as_deref().filter(|_| arch.emu_fex) {
+        effective_env.extend(fex_thunk_env(dir)?);
+    }
 
-    let (run_report, _combined) = run_appimage(
+    let (run_report, combined) = run_appimage(
         &extract_dir,
         &args.args,
         &effective_env,
+        arch.emu_fex,
         &fex_images,
         &muvm_path,
         &args.guest.muvm_arg,
         args.guest.timeout_seconds,
         effective_guest_pre.as_deref(),
+        &args.guest.pty_settings(),
+        None,
     )?;
 
     pcsc.shutdown();
 
+    let fuse_mount_attempt = detect_fuse_mount_attempt(&combined);
+    print_fuse_mount_hint(fuse_mount_attempt.as_ref());
+
     if let Some(path) = args.report.as_ref() {
+        let (exit_code, exit_reason) = run_exit(&run_report);
+        let guest_kernel = run_report.guest_kernel();
+        let kernel_too_old = kernel_too_old(abi_compat.as_ref(), guest_kernel);
         let report = RunnerReport {
             appimage: appimage_path.display().to_string(),
             extract_dir: extract_dir.display().to_string(),
@@ -682,7 +1321,28 @@ fn legacy_mode(args: LegacyRunArgs) -> Result<()> {
             muvm_guest_terminated_signal: run_report.muvm_guest_terminated_signal,
             timeout_seconds: args.guest.timeout_seconds,
             timed_out: run_report.timed_out,
+            exit_code,
+            exit_reason: exit_reason.to_string(),
+            extraction_backend,
+            squashfs_compression_id,
+            squashfs_compression: squashfs_compression_id.map(squashfs_compression_name),
             strip_report,
+            abi_compat,
+            guest_kernel_version: run_report.guest_kernel_version.clone(),
+            fex_binfmt: run_report.fex_binfmt.clone(),
+            fexserver: run_report.fexserver.clone(),
+            guest_kernel,
+            kernel_too_old,
+            host_env_passthrough: args.guest.pty_settings().host_env_passthrough(),
+            stdin: run_report.stdin.clone(),
+            timeout_screenshot: run_report.timeout_screenshot.clone(),
+            failure_class: classify_failure(&run_report, &combined),
+            attempted_fuse_mount: fuse_mount_attempt.is_some(),
+            fuse_mount_attempt,
+            guest_mounts: None,
+            hashes: input_hashes,
+            fex_thunks: fex_thunks_report(&effective_env),
+            fex_log: None,
         };
 
         write_json(path, &report).with_context(|| format!("Writing report {}", path.display()))?;
@@ -692,29 +1352,176 @@ fn legacy_mode(args: LegacyRunArgs) -> Result<()> {
     exit_from_run_report(&run_report)
 }
 
+/// Extracts into the same cache dir `run` uses and prints only that path on stdout, so
+/// scripts can `cd "$(appimage-runner extract-only app.AppImage)"`.
+fn extract_only_mode(args: ExtractOnlyArgs) -> Result<()> {
+    let appimage_path = args
+        .appimage
+        .canonicalize()
+        .context("Failed to canonicalize AppImage path")?;
+    let offset = get_offset(&appimage_path)?;
+    let extract_dir = extract_appimage(
+        &appimage_path,
+        offset,
+        args.extraction.extract_with,
+        args.extraction.keep,
+    )?;
+
+    if args.extraction.strip_gnu_property {
+        let objcopy = resolve_objcopy_path(args.extraction.objcopy_path.as_deref())
+            .context("Resolving objcopy path")?;
+        let report = strip_gnu_property_notes_in_appdir(&extract_dir, &objcopy)
+            .context("Stripping .note.gnu.property inside extracted AppImage")?;
+        eprintln!(
+            "Stripped .note.gnu.property: stripped={} failed={} remaining={}",
+            report.stripped_files.len(),
+            report.strip_failures.len(),
+            report.remaining_gnu_property_files.len()
+        );
+    }
+
+    println!("{}", extract_dir.display());
+    Ok(())
+}
+
 fn run_mode(args: RunArgs) -> Result<()> {
+    if args.print_extract_dir {
+        return extract_only_mode(ExtractOnlyArgs {
+            appimage: args.appimage,
+            extraction: args.extraction,
+        });
+    }
+
     let appimage_path = args
         .appimage
         .canonicalize()
         .context("Failed to canonicalize AppImage path")?;
     let muvm_path = canonicalize_muvm_path(&args.guest.muvm_path)?;
 
-    validate_muvm_args(&muvm_path, &args.guest.muvm_arg)?;
+    let muvm_help = probe_muvm(&muvm_path)?;
+    validate_muvm_args(&muvm_path, &muvm_help, &args.guest.muvm_arg)?;
 
     let app_name = appimage_path
         .file_stem()
         .map(|s| s.to_string_lossy().to_string())
         .unwrap_or_else(|| "appimage".to_string());
-    let out_dir = args.out_dir.unwrap_or_else(|| default_out_dir(&app_name));
-    std::fs::create_dir_all(&out_dir)
+    let out_dir = args
+        .out_dir
+        .clone()
+        .unwrap_or_else(|| default_out_dir(&app_name));
+
+    if args.watch {
+        return watch_mode(&args, &appimage_path, &muvm_path, &out_dir);
+    }
+    let run_report = run_once(&args, &appimage_path, &muvm_path, &out_dir, &mut None)?;
+    exit_from_run_report(&run_report)
+}
+
+/// `run --watch`: run, then poll the AppImage's mtime and re-run after each change.
+///
+/// The extraction cache is keyed on sampled content, so each rebuild would leave the previous
+/// build's tree behind in `~/.cache/appimage-runner`, and a rebuild that happens to keep those
+/// samples would reuse it; drop the last run's cache dir before every re-run.
+fn watch_mode(
+    args: &RunArgs,
+    appimage_path: &Path,
+    muvm_path: &Path,
+    out_dir: &Path,
+) -> Result<()> {
+    let interval = Duration::from_secs(args.watch_interval_seconds.max(1));
+    let mtime = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    let mut iteration = 1u32;
+    let mut last_extract_dir = None;
+    loop {
+        let seen = mtime(appimage_path);
+        println!(
+            "==== appimage-runner --watch: run {iteration} of {} ====",
+            appimage_path.display()
+        );
+        let run_out_dir = out_dir.join(format!("run-{iteration:03}"));
+        match run_once(
+            args,
+            appimage_path,
+            muvm_path,
+            &run_out_dir,
+            &mut last_extract_dir,
+        ) {
+            Ok(report) => {
+                let (code, reason) = run_exit(&report);
+                println!("Run {iteration} finished: exit_code={code} ({reason})");
+            }
+            Err(err) => eprintln!("Run {iteration} failed: {err:#}"),
+        }
+        println!(
+            "Watching {} for changes (Ctrl-C to stop)...",
+            appimage_path.display()
+        );
+
+        // Wait for a change, then for the mtime to hold still for one interval so a
+        // rebuild that is still writing the file isn't picked up half-done.
+        let mut current = seen;
+        while current == seen || current.is_none() {
+            std::thread::sleep(interval);
+            current = mtime(appimage_path);
+        }
+        loop {
+            std::thread::sleep(interval);
+            let next = mtime(appimage_path);
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+
+        if let Some(dir) = last_extract_dir.take() {
+            invalidate_extraction(&dir);
+        }
+        iteration += 1;
+    }
+}
+
+/// Remove an extraction cache dir a previous `--watch` run used. Failures are only logged
+/// so a transient error doesn't end the watch.
+fn invalidate_extraction(cache_dir: &Path) {
+    if !cache_dir.exists() {
+        return;
+    }
+    println!("Invalidating extraction cache: {}", cache_dir.display());
+    if let Err(err) = std::fs::remove_dir_all(cache_dir) {
+        eprintln!("WARNING: failed to remove {}: {err}", cache_dir.display());
+    }
+}
+
+/// One extract + run into `out_dir`, writing the usual artifacts. Exit-code handling is
+/// left to the caller so `--watch` can keep going. The extraction cache dir used is stored in
+/// `cache_dir` as soon as it exists, even if a later step fails.
+fn run_once(
+    args: &RunArgs,
+    appimage_path: &Path,
+    muvm_path: &Path,
+    out_dir: &Path,
+    cache_dir: &mut Option<PathBuf>,
+) -> Result<RunReport> {
+    std::fs::create_dir_all(out_dir)
         .with_context(|| format!("Creating out dir {}", out_dir.display()))?;
+    let app_args = args.app_args()?;
 
     println!("Getting offset for: {}", appimage_path.display());
-    let offset = get_offset(&appimage_path)?;
+    let offset = get_offset(appimage_path)?;
     println!("Detected offset: {}", offset);
 
-    let extract_dir = extract_appimage(&appimage_path, offset, args.extraction.extract_with)?;
+    let extract_dir = extract_appimage(
+        appimage_path,
+        offset,
+        args.extraction.extract_with,
+        args.extraction.keep,
+    )?;
+    *cache_dir = extract_dir.parent().map(Path::to_path_buf);
     println!("Extracted to: {}", extract_dir.display());
+    let extraction_backend = extraction_backend(&extract_dir);
+    let squashfs_compression_id = squashfs_compression_id(appimage_path, offset).ok();
+    print_extraction(&extraction_backend, squashfs_compression_id);
 
     let mut strip_report = StripReport::default();
     if args.extraction.strip_gnu_property {
@@ -727,11 +1534,25 @@ fn run_mode(args: RunArgs) -> Result<()> {
     let (fex_images, fex_rootfs_compat_overlay) =
         prepare_fex_images(&args.guest.fex_image, args.guest.fex_profile)
             .context("Preparing FEX images")?;
+    let input_hashes = InputHashes::of(Some(appimage_path), muvm_path, &fex_images)?;
 
-    let pcsc = maybe_enable_pcsc_bridge(&args.guest, Some(&out_dir))?;
-    let effective_env = pcsc.apply_env(&args.guest.env);
+    let arch =
+        decide_arch(appimage_path, &extract_dir, &args.arch).context("Deciding AppImage arch")?;
+    println!("Arch: {} (emu_fex={})", arch.effective, arch.emu_fex);
+    let abi_compat = abi_compat_report(&extract_dir).context("Checking payload ELF ABI")?;
+
+    let pcsc = maybe_enable_pcsc_bridge(&args.guest, Some(out_dir))?;
+    let mut effective_env = pcsc.apply_env(&args.guest.env);
     let effective_guest_pre = pcsc.apply_guest_pre(args.guest.guest_pre.as_deref());
 
+    if args.fex_verbose && arch.emu_fex {
+        effective_env.extend(fex_verbose_env(out_dir)?);
+    }
+    if let Some(dir) = args.guest.fex_thunk_dir.as_deref().filter(|_| arch.emu_fex) {
+        effective_env.extend(fex_thunk_env(dir)?);
+    }
+
+    let pty = args.pty_settings()?.with_out_dir(out_dir);
     let inputs = InputsReport {
         kind: "run".to_string(),
         appimage: Some(appimage_path.display().to_string()),
@@ -748,30 +1569,58 @@ fn run_mode(args: RunArgs) -> Result<()> {
         env: effective_env.clone(),
         timeout_seconds: args.guest.timeout_seconds,
         guest_pre: effective_guest_pre.clone(),
-        argv_after_double_dash: Some(args.args.clone()),
+        guest_cwd: args.guest.guest_cwd.clone(),
+        guest_pre_trace_log: guest_pre_trace_log(args.guest.guest_shell_trace, out_dir)?,
+        guest_mountinfo: guest_artifact_path(
+            args.guest.dump_guest_mounts,
+            out_dir,
+            GUEST_MOUNTINFO,
+        )?,
+        argv_after_double_dash: Some(app_args.clone()),
+        args_file: args.args_file.as_ref().map(|p| p.display().to_string()),
+        replay_of: None,
+        arch: Some(arch.clone()),
+        pty: Some(pty.clone()),
+        redacted_keys: Vec::new(),
+        extraction_backend: Some(extraction_backend),
+        squashfs_compression_id,
+        hashes: input_hashes,
     };
+    let (shareable_inputs, redacted_values) = inputs.shareable(&args.guest.redact);
 
     let inputs_path = out_dir.join("inputs.json");
-    write_json(&inputs_path, &inputs)
+    write_json(&inputs_path, &shareable_inputs)
         .with_context(|| format!("Writing inputs {}", inputs_path.display()))?;
 
     let (run_report, combined) = run_appimage(
         &extract_dir,
-        &args.args,
+        &app_args,
         &effective_env,
+        arch.emu_fex,
         &fex_images,
-        &muvm_path,
+        muvm_path,
         &args.guest.muvm_arg,
         args.guest.timeout_seconds,
-        effective_guest_pre.as_deref(),
+        traced_guest_pre(&inputs).as_deref(),
+        &pty,
+        Some(out_dir),
     )?;
 
     pcsc.shutdown();
 
+    let redacted_combined = redact_values(&combined, &redacted_values);
+    let failure_class = classify_failure(&run_report, &redacted_combined);
+    print_failure_class(failure_class.as_ref());
+    let fuse_mount_attempt = detect_fuse_mount_attempt(&redacted_combined);
+    print_fuse_mount_hint(fuse_mount_attempt.as_ref());
     let log_path = out_dir.join("run.log");
     std::fs::write(&log_path, combined)
         .with_context(|| format!("Writing log {}", log_path.display()))?;
+    redact_text_artifacts(out_dir, &redacted_values)?;
 
+    let (exit_code, exit_reason) = run_exit(&run_report);
+    let guest_kernel = run_report.guest_kernel();
+    let kernel_too_old = kernel_too_old(abi_compat.as_ref(), guest_kernel);
     let report = RunnerReport {
         appimage: appimage_path.display().to_string(),
         extract_dir: extract_dir.display().to_string(),
@@ -787,16 +1636,255 @@ fn run_mode(args: RunArgs) -> Result<()> {
         muvm_guest_terminated_signal: run_report.muvm_guest_terminated_signal,
         timeout_seconds: args.guest.timeout_seconds,
         timed_out: run_report.timed_out,
+        exit_code,
+        exit_reason: exit_reason.to_string(),
+        extraction_backend: inputs.extraction_backend.clone().unwrap_or_default(),
+        squashfs_compression_id: inputs.squashfs_compression_id,
+        squashfs_compression: inputs
+            .squashfs_compression_id
+            .map(squashfs_compression_name),
         strip_report,
+        abi_compat,
+        guest_kernel_version: run_report.guest_kernel_version.clone(),
+        fex_binfmt: run_report.fex_binfmt.clone(),
+        fexserver: run_report.fexserver.clone(),
+        guest_kernel,
+        kernel_too_old,
+        host_env_passthrough: inputs
+            .pty
+            .clone()
+            .unwrap_or_default()
+            .host_env_passthrough(),
+        stdin: run_report.stdin.clone(),
+        timeout_screenshot: run_report.timeout_screenshot.clone(),
+        failure_class,
+        attempted_fuse_mount: fuse_mount_attempt.is_some(),
+        fuse_mount_attempt,
+        guest_mounts: guest_mounts_report(out_dir, Some(&extract_dir)),
+        hashes: inputs.hashes.clone(),
+        fex_thunks: fex_thunks_report(&inputs.env),
+        fex_log: fex_log_report_if_enabled(&inputs.env, out_dir),
     };
     let report_path = out_dir.join("run.report.json");
     write_json(&report_path, &report)
         .with_context(|| format!("Writing report {}", report_path.display()))?;
+    write_full_report(out_dir, &shareable_inputs, &report)?;
 
     if let Some(path) = args.report.as_ref() {
         write_json(path, &report).with_context(|| format!("Writing report {}", path.display()))?;
     }
 
+    println!("Wrote artifacts: {}", out_dir.display());
+    Ok(run_report)
+}
+
+fn replay_mode(args: ReplayArgs) -> Result<()> {
+    let text = std::fs::read_to_string(&args.inputs)
+        .with_context(|| format!("Reading inputs {}", args.inputs.display()))?;
+    let recorded: InputsReport = serde_json::from_str(&text)
+        .with_context(|| format!("Parsing inputs {}", args.inputs.display()))?;
+    if recorded.kind != "run" {
+        anyhow::bail!(
+            "replay only supports `run` inputs (got kind `{}`); probes do not record their guest script",
+            recorded.kind
+        );
+    }
+    let appimage = recorded
+        .appimage
+        .as_deref()
+        .map(PathBuf::from)
+        .context("inputs.json is missing `appimage`")?;
+    let app_args = recorded.argv_after_double_dash.clone().unwrap_or_default();
+
+    let muvm_path = PathBuf::from(&recorded.muvm_path);
+    let muvm_args: Vec<OsString> = recorded.muvm_args.iter().map(OsString::from).collect();
+    let muvm_help = probe_muvm(&muvm_path)?;
+    validate_muvm_args(&muvm_path, &muvm_help, &muvm_args)?;
+
+    let app_name = appimage
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "appimage".to_string());
+    let out_dir = args.out_dir.unwrap_or_else(|| default_out_dir(&app_name));
+    std::fs::create_dir_all(&out_dir)
+        .with_context(|| format!("Creating out dir {}", out_dir.display()))?;
+
+    // Reuse the recorded extraction when it is still in the cache; otherwise re-extract.
+    let mut strip_report = StripReport::default();
+    let extract_dir = match recorded.extract_dir.as_deref().map(PathBuf::from) {
+        Some(dir)
+            if dir.join("AppRun").exists()
+                && !dir.with_file_name(".extract-incomplete").exists() =>
+        {
+            dir
+        }
+        _ => {
+            println!("Getting offset for: {}", appimage.display());
+            let offset = get_offset(&appimage)?;
+            println!("Detected offset: {}", offset);
+
+            let extract_dir = extract_appimage(
+                &appimage,
+                offset,
+                args.extraction.extract_with,
+                args.extraction.keep,
+            )?;
+            println!("Extracted to: {}", extract_dir.display());
+
+            if args.extraction.strip_gnu_property {
+                let objcopy = resolve_objcopy_path(args.extraction.objcopy_path.as_deref())
+                    .context("Resolving objcopy path")?;
+                strip_report = strip_gnu_property_notes_in_appdir(&extract_dir, &objcopy)
+                    .context("Stripping .note.gnu.property inside extracted AppImage")?;
+            }
+            extract_dir
+        }
+    };
+    let extraction_backend = extraction_backend(&extract_dir);
+    let squashfs_compression_id = get_offset(&appimage)
+        .and_then(|offset| squashfs_compression_id(&appimage, offset))
+        .ok();
+    print_extraction(&extraction_backend, squashfs_compression_id);
+
+    if recorded
+        .env
+        .iter()
+        .any(|kv| kv.starts_with("PCSCLITE_CSOCK_NAME="))
+    {
+        println!(
+            "note: the recorded run used --pcsc-bridge; replay does not recreate the host-side link"
+        );
+    }
+
+    // A recorded `--fex-verbose` log path points at the old out dir; retarget it.
+    let mut env = recorded.env.clone();
+    // Redacted values were never recorded; take them from this environment instead.
+    for kv in &mut env {
+        let Some((key, value)) = kv.split_once('=') else {
+            continue;
+        };
+        if value != REDACTED || !recorded.redacted_keys.iter().any(|k| k == key) {
+            continue;
+        }
+        let value = std::env::var(key).with_context(|| {
+            format!("inputs.json has {key} redacted; set it in the environment to replay")
+        })?;
+        *kv = format!("{key}={value}");
+    }
+    if env.iter().any(|kv| kv.starts_with("FEX_OUTPUTLOG=")) {
+        env.retain(|kv| !kv.starts_with("FEX_OUTPUTLOG=") && !kv.starts_with("FEX_SILENTLOG="));
+        env.extend(fex_verbose_env(&out_dir)?);
+    }
+
+    let guest_pre_trace_log =
+        guest_pre_trace_log(recorded.guest_pre_trace_log.is_some(), &out_dir)?;
+
+    let fex_images: Vec<PathBuf> = recorded.fex_images.iter().map(PathBuf::from).collect();
+    let input_hashes = InputHashes::of(Some(&appimage), &muvm_path, &fex_images)?;
+    let inputs = InputsReport {
+        extract_dir: Some(extract_dir.display().to_string()),
+        env,
+        guest_pre_trace_log,
+        guest_mountinfo: guest_artifact_path(
+            recorded.guest_mountinfo.is_some(),
+            &out_dir,
+            GUEST_MOUNTINFO,
+        )?,
+        replay_of: Some(args.inputs.display().to_string()),
+        extraction_backend: Some(extraction_backend),
+        squashfs_compression_id,
+        hashes: input_hashes,
+        ..recorded
+    };
+    let (shareable_inputs, redacted_values) = inputs.shareable(&inputs.redacted_keys);
+    let inputs_path = out_dir.join("inputs.json");
+    write_json(&inputs_path, &shareable_inputs)
+        .with_context(|| format!("Writing inputs {}", inputs_path.display()))?;
+
+    let abi_compat = abi_compat_report(&extract_dir).context("Checking payload ELF ABI")?;
+
+    // Runs recorded before arch detection existed always used FEX.
+    let emu_fex = inputs.arch.as_ref().is_none_or(|a| a.emu_fex);
+    let (run_report, combined) = run_appimage(
+        &extract_dir,
+        &app_args,
+        &inputs.env,
+        emu_fex,
+        &fex_images,
+        &muvm_path,
+        &muvm_args,
+        inputs.timeout_seconds,
+        traced_guest_pre(&inputs).as_deref(),
+        &inputs
+            .pty
+            .clone()
+            .unwrap_or_default()
+            .with_out_dir(&out_dir),
+        Some(&out_dir),
+    )?;
+
+    let redacted_combined = redact_values(&combined, &redacted_values);
+    let failure_class = classify_failure(&run_report, &redacted_combined);
+    print_failure_class(failure_class.as_ref());
+    let fuse_mount_attempt = detect_fuse_mount_attempt(&redacted_combined);
+    print_fuse_mount_hint(fuse_mount_attempt.as_ref());
+    let log_path = out_dir.join("run.log");
+    std::fs::write(&log_path, combined)
+        .with_context(|| format!("Writing log {}", log_path.display()))?;
+    redact_text_artifacts(&out_dir, &redacted_values)?;
+
+    let (exit_code, exit_reason) = run_exit(&run_report);
+    let guest_kernel = run_report.guest_kernel();
+    let kernel_too_old = kernel_too_old(abi_compat.as_ref(), guest_kernel);
+    let report = RunnerReport {
+        appimage: appimage.display().to_string(),
+        extract_dir: extract_dir.display().to_string(),
+        strip_gnu_property: args.extraction.strip_gnu_property,
+        fex_images: inputs.fex_images.clone(),
+        fex_rootfs_compat_overlay: inputs.fex_rootfs_compat_overlay.clone(),
+        muvm_path: inputs.muvm_path.clone(),
+        muvm_args: inputs.muvm_args.clone(),
+        entrypoint: run_report.entrypoint.clone(),
+        muvm_exit_status: run_report.muvm_exit_status.clone(),
+        muvm_succeeded: run_report.muvm_succeeded,
+        muvm_guest_status_code: run_report.muvm_guest_status_code,
+        muvm_guest_terminated_signal: run_report.muvm_guest_terminated_signal,
+        timeout_seconds: inputs.timeout_seconds,
+        timed_out: run_report.timed_out,
+        exit_code,
+        exit_reason: exit_reason.to_string(),
+        extraction_backend: inputs.extraction_backend.clone().unwrap_or_default(),
+        squashfs_compression_id: inputs.squashfs_compression_id,
+        squashfs_compression: inputs
+            .squashfs_compression_id
+            .map(squashfs_compression_name),
+        strip_report,
+        abi_compat,
+        guest_kernel_version: run_report.guest_kernel_version.clone(),
+        fex_binfmt: run_report.fex_binfmt.clone(),
+        fexserver: run_report.fexserver.clone(),
+        guest_kernel,
+        kernel_too_old,
+        host_env_passthrough: inputs
+            .pty
+            .clone()
+            .unwrap_or_default()
+            .host_env_passthrough(),
+        stdin: run_report.stdin.clone(),
+        timeout_screenshot: run_report.timeout_screenshot.clone(),
+        failure_class,
+        attempted_fuse_mount: fuse_mount_attempt.is_some(),
+        fuse_mount_attempt,
+        guest_mounts: guest_mounts_report(&out_dir, Some(&extract_dir)),
+        hashes: inputs.hashes.clone(),
+        fex_thunks: fex_thunks_report(&inputs.env),
+        fex_log: fex_log_report_if_enabled(&inputs.env, &out_dir),
+    };
+    let report_path = out_dir.join("run.report.json");
+    write_json(&report_path, &report)
+        .with_context(|| format!("Writing report {}", report_path.display()))?;
+    write_full_report(&out_dir, &shareable_inputs, &report)?;
+
     println!("Wrote artifacts: {}", out_dir.display());
     exit_from_run_report(&run_report)
 }
@@ -804,13 +1892,9 @@ fn run_mode(args: RunArgs) -> Result<()> {
 fn probe_mode(args: ProbeArgs) -> Result<()> {
     let muvm_path = canonicalize_muvm_path(&args.guest.muvm_path)?;
 
-    validate_muvm_args(&muvm_path, &args.guest.muvm_arg)?;
-    let probe_name = match args.kind {
-        ProbeKind::Display => "probe-display",
-        ProbeKind::Gpu => "probe-gpu",
-        ProbeKind::Devices => "probe-devices",
-        ProbeKind::X11Opcodes => "probe-x11-opcodes",
-    };
+    let muvm_help = probe_muvm(&muvm_path)?;
+    validate_muvm_args(&muvm_path, &muvm_help, &args.guest.muvm_arg)?;
+    let probe_name = probe_name(&args.kind);
     let out_dir = args.out_dir.unwrap_or_else(|| default_out_dir(probe_name));
     std::fs::create_dir_all(&out_dir)
         .with_context(|| format!("Creating out dir {}", out_dir.display()))?;
@@ -818,16 +1902,149 @@ fn probe_mode(args: ProbeArgs) -> Result<()> {
     let (fex_images, fex_rootfs_compat_overlay) =
         prepare_fex_images(&args.guest.fex_image, args.guest.fex_profile)
             .context("Preparing FEX images")?;
+    let input_hashes = InputHashes::of(None, &muvm_path, &fex_images)?;
 
     let pcsc = maybe_enable_pcsc_bridge(&args.guest, Some(&out_dir))?;
-    let effective_env = pcsc.apply_env(&args.guest.env);
+    let mut effective_env = pcsc.apply_env(&args.guest.env);
+    if let Some(dir) = args.guest.fex_thunk_dir.as_deref() {
+        effective_env.extend(fex_thunk_env(dir)?);
+    }
     let effective_guest_pre = pcsc.apply_guest_pre(args.guest.guest_pre.as_deref());
 
-    let guest_cmd: String = match args.kind {
+    let guest_cmd = probe_guest_script(&args.kind, &out_dir)?;
+
+    let inputs = InputsReport {
+        kind: probe_name.to_string(),
+        appimage: None,
+        extract_dir: None,
+        fex_images: fex_images.iter().map(|p| p.display().to_string()).collect(),
+        fex_rootfs_compat_overlay,
+        muvm_path: muvm_path.display().to_string(),
+        muvm_args: args
+            .guest
+            .muvm_arg
+            .iter()
+            .map(|s| s.to_string_lossy().to_string())
+            .collect(),
+        env: effective_env.clone(),
+        timeout_seconds: args.guest.timeout_seconds,
+        guest_pre: effective_guest_pre.clone(),
+        guest_cwd: args.guest.guest_cwd.clone(),
+        guest_pre_trace_log: guest_pre_trace_log(args.guest.guest_shell_trace, &out_dir)?,
+        guest_mountinfo: guest_artifact_path(
+            args.guest.dump_guest_mounts,
+            &out_dir,
+            GUEST_MOUNTINFO,
+        )?,
+        argv_after_double_dash: None,
+        args_file: None,
+        replay_of: None,
+        arch: None,
+        pty: Some(args.guest.pty_settings()),
+        redacted_keys: Vec::new(),
+        extraction_backend: None,
+        squashfs_compression_id: None,
+        hashes: input_hashes,
+    };
+    let (shareable_inputs, redacted_values) = inputs.shareable(&args.guest.redact);
+    let inputs_path = out_dir.join("inputs.json");
+    write_json(&inputs_path, &shareable_inputs)
+        .with_context(|| format!("Writing inputs {}", inputs_path.display()))?;
+
+    let PtyOutcome {
+        status,
+        output: combined,
+        timed_out,
+        screenshot,
+        ..
+    } = run_guest_command(&muvm_path, &fex_images, &inputs, &guest_cmd, &out_dir)
+        .context("Running probe")?;
+
+    pcsc.shutdown();
+
+    let log_path = out_dir.join("run.log");
+    std::fs::write(&log_path, &combined)
+        .with_context(|| format!("Writing log {}", log_path.display()))?;
+    redact_text_artifacts(&out_dir, &redacted_values)?;
+
+    let muvm_guest_status_code = parse_muvm_guest_status_code(&combined);
+    let muvm_guest_terminated_signal = parse_muvm_guest_terminated_signal(&combined);
+    let facts = read_guest_facts(&out_dir);
+    let guest_kernel_version = parse_guest_kernel_version(&facts);
+
+    let report = ProbeReport {
+        kind: inputs.kind.clone(),
+        fex_images: inputs.fex_images.clone(),
+        fex_rootfs_compat_overlay: inputs.fex_rootfs_compat_overlay.clone(),
+        muvm_path: inputs.muvm_path.clone(),
+        muvm_args: inputs.muvm_args.clone(),
+        env: shareable_inputs.env.clone(),
+        guest_pre: inputs.guest_pre.clone(),
+        muvm_exit_status: format!("{:?}", status),
+        muvm_succeeded: status.success(),
+        muvm_guest_status_code,
+        muvm_guest_terminated_signal,
+        timeout_seconds: args.guest.timeout_seconds,
+        timed_out,
+        guest_kernel_version: guest_kernel_version.clone(),
+        fex_binfmt: parse_fex_binfmt(&facts),
+        fexserver: parse_fexserver(&facts),
+        guest_kernel: guest_kernel_version
+            .as_deref()
+            .and_then(KernelVersion::parse_release),
+        host_env_passthrough: args.guest.pty_settings().host_env_passthrough(),
+        timeout_screenshot: screenshot,
+        fex_thunks: fex_thunks_report(&inputs.env),
+        guest_mounts: guest_mounts_report(&out_dir, None),
+        display_env: display_env_report(&args.kind, &out_dir, &combined),
+    };
+    let report_path = out_dir.join("run.report.json");
+    write_json(&report_path, &report)
+        .with_context(|| format!("Writing report {}", report_path.display()))?;
+    write_full_report(&out_dir, &shareable_inputs, &report)?;
+    if let ProbeKind::All { timeout_per_probe } = args.kind {
+        let probes = write_probes_report(&out_dir, &status, timed_out, timeout_per_probe)?;
+        for p in &probes.probes {
+            let exit = match p.exit_code {
+                Some(_) if p.timed_out => "timed out".to_string(),
+                Some(c) => c.to_string(),
+                None => "not run".to_string(),
+            };
+            println!("{}: {}", p.kind, exit);
+        }
+    }
+    println!("Wrote artifacts: {}", out_dir.display());
+
+    if !status.success() {
+        anyhow::bail!("muvm failed with status: {:?}", status);
+    }
+    Ok(())
+}
+
+/// Guest script for a probe. `out_dir` is only used by `probe all`, which stages
+/// each probe's script there.
+fn probe_guest_script(kind: &ProbeKind, out_dir: &Path) -> Result<String> {
+    let script = match kind {
         ProbeKind::Display => r#"set -euo pipefail
 echo '== env =='
 env | sort | egrep '^(DISPLAY|XAUTHORITY|XDG_SESSION_TYPE|WAYLAND_DISPLAY|APPDIR)=' || true
 
+echo '== locale =='
+locale 2>&1 || true
+if [ -f /usr/lib/locale/locale-archive ]; then s=present; else s=missing; fi
+echo "locale_archive: $s"
+
+echo '== fontconfig =='
+if command -v fc-list >/dev/null 2>&1; then
+    echo "fc_list_fonts: $(fc-list 2>/dev/null | wc -l)"
+else
+    echo 'fc-list not present'
+fi
+for d in /var/cache/fontconfig /usr/lib/fontconfig/cache "${HOME:-/root}/.cache/fontconfig"; do
+    if [ -d "$d" ]; then s=present; else s=missing; fi
+    echo "fontconfig_cache_dir: $s $d"
+done
+
 echo '== x11 =='
 if command -v xdpyinfo >/dev/null 2>&1; then
     xdpyinfo -display "${DISPLAY:-:1}" | sed -n '1,60p'
@@ -837,6 +2054,9 @@ fi
 "#
         .to_string(),
         ProbeKind::Gpu => r#"set -euo pipefail
+echo '== fex thunks =='
+env | sort | egrep '^FEX_THUNK' || echo 'no FEX_THUNK* env (thunks not configured)'
+
 echo '== glxinfo =='
 if command -v glxinfo >/dev/null 2>&1; then
     glxinfo -B
@@ -945,72 +2165,273 @@ fi
 "#
             )
         }
+        ProbeKind::All { timeout_per_probe } => {
+            all_probes_guest_script(out_dir, *timeout_per_probe)?
+        }
     };
+    Ok(script)
+}
 
-    let inputs = InputsReport {
-        kind: probe_name.to_string(),
-        appimage: None,
-        extract_dir: None,
-        fex_images: fex_images.iter().map(|p| p.display().to_string()).collect(),
-        fex_rootfs_compat_overlay,
-        muvm_path: muvm_path.display().to_string(),
-        muvm_args: args
-            .guest
-            .muvm_arg
-            .iter()
-            .map(|s| s.to_string_lossy().to_string())
-            .collect(),
-        env: effective_env.clone(),
-        timeout_seconds: args.guest.timeout_seconds,
-        guest_pre: effective_guest_pre.clone(),
-        argv_after_double_dash: None,
-    };
-    let inputs_path = out_dir.join("inputs.json");
-    write_json(&inputs_path, &inputs)
-        .with_context(|| format!("Writing inputs {}", inputs_path.display()))?;
+/// Write each probe's script to `<out_dir>/<probe-name>/probe.sh` and return a guest
+/// script that runs them in order via muvm's host mount, capturing `output.txt` and
+/// `exit-code.txt` next to each script. A failing probe does not stop the rest.
+///
+/// With `timeout_per_probe`, each script runs under the guest's `timeout -v` (SIGKILL 5s after
+/// SIGTERM). The script's own stderr still goes to `output.txt`; `timeout`'s goes to
+/// `timeout.txt`, so a probe that exits 124 by itself isn't mistaken for a timed-out one.
+fn all_probes_guest_script(out_dir: &Path, timeout_per_probe: Option<u64>) -> Result<String> {
+    let out_dir = out_dir
+        .canonicalize()
+        .with_context(|| format!("canonicalize {}", out_dir.display()))?;
+    let mut script = String::new();
+    for kind in &ALL_PROBE_KINDS {
+        let name = probe_name(kind);
+        let dir = out_dir.join(name);
+        std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        let probe_script = dir.join("probe.sh");
+        std::fs::write(&probe_script, probe_guest_script(kind, &dir)?)
+            .with_context(|| format!("write {}", probe_script.display()))?;
+
+        let guest_dir = format!("/run/muvm-host{}", dir.display());
+        let run = match timeout_per_probe {
+            Some(secs) => format!(
+                r#"timeout -v -k 5 {secs} /bin/bash -c 'exec 2>&1; exec /bin/bash "$0"' '{guest_dir}/probe.sh' >'{guest_dir}/output.txt' 2>'{guest_dir}/{PROBE_TIMEOUT_LOG}'"#
+            ),
+            None => format!("/bin/bash '{guest_dir}/probe.sh' >'{guest_dir}/output.txt' 2>&1"),
+        };
+        script.push_str(&format!(
+            r#"echo '== {name} =='
+rc=0
+{run} || rc=$?
+echo "$rc" >'{guest_dir}/exit-code.txt'
+echo "{name}: exit $rc"
+"#
+        ));
+    }
+    Ok(script)
+}
 
-    let (status, combined, timed_out) = run_guest_command(
-        &muvm_path,
-        &inputs.muvm_args,
-        &fex_images,
-        &inputs.env,
-        args.guest.timeout_seconds,
-        inputs.guest_pre.as_deref(),
-        &guest_cmd,
-    )
-    .context("Running probe")?;
+/// Where `probe all --timeout-per-probe` sends the guest `timeout`'s own messages.
+const PROBE_TIMEOUT_LOG: &str = "timeout.txt";
 
-    pcsc.shutdown();
+/// Whether a `timeout -v` log says it signalled the command, as opposed to the command
+/// exiting by itself (with any code, 124 included).
+fn probe_timeout_fired(timeout_log: &str) -> bool {
+    timeout_log.contains("sending signal")
+}
 
-    let log_path = out_dir.join("run.log");
-    std::fs::write(&log_path, &combined)
-        .with_context(|| format!("Writing log {}", log_path.display()))?;
+#[derive(Serialize, JsonSchema)]
+struct ProbeResult {
+    kind: String,
+    dir: String,
+    /// `None` when the guest never reached this probe (e.g. timeout).
+    exit_code: Option<i32>,
+    /// Signalled by the `--timeout-per-probe` wrapper, per its `timeout.txt`.
+    timed_out: bool,
+    output_bytes: Option<u64>,
+}
 
-    let muvm_guest_status_code = parse_muvm_guest_status_code(&combined);
-    let muvm_guest_terminated_signal = parse_muvm_guest_terminated_signal(&combined);
+#[derive(Serialize, JsonSchema)]
+struct ProbesReport {
+    muvm_exit_status: String,
+    muvm_succeeded: bool,
+    timed_out: bool,
+    timeout_per_probe_seconds: Option<u64>,
+    all_succeeded: bool,
+    probes: Vec<ProbeResult>,
+}
 
-    let report = ProbeReport {
-        kind: inputs.kind.clone(),
-        fex_images: inputs.fex_images.clone(),
-        fex_rootfs_compat_overlay: inputs.fex_rootfs_compat_overlay.clone(),
-        muvm_path: inputs.muvm_path.clone(),
-        muvm_args: inputs.muvm_args.clone(),
-        env: inputs.env.clone(),
-        guest_pre: inputs.guest_pre.clone(),
+fn write_probes_report(
+    out_dir: &Path,
+    status: &portable_pty::ExitStatus,
+    timed_out: bool,
+    timeout_per_probe: Option<u64>,
+) -> Result<ProbesReport> {
+    let probes: Vec<ProbeResult> = ALL_PROBE_KINDS
+        .iter()
+        .map(|kind| {
+            let dir = out_dir.join(probe_name(kind));
+            let exit_code = std::fs::read_to_string(dir.join("exit-code.txt"))
+                .ok()
+                .and_then(|s| s.trim().parse().ok());
+            let output_bytes = std::fs::metadata(dir.join("output.txt"))
+                .ok()
+                .map(|m| m.len());
+            ProbeResult {
+                kind: probe_name(kind).to_string(),
+                dir: dir.display().to_string(),
+                exit_code,
+                timed_out: timeout_per_probe.is_some()
+                    && std::fs::read_to_string(dir.join(PROBE_TIMEOUT_LOG))
+                        .is_ok_and(|log| probe_timeout_fired(&log)),
+                output_bytes,
+            }
+        })
+        .collect();
+    let report = ProbesReport {
         muvm_exit_status: format!("{:?}", status),
         muvm_succeeded: status.success(),
-        muvm_guest_status_code,
-        muvm_guest_terminated_signal,
-        timeout_seconds: args.guest.timeout_seconds,
         timed_out,
+        timeout_per_probe_seconds: timeout_per_probe,
+        all_succeeded: probes.iter().all(|p| p.exit_code == Some(0)),
+        probes,
     };
-    let report_path = out_dir.join("run.report.json");
-    write_json(&report_path, &report)
-        .with_context(|| format!("Writing report {}", report_path.display()))?;
-    println!("Wrote artifacts: {}", out_dir.display());
+    let path = out_dir.join("probes.report.json");
+    write_json(&path, &report).with_context(|| format!("Writing report {}", path.display()))?;
+    Ok(report)
+}
 
-    if !status.success() {
-        anyhow::bail!("muvm failed with status: {:?}", status);
+/// Artifacts the runner knows how to write, in the order they are listed.
+const KNOWN_ARTIFACTS: &[&str] = &[
+    "inputs.json",
+    "run.log",
+    "run.report.json",
+    "run.full.json",
+    "fex.log",
+    "pcsc-host.log",
+    "probes.report.json",
+    "timeout-screenshot.png",
+    "timeout-screenshot.xwd",
+    "guest-mountinfo.txt",
+    GUEST_FACTS,
+];
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct ArtifactEntry {
+    name: String,
+    known: bool,
+    expected: bool,
+    present: bool,
+    size_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct ArtifactInventory {
+    dir: String,
+    kind: Option<String>,
+    complete: bool,
+    missing: Vec<String>,
+    artifacts: Vec<ArtifactEntry>,
+}
+
+fn list_artifacts_mode(args: ListArtifactsArgs) -> Result<()> {
+    if !args.dir.is_dir() {
+        anyhow::bail!("Not a directory: {}", args.dir.display());
+    }
+
+    // The mode (and which optional artifacts apply) comes from the recorded inputs.
+    let inputs: Option<serde_json::Value> = std::fs::read_to_string(args.dir.join("inputs.json"))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok());
+    let kind = inputs
+        .as_ref()
+        .and_then(|v| v.get("kind"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let env: Vec<String> = inputs
+        .as_ref()
+        .and_then(|v| v.get("env"))
+        .and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|e| e.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let env_has = |prefix: &str| env.iter().any(|kv| kv.starts_with(prefix));
+
+    let expected = |name: &str| -> bool {
+        match name {
+            "inputs.json" | "run.log" | "run.report.json" | "run.full.json" => true,
+            "fex.log" => env_has("FEX_OUTPUTLOG="),
+            "pcsc-host.log" => env_has("PCSCLITE_CSOCK_NAME="),
+            "probes.report.json" => kind.as_deref() == Some("probe-all"),
+            _ => false,
+        }
+    };
+
+    let mut artifacts: Vec<ArtifactEntry> = KNOWN_ARTIFACTS
+        .iter()
+        .map(|name| {
+            let meta = std::fs::metadata(args.dir.join(name)).ok();
+            ArtifactEntry {
+                name: name.to_string(),
+                known: true,
+                expected: expected(name),
+                present: meta.is_some(),
+                size_bytes: meta.map(|m| m.len()),
+            }
+        })
+        .collect();
+
+    let mut extra: Vec<ArtifactEntry> = std::fs::read_dir(&args.dir)
+        .with_context(|| format!("read_dir {}", args.dir.display()))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if KNOWN_ARTIFACTS.contains(&name.as_str()) {
+                return None;
+            }
+            let meta = e.metadata().ok()?;
+            Some(ArtifactEntry {
+                name,
+                known: false,
+                expected: false,
+                present: true,
+                size_bytes: meta.is_file().then_some(meta.len()),
+            })
+        })
+        .collect();
+    extra.sort_by(|a, b| a.name.cmp(&b.name));
+    artifacts.extend(extra);
+
+    let missing: Vec<String> = artifacts
+        .iter()
+        .filter(|a| a.expected && !a.present)
+        .map(|a| a.name.clone())
+        .collect();
+    let inventory = ArtifactInventory {
+        dir: args.dir.display().to_string(),
+        kind,
+        complete: missing.is_empty(),
+        missing,
+        artifacts,
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&inventory).context("Serializing JSON")?
+        );
+    } else {
+        println!("Dir: {}", inventory.dir);
+        println!(
+            "Kind: {}",
+            inventory
+                .kind
+                .as_deref()
+                .unwrap_or("(unknown; no inputs.json)")
+        );
+        for a in &inventory.artifacts {
+            let status = match (a.present, a.expected) {
+                (true, _) => "present",
+                (false, true) => "MISSING",
+                (false, false) => "-",
+            };
+            let size = a
+                .size_bytes
+                .map(|n| format!("{n} bytes"))
+                .unwrap_or_default();
+            let note = if a.known { "" } else { " (unknown)" };
+            println!("  {:<8} {:<24} {}{}", status, a.name, size, note);
+        }
+    }
+
+    if !inventory.complete {
+        anyhow::bail!(
+            "evidence bundle is incomplete; missing: {}",
+            inventory.missing.join(", ")
+        );
     }
     Ok(())
 }
@@ -1034,7 +2455,50 @@ fn canonicalize_muvm_path(muvm_path: &Path) -> Result<PathBuf> {
     }
 }
 
-fn validate_muvm_args(muvm_path: &Path, muvm_args: &[OsString]) -> Result<()> {
+/// Where `Command::new(program)` finds `program`: paths as given, bare names (no `/`)
+/// searched in `search_path` (a `PATH`-style list).
+fn find_program(program: &Path, search_path: Option<&std::ffi::OsStr>) -> Option<PathBuf> {
+    let mut comps = program.components();
+    let bare =
+        matches!(comps.next(), Some(std::path::Component::Normal(_))) && comps.next().is_none();
+    if !bare {
+        return Some(program.to_path_buf());
+    }
+    std::env::split_paths(search_path?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Run `muvm --help` once up front so a missing or broken muvm fails fast (before extraction),
+/// instead of surfacing later as a confusing PTY error. Returns the combined help text.
+fn probe_muvm(muvm_path: &Path) -> Result<String> {
+    let out = Command::new(muvm_path)
+        .arg("--help")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "muvm at {} is not executable: {e}. Install muvm or pass --muvm-path.",
+                muvm_path.display()
+            )
+        })?;
+
+    let mut help = String::new();
+    help.push_str(&String::from_utf8_lossy(&out.stdout));
+    help.push_str(&String::from_utf8_lossy(&out.stderr));
+
+    if !out.status.success() {
+        anyhow::bail!(
+            "muvm at {} failed `--help` ({}):\n{}",
+            muvm_path.display(),
+            out.status,
+            help.trim()
+        );
+    }
+    Ok(help)
+}
+
+fn validate_muvm_args(muvm_path: &Path, muvm_help: &str, muvm_args: &[OsString]) -> Result<()> {
     // Some muvm builds support extra flags (e.g. gpu mode selection). Others will forward unknown
     // flags into the guest argv, which is confusing (e.g. `/bin/bash: --gpu-mode=...: invalid option`).
     //
@@ -1047,15 +2511,7 @@ fn validate_muvm_args(muvm_path: &Path, muvm_args: &[OsString]) -> Result<()> {
         return Ok(());
     }
 
-    let out = Command::new(muvm_path)
-        .arg("--help")
-        .output()
-        .with_context(|| format!("running {} --help", muvm_path.display()))?;
-    let mut help = String::new();
-    help.push_str(&String::from_utf8_lossy(&out.stdout));
-    help.push_str(&String::from_utf8_lossy(&out.stderr));
-
-    if !help.contains("--gpu-mode") {
+    if !muvm_help.contains("--gpu-mode") {
         anyhow::bail!(
             "{} does not appear to support `--gpu-mode`. \
 You may be using the system muvm; try `--muvm-path third_party/muvm/target/debug/muvm` (or another muvm build that supports GPU modes).",
@@ -1168,43 +2624,211 @@ fn write_json<P: AsRef<Path>, T: Serialize>(path: P, value: &T) -> Result<()> {
         .with_context(|| format!("Writing {}", path.as_ref().display()))
 }
 
-fn exit_from_run_report(run_report: &RunReport) -> Result<()> {
-    if !run_report.muvm_succeeded {
-        anyhow::bail!("muvm failed with status: {}", run_report.muvm_exit_status);
+/// Self-contained `run.full.json`: inputs + report under one object, plus run metadata.
+///
+/// `inputs.json` and `run.report.json` are still written separately for compatibility.
+#[derive(Serialize, JsonSchema)]
+struct FullReport<'a, I: Serialize, R: Serialize> {
+    timestamp: String,
+    out_dir: String,
+    runner_version: &'static str,
+    inputs: &'a I,
+    report: &'a R,
+}
+
+fn write_full_report<I: Serialize, R: Serialize>(
+    out_dir: &Path,
+    inputs: &I,
+    report: &R,
+) -> Result<()> {
+    let full = FullReport {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        out_dir: out_dir.display().to_string(),
+        runner_version: env!("CARGO_PKG_VERSION"),
+        inputs,
+        report,
+    };
+    let full_path = out_dir.join("run.full.json");
+    write_json(&full_path, &full)
+        .with_context(|| format!("Writing merged report {}", full_path.display()))
+}
+
+/// Exit code for a run timed out by `--timeout-seconds` (matches GNU `timeout`).
+const EXIT_TIMEOUT: i32 = 124;
+/// Exit code when muvm itself failed and no guest status was reported.
+const EXIT_MUVM_FAILED: i32 = 125;
+
+/// Exit code convention for `run`/`replay`/legacy:
+///
+/// - `0`: guest exited 0
+/// - `124`: timed out (`--timeout-seconds`)
+/// - `125`: muvm failed before reporting a guest status (launch failure)
+/// - `128 + N`: guest terminated by signal N
+/// - otherwise: the guest's own exit code
+fn run_exit(run_report: &RunReport) -> (i32, &'static str) {
+    if run_report.timed_out {
+        return (EXIT_TIMEOUT, "timeout");
     }
-    if let Some(code) = run_report.muvm_guest_status_code {
-        if code != 0 {
-            anyhow::bail!("guest process exited with status code: {}", code);
-        }
+    if let Some(sig) = run_report.muvm_guest_terminated_signal {
+        return (128 + sig, "guest_signal");
+    }
+    match run_report.muvm_guest_status_code {
+        Some(0) => (0, "ok"),
+        Some(code) => (code, "guest_exit"),
+        None if !run_report.muvm_succeeded => (EXIT_MUVM_FAILED, "muvm_failed"),
+        None => (0, "ok"),
     }
-    Ok(())
 }
 
-fn resolve_objcopy_path(explicit: Option<&Path>) -> Result<OsString> {
-    if let Some(p) = explicit {
-        return Ok(p.as_os_str().to_os_string());
+fn exit_from_run_report(run_report: &RunReport) -> Result<()> {
+    let (code, reason) = run_exit(run_report);
+    match reason {
+        "ok" => return Ok(()),
+        "timeout" => eprintln!(
+            "Error: timed out (muvm status: {})",
+            run_report.muvm_exit_status
+        ),
+        "muvm_failed" => {
+            eprintln!(
+                "Error: muvm failed with status: {}",
+                run_report.muvm_exit_status
+            )
+        }
+        "guest_signal" => eprintln!("Error: guest process terminated by signal {}", code - 128),
+        _ => eprintln!("Error: guest process exited with status code: {code}"),
     }
+    std::process::exit(code);
+}
 
-    fn works(candidate: &str, arg: &str) -> bool {
-        Command::new(candidate)
-            .arg(arg)
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false)
+/// Known failure signature in a failed run's output, most specific first.
+#[derive(Debug, Serialize, JsonSchema)]
+struct FailureClass {
+    /// `missing_shared_library`, `missing_interpreter`, `fex_unsupported_instruction`,
+    /// `display_connection_failure`, `segfault`, or `unknown`.
+    class: String,
+    /// Output line (or guest signal) that matched.
+    evidence: Option<String>,
+}
+
+/// Lowercased substrings of an output line, per failure class, checked in order.
+const FAILURE_SIGNATURES: &[(&str, &[&str])] = &[
+    (
+        "missing_shared_library",
+        &["error while loading shared libraries"],
+    ),
+    (
+        "missing_interpreter",
+        &[
+            "bad interpreter",
+            "required file not found",
+            "exec format error",
+            "ld-linux-x86-64.so.2: no such file",
+        ],
+    ),
+    (
+        "fex_unsupported_instruction",
+        &[
+            "unhandled op",
+            "unknown instruction",
+            "unsupported instruction",
+            "illegal instruction",
+        ],
+    ),
+    (
+        "display_connection_failure",
+        &[
+            "cannot open display",
+            "can't open display",
+            "failed to open display",
+            "could not connect to display",
+            "could not connect to any x display",
+            "failed to connect to wayland",
+            "gtk_init_check",
+        ],
+    ),
+    ("segfault", &["segmentation fault", "sigsegv"]),
+];
+
+fn print_failure_class(failure: Option<&FailureClass>) {
+    if let Some(f) = failure {
+        match &f.evidence {
+            Some(evidence) => println!("Failure class: {} ({evidence})", f.class),
+            None => println!("Failure class: {}", f.class),
+        }
     }
+}
 
-    for candidate in ["objcopy", "llvm-objcopy", "eu-objcopy"] {
-        if works(candidate, "--version") || works(candidate, "-V") {
-            return Ok(OsString::from(candidate));
+/// Classify why a run failed from its combined output, falling back to the guest's fatal
+/// signal. `None` when the run exited 0.
+fn classify_failure(run_report: &RunReport, combined: &str) -> Option<FailureClass> {
+    if run_exit(run_report).1 == "ok" {
+        return None;
+    }
+    for (class, needles) in FAILURE_SIGNATURES {
+        let hit = combined.lines().find(|line| {
+            let lower = line.to_ascii_lowercase();
+            needles.iter().any(|n| lower.contains(n))
+        });
+        if let Some(line) = hit {
+            return Some(FailureClass {
+                class: class.to_string(),
+                evidence: Some(line.trim().to_string()),
+            });
         }
     }
+    let signal = run_report.muvm_guest_terminated_signal;
+    let class = match signal {
+        Some(4) => "fex_unsupported_instruction",
+        Some(11) => "segfault",
+        _ => "unknown",
+    };
+    Some(FailureClass {
+        class: class.to_string(),
+        evidence: signal.map(|sig| format!("guest terminated by {}", signal_name(sig as u32))),
+    })
+}
 
-    anyhow::bail!(
-        "No usable objcopy found (tried: objcopy, llvm-objcopy, eu-objcopy). Install binutils (or llvm/eu-binutils) or pass --objcopy-path."
-    )
+/// Output of an AppRun (or the type-2 runtime it re-execs) trying to FUSE-mount the image,
+/// which fails under muvm + FEX and drowns out the real error.
+#[derive(Debug, Serialize, JsonSchema)]
+struct FuseMountAttempt {
+    /// Output line that matched.
+    evidence: String,
+    hint: &'static str,
+}
+
+/// Lowercased substrings of AppImage runtime / libfuse errors. Kept to whole phrases: a bare
+/// `fuse: ` also matches `refuse: ` and `diffuse: ` in unrelated output.
+const FUSE_MOUNT_SIGNATURES: &[&str] = &[
+    "/dev/fuse",
+    "fusermount",
+    "error loading libfuse",
+    "fuse: device not found",
+    "fuse: mount failed",
+    "require fuse",
+    "cannot mount appimage",
+    "fuse setup",
+];
+
+const FUSE_MOUNT_HINT: &str = "the runner already extracted the AppImage, so its FUSE mount \
+     is not needed: pass `-e APPIMAGE_EXTRACT_AND_RUN=1`, or check whether AppRun re-executes \
+     the .AppImage instead of its extracted payload";
+
+fn detect_fuse_mount_attempt(combined: &str) -> Option<FuseMountAttempt> {
+    let line = combined.lines().find(|line| {
+        let lower = line.to_ascii_lowercase();
+        FUSE_MOUNT_SIGNATURES.iter().any(|n| lower.contains(n))
+    })?;
+    Some(FuseMountAttempt {
+        evidence: line.trim().to_string(),
+        hint: FUSE_MOUNT_HINT,
+    })
+}
+
+fn print_fuse_mount_hint(attempt: Option<&FuseMountAttempt>) {
+    if let Some(a) = attempt {
+        println!("Attempted FUSE mount ({}): {}", a.evidence, a.hint);
+    }
 }
 
 fn get_offset(path: &Path) -> Result<u64> {
@@ -1278,43 +2902,259 @@ fn verify_superblock(file: &mut std::fs::File, offset: u64) -> Result<bool> {
     Ok(true)
 }
 
-fn extract_appimage(path: &Path, offset: u64, extract_with: ExtractWith) -> Result<PathBuf> {
-    // Determine cache directory
+#[derive(Debug, Serialize, JsonSchema)]
+struct SquashfsInfo {
+    appimage: String,
+    offset: u64,
+    version: String,
+    compression_id: u16,
+    compression: String,
+    block_size: u32,
+    bytes_used: u64,
+    inode_count: u32,
+    /// Whether this binary can use `--extract-with squashfs-ng`.
+    squashfs_ng_available: bool,
+}
+
+fn squashfs_compression_name(id: u16) -> String {
+    let name = match id {
+        1 => "gzip",
+        2 => "lzma",
+        3 => "lzo",
+        4 => "xz",
+        5 => "lz4",
+        6 => "zstd",
+        _ => return format!("unknown({id})"),
+    };
+    name.to_string()
+}
+
+fn artifact_schema(artifact: SchemaArtifact) -> schemars::Schema {
+    match artifact {
+        SchemaArtifact::Inputs => schemars::schema_for!(InputsReport),
+        SchemaArtifact::RunReport => schemars::schema_for!(RunnerReport),
+        SchemaArtifact::ProbeReport => schemars::schema_for!(ProbeReport),
+        SchemaArtifact::RunFull => {
+            schemars::schema_for!(FullReport<'static, InputsReport, RunnerReport>)
+        }
+        SchemaArtifact::ProbeFull => {
+            schemars::schema_for!(FullReport<'static, InputsReport, ProbeReport>)
+        }
+        SchemaArtifact::ProbesReport => schemars::schema_for!(ProbesReport),
+        SchemaArtifact::ListArtifacts => schemars::schema_for!(ArtifactInventory),
+        SchemaArtifact::SquashfsInfo => schemars::schema_for!(SquashfsInfo),
+    }
+}
+
+fn emit_schema_mode(args: EmitSchemaArgs) -> Result<()> {
+    use clap::ValueEnum;
+
+    let value = match args.artifact {
+        Some(artifact) => serde_json::to_value(artifact_schema(artifact))?,
+        None => serde_json::Value::Object(
+            SchemaArtifact::value_variants()
+                .iter()
+                .map(|a| {
+                    let name = a.to_possible_value().expect("no skipped variants");
+                    Ok((
+                        name.get_name().to_string(),
+                        serde_json::to_value(artifact_schema(*a))?,
+                    ))
+                })
+                .collect::<Result<_>>()?,
+        ),
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&value).context("Serializing JSON")?
+    );
+    Ok(())
+}
+
+fn squashfs_info_mode(args: SquashfsInfoArgs) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let offset = get_offset(&args.appimage)
+        .with_context(|| format!("Finding SquashFS in {}", args.appimage.display()))?;
+    let mut f = std::fs::File::open(&args.appimage)
+        .with_context(|| format!("open {}", args.appimage.display()))?;
+    // get_offset already validated magic, s_major and block size here.
+    if !verify_superblock(&mut f, offset)? {
+        anyhow::bail!("SquashFS superblock at {offset} failed verification");
+    }
+    f.seek(SeekFrom::Start(offset))
+        .context("seek to squashfs superblock")?;
+    let mut sb = [0u8; 96];
+    f.read_exact(&mut sb)
+        .with_context(|| format!("read squashfs superblock at {}", offset))?;
+
+    let compression_id = u16::from_le_bytes([sb[20], sb[21]]);
+    let info = SquashfsInfo {
+        appimage: args.appimage.display().to_string(),
+        offset,
+        version: format!(
+            "{}.{}",
+            u16::from_le_bytes([sb[28], sb[29]]),
+            u16::from_le_bytes([sb[30], sb[31]])
+        ),
+        compression_id,
+        compression: squashfs_compression_name(compression_id),
+        block_size: u32::from_le_bytes(sb[12..16].try_into().unwrap()),
+        bytes_used: read_squashfs_bytes_used(&args.appimage, offset)?,
+        inode_count: u32::from_le_bytes(sb[4..8].try_into().unwrap()),
+        squashfs_ng_available: cfg!(feature = "squashfs-ng"),
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&info).context("Serializing JSON")?
+        );
+    } else {
+        println!("  {:<22} {}", "appimage", info.appimage);
+        println!("  {:<22} {}", "offset", info.offset);
+        println!("  {:<22} {}", "version", info.version);
+        println!(
+            "  {:<22} {} (id {})",
+            "compression", info.compression, info.compression_id
+        );
+        println!("  {:<22} {}", "block_size", info.block_size);
+        println!("  {:<22} {}", "bytes_used", info.bytes_used);
+        println!("  {:<22} {}", "inode_count", info.inode_count);
+        println!(
+            "  {:<22} {}",
+            "squashfs_ng_available", info.squashfs_ng_available
+        );
+    }
+    Ok(())
+}
+
+/// Bytes hashed from the start of the file and from the start of the SquashFS payload.
+const CACHE_KEY_SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// Stable cache key: FNV-1a 64 over the file size, the first 64 KiB and the first 64 KiB
+/// of the SquashFS payload.
+///
+/// The leading bytes are mostly the AppImage runtime, which many AppImages share; the
+/// payload sample covers the superblock (creation time, size), which tells builds apart.
+/// Unlike `DefaultHasher`, FNV is fixed, so the key survives toolchain upgrades and machines.
+fn appimage_cache_key(path: &Path, squashfs_offset: u64) -> Result<u64> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+        for &b in bytes {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        hash
+    }
+
+    let mut file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let size = file
+        .metadata()
+        .with_context(|| format!("stat {}", path.display()))?
+        .len();
+    let mut hash = fnv1a(FNV_OFFSET, &size.to_le_bytes());
+
+    let mut buf = Vec::new();
+    for start in [0, squashfs_offset] {
+        buf.clear();
+        file.seek(SeekFrom::Start(start))
+            .with_context(|| format!("seek {}", path.display()))?;
+        (&mut file)
+            .take(CACHE_KEY_SAMPLE_BYTES)
+            .read_to_end(&mut buf)
+            .with_context(|| format!("read {}", path.display()))?;
+        hash = fnv1a(hash, &buf);
+    }
+    Ok(hash)
+}
+
+/// Cache dir `extract_appimage` uses for the AppImage at `path`.
+fn extraction_cache_dir(path: &Path, offset: u64) -> Result<PathBuf> {
     let home = std::env::var("HOME").context("HOME not set")?;
     let cache_base = PathBuf::from(home).join(".cache/appimage-runner");
 
-    // Use filename + simple hash of path for uniqueness
+    // Filename for readability + a content key so the same AppImage always maps here.
     let filename = path.file_name().unwrap_or_default().to_string_lossy();
-    use std::hash::{Hash, Hasher};
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    path.hash(&mut hasher);
-    let hash = hasher.finish();
+    let key = appimage_cache_key(path, offset)?;
+    Ok(cache_base.join(format!("{filename}-{key:016x}")))
+}
 
-    let extract_dir = cache_base.join(format!("{}-{}", filename, hash));
+/// Removes an `embedded.squashfs` an earlier `--keep` run left in the cache dir.
+fn remove_stale_squashfs_copy(extract_dir: &Path) {
+    let sfs_path = extract_dir.join("embedded.squashfs");
+    let Ok(meta) = std::fs::metadata(&sfs_path) else {
+        return;
+    };
+    if std::fs::remove_file(&sfs_path).is_ok() {
+        println!(
+            "Removed cached {} ({} bytes)",
+            sfs_path.display(),
+            meta.len()
+        );
+    }
+}
+
+fn extract_appimage(
+    path: &Path,
+    offset: u64,
+    extract_with: ExtractWith,
+    keep: bool,
+) -> Result<PathBuf> {
+    let extract_dir = extraction_cache_dir(path, offset)?;
     let squashfs_root = extract_dir.join("squashfs-root");
+    // Present while an extraction is in flight; a leftover marker means the previous
+    // extraction was interrupted and `squashfs-root` can't be trusted.
+    let incomplete_marker = extract_dir.join(".extract-incomplete");
 
     if squashfs_root.exists() {
-        // Assume already extracted
-        // TODO: Check freshness?
-        return Ok(squashfs_root);
+        if !incomplete_marker.exists() {
+            // Assume already extracted
+            // TODO: Check freshness?
+            if !keep {
+                remove_stale_squashfs_copy(&extract_dir);
+            }
+            return Ok(squashfs_root);
+        }
+        eprintln!(
+            "Previous extraction into {} was interrupted; re-extracting.",
+            squashfs_root.display()
+        );
+        std::fs::remove_dir_all(&squashfs_root)
+            .with_context(|| format!("remove incomplete {}", squashfs_root.display()))?;
     }
 
     std::fs::create_dir_all(&extract_dir).context("Failed to create cache dir")?;
+    std::fs::write(&incomplete_marker, b"")
+        .with_context(|| format!("write {}", incomplete_marker.display()))?;
 
-    match extract_with {
+    let backend_marker = extract_dir.join(EXTRACT_BACKEND_MARKER);
+    let extract_with = match extract_with {
+        ExtractWith::Auto if cfg!(feature = "squashfs-ng") => ExtractWith::SquashfsNg,
+        ExtractWith::Auto if unsquashfs_available() => ExtractWith::Unsquashfs,
         ExtractWith::Auto => {
-            #[cfg(feature = "squashfs-ng")]
-            {
-                extract_appimage_squashfs_ng(path, offset, &extract_dir, &squashfs_root)
-                    .context("extract via squashfs-ng")?;
-                return Ok(squashfs_root);
-            }
-
-            #[cfg(not(feature = "squashfs-ng"))]
-            {
-                extract_appimage_unsquashfs(path, offset, &squashfs_root)
-                    .context("extract via unsquashfs")?;
-            }
+            eprintln!(
+                "unsquashfs not found; extracting with the built-in SquashFS reader (slow). \
+                 Install squashfs-tools for faster extraction."
+            );
+            ExtractWith::Builtin
+        }
+        explicit => explicit,
+    };
+    let backend = match extract_with {
+        ExtractWith::Auto => unreachable!("resolved above"),
+        ExtractWith::Unsquashfs => "unsquashfs",
+        ExtractWith::SquashfsNg => "squashfs-ng",
+        ExtractWith::Builtin => "builtin",
+    };
+    match extract_with {
+        ExtractWith::Auto => unreachable!("resolved above"),
+        ExtractWith::Builtin => {
+            extract_appimage_builtin(path, offset, &squashfs_root)
+                .context("extract via the built-in SquashFS reader")?;
         }
         ExtractWith::Unsquashfs => {
             extract_appimage_unsquashfs(path, offset, &squashfs_root)
@@ -1323,12 +3163,13 @@ fn extract_appimage(path: &Path, offset: u64, extract_with: ExtractWith) -> Resu
         ExtractWith::SquashfsNg => {
             #[cfg(feature = "squashfs-ng")]
             {
-                extract_appimage_squashfs_ng(path, offset, &extract_dir, &squashfs_root)
+                extract_appimage_squashfs_ng(path, offset, &extract_dir, &squashfs_root, keep)
                     .context("extract via squashfs-ng")?;
             }
 
             #[cfg(not(feature = "squashfs-ng"))]
             {
+                let _ = keep;
                 anyhow::bail!(
                     "--extract-with=squashfs-ng requires building with Cargo feature `squashfs-ng`"
                 );
@@ -1336,10 +3177,53 @@ fn extract_appimage(path: &Path, offset: u64, extract_with: ExtractWith) -> Resu
         }
     }
 
+    std::fs::write(&backend_marker, backend)
+        .with_context(|| format!("write {}", backend_marker.display()))?;
+    std::fs::remove_file(&incomplete_marker)
+        .with_context(|| format!("remove {}", incomplete_marker.display()))?;
     Ok(squashfs_root)
 }
 
+/// Written next to `squashfs-root` with the backend that produced it.
+const EXTRACT_BACKEND_MARKER: &str = ".extract-backend";
+
+/// Backend (`squashfs-ng`, `unsquashfs` or `builtin`) that extracted `squashfs_root`, which may be an
+/// earlier run's cached tree. `unknown` if it was extracted before this was recorded.
+fn extraction_backend(squashfs_root: &Path) -> String {
+    std::fs::read_to_string(squashfs_root.with_file_name(EXTRACT_BACKEND_MARKER))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Compression id from the SquashFS superblock at `offset` (see `squashfs_compression_name`).
+fn squashfs_compression_id(path: &Path, offset: u64) -> Result<u16> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut f = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    f.seek(SeekFrom::Start(offset + 20))
+        .context("seek to squashfs compression id")?;
+    let mut id = [0u8; 2];
+    f.read_exact(&mut id)
+        .with_context(|| format!("read squashfs superblock at {}", offset))?;
+    Ok(u16::from_le_bytes(id))
+}
+
+fn print_extraction(backend: &str, compression_id: Option<u16>) {
+    let compression = compression_id
+        .map(squashfs_compression_name)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("Extraction backend: {backend} (compression: {compression})");
+}
+
 fn extract_appimage_unsquashfs(path: &Path, offset: u64, squashfs_root: &Path) -> Result<()> {
+    // Extract into a sibling temp dir and rename on success, so an interrupted unsquashfs
+    // never leaves a partial tree at `squashfs-root`.
+    let tmp_root = squashfs_root.with_file_name("squashfs-root.partial");
+    if tmp_root.exists() {
+        std::fs::remove_dir_all(&tmp_root)
+            .with_context(|| format!("remove stale {}", tmp_root.display()))?;
+    }
+
     // Run unsquashfs
     // unsquashfs -no-xattrs -o <offset> -d <dest> <path>
     let status = Command::new("unsquashfs")
@@ -1347,7 +3231,7 @@ fn extract_appimage_unsquashfs(path: &Path, offset: u64, squashfs_root: &Path) -
         .arg("-o")
         .arg(offset.to_string())
         .arg("-d")
-        .arg(squashfs_root)
+        .arg(&tmp_root)
         .arg(path)
         .status()
         .context("Failed to run unsquashfs")?;
@@ -1355,15 +3239,52 @@ fn extract_appimage_unsquashfs(path: &Path, offset: u64, squashfs_root: &Path) -
     if !status.success() {
         anyhow::bail!("unsquashfs failed");
     }
+
+    std::fs::rename(&tmp_root, squashfs_root).with_context(|| {
+        format!(
+            "rename {} -> {}",
+            tmp_root.display(),
+            squashfs_root.display()
+        )
+    })?;
     Ok(())
 }
 
+/// Whether an `unsquashfs` binary can be spawned (`auto` falls back to `builtin` otherwise).
+fn unsquashfs_available() -> bool {
+    Command::new("unsquashfs")
+        .arg("-version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+fn extract_appimage_builtin(path: &Path, offset: u64, squashfs_root: &Path) -> Result<()> {
+    // Same temp-dir-then-rename scheme as `extract_appimage_unsquashfs`.
+    let tmp_root = squashfs_root.with_file_name("squashfs-root.partial");
+    if tmp_root.exists() {
+        std::fs::remove_dir_all(&tmp_root)
+            .with_context(|| format!("remove stale {}", tmp_root.display()))?;
+    }
+    squashfs::extract(path, offset, &tmp_root)?;
+    std::fs::rename(&tmp_root, squashfs_root).with_context(|| {
+        format!(
+            "rename {} -> {}",
+            tmp_root.display(),
+            squashfs_root.display()
+        )
+    })
+}
+
 #[cfg(feature = "squashfs-ng")]
 fn extract_appimage_squashfs_ng(
     appimage_path: &Path,
     offset: u64,
     extract_dir: &Path,
     squashfs_root: &Path,
+    keep: bool,
 ) -> Result<()> {
     use anyhow::anyhow;
     use squashfs_ng::read::{Archive, Data};
@@ -1372,25 +3293,66 @@ fn extract_appimage_squashfs_ng(
 
     std::fs::create_dir_all(squashfs_root).context("create squashfs-root")?;
 
-    // squashfs-ng can only open archives by path and expects the superblock at file offset 0.
-    // AppImages embed SquashFS at a non-zero offset, so we copy the SquashFS payload into a
-    // cache file and then traverse/extract using squashfs-ng.
+    // squashfs-ng can only open archives by path and expects the superblock at file offset 0
+    // (the 0.1.x API exposes neither an offset nor a custom reader). AppImages embed SquashFS
+    // at a non-zero offset, so we copy the SquashFS payload into a cache file and then
+    // traverse/extract using squashfs-ng.
     let sfs_path = extract_dir.join("embedded.squashfs");
     if !sfs_path.exists() {
         let bytes_used = read_squashfs_bytes_used(appimage_path, offset)
             .context("read bytes_used from squashfs superblock")?;
 
+        // Fail before copying rather than filling the cache filesystem halfway through.
+        let available = available_disk_bytes(extract_dir)
+            .with_context(|| format!("statvfs {}", extract_dir.display()))?;
+        if bytes_used > available {
+            anyhow::bail!(
+                "not enough space in {} to copy the embedded SquashFS ({} bytes needed, {} available). \
+Free some space or use `--extract-with=unsquashfs`, which reads the AppImage in place.",
+                extract_dir.display(),
+                bytes_used,
+                available
+            );
+        }
+
         let mut src = File::open(appimage_path)
             .with_context(|| format!("open {}", appimage_path.display()))?;
         src.seek(SeekFrom::Start(offset))
             .context("seek to squashfs offset")?;
 
-        let mut dst =
-            File::create(&sfs_path).with_context(|| format!("create {}", sfs_path.display()))?;
+        // Copy into a sibling temp file and rename, so an interrupted copy is never mistaken
+        // for a complete payload on the next run.
+        let partial_path = extract_dir.join("embedded.squashfs.partial");
+        let mut dst = File::create(&partial_path)
+            .with_context(|| format!("create {}", partial_path.display()))?;
+
+        let mut remaining = bytes_used;
+        let mut buf = vec![0u8; 1024 * 1024];
+        while remaining > 0 {
+            let want = remaining.min(buf.len() as u64) as usize;
+            let n = src.read(&mut buf[..want]).with_context(|| {
+                format!("read squashfs payload from {}", appimage_path.display())
+            })?;
+            if n == 0 {
+                let _ = std::fs::remove_file(&partial_path);
+                anyhow::bail!(
+                    "AppImage ended {} bytes before the end of the embedded SquashFS",
+                    remaining
+                );
+            }
+            dst.write_all(&buf[..n])
+                .with_context(|| format!("write {}", partial_path.display()))?;
+            remaining -= n as u64;
+        }
+        drop(dst);
 
-        let mut limited = src.take(bytes_used);
-        std::io::copy(&mut limited, &mut dst)
-            .with_context(|| format!("copy squashfs payload to {}", sfs_path.display()))?;
+        std::fs::rename(&partial_path, &sfs_path).with_context(|| {
+            format!(
+                "rename {} -> {}",
+                partial_path.display(),
+                sfs_path.display()
+            )
+        })?;
     }
 
     let archive =
@@ -1504,10 +3466,29 @@ fn extract_appimage_squashfs_ng(
     }
 
     extract_node(squashfs_root, root, &mut hardlinks).context("extract archive")?;
+    drop(archive);
+
+    if !keep {
+        // The payload copy is as large as the AppImage itself; reclaim it once extracted.
+        let _ = std::fs::remove_file(&sfs_path);
+    }
     Ok(())
 }
 
 #[cfg(feature = "squashfs-ng")]
+fn available_disk_bytes(path: &Path) -> Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .context("path contains an interior NUL byte")?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    let rc = unsafe { libc::statvfs(c_path.as_ptr(), &mut st) };
+    if rc != 0 {
+        return Err(std::io::Error::last_os_error()).context("statvfs");
+    }
+    Ok(st.f_bavail as u64 * st.f_frsize as u64)
+}
+
 fn read_squashfs_bytes_used(appimage_path: &Path, offset: u64) -> Result<u64> {
     use std::fs::File;
     use std::io::{Read, Seek, SeekFrom};
@@ -1533,17 +3514,20 @@ fn run_appimage(
     extract_dir: &Path,
     args: &[String],
     envs: &[String],
+    emu_fex: bool,
     fex_images: &[PathBuf],
     muvm_path: &Path,
     muvm_args: &[OsString],
     timeout_seconds: Option<u64>,
     guest_pre: Option<&str>,
+    pty: &PtySettings,
+    facts_dir: Option<&Path>,
 ) -> Result<(RunReport, String)> {
     let apprun = extract_dir.join("AppRun");
 
     // Some AppImages ship AppRun as a script (e.g. #!/bin/bash). muvm+FEX expects an ELF
     // entrypoint, so detect scripts and run them via their interpreter explicitly.
-    let resolved = resolve_entrypoint(&apprun)
+    let mut resolved = resolve_entrypoint(&apprun)
         .with_context(|| format!("Resolving AppRun entrypoint: {}", apprun.display()))?;
     let entry = resolved.entry.clone();
     let entry_args = resolved.entry_args.clone();
@@ -1557,11 +3541,14 @@ fn run_appimage(
     // Important: muvm is order-sensitive for some flags, and expects them before `--emu=fex`.
     argv.extend(muvm_args.iter().map(|s| s.to_string_lossy().to_string()));
 
-    argv.push("--emu=fex".to_string());
+    // Native (aarch64) payloads skip FEX entirely; FEX images only make sense under emulation.
+    if emu_fex {
+        argv.push("--emu=fex".to_string());
 
-    for img in fex_images {
-        argv.push("--fex-image".to_string());
-        argv.push(img.display().to_string());
+        for img in fex_images {
+            argv.push("--fex-image".to_string());
+            argv.push(img.display().to_string());
+        }
     }
 
     // Set APPDIR (Required by AppImage spec)
@@ -1576,79 +3563,221 @@ fn run_appimage(
 
     argv.push("--".to_string());
 
-    if let Some(pre) = guest_pre {
+    let facts = match facts_dir {
+        Some(dir) => guest_artifact_path(true, dir, GUEST_FACTS)?,
+        None => None,
+    };
+    if facts.is_some() || guest_pre.is_some() {
         // Run an inline prelude in the guest before executing the AppImage entrypoint.
         // We avoid writing any wrapper scripts into the extracted AppImage directory.
         //
         // bash -lc '<pre>; exec "$@"' bash <entry> <entry_args...> <args...>
+        let mut script = String::new();
+        if let Some(path) = facts.as_deref() {
+            script.push_str(&guest_facts_prelude(path));
+        }
+        if let Some(pre) = guest_pre {
+            script.push_str(&format!("set -euo pipefail\n{pre}\n"));
+        }
+        if let Some(path) = facts.as_deref() {
+            script.push_str(&guest_entry_check(path));
+        }
+        script.push_str("exec \"$@\"");
         argv.push("/bin/bash".to_string());
-        argv.push("-lc".to_string());
-        argv.push(format!("set -euo pipefail\n{}\nexec \"$@\"", pre));
+        argv.push(if guest_pre.is_some() { "-lc" } else { "-c" }.to_string());
+        argv.push(script);
         argv.push("bash".to_string());
-        argv.push(entry.display().to_string());
-        argv.extend(entry_args);
-        argv.extend(args.iter().cloned());
-    } else {
-        argv.push(entry.display().to_string());
-        argv.extend(entry_args);
-        argv.extend(args.iter().cloned());
     }
+    argv.push(entry.display().to_string());
+    argv.extend(entry_args);
+    argv.extend(args.iter().cloned());
 
     let timeout = timeout_seconds.map(Duration::from_secs);
-    let (status, combined, timed_out) = run_in_pty(muvm_path, &argv, timeout)
+    let outcome = run_in_pty(muvm_path, &argv, timeout, pty)
         .with_context(|| format!("Failed to run AppRun via muvm ({})", muvm_path.display()))?;
+    let combined = outcome.output;
     let muvm_guest_status_code = parse_muvm_guest_status_code(&combined);
     let muvm_guest_terminated_signal = parse_muvm_guest_terminated_signal(&combined);
+    let facts = facts_dir.map(read_guest_facts).unwrap_or_default();
+    resolved.guest_entry = parse_guest_entry(&facts);
 
     Ok((
         RunReport {
             entrypoint: resolved,
-            muvm_exit_status: format!("{:?}", status),
-            muvm_succeeded: status.success(),
+            muvm_exit_status: format!("{:?}", outcome.status),
+            muvm_succeeded: outcome.status.success(),
             muvm_guest_status_code,
             muvm_guest_terminated_signal,
-            timed_out,
+            timed_out: outcome.timed_out,
+            guest_kernel_version: parse_guest_kernel_version(&facts),
+            fex_binfmt: parse_fex_binfmt(&facts),
+            fexserver: parse_fexserver(&facts),
+            stdin: outcome.stdin,
+            timeout_screenshot: outcome.screenshot,
         },
         combined,
     ))
 }
 
+/// Prefix of the guest facts line with the guest's `uname -r`, recorded before anything else.
+const GUEST_KERNEL_MARKER: &str = "appimage-runner: guest kernel ";
+const GUEST_KERNEL_ECHO: &str = "echo \"appimage-runner: guest kernel $(uname -r)\"";
+
+/// Prefix of the guest facts line with its FEX binfmt_misc entry (lines joined by `;`),
+/// or `unregistered`.
+const GUEST_BINFMT_MARKER: &str = "appimage-runner: fex binfmt ";
+
+/// Prefix of the guest facts line with its FEXServer socket(s) (or `none`) and whether a
+/// FEXServer process is running, checked before anything launches FEX.
+const GUEST_FEXSERVER_MARKER: &str = "appimage-runner: fexserver ";
+const GUEST_FEXSERVER_ECHO: &str = "fs=$(ls -d \"${XDG_RUNTIME_DIR:-/run/user/$(id -u)}\"/*.FEXServer.Socket \
+2>/dev/null | paste -sd, -)\necho \"appimage-runner: fexserver socket=${fs:-none} \
+running=$(grep -lx FEXServer /proc/[0-9]*/comm >/dev/null 2>&1 && echo yes || echo no)\"";
+
+/// Written by the guest into the out dir: the `GUEST_*_MARKER` lines (kernel release, FEX
+/// binfmt and FEXServer state, entrypoint state), kept out of the app's own output.
+const GUEST_FACTS: &str = "guest-facts.txt";
+
+/// Lines the guest records before anything else: kernel release, FEX binfmt and FEXServer
+/// state, written to `guest_path` (see `GUEST_FACTS`).
+fn guest_facts_prelude(guest_path: &str) -> String {
+    let path = guest_path.replace('\'', r"'\''");
+    format!(
+        "{{\n{GUEST_KERNEL_ECHO}\necho \"{GUEST_BINFMT_MARKER}$(tr '\\n' ';' 2>/dev/null \
+         <{FEX_BINFMT_PATH} || echo unregistered)\"\n{GUEST_FEXSERVER_ECHO}\n}} >'{path}' \
+         2>/dev/null || true\n"
+    )
+}
+
+/// Appends the entrypoint state to the guest facts file just before `exec`.
+fn guest_entry_check(guest_path: &str) -> String {
+    let path = guest_path.replace('\'', r"'\''");
+    format!("{{ {GUEST_ENTRY_CHECK}; }} >>'{path}' 2>/dev/null || true\n")
+}
+
+/// The guest facts file from `out_dir`; empty if the guest never wrote it.
+fn read_guest_facts(out_dir: &Path) -> String {
+    std::fs::read_to_string(out_dir.join(GUEST_FACTS)).unwrap_or_default()
+}
+
+/// Whether FEX is registered with binfmt_misc in the guest, so x86_64 binaries exec'd
+/// without an explicit FEX launch still route through it (and with which interpreter).
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+struct FexBinfmtReport {
+    registered: bool,
+    #[serde(flatten)]
+    entry: BinfmtEntry,
+}
+
+fn parse_fex_binfmt(combined: &str) -> Option<FexBinfmtReport> {
+    combined.lines().find_map(|line| {
+        let state = line.trim().strip_prefix(GUEST_BINFMT_MARKER)?.trim();
+        Some(if state == "unregistered" {
+            FexBinfmtReport {
+                registered: false,
+                entry: BinfmtEntry::default(),
+            }
+        } else {
+            FexBinfmtReport {
+                registered: true,
+                entry: parse_binfmt_entry(&state.replace(';', "\n")),
+            }
+        })
+    })
+}
+
+/// FEXServer state in the guest before the app starts. A socket with no server behind it is
+/// stale: FEX will try to connect to it instead of spawning its own server.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+struct FexServerReport {
+    socket_present: bool,
+    sockets: Vec<String>,
+    running: bool,
+    stale_socket: bool,
+}
+
+fn parse_fexserver(combined: &str) -> Option<FexServerReport> {
+    combined.lines().find_map(|line| {
+        let state = line.trim().strip_prefix(GUEST_FEXSERVER_MARKER)?;
+        let mut sockets = Vec::new();
+        let mut running = false;
+        for field in state.split_whitespace() {
+            match field.split_once('=') {
+                Some(("socket", v)) if v != "none" => {
+                    sockets = v.split(',').map(str::to_string).collect();
+                }
+                Some(("running", v)) => running = v == "yes",
+                _ => {}
+            }
+        }
+        Some(FexServerReport {
+            socket_present: !sockets.is_empty(),
+            stale_socket: !sockets.is_empty() && !running,
+            sockets,
+            running,
+        })
+    })
+}
+
+/// Prefix of the guest facts line with the state of the entrypoint just before `exec`.
+const GUEST_ENTRY_MARKER: &str = "appimage-runner: guest entry ";
+const GUEST_ENTRY_CHECK: &str = "if [ -x \"$1\" ]; then s=executable; elif [ -e \"$1\" ]; then \
+s=not-executable; else s=missing; fi; echo \"appimage-runner: guest entry $s\"";
+
+fn parse_guest_entry(combined: &str) -> Option<String> {
+    combined.lines().find_map(|line| {
+        let state = line.trim().strip_prefix(GUEST_ENTRY_MARKER)?.trim();
+        (!state.is_empty()).then(|| state.to_string())
+    })
+}
+
+fn parse_guest_kernel_version(combined: &str) -> Option<String> {
+    combined.lines().find_map(|line| {
+        let release = line.trim().strip_prefix(GUEST_KERNEL_MARKER)?.trim();
+        (!release.is_empty()).then(|| release.to_string())
+    })
+}
+
+/// Run `guest_cmd` under muvm + FEX with the muvm args, env, prelude, timeout and PTY
+/// settings recorded in `inputs`.
 fn run_guest_command(
     muvm_path: &Path,
-    muvm_args: &[String],
     fex_images: &[PathBuf],
-    envs: &[String],
-    timeout_seconds: Option<u64>,
-    guest_pre: Option<&str>,
+    inputs: &InputsReport,
     guest_cmd: &str,
-) -> Result<(portable_pty::ExitStatus, String, bool)> {
+    out_dir: &Path,
+) -> Result<PtyOutcome> {
     let mut argv: Vec<String> = Vec::new();
 
     // muvm is order-sensitive for some flags; put pass-through args first.
-    argv.extend(muvm_args.iter().cloned());
+    argv.extend(inputs.muvm_args.iter().cloned());
     argv.push("--emu=fex".to_string());
     for img in fex_images {
         argv.push("--fex-image".to_string());
         argv.push(img.display().to_string());
     }
-    for env in envs {
+    for env in &inputs.env {
         argv.push("-e".to_string());
         argv.push(env.clone());
     }
     argv.push("--".to_string());
 
-    let script = if let Some(pre) = guest_pre {
-        format!("set -euo pipefail\n{}\n{}\n", pre, guest_cmd)
+    let prelude = guest_artifact_path(true, out_dir, GUEST_FACTS)?
+        .map(|path| guest_facts_prelude(&path))
+        .unwrap_or_default();
+    let script = if let Some(pre) = traced_guest_pre(inputs).as_deref() {
+        format!("{prelude}set -euo pipefail\n{}\n{}\n", pre, guest_cmd)
     } else {
-        format!("{}\n", guest_cmd)
+        format!("{prelude}{}\n", guest_cmd)
     };
 
     argv.push("/bin/bash".to_string());
     argv.push("-lc".to_string());
     argv.push(script);
 
-    let timeout = timeout_seconds.map(Duration::from_secs);
-    run_in_pty(muvm_path, &argv, timeout).with_context(|| {
+    let timeout = inputs.timeout_seconds.map(Duration::from_secs);
+    let pty = inputs.pty.clone().unwrap_or_default().with_out_dir(out_dir);
+    run_in_pty(muvm_path, &argv, timeout, &pty).with_context(|| {
         format!(
             "Failed to run guest command via muvm ({})",
             muvm_path.display()
@@ -1656,7 +3785,7 @@ fn run_guest_command(
     })
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 struct InputsReport {
     kind: String,
     appimage: Option<String>,
@@ -1668,10 +3797,136 @@ struct InputsReport {
     env: Vec<String>,
     timeout_seconds: Option<u64>,
     guest_pre: Option<String>,
+    /// Guest directory the command runs from (`--guest-cwd`).
+    #[serde(default)]
+    guest_cwd: Option<String>,
+    /// Guest path the prelude's xtrace is written to (`--guest-shell-trace`).
+    #[serde(default)]
+    guest_pre_trace_log: Option<String>,
+    /// Guest path the guest's mountinfo is copied to (`--dump-guest-mounts`).
+    #[serde(default)]
+    guest_mountinfo: Option<String>,
+    /// Combined argv: the args after `--` plus any from `--args-file`.
     argv_after_double_dash: Option<Vec<String>>,
+    /// `--args-file` the argv was partly read from.
+    #[serde(default)]
+    args_file: Option<String>,
+    /// Set when this run was produced by `replay`: the `inputs.json` it was replayed from.
+    #[serde(default)]
+    replay_of: Option<String>,
+    /// How the payload arch was decided (and whether `--emu=fex` was passed).
+    #[serde(default)]
+    arch: Option<ArchDecision>,
+    /// PTY size and host env policy muvm was started with.
+    #[serde(default)]
+    pty: Option<PtySettings>,
+    /// Keys of `env` whose values were written as `***`; `replay` takes them from its own
+    /// environment.
+    #[serde(default)]
+    redacted_keys: Vec<String>,
+    /// Backend that extracted `extract_dir` (`squashfs-ng` or `unsquashfs`).
+    #[serde(default)]
+    extraction_backend: Option<String>,
+    #[serde(default)]
+    squashfs_compression_id: Option<u16>,
+    #[serde(flatten)]
+    hashes: InputHashes,
+}
+
+/// SHA-256 of the files a run depends on, so two runs can be shown to use identical bytes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+struct InputHashes {
+    appimage_sha256: Option<String>,
+    muvm_sha256: Option<String>,
+    /// Same order as `fex_images`.
+    fex_images_sha256: Vec<String>,
+}
+
+impl InputHashes {
+    /// A bare `muvm_path` (the default `muvm`) is hashed where PATH finds it, like the spawn
+    /// does; `muvm_sha256` is `None` when it isn't on PATH.
+    fn of(appimage: Option<&Path>, muvm_path: &Path, fex_images: &[PathBuf]) -> Result<Self> {
+        let muvm_path = find_program(muvm_path, std::env::var_os("PATH").as_deref());
+        Ok(Self {
+            appimage_sha256: appimage.map(hash_file).transpose()?,
+            muvm_sha256: muvm_path.as_deref().map(hash_file).transpose()?,
+            fex_images_sha256: fex_images
+                .iter()
+                .map(|p| hash_file(p))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Stands in for a redacted env value in `inputs.json` and the other run artifacts.
+const REDACTED: &str = "***";
+
+impl InputsReport {
+    /// Copy safe to write to the out dir: sensitive `env` values replaced with `***` and
+    /// their keys in `redacted_keys`. Also returns the replaced values, for scrubbing logs.
+    fn shareable(&self, redact: &[String]) -> (InputsReport, Vec<String>) {
+        let mut out = self.clone();
+        let mut values = Vec::new();
+        out.redacted_keys.clear();
+        for kv in &mut out.env {
+            let Some((key, value)) = kv.split_once('=') else {
+                continue;
+            };
+            if value.is_empty() || !is_sensitive_env_key(key, redact) {
+                continue;
+            }
+            out.redacted_keys.push(key.to_string());
+            values.push(value.to_string());
+            *kv = format!("{key}={REDACTED}");
+        }
+        (out, values)
+    }
+}
+
+/// `text` with every redacted env value replaced by `***`.
+fn redact_values(text: &str, values: &[String]) -> String {
+    let mut redacted = text.to_string();
+    for value in values {
+        redacted = redacted.replace(value.as_str(), REDACTED);
+    }
+    redacted
+}
+
+/// Replaces the redacted env values wherever the guest echoed them into the text logs.
+fn redact_text_artifacts(out_dir: &Path, values: &[String]) -> Result<()> {
+    if values.is_empty() {
+        return Ok(());
+    }
+    for name in ["run.log", "guest-pre.log", "fex.log"] {
+        let path = out_dir.join(name);
+        let Ok(bytes) = std::fs::read(&path) else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        let redacted = redact_values(&text, values);
+        if redacted != text {
+            std::fs::write(&path, redacted)
+                .with_context(|| format!("Writing {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+struct ArchDecision {
+    requested: String,
+    detected: Option<String>,
+    detected_from: Option<String>,
+    effective: String,
+    emu_fex: bool,
+    /// `e_machine` of the AppImage's type-2 runtime (the file itself), which is built for
+    /// the same arch as the payload.
+    #[serde(default)]
+    appimage_arch: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct ProbeReport {
     kind: String,
     fex_images: Vec<String>,
@@ -1686,21 +3941,203 @@ struct ProbeReport {
     muvm_guest_terminated_signal: Option<i32>,
     timeout_seconds: Option<u64>,
     timed_out: bool,
+    guest_kernel_version: Option<String>,
+    guest_kernel: Option<KernelVersion>,
+    fex_binfmt: Option<FexBinfmtReport>,
+    fexserver: Option<FexServerReport>,
+    host_env_passthrough: Option<Vec<String>>,
+    timeout_screenshot: Option<ScreenshotReport>,
+    fex_thunks: Option<FexThunksReport>,
+    guest_mounts: Option<GuestMountsReport>,
+    /// Locale and fontconfig state seen by the display probe.
+    display_env: Option<DisplayEnvReport>,
+}
+
+/// Locale and fontconfig facts parsed from the display probe's output; missing locales and
+/// fontconfig caches are common causes of GTK/Qt misbehavior.
+#[derive(Debug, Default, Serialize, JsonSchema)]
+struct DisplayEnvReport {
+    /// `LANG` as printed by the guest's `locale`.
+    lang: Option<String>,
+    /// `locale: ...` complaints (e.g. "Cannot set LC_CTYPE to default locale").
+    locale_errors: Vec<String>,
+    locale_archive_present: Option<bool>,
+    /// `fc-list | wc -l`; `None` if `fc-list` is missing.
+    fc_list_fonts: Option<u64>,
+    /// Fontconfig cache dirs that exist in the guest.
+    fontconfig_cache_dirs: Vec<String>,
+}
+
+fn parse_display_env(output: &str) -> Option<DisplayEnvReport> {
+    let mut report = DisplayEnvReport::default();
+    let mut seen = false;
+    for line in output.lines().map(str::trim) {
+        if let Some(v) = line.strip_prefix("LANG=") {
+            report.lang = Some(v.trim_matches('"').to_string()).filter(|v| !v.is_empty());
+        } else if line.starts_with("locale: ") {
+            report.locale_errors.push(line.to_string());
+        } else if let Some(v) = line.strip_prefix("locale_archive: ") {
+            report.locale_archive_present = Some(v == "present");
+            seen = true;
+        } else if let Some(v) = line.strip_prefix("fc_list_fonts: ") {
+            report.fc_list_fonts = v.trim().parse().ok();
+        } else if let Some(v) = line.strip_prefix("fontconfig_cache_dir: present ") {
+            report.fontconfig_cache_dirs.push(v.to_string());
+        }
+    }
+    seen.then_some(report)
+}
+
+/// Display probe output: `run.log` for `probe display`, the staged `output.txt` for
+/// `probe all`.
+fn display_env_report(
+    kind: &ProbeKind,
+    out_dir: &Path,
+    combined: &str,
+) -> Option<DisplayEnvReport> {
+    match kind {
+        ProbeKind::Display => parse_display_env(combined),
+        ProbeKind::All { .. } => {
+            let path = out_dir
+                .join(probe_name(&ProbeKind::Display))
+                .join("output.txt");
+            parse_display_env(&std::fs::read_to_string(path).ok()?)
+        }
+        _ => None,
+    }
+}
+
+struct PtyOutcome {
+    status: portable_pty::ExitStatus,
+    /// Everything the PTY produced (stdout and stderr interleaved).
+    output: String,
+    timed_out: bool,
+    stdin: Option<StdinReport>,
+    screenshot: Option<ScreenshotReport>,
+}
+
+/// Result of `--capture-screenshot-on-timeout`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+struct ScreenshotReport {
+    /// Host path of the screenshot, if one was taken.
+    path: Option<String>,
+    /// Guest tool that took it (`grim`, `import` or `xwd`).
+    tool: Option<String>,
+    error: Option<String>,
+}
+
+/// How long the screenshot command may take before the timed-out run is killed anyway.
+const SCREENSHOT_TIMEOUT: Duration = Duration::from_secs(20);
+const SCREENSHOT_TOOL_MARKER: &str = "appimage-runner: screenshot tool ";
+
+/// Screenshot the guest display of a timed-out run. muvm hands a second invocation to the
+/// VM that is already running, so this reuses the run's muvm args (everything before `--`).
+fn capture_timeout_screenshot(
+    muvm_path: &Path,
+    argv: &[String],
+    out_dir: &Path,
+) -> ScreenshotReport {
+    let failed = |error: String| ScreenshotReport {
+        path: None,
+        tool: None,
+        error: Some(error),
+    };
+    let out_dir = match out_dir.canonicalize() {
+        Ok(dir) => dir,
+        Err(e) => return failed(format!("canonicalize {}: {e}", out_dir.display())),
+    };
+    let base = format!("/run/muvm-host{}/timeout-screenshot", out_dir.display());
+    let script = format!(
+        r#"out='{base}'
+d="${{DISPLAY:-:1}}"
+if [ -n "${{WAYLAND_DISPLAY:-}}" ] && command -v grim >/dev/null 2>&1 && grim "$out.png"; then
+    echo '{SCREENSHOT_TOOL_MARKER}grim'; exit 0
+fi
+if command -v import >/dev/null 2>&1 && import -display "$d" -window root "$out.png"; then
+    echo '{SCREENSHOT_TOOL_MARKER}import'; exit 0
+fi
+if command -v xwd >/dev/null 2>&1 && xwd -display "$d" -root -silent -out "$out.xwd"; then
+    echo '{SCREENSHOT_TOOL_MARKER}xwd'; exit 0
+fi
+echo 'no working screenshot tool (grim/import/xwd) in the guest' >&2
+exit 3
+"#
+    );
+
+    let muvm_args = argv.iter().take_while(|a| a.as_str() != "--");
+    let mut child = match Command::new(muvm_path)
+        .args(muvm_args)
+        .args(["--", "/bin/bash", "-c", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return failed(format!("spawn {}: {e}", muvm_path.display())),
+    };
+    let started = std::time::Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < SCREENSHOT_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(100))
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return failed(format!(
+                    "screenshot command timed out after {}s",
+                    SCREENSHOT_TIMEOUT.as_secs()
+                ));
+            }
+            Err(e) => return failed(format!("wait for screenshot command: {e}")),
+        }
+    }
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(e) => return failed(format!("read screenshot command output: {e}")),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let tool = stdout
+        .lines()
+        .find_map(|l| l.trim().strip_prefix(SCREENSHOT_TOOL_MARKER))
+        .map(|t| t.to_string());
+    let path = ["png", "xwd"]
+        .iter()
+        .map(|ext| out_dir.join(format!("timeout-screenshot.{ext}")))
+        .find(|p| p.exists());
+    match (tool, path) {
+        (Some(tool), Some(path)) => ScreenshotReport {
+            path: Some(path.display().to_string()),
+            tool: Some(tool),
+            error: None,
+        },
+        _ => failed(
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .last()
+                .unwrap_or("screenshot command produced no file")
+                .to_string(),
+        ),
+    }
 }
 
 fn run_in_pty(
     program: &Path,
     args: &[String],
     timeout: Option<Duration>,
-) -> Result<(portable_pty::ExitStatus, String, bool)> {
+    pty: &PtySettings,
+) -> Result<PtyOutcome> {
     use portable_pty::{CommandBuilder, PtySize, native_pty_system};
     use std::sync::mpsc;
     use std::thread;
     let pty_system = native_pty_system();
     let pair = pty_system
         .openpty(PtySize {
-            rows: 24,
-            cols: 120,
+            rows: pty.rows,
+            cols: pty.cols,
             pixel_width: 0,
             pixel_height: 0,
         })
@@ -1710,6 +4147,21 @@ fn run_in_pty(
     for a in args {
         cmd.arg(a);
     }
+    if let Some(keys) = pty.host_env_passthrough() {
+        cmd.env_clear();
+        for key in keys {
+            if let Some(val) = std::env::var_os(&key) {
+                cmd.env(key, val);
+            }
+        }
+    }
+    cmd.env("LINES", pty.rows.to_string());
+    cmd.env("COLUMNS", pty.cols.to_string());
+
+    let stdin = match &pty.stdin {
+        Some(stdin) => Some((stdin.bytes()?, stdin.eof)),
+        None => None,
+    };
 
     let mut child = pair.slave.spawn_command(cmd).context("spawn_command")?;
     let mut killer = child.clone_killer();
@@ -1736,9 +4188,26 @@ fn run_in_pty(
         }
     });
 
+    // Feed stdin from its own thread so a guest that never reads can't stall the
+    // timeout. The PTY echoes the input, so it also shows up in the captured output.
+    let stdin_len = stdin.as_ref().map(|(bytes, _)| bytes.len() as u64);
+    let stdin_thread = match stdin {
+        Some((bytes, eof)) => {
+            let mut writer = pair.master.take_writer().context("take_writer")?;
+            Some(thread::spawn(move || {
+                writer.write_all(&bytes)?;
+                writer.flush()?;
+                // Dropping the writer sends a newline and VEOF, i.e. EOF on the guest side.
+                Ok::<_, std::io::Error>(if eof { None } else { Some(writer) })
+            }))
+        }
+        None => None,
+    };
+
     let mut output: Vec<u8> = Vec::new();
     let started = std::time::Instant::now();
     let mut timed_out = false;
+    let mut screenshot = None;
 
     loop {
         match rx.recv_timeout(Duration::from_millis(100)) {
@@ -1757,17 +4226,46 @@ fn run_in_pty(
         if let Some(max) = timeout {
             if !timed_out && started.elapsed() >= max {
                 timed_out = true;
+                if let Some(dir) = &pty.screenshot_dir {
+                    screenshot = Some(capture_timeout_screenshot(program, args, dir));
+                }
                 let _ = killer.kill();
             }
         }
 
         if let Some(status) = child.try_wait().context("try_wait")? {
             let _ = reader_thread.join();
-            return Ok((
+            let stdin_report = stdin_thread.map(|t| {
+                let bytes = stdin_len.unwrap_or_default();
+                let result = if t.is_finished() {
+                    t.join()
+                        .unwrap_or_else(|_| Err(std::io::Error::other("stdin writer panicked")))
+                        .map_err(|e| e.to_string())
+                } else {
+                    Err("child exited before all stdin was written".to_string())
+                };
+                match result {
+                    Ok(writer) => StdinReport {
+                        bytes,
+                        fed: true,
+                        eof_sent: writer.is_none(),
+                        error: None,
+                    },
+                    Err(e) => StdinReport {
+                        bytes,
+                        fed: false,
+                        eof_sent: false,
+                        error: Some(e),
+                    },
+                }
+            });
+            return Ok(PtyOutcome {
                 status,
-                String::from_utf8_lossy(&output).to_string(),
+                output: String::from_utf8_lossy(&output).to_string(),
                 timed_out,
-            ));
+                stdin: stdin_report,
+                screenshot,
+            });
         }
     }
 }
@@ -1806,22 +4304,33 @@ fn parse_muvm_guest_terminated_signal(text: &str) -> Option<i32> {
     last
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, JsonSchema)]
 #[serde(tag = "kind")]
 enum EntrypointKind {
     Elf,
-    Script { interpreter: String },
+    Script {
+        interpreter: String,
+        /// The raw `#!` line, without the `#!`.
+        shebang: String,
+        /// Whether the extracted AppDir ships a file at `interpreter`'s path (e.g.
+        /// `usr/bin/python3`). The shebang still resolves it against the guest rootfs (or FEX
+        /// image), which is what has to provide it.
+        interpreter_in_appdir: bool,
+    },
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, JsonSchema)]
 struct ResolvedEntrypoint {
     apprun: String,
     entry: PathBuf,
     entry_args: Vec<String>,
     kind: EntrypointKind,
+    /// How `entry` looked in the guest right before `exec`: `executable`, `not-executable`
+    /// or `missing` (see `GUEST_ENTRY_MARKER`). `None` if the guest never got that far.
+    guest_entry: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct RunReport {
     entrypoint: ResolvedEntrypoint,
     muvm_exit_status: String,
@@ -1829,22 +4338,23 @@ struct RunReport {
     muvm_guest_status_code: Option<i32>,
     muvm_guest_terminated_signal: Option<i32>,
     timed_out: bool,
+    /// `uname -r` as seen by the guest (see `GUEST_KERNEL_MARKER`).
+    guest_kernel_version: Option<String>,
+    fex_binfmt: Option<FexBinfmtReport>,
+    fexserver: Option<FexServerReport>,
+    #[serde(default)]
+    stdin: Option<StdinReport>,
+    #[serde(default)]
+    timeout_screenshot: Option<ScreenshotReport>,
 }
 
-#[derive(Default, Debug, Serialize)]
-struct StripReport {
-    stripped_files: Vec<String>,
-    strip_failures: Vec<StripFailure>,
-    remaining_gnu_property_files: Vec<String>,
-}
-
-#[derive(Debug, Serialize)]
-struct StripFailure {
-    path: String,
-    error: String,
+impl RunReport {
+    fn guest_kernel(&self) -> Option<KernelVersion> {
+        KernelVersion::parse_release(self.guest_kernel_version.as_deref()?)
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct RunnerReport {
     appimage: String,
     extract_dir: String,
@@ -1860,7 +4370,237 @@ struct RunnerReport {
     muvm_guest_terminated_signal: Option<i32>,
     timeout_seconds: Option<u64>,
     timed_out: bool,
+    /// Exit code this runner exits with (see `run_exit`) and why.
+    exit_code: i32,
+    exit_reason: String,
+    /// Backend that extracted `extract_dir` (`squashfs-ng` or `unsquashfs`).
+    extraction_backend: String,
+    squashfs_compression_id: Option<u16>,
+    squashfs_compression: Option<String>,
     strip_report: StripReport,
+    /// OS/ABI requirements of the payload's main binary, with warnings for likely mismatches.
+    abi_compat: Option<AbiCompatReport>,
+    guest_kernel_version: Option<String>,
+    guest_kernel: Option<KernelVersion>,
+    fex_binfmt: Option<FexBinfmtReport>,
+    fexserver: Option<FexServerReport>,
+    /// `guest_kernel` is older than `abi_compat.abi_tag_min_kernel`.
+    kernel_too_old: Option<bool>,
+    /// Host variables muvm was started with; `None` means the whole ambient env.
+    host_env_passthrough: Option<Vec<String>>,
+    /// Set when `--stdin-file`/`--stdin-string` input was written to the PTY.
+    stdin: Option<StdinReport>,
+    /// Set when the run timed out with `--capture-screenshot-on-timeout`.
+    timeout_screenshot: Option<ScreenshotReport>,
+    failure_class: Option<FailureClass>,
+    /// The app's output shows it tried to FUSE-mount the AppImage itself.
+    attempted_fuse_mount: bool,
+    fuse_mount_attempt: Option<FuseMountAttempt>,
+    guest_mounts: Option<GuestMountsReport>,
+    #[serde(flatten)]
+    hashes: InputHashes,
+    /// Host thunk dir the guest's FEX was pointed at, if any.
+    fex_thunks: Option<FexThunksReport>,
+    fex_log: Option<FexLogReport>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct AbiCompatReport {
+    binary: String,
+    osabi: String,
+    abi_tag_os: Option<String>,
+    abi_tag_min_kernel: Option<KernelVersion>,
+    /// The host's kernel release: muvm's guest kernel can't be queried before launch, and
+    /// FEX reports the guest's own release to the app.
+    guest_kernel_estimate: Option<String>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct FexLogReport {
+    path: String,
+    lines: usize,
+    unsupported_lines: Vec<String>,
+    thunk_lines: Vec<String>,
+}
+
+/// Guest path for the prelude xtrace log, when `--guest-shell-trace` is on.
+fn guest_pre_trace_log(enabled: bool, out_dir: &Path) -> Result<Option<String>> {
+    guest_artifact_path(enabled, out_dir, "guest-pre.log")
+}
+
+const GUEST_MOUNTINFO: &str = "guest-mountinfo.txt";
+
+/// Guest path of `<out_dir>/<name>`, when `enabled`.
+fn guest_artifact_path(enabled: bool, out_dir: &Path, name: &str) -> Result<Option<String>> {
+    if !enabled {
+        return Ok(None);
+    }
+    let out_dir = out_dir
+        .canonicalize()
+        .with_context(|| format!("canonicalize {}", out_dir.display()))?;
+    // muvm mounts the host root at /run/muvm-host.
+    Ok(Some(format!(
+        "/run/muvm-host{}",
+        out_dir.join(name).display()
+    )))
+}
+
+/// The recorded prelude, wrapped in `set -x` with the xtrace sent to its own fd/log file.
+/// The fd is closed again before the entrypoint runs, so the app's output stays separate.
+/// With `--dump-guest-mounts`, the mountinfo copy runs first (even without a prelude),
+/// and with `--guest-cwd` the `cd` comes before both.
+fn traced_guest_pre(inputs: &InputsReport) -> Option<String> {
+    let dump = inputs.guest_mountinfo.as_deref().map(|path| {
+        let path = path.replace('\'', r"'\''");
+        format!("cat /proc/self/mountinfo >'{path}' || true")
+    });
+    let Some(pre) = inputs.guest_pre.as_deref() else {
+        return with_guest_cwd(inputs.guest_cwd.as_deref(), dump);
+    };
+    let traced = match inputs.guest_pre_trace_log.as_deref() {
+        Some(log) => {
+            let log = log.replace('\'', r"'\''");
+            format!(
+                "exec 19>'{log}'\nBASH_XTRACEFD=19\nset -x\n{pre}\n{{ set +x; }} 2>/dev/null\nunset BASH_XTRACEFD\nexec 19>&-"
+            )
+        }
+        None => pre.to_string(),
+    };
+    let pre = match dump {
+        Some(dump) => format!("{dump}\n{traced}"),
+        None => traced,
+    };
+    with_guest_cwd(inputs.guest_cwd.as_deref(), Some(pre))
+}
+
+/// Prefix `pre` with a `cd` into `cwd` (creating it first); the run fails early if the
+/// directory can't be entered rather than starting the app somewhere unexpected.
+fn with_guest_cwd(cwd: Option<&str>, pre: Option<String>) -> Option<String> {
+    let Some(cwd) = cwd else {
+        return pre;
+    };
+    let quoted = cwd.replace('\'', r"'\''");
+    let cd = format!(
+        "mkdir -p -- '{quoted}' && cd -- '{quoted}' || \
+         {{ echo \"appimage-runner: cannot enter guest cwd\" >&2; exit 1; }}"
+    );
+    Some(match pre {
+        Some(pre) => format!("{cd}\n{pre}"),
+        None => cd,
+    })
+}
+
+/// Filesystems backing the extract and out dirs, from the guest's `guest-mountinfo.txt`.
+#[derive(Debug, Serialize, JsonSchema)]
+struct GuestMountsReport {
+    mountinfo: String,
+    mount_count: usize,
+    /// Mount backing the extracted AppImage (`run`/`replay` only).
+    extract_dir: Option<MountEntry>,
+    /// Mount backing the out dir as the guest writes to it (under `/run/muvm-host`).
+    out_dir: Option<MountEntry>,
+}
+
+fn guest_mounts_report(out_dir: &Path, extract_dir: Option<&Path>) -> Option<GuestMountsReport> {
+    let path = out_dir.join(GUEST_MOUNTINFO);
+    let mounts = parse_mountinfo(&std::fs::read_to_string(&path).ok()?);
+    let guest_out_dir = out_dir
+        .canonicalize()
+        .ok()
+        .map(|dir| PathBuf::from(format!("/run/muvm-host{}", dir.display())));
+    Some(GuestMountsReport {
+        mountinfo: path.display().to_string(),
+        mount_count: mounts.len(),
+        extract_dir: extract_dir.and_then(|dir| mount_for_path(&mounts, dir).cloned()),
+        out_dir: guest_out_dir.and_then(|dir| mount_for_path(&mounts, &dir).cloned()),
+    })
+}
+
+/// Guest env that turns on FEX logging and sends it to `<out_dir>/fex.log`.
+///
+/// FEX reads its options from `FEX_<OPTION>` env vars, and logging is the `SilentLog` and
+/// `OutputLog` pair; there is no `FEX_VERBOSE`. `FEX_SERVERSOCKETPATH` is left alone: a
+/// different socket would start a second FEXServer instead of using the guest's.
+fn fex_verbose_env(out_dir: &Path) -> Result<Vec<String>> {
+    let out_dir = out_dir
+        .canonicalize()
+        .with_context(|| format!("canonicalize {}", out_dir.display()))?;
+    // muvm mounts the host root at /run/muvm-host.
+    let guest_log = format!("/run/muvm-host{}", out_dir.join("fex.log").display());
+    Ok(vec![
+        "FEX_SILENTLOG=0".to_string(),
+        format!("FEX_OUTPUTLOG={guest_log}"),
+    ])
+}
+
+/// Guest env pointing FEX at the host thunk libraries in `dir`. muvm's guest shares the
+/// host root, so the host path works as-is.
+fn fex_thunk_env(dir: &Path) -> Result<Vec<String>> {
+    let dir = dir
+        .canonicalize()
+        .with_context(|| format!("--fex-thunk-dir {}", dir.display()))?;
+    if !dir.is_dir() {
+        anyhow::bail!("--fex-thunk-dir {} is not a directory", dir.display());
+    }
+    Ok(vec![format!("FEX_THUNKHOSTLIBS={}", dir.display())])
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct FexThunksReport {
+    host_libs_dir: String,
+    /// Shared libraries in `host_libs_dir` (e.g. `libGL-host.so`, `libvulkan-host.so`).
+    libraries: Vec<String>,
+}
+
+fn fex_thunks_report(env: &[String]) -> Option<FexThunksReport> {
+    let dir = env
+        .iter()
+        .rev()
+        .find_map(|kv| kv.strip_prefix("FEX_THUNKHOSTLIBS="))?;
+    let mut libraries: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| name.contains(".so"))
+                .collect()
+        })
+        .unwrap_or_default();
+    libraries.sort();
+    Some(FexThunksReport {
+        host_libs_dir: dir.to_string(),
+        libraries,
+    })
+}
+
+fn fex_log_report_if_enabled(env: &[String], out_dir: &Path) -> Option<FexLogReport> {
+    if !env.iter().any(|kv| kv.starts_with("FEX_OUTPUTLOG=")) {
+        return None;
+    }
+    let path = out_dir.join("fex.log");
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+
+    // Keep the report readable; the full log stays in fex.log.
+    const MAX_LINES_PER_FIELD: usize = 200;
+    let mut unsupported_lines = Vec::new();
+    let mut thunk_lines = Vec::new();
+    for line in text.lines() {
+        let lower = line.to_ascii_lowercase();
+        if lower.contains("unsupported") && unsupported_lines.len() < MAX_LINES_PER_FIELD {
+            unsupported_lines.push(line.to_string());
+        }
+        if lower.contains("thunk") && thunk_lines.len() < MAX_LINES_PER_FIELD {
+            thunk_lines.push(line.to_string());
+        }
+    }
+
+    Some(FexLogReport {
+        path: path.display().to_string(),
+        lines: text.lines().count(),
+        unsupported_lines,
+        thunk_lines,
+    })
 }
 
 fn ensure_fex_rootfs_compat_overlay() -> Result<Option<PathBuf>> {
@@ -1922,6 +4662,7 @@ fn resolve_entrypoint(apprun: &Path) -> Result<ResolvedEntrypoint> {
         let line = String::from_utf8_lossy(&data[2..line_end])
             .trim()
             .to_string();
+        let appdir = apprun.parent().unwrap_or(Path::new("."));
         let mut parts = line.split_whitespace();
         let interp = parts
             .next()
@@ -1932,13 +4673,17 @@ fn resolve_entrypoint(apprun: &Path) -> Result<ResolvedEntrypoint> {
             argv.push(arg.to_string());
         }
         argv.push(apprun.display().to_string());
+        let interpreter_in_appdir = appdir.join(interp.trim_start_matches('/')).is_file();
         return Ok(ResolvedEntrypoint {
             apprun: apprun.display().to_string(),
             entry: PathBuf::from(&interp),
             entry_args: argv,
             kind: EntrypointKind::Script {
                 interpreter: interp,
+                shebang: line,
+                interpreter_in_appdir,
             },
+            guest_entry: None,
         });
     }
 
@@ -1947,204 +4692,254 @@ fn resolve_entrypoint(apprun: &Path) -> Result<ResolvedEntrypoint> {
         entry: apprun.to_path_buf(),
         entry_args: Vec::new(),
         kind: EntrypointKind::Elf,
+        guest_entry: None,
     })
 }
 
+/// Conservative: only touch likely load-bearing executable/library locations.
+const APPDIR_STRIP_DIRS: &[&str] = &["bin", "usr/bin", "usr/lib", "usr/lib64", "lib", "lib64"];
+
 fn strip_gnu_property_notes_in_appdir(appdir: &Path, objcopy: &OsString) -> Result<StripReport> {
-    let mut report = StripReport::default();
+    strip_gnu_property_notes_under(appdir, APPDIR_STRIP_DIRS, objcopy)
+}
 
-    // Conservative: only touch likely load-bearing executable/library locations.
-    for rel in ["bin", "usr/bin", "usr/lib", "usr/lib64", "lib", "lib64"] {
-        let dir = appdir.join(rel);
-        if dir.exists() {
-            strip_gnu_property_notes_in_tree(&dir, &mut report, objcopy)
-                .with_context(|| format!("Stripping notes under {}", dir.display()))?;
-        }
+fn arch_name(e_machine: u16) -> String {
+    match e_machine {
+        EM_X86_64 => "x86_64".to_string(),
+        EM_AARCH64 => "aarch64".to_string(),
+        other => format!("e_machine={other}"),
+    }
+}
+
+fn detect_payload_arch(extract_dir: &Path) -> Result<Option<(u16, PathBuf)>> {
+    let apprun = extract_dir.join("AppRun");
+    if let Some(m) = elf64_le_machine(&apprun)? {
+        return Ok(Some((m, apprun)));
     }
 
-    // Verify: collect any remaining x86_64 ELFs that still contain the note.
-    for rel in ["bin", "usr/bin", "usr/lib", "usr/lib64", "lib", "lib64"] {
-        let dir = appdir.join(rel);
-        if !dir.exists() {
+    // Script AppRun: fall back to the first ELF shipped in usr/bin.
+    let bin_dir = extract_dir.join("usr/bin");
+    let Ok(entries) = std::fs::read_dir(&bin_dir) else {
+        return Ok(None);
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if !path.is_file() {
             continue;
         }
-        collect_remaining_gnu_property_files(&dir, &mut report)
-            .with_context(|| format!("Scanning remaining notes under {}", dir.display()))?;
+        if let Some(m) = elf64_le_machine(&path)? {
+            return Ok(Some((m, path)));
+        }
     }
-
-    report.stripped_files.sort();
-    report.strip_failures.sort_by(|a, b| a.path.cmp(&b.path));
-    report.remaining_gnu_property_files.sort();
-    report.remaining_gnu_property_files.dedup();
-
-    Ok(report)
+    Ok(None)
 }
 
-fn strip_gnu_property_notes_in_tree(
-    root: &Path,
-    report: &mut StripReport,
-    objcopy: &OsString,
-) -> Result<()> {
-    fn walk(dir: &Path, f: &mut dyn FnMut(&Path) -> Result<()>) -> Result<()> {
-        for entry in
-            std::fs::read_dir(dir).with_context(|| format!("read_dir {}", dir.display()))?
+/// Checks `e_ident[EI_OSABI]` and `.note.ABI-tag` of the binary `detect_payload_arch` picks.
+///
+/// Warns (on stderr and in the report) when the OS/ABI isn't Linux or the ABI tag asks for a
+/// newer kernel than the guest likely runs; FEX then fails in ways that look unrelated.
+fn abi_compat_report(extract_dir: &Path) -> Result<Option<AbiCompatReport>> {
+    let Some((_, binary)) = detect_payload_arch(extract_dir)? else {
+        return Ok(None);
+    };
+    let Some(info) = elf_abi_info(&binary)? else {
+        return Ok(None);
+    };
+    let guest_kernel = std::fs::read_to_string("/proc/sys/kernel/osrelease")
+        .ok()
+        .map(|s| s.trim().to_string());
+
+    let mut warnings = Vec::new();
+    if info.osabi != ELFOSABI_SYSV && info.osabi != ELFOSABI_GNU {
+        warnings.push(format!(
+            "{} has OS/ABI {}, not SYSV or GNU/Linux",
+            binary.display(),
+            osabi_name(info.osabi)
+        ));
+    }
+    if let Some(tag) = &info.abi_tag {
+        if tag.os != 0 {
+            warnings.push(format!(
+                "{} .note.ABI-tag targets {}, not Linux",
+                binary.display(),
+                abi_tag_os_name(tag.os)
+            ));
+        } else if let Some(guest) = guest_kernel
+            .as_deref()
+            .and_then(KernelVersion::parse_release)
+            && tag.min_kernel > guest
         {
-            let entry = entry?;
-            let path = entry.path();
-            let meta = std::fs::symlink_metadata(&path)
-                .with_context(|| format!("symlink_metadata {}", path.display()))?;
-            if meta.is_dir() {
-                walk(&path, f)?;
-            } else if meta.is_file() {
-                f(&path)?;
-            }
+            warnings.push(format!(
+                "{} requires kernel >= {} but the guest likely runs {}",
+                binary.display(),
+                tag.min_kernel,
+                guest_kernel.as_deref().unwrap_or_default()
+            ));
         }
-        Ok(())
+    }
+    for w in &warnings {
+        eprintln!("WARNING: {w}");
     }
 
-    walk(root, &mut |path| {
-        if !is_elf_x86_64(path)? {
-            return Ok(());
-        }
-        if !elf_has_section(path, b".note.gnu.property")? {
-            return Ok(());
-        }
-
-        // objcopy edits the file in-place.
-        let out = Command::new(objcopy)
-            .arg("--remove-section")
-            .arg(".note.gnu.property")
-            .arg(path)
-            .stdin(Stdio::null())
-            .output()
-            .with_context(|| format!("objcopy on {}", path.display()))?;
-        if !out.status.success() {
-            // Don't hard-fail on a single file; keep going but surface stderr.
-            report.strip_failures.push(StripFailure {
-                path: path.display().to_string(),
-                error: String::from_utf8_lossy(&out.stderr).to_string(),
-            });
-        } else {
-            report.stripped_files.push(path.display().to_string());
-        }
-        Ok(())
-    })
+    Ok(Some(AbiCompatReport {
+        binary: binary.display().to_string(),
+        osabi: osabi_name(info.osabi),
+        abi_tag_os: info.abi_tag.as_ref().map(|t| abi_tag_os_name(t.os)),
+        abi_tag_min_kernel: info.abi_tag.as_ref().map(|t| t.min_kernel),
+        guest_kernel_estimate: guest_kernel,
+        warnings,
+    }))
 }
 
-fn collect_remaining_gnu_property_files(root: &Path, report: &mut StripReport) -> Result<()> {
-    fn walk(dir: &Path, f: &mut dyn FnMut(&Path) -> Result<()>) -> Result<()> {
-        for entry in
-            std::fs::read_dir(dir).with_context(|| format!("read_dir {}", dir.display()))?
-        {
-            let entry = entry?;
-            let path = entry.path();
-            let meta = std::fs::symlink_metadata(&path)
-                .with_context(|| format!("symlink_metadata {}", path.display()))?;
-            if meta.is_dir() {
-                walk(&path, f)?;
-            } else if meta.is_file() {
-                f(&path)?;
-            }
-        }
-        Ok(())
+/// Whether the guest kernel (reported by the guest itself) is older than the payload's
+/// `.note.ABI-tag` minimum; `None` when either side is unknown.
+fn kernel_too_old(
+    abi_compat: Option<&AbiCompatReport>,
+    guest_kernel: Option<KernelVersion>,
+) -> Option<bool> {
+    let min = abi_compat?.abi_tag_min_kernel?;
+    let too_old = guest_kernel? < min;
+    if too_old {
+        eprintln!(
+            "WARNING: guest kernel {} is older than the {} the payload requires",
+            guest_kernel?, min
+        );
     }
-
-    walk(root, &mut |path| {
-        if !is_elf_x86_64(path)? {
-            return Ok(());
-        }
-        if elf_has_section(path, b".note.gnu.property")? {
-            report
-                .remaining_gnu_property_files
-                .push(path.display().to_string());
-        }
-        Ok(())
-    })
+    Some(too_old)
 }
 
-fn is_elf_x86_64(path: &Path) -> Result<bool> {
-    use std::io::Read;
-    let mut f = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
-    let mut hdr = [0u8; 64];
-    let n = f
-        .read(&mut hdr)
-        .with_context(|| format!("read {}", path.display()))?;
-    if n < 20 {
-        return Ok(false);
+fn decide_arch(appimage: &Path, extract_dir: &Path, opts: &ArchOpts) -> Result<ArchDecision> {
+    let detected = detect_payload_arch(extract_dir)?;
+    let runtime = elf64_le_machine(appimage)?;
+
+    let requested = match opts.arch {
+        AppArch::Auto => "auto",
+        AppArch::X86_64 => "x86_64",
+        AppArch::Aarch64 => "aarch64",
+    };
+    let effective = match opts.arch {
+        AppArch::X86_64 => "x86_64".to_string(),
+        AppArch::Aarch64 => "aarch64".to_string(),
+        // Unknown payloads keep the historical behavior (FEX).
+        AppArch::Auto => match detected {
+            Some((EM_AARCH64, _)) => "aarch64".to_string(),
+            _ => "x86_64".to_string(),
+        },
+    };
+    let emu_fex = effective != "aarch64" || opts.force_fex;
+
+    let decision = ArchDecision {
+        requested: requested.to_string(),
+        detected: detected.as_ref().map(|(m, _)| arch_name(*m)),
+        detected_from: detected.map(|(_, p)| p.display().to_string()),
+        effective,
+        emu_fex,
+        appimage_arch: runtime.map(arch_name),
+    };
+    for w in arch_warnings(&decision) {
+        eprintln!("WARNING: {w}");
     }
-    if &hdr[0..4] != b"\x7fELF" {
-        return Ok(false);
+    Ok(decision)
+}
+
+/// Mismatches between the AppImage runtime's arch, the payload's and how it will be run.
+fn arch_warnings(arch: &ArchDecision) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let Some(runtime) = arch.appimage_arch.as_deref() else {
+        return warnings;
+    };
+    if let Some(payload) = arch.detected.as_deref().filter(|p| *p != runtime) {
+        warnings.push(format!(
+            "AppImage runtime is {runtime} but the payload ({}) is {payload}",
+            arch.detected_from.as_deref().unwrap_or("?")
+        ));
     }
-    // Only handle ELF64 little-endian here (fits our target).
-    if hdr[4] != 2 || hdr[5] != 1 {
-        return Ok(false);
+    match runtime {
+        "aarch64" if arch.emu_fex => warnings.push(
+            "AppImage runtime is aarch64 but it will run under FEX (see --arch/--force-fex)"
+                .to_string(),
+        ),
+        "x86_64" if !arch.emu_fex => warnings.push(
+            "AppImage runtime is x86_64 but it will run natively without FEX (see --arch)"
+                .to_string(),
+        ),
+        _ => {}
     }
-    let e_machine = u16::from_le_bytes([hdr[18], hdr[19]]);
-    Ok(e_machine == 62)
+    warnings
 }
 
-fn elf_has_section(path: &Path, section_name: &[u8]) -> Result<bool> {
-    use std::io::{Read, Seek, SeekFrom};
-    let mut f = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_bare_muvm_name_found_on_path() {
+        let dir = std::env::temp_dir().join(format!("appimage-runner-muvm-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("muvm"), b"#!/bin/sh\n").unwrap();
+        let search_path = std::env::join_paths([Path::new("/nonexistent"), &dir]).unwrap();
+
+        let found = find_program(Path::new("muvm"), Some(&search_path)).unwrap();
+        assert_eq!(found, dir.join("muvm"));
+        assert_eq!(
+            hash_file(&found).unwrap(),
+            "a8076d3d28d21e02012b20eaf7dbf75409a6277134439025f282e368e3305abf"
+        );
+        assert_eq!(find_program(Path::new("muvm"), None), None);
+        assert_eq!(
+            find_program(Path::new("./muvm"), None),
+            Some(PathBuf::from("./muvm"))
+        );
 
-    let mut ehdr = [0u8; 64];
-    f.read_exact(&mut ehdr)
-        .with_context(|| format!("read ELF header {}", path.display()))?;
-    if &ehdr[0..4] != b"\x7fELF" {
-        return Ok(false);
-    }
-    if ehdr[4] != 2 || ehdr[5] != 1 {
-        return Ok(false);
+        // A bare name that isn't on PATH leaves the hash out instead of failing the run.
+        let hashes = InputHashes::of(None, Path::new("appimage-runner-no-such-muvm"), &[]).unwrap();
+        assert_eq!(hashes.muvm_sha256, None);
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    let e_shoff = u64::from_le_bytes(ehdr[40..48].try_into().unwrap());
-    let e_shentsize = u16::from_le_bytes(ehdr[58..60].try_into().unwrap()) as u64;
-    let e_shnum = u16::from_le_bytes(ehdr[60..62].try_into().unwrap()) as u64;
-    let e_shstrndx = u16::from_le_bytes(ehdr[62..64].try_into().unwrap()) as u64;
-    if e_shoff == 0 || e_shentsize == 0 || e_shnum == 0 || e_shstrndx >= e_shnum {
-        return Ok(false);
+    #[test]
+    fn probe_timeout_is_keyed_on_the_wrapper_not_the_exit_code() {
+        assert!(probe_timeout_fired(
+            "timeout: sending signal TERM to command '/bin/bash'\n"
+        ));
+        assert!(probe_timeout_fired(
+            "timeout: sending signal TERM to command '/bin/bash'\n\
+             timeout: sending signal KILL to command '/bin/bash'\n"
+        ));
+        // A probe that exits 124 on its own leaves the wrapper's log empty.
+        assert!(!probe_timeout_fired(""));
     }
 
-    // Read the section header string table header.
-    f.seek(SeekFrom::Start(e_shoff + e_shentsize * e_shstrndx))
-        .with_context(|| format!("seek shstrndx {}", path.display()))?;
-    let mut sh = vec![0u8; e_shentsize as usize];
-    f.read_exact(&mut sh)
-        .with_context(|| format!("read shstr header {}", path.display()))?;
-
-    // sh_offset/sh_size in ELF64 section header: offsets 24..32, 32..40.
-    let shstr_off = u64::from_le_bytes(sh[24..32].try_into().unwrap());
-    let shstr_size = u64::from_le_bytes(sh[32..40].try_into().unwrap());
-    if shstr_size == 0 {
-        return Ok(false);
-    }
-    // Cap to something sane to avoid huge allocations on corrupt binaries.
-    let cap = shstr_size.min(16 * 1024 * 1024);
-    f.seek(SeekFrom::Start(shstr_off))
-        .with_context(|| format!("seek shstrtab {}", path.display()))?;
-    let mut shstr = vec![0u8; cap as usize];
-    f.read_exact(&mut shstr)
-        .with_context(|| format!("read shstrtab {}", path.display()))?;
-
-    // Iterate section headers and compare names.
-    for idx in 0..e_shnum {
-        f.seek(SeekFrom::Start(e_shoff + e_shentsize * idx))
-            .with_context(|| format!("seek section header {}", path.display()))?;
-        f.read_exact(&mut sh)
-            .with_context(|| format!("read section header {}", path.display()))?;
-        let name_off = u32::from_le_bytes(sh[0..4].try_into().unwrap()) as usize;
-        if name_off >= shstr.len() {
-            continue;
-        }
-        let end = shstr[name_off..]
-            .iter()
-            .position(|&b| b == 0)
-            .map(|p| name_off + p)
-            .unwrap_or(shstr.len());
-        if &shstr[name_off..end] == section_name {
-            return Ok(true);
+    #[test]
+    fn detects_appimage_runtime_fuse_errors() {
+        for line in [
+            "fuse: device not found, try 'modprobe fuse' first",
+            "fuse: failed to exec fusermount: No such file or directory",
+            "dlopen(): error loading libfuse.so.2",
+            "AppImages require FUSE to run.",
+            "Cannot mount AppImage, please check your FUSE setup.",
+            "open /dev/fuse: Permission denied",
+        ] {
+            let combined = format!("starting\n  {line}\nexiting\n");
+            let attempt =
+                detect_fuse_mount_attempt(&combined).unwrap_or_else(|| panic!("missed {line:?}"));
+            assert_eq!(attempt.evidence, line);
         }
     }
 
-    Ok(false)
+    #[test]
+    fn ignores_lines_that_only_mention_fuse() {
+        for line in [
+            "[nss] refuse: untrusted certificate",
+            "Gsk-Message: diffuse: falling back to cairo",
+            "loaded /usr/lib64/libfuse3.so.3",
+            "Fused multiply-add available",
+        ] {
+            assert!(
+                detect_fuse_mount_attempt(line).is_none(),
+                "matched {line:?}"
+            );
+        }
+    }
 }
@@ -0,0 +1,602 @@
+//! Minimal pure-Rust SquashFS 4.0 reader, used to extract an AppImage's payload when neither
+//! `squashfs-ng` nor an `unsquashfs` binary is available.
+//!
+//! It reads straight from the AppImage at the payload offset and only does what extraction
+//! needs: directories, regular files (blocks, fragments, sparse blocks), symlinks, and the gzip,
+//! lzma, xz, lz4 and zstd compressors. Devices, FIFOs and sockets are skipped (as `unsquashfs`
+//! does without root), as are xattrs and ownership. Nothing is cached beyond metadata blocks, so
+//! it is slow on large images but has no runtime dependencies.
+
+use anyhow::{Context, Result, bail, ensure};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const MAGIC: u32 = 0x7371_7368;
+const METADATA_SIZE: usize = 8192;
+const NO_FRAGMENT: u32 = 0xFFFF_FFFF;
+/// Set in a data block / fragment size when the block is stored uncompressed.
+const BLOCK_UNCOMPRESSED: u32 = 1 << 24;
+
+struct Superblock {
+    block_size: u32,
+    fragment_count: u32,
+    compressor: u16,
+    root_inode: u64,
+    inode_table: u64,
+    directory_table: u64,
+    fragment_table: u64,
+}
+
+impl Superblock {
+    fn parse(sb: &[u8; 96]) -> Result<Self> {
+        let u16_at = |o: usize| u16::from_le_bytes([sb[o], sb[o + 1]]);
+        let u32_at = |o: usize| u32::from_le_bytes(sb[o..o + 4].try_into().unwrap());
+        let u64_at = |o: usize| u64::from_le_bytes(sb[o..o + 8].try_into().unwrap());
+        ensure!(u32_at(0) == MAGIC, "not a SquashFS superblock");
+        ensure!(
+            (u16_at(28), u16_at(30)) == (4, 0),
+            "unsupported SquashFS version {}.{}",
+            u16_at(28),
+            u16_at(30)
+        );
+        let block_size = u32_at(12);
+        ensure!(
+            block_size.is_power_of_two() && (4096..=1 << 20).contains(&block_size),
+            "invalid SquashFS block size {block_size}"
+        );
+        ensure!(
+            u32::from(u16_at(22)) == block_size.trailing_zeros(),
+            "SquashFS block_log {} does not match block size {block_size}",
+            u16_at(22)
+        );
+        Ok(Self {
+            block_size,
+            fragment_count: u32_at(16),
+            compressor: u16_at(20),
+            root_inode: u64_at(32),
+            inode_table: u64_at(64),
+            directory_table: u64_at(72),
+            fragment_table: u64_at(80),
+        })
+    }
+}
+
+enum InodeKind {
+    Dir {
+        block_index: u32,
+        block_offset: u16,
+        /// Size of the directory listing (the on-disk value counts 3 extra bytes).
+        listing_size: u32,
+    },
+    File {
+        blocks_start: u64,
+        file_size: u64,
+        fragment: u32,
+        fragment_offset: u32,
+        block_sizes: Vec<u32>,
+    },
+    Symlink {
+        target: Vec<u8>,
+    },
+    /// Device, FIFO or socket.
+    Special,
+}
+
+struct Inode {
+    mode: u16,
+    kind: InodeKind,
+}
+
+struct Archive {
+    file: File,
+    /// Byte offset of the superblock in `file` (the AppImage runtime's size).
+    base: u64,
+    /// Bytes from `base` to the end of `file`; no read may go past it.
+    len: u64,
+    sb: Superblock,
+    /// Decompressed metadata blocks keyed by absolute position, with the next block's position.
+    metadata: HashMap<u64, (Vec<u8>, u64)>,
+    fragments: Option<Vec<(u64, u32)>>,
+}
+
+/// Extracts the SquashFS at `offset` in `path` into `dest`, which must not exist yet.
+pub(crate) fn extract(path: &Path, offset: u64, dest: &Path) -> Result<()> {
+    let mut file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut sb = [0u8; 96];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut sb)
+        .context("read SquashFS superblock")?;
+    let sb = Superblock::parse(&sb)?;
+    let len = file.metadata()?.len().saturating_sub(offset);
+    ensure!(
+        matches!(sb.compressor, 1 | 2 | 4 | 5 | 6),
+        "the built-in SquashFS reader does not support {} compression",
+        crate::squashfs_compression_name(sb.compressor)
+    );
+    let mut archive = Archive {
+        file,
+        base: offset,
+        len,
+        sb,
+        metadata: HashMap::new(),
+        fragments: None,
+    };
+    let root = archive.read_inode(archive.sb.root_inode)?;
+    std::fs::create_dir(dest).with_context(|| format!("create {}", dest.display()))?;
+    archive.extract_dir(&root, dest)?;
+    set_mode(dest, root.mode)
+}
+
+impl Archive {
+    fn read_at(&mut self, pos: u64, len: usize) -> Result<Vec<u8>> {
+        // Checked before allocating, so a corrupt length can't ask for more than the image.
+        ensure!(
+            pos.checked_add(len as u64)
+                .is_some_and(|end| end <= self.len),
+            "read of {len} bytes at SquashFS offset {pos} is past the end of the image"
+        );
+        let mut buf = vec![0u8; len];
+        self.file.seek(SeekFrom::Start(self.base + pos))?;
+        self.file
+            .read_exact(&mut buf)
+            .with_context(|| format!("read {len} bytes at SquashFS offset {pos}"))?;
+        Ok(buf)
+    }
+
+    fn decompress(&self, src: &[u8], max_len: usize) -> Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(max_len);
+        match self.sb.compressor {
+            1 => {
+                flate2::read::ZlibDecoder::new(src)
+                    .take(max_len as u64)
+                    .read_to_end(&mut out)
+                    .context("gzip")?;
+            }
+            2 => lzma_rs::lzma_decompress(&mut std::io::Cursor::new(src), &mut out)
+                .map_err(|e| anyhow::anyhow!("lzma: {e:?}"))?,
+            4 => lzma_rs::xz_decompress(&mut std::io::Cursor::new(src), &mut out)
+                .map_err(|e| anyhow::anyhow!("xz: {e:?}"))?,
+            5 => {
+                out.resize(max_len, 0);
+                let n = lz4_flex::block::decompress_into(src, &mut out)
+                    .map_err(|e| anyhow::anyhow!("lz4: {e}"))?;
+                out.truncate(n);
+            }
+            6 => {
+                ruzstd::decoding::StreamingDecoder::new(src)
+                    .map_err(|e| anyhow::anyhow!("zstd: {e}"))?
+                    .take(max_len as u64)
+                    .read_to_end(&mut out)
+                    .context("zstd")?;
+            }
+            id => bail!("unsupported compressor id {id}"),
+        }
+        Ok(out)
+    }
+
+    /// Metadata block at absolute position `pos` and the position of the one after it.
+    fn metadata_block(&mut self, pos: u64) -> Result<(&[u8], u64)> {
+        if !self.metadata.contains_key(&pos) {
+            let header = self.read_at(pos, 2)?;
+            let header = u16::from_le_bytes([header[0], header[1]]);
+            let len = usize::from(header & 0x7FFF);
+            let data_pos = pos.checked_add(2).context("metadata position overflows")?;
+            let raw = self.read_at(data_pos, len)?;
+            let data = if header & 0x8000 != 0 {
+                raw
+            } else {
+                self.decompress(&raw, METADATA_SIZE)
+                    .with_context(|| format!("decompress metadata block at {pos}"))?
+            };
+            self.metadata.insert(pos, (data, data_pos + len as u64));
+        }
+        let (data, next) = &self.metadata[&pos];
+        Ok((data, *next))
+    }
+
+    /// `len` bytes of metadata starting `offset` bytes into the block at `pos`, continuing into
+    /// the following blocks as needed.
+    fn read_metadata(&mut self, mut pos: u64, mut offset: usize, len: usize) -> Result<Vec<u8>> {
+        // `len` comes from the image; grow as blocks are actually read rather than trusting it.
+        let mut out = Vec::with_capacity(len.min(METADATA_SIZE));
+        while out.len() < len {
+            let (data, next) = self.metadata_block(pos)?;
+            ensure!(
+                offset <= data.len(),
+                "metadata offset {offset} out of range"
+            );
+            let take = (len - out.len()).min(data.len() - offset);
+            out.extend_from_slice(&data[offset..offset + take]);
+            ensure!(
+                take > 0 || out.len() == len,
+                "metadata ended early at {pos}"
+            );
+            pos = next;
+            offset = 0;
+        }
+        Ok(out)
+    }
+
+    /// Reads the inode at a SquashFS inode reference (block << 16 | offset).
+    fn read_inode(&mut self, reference: u64) -> Result<Inode> {
+        let pos = self
+            .sb
+            .inode_table
+            .checked_add(reference >> 16)
+            .context("inode reference overflows")?;
+        let offset = (reference & 0xFFFF) as usize;
+        let common = self.read_metadata(pos, offset, 16)?;
+        let inode_type = u16::from_le_bytes([common[0], common[1]]);
+        let mode = u16::from_le_bytes([common[2], common[3]]);
+        // Only the fixed part this type has: reading further could run past the last inode
+        // into whatever follows the inode table.
+        let fixed = match inode_type {
+            1 | 2 => 32,
+            8 => 40,
+            9 => 56,
+            3 | 10 => 24,
+            4..=7 | 11..=14 => {
+                return Ok(Inode {
+                    mode,
+                    kind: InodeKind::Special,
+                });
+            }
+            t => bail!("unknown inode type {t}"),
+        };
+        let head = self.read_metadata(pos, offset, fixed)?;
+        let u16_at = |o: usize| u16::from_le_bytes([head[o], head[o + 1]]);
+        let u32_at = |o: usize| u32::from_le_bytes(head[o..o + 4].try_into().unwrap());
+        let u64_at = |o: usize| u64::from_le_bytes(head[o..o + 8].try_into().unwrap());
+        let kind = match inode_type {
+            1 => InodeKind::Dir {
+                block_index: u32_at(16),
+                listing_size: u32::from(u16_at(24)).saturating_sub(3),
+                block_offset: u16_at(26),
+            },
+            8 => InodeKind::Dir {
+                listing_size: u32_at(20).saturating_sub(3),
+                block_index: u32_at(24),
+                block_offset: u16_at(34),
+            },
+            t @ (2 | 9) => {
+                let (blocks_start, file_size, fragment, fragment_offset) = if t == 2 {
+                    (
+                        u64::from(u32_at(16)),
+                        u64::from(u32_at(28)),
+                        u32_at(20),
+                        u32_at(24),
+                    )
+                } else {
+                    (u64_at(16), u64_at(24), u32_at(44), u32_at(48))
+                };
+                let block_size = u64::from(self.sb.block_size);
+                let count = if fragment == NO_FRAGMENT {
+                    file_size.div_ceil(block_size)
+                } else {
+                    file_size / block_size
+                };
+                let sizes_len = usize::try_from(count)
+                    .ok()
+                    .and_then(|n| n.checked_mul(4)?.checked_add(fixed))
+                    .context("file inode block count overflows")?;
+                let sizes = self.read_metadata(pos, offset, sizes_len)?;
+                InodeKind::File {
+                    blocks_start,
+                    file_size,
+                    fragment,
+                    fragment_offset,
+                    block_sizes: sizes[fixed..]
+                        .chunks_exact(4)
+                        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+                        .collect(),
+                }
+            }
+            3 | 10 => {
+                let len = u32_at(20) as usize;
+                let full = self.read_metadata(pos, offset, fixed + len)?;
+                InodeKind::Symlink {
+                    target: full[fixed..].to_vec(),
+                }
+            }
+            _ => unreachable!("inode type {inode_type} has no fixed size"),
+        };
+        Ok(Inode { mode, kind })
+    }
+
+    /// `(start, size)` of fragment block `index`.
+    fn fragment(&mut self, index: u32) -> Result<(u64, u32)> {
+        if self.fragments.is_none() {
+            let count = self.sb.fragment_count as usize;
+            let pointers = self.read_at(self.sb.fragment_table, count.div_ceil(512) * 8)?;
+            let mut entries = Vec::new();
+            for (i, p) in pointers.chunks_exact(8).enumerate() {
+                let pos = u64::from_le_bytes(p.try_into().unwrap());
+                let n = (count - i * 512).min(512);
+                let raw = self.read_metadata(pos, 0, n * 16)?;
+                entries.extend(raw.chunks_exact(16).map(|e| {
+                    (
+                        u64::from_le_bytes(e[0..8].try_into().unwrap()),
+                        u32::from_le_bytes(e[8..12].try_into().unwrap()),
+                    )
+                }));
+            }
+            self.fragments = Some(entries);
+        }
+        self.fragments
+            .as_ref()
+            .and_then(|f| f.get(index as usize).copied())
+            .with_context(|| format!("fragment {index} out of range"))
+    }
+
+    /// Reads a data block or fragment block given its on-disk size word.
+    fn read_block(&mut self, pos: u64, size: u32) -> Result<Vec<u8>> {
+        let len = (size & !BLOCK_UNCOMPRESSED) as usize;
+        let raw = self.read_at(pos, len)?;
+        if size & BLOCK_UNCOMPRESSED != 0 {
+            Ok(raw)
+        } else {
+            self.decompress(&raw, self.sb.block_size as usize)
+        }
+    }
+
+    fn extract_dir(&mut self, inode: &Inode, dest: &Path) -> Result<()> {
+        let InodeKind::Dir {
+            block_index,
+            block_offset,
+            listing_size,
+        } = inode.kind
+        else {
+            bail!("{} is not a directory inode", dest.display());
+        };
+        let listing_pos = self
+            .sb
+            .directory_table
+            .checked_add(u64::from(block_index))
+            .context("directory block index overflows")?;
+        let listing = self.read_metadata(
+            listing_pos,
+            usize::from(block_offset),
+            listing_size as usize,
+        )?;
+
+        let mut at = 0;
+        while at + 12 <= listing.len() {
+            let u32_at = |o: usize| u32::from_le_bytes(listing[o..o + 4].try_into().unwrap());
+            let count = u32_at(at)
+                .checked_add(1)
+                .context("directory header count overflows")?;
+            let inode_block = u64::from(u32_at(at + 4));
+            at += 12;
+            for _ in 0..count {
+                ensure!(at + 8 <= listing.len(), "truncated directory listing");
+                let entry_offset = u16::from_le_bytes([listing[at], listing[at + 1]]);
+                let name_len = usize::from(u16::from_le_bytes([listing[at + 6], listing[at + 7]]))
+                    .checked_add(1)
+                    .context("directory entry name size overflows")?;
+                let name = listing
+                    .get(at + 8..at + 8 + name_len)
+                    .context("truncated directory entry name")?;
+                at += 8 + name_len;
+                let name = std::str::from_utf8(name)
+                    .map_err(|_| anyhow::anyhow!("non-UTF-8 name in {}", dest.display()))?;
+                ensure!(
+                    !name.is_empty() && name != "." && name != ".." && !name.contains('/'),
+                    "invalid entry name {name:?} in {}",
+                    dest.display()
+                );
+                let child = self.read_inode((inode_block << 16) | u64::from(entry_offset))?;
+                self.extract_entry(&child, &dest.join(name))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn extract_entry(&mut self, inode: &Inode, path: &Path) -> Result<()> {
+        match &inode.kind {
+            InodeKind::Dir { .. } => {
+                std::fs::create_dir(path).with_context(|| format!("create {}", path.display()))?;
+                self.extract_dir(inode, path)?;
+                set_mode(path, inode.mode)
+            }
+            InodeKind::File { .. } => {
+                self.extract_file(inode, path)?;
+                set_mode(path, inode.mode)
+            }
+            InodeKind::Symlink { target } => {
+                use std::os::unix::ffi::OsStrExt;
+                std::os::unix::fs::symlink(std::ffi::OsStr::from_bytes(target), path)
+                    .with_context(|| format!("symlink {}", path.display()))
+            }
+            InodeKind::Special => {
+                eprintln!("Skipping special file {}", path.display());
+                Ok(())
+            }
+        }
+    }
+
+    fn extract_file(&mut self, inode: &Inode, path: &Path) -> Result<()> {
+        use std::io::Write;
+
+        let InodeKind::File {
+            blocks_start,
+            file_size,
+            fragment,
+            fragment_offset,
+            block_sizes,
+        } = &inode.kind
+        else {
+            unreachable!("extract_file called on a non-file inode");
+        };
+        let block_size = u64::from(self.sb.block_size);
+        let mut out = std::io::BufWriter::new(
+            File::create(path).with_context(|| format!("create {}", path.display()))?,
+        );
+        let mut pos = *blocks_start;
+        let mut written = 0u64;
+        for &size in block_sizes {
+            let want = (file_size - written).min(block_size);
+            if size == 0 {
+                // Sparse block.
+                out.write_all(&vec![0u8; want as usize])?;
+            } else {
+                let block = self.read_block(pos, size)?;
+                ensure!(
+                    block.len() as u64 >= want,
+                    "short data block in {}",
+                    path.display()
+                );
+                out.write_all(&block[..want as usize])?;
+                pos = pos
+                    .checked_add(u64::from(size & !BLOCK_UNCOMPRESSED))
+                    .context("data block position overflows")?;
+            }
+            written += want;
+        }
+        if *fragment != NO_FRAGMENT {
+            let (start, size) = self.fragment(*fragment)?;
+            let block = self.read_block(start, size)?;
+            let from = *fragment_offset as usize;
+            let tail = from
+                .checked_add((file_size - written) as usize)
+                .and_then(|end| block.get(from..end))
+                .with_context(|| format!("fragment too short for {}", path.display()))?;
+            out.write_all(tail)?;
+        }
+        out.flush()
+            .with_context(|| format!("write {}", path.display()))
+    }
+}
+
+fn set_mode(path: &Path, mode: u16) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::set_permissions(
+        path,
+        std::fs::Permissions::from_mode(u32::from(mode) & 0o7777),
+    )
+    .with_context(|| format!("chmod {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fixture in `tests/fixtures`, written by `make_squashfs_fixtures.py` next to them.
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    fn scratch_dir(label: &str) -> std::path::PathBuf {
+        let dest =
+            std::env::temp_dir().join(format!("squashfs-test-{label}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dest);
+        dest
+    }
+
+    /// The tree every fixture holds: hello.txt, link -> hello.txt, sub/inner.txt.
+    fn assert_common_tree(dest: &Path) {
+        use std::os::unix::fs::PermissionsExt;
+
+        assert_eq!(
+            std::fs::read_to_string(dest.join("hello.txt")).unwrap(),
+            "hello, squashfs\n"
+        );
+        assert_eq!(
+            std::fs::read_link(dest.join("link")).unwrap(),
+            Path::new("hello.txt")
+        );
+        assert!(dest.join("sub").is_dir());
+        let inner = dest.join("sub/inner.txt");
+        assert_eq!(std::fs::read_to_string(&inner).unwrap(), "nested\n");
+        let mode = std::fs::metadata(&inner).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    fn names(dest: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dest)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn extracts_dir_file_and_symlink() {
+        let dest = scratch_dir("uncompressed");
+        extract(&fixture("uncompressed.squashfs"), 0, &dest).unwrap();
+        assert_eq!(names(&dest), ["hello.txt", "link", "sub"]);
+        assert_common_tree(&dest);
+        std::fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn extracts_compressed_blocks_and_fragment_tails() {
+        // big.bin: two full data blocks plus a tail in the shared fragment block, which also
+        // holds the whole of the small files.
+        let big: Vec<u8> = (0..2 * 4096 + 1000)
+            .map(|i: u32| ((i * 7 + i / 97) % 251) as u8)
+            .collect();
+        for compressor in ["gzip", "xz", "zstd"] {
+            let dest = scratch_dir(compressor);
+            extract(&fixture(&format!("{compressor}.squashfs")), 0, &dest)
+                .unwrap_or_else(|e| panic!("{compressor}: {e:#}"));
+            assert_eq!(names(&dest), ["big.bin", "hello.txt", "link", "sub"]);
+            assert_eq!(
+                std::fs::read(dest.join("big.bin")).unwrap(),
+                big,
+                "{compressor}"
+            );
+            assert_common_tree(&dest);
+            std::fs::remove_dir_all(&dest).unwrap();
+        }
+    }
+
+    #[test]
+    fn truncated_or_corrupt_images_are_errors() {
+        let image = std::fs::read(fixture("gzip.squashfs")).unwrap();
+        let mut cases: Vec<(&str, Vec<u8>)> = vec![
+            ("short superblock", image[..50].to_vec()),
+            ("cut after the superblock", image[..200].to_vec()),
+        ];
+        let mut bad_magic = image.clone();
+        bad_magic[0] ^= 0xFF;
+        cases.push(("bad magic", bad_magic));
+        let mut zero_block_size = image.clone();
+        zero_block_size[12..16].fill(0);
+        cases.push(("block size 0", zero_block_size));
+        let mut far_inode_table = image.clone();
+        far_inode_table[64..72].copy_from_slice(&u64::MAX.to_le_bytes());
+        cases.push(("inode table past u64", far_inode_table));
+
+        for (label, bytes) in cases {
+            let dir = scratch_dir("corrupt");
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("image.squashfs");
+            std::fs::write(&path, bytes).unwrap();
+            assert!(
+                extract(&path, 0, &dir.join("out")).is_err(),
+                "{label} extracted"
+            );
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_block_size_that_is_not_a_power_of_two_in_range() {
+        let image = std::fs::read(fixture("uncompressed.squashfs")).unwrap();
+        let mut sb: [u8; 96] = image[..96].try_into().unwrap();
+        assert_eq!(Superblock::parse(&sb).unwrap().block_size, 4096);
+        for bad in [0u32, 4095, 6144, 2 << 20] {
+            sb[12..16].copy_from_slice(&bad.to_le_bytes());
+            assert!(Superblock::parse(&sb).is_err(), "block size {bad}");
+        }
+        // A valid size that disagrees with block_log.
+        sb[12..16].copy_from_slice(&8192u32.to_le_bytes());
+        assert!(Superblock::parse(&sb).is_err());
+    }
+}
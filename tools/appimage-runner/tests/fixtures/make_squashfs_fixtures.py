@@ -0,0 +1,222 @@
+#!/usr/bin/env python3
+"""Writes the SquashFS fixtures for the built-in SquashFS reader's tests.
+
+Each is a SquashFS 4.0 image built by hand (no mksquashfs needed):
+
+  uncompressed.squashfs  every block stored uncompressed, no fragments, holding
+                             hello.txt       "hello, squashfs\\n", mode 0644
+                             link -> hello.txt
+                             sub/            mode 0755
+                             sub/inner.txt   "nested\\n", mode 0600
+  gzip.squashfs,         the same tree plus big.bin (BIG_SIZE bytes: two full blocks and a
+  xz.squashfs,           tail), with compressed data and metadata blocks; every file's tail,
+  zstd.squashfs          and all of the small files, live in one shared fragment block
+
+Blocks that don't shrink when compressed are stored uncompressed, as mksquashfs does. xz
+uses Python's lzma module and zstd the `zstd` CLI, which must be on PATH.
+
+Run it from this directory: python3 make_squashfs_fixtures.py
+"""
+
+import lzma
+import struct
+import subprocess
+import zlib
+
+BLOCK_SIZE = 4096
+NO_FRAGMENT = 0xFFFFFFFF
+BLOCK_UNCOMPRESSED = 1 << 24
+METADATA_UNCOMPRESSED = 0x8000
+MTIME = 1700000000
+BIG_SIZE = 2 * BLOCK_SIZE + 1000
+
+# Superblock flags. Uncompressed: uncompressed inodes, data, fragments and ids; no fragments;
+# no xattrs. Compressed: only no xattrs.
+FLAGS_UNCOMPRESSED = 0x0001 | 0x0002 | 0x0008 | 0x0010 | 0x0200 | 0x0800
+FLAGS_COMPRESSED = 0x0200
+
+DIR, FILE, SYMLINK = 1, 2, 3
+
+COMPRESSORS = {
+    None: (1, None),  # gzip id, though nothing is compressed
+    "gzip": (1, lambda data: zlib.compress(data, 9)),
+    "xz": (4, lambda data: lzma.compress(data, format=lzma.FORMAT_XZ, check=lzma.CHECK_CRC32)),
+    "zstd": (
+        6,
+        lambda data: subprocess.run(
+            ["zstd", "-19", "-q", "-c"], input=data, capture_output=True, check=True
+        ).stdout,
+    ),
+}
+
+
+def big_content():
+    # Compressible but not trivially so, and different in every block.
+    return bytes((i * 7 + i // 97) % 251 for i in range(BIG_SIZE))
+
+
+def build(compressor):
+    compressor_id, compress = COMPRESSORS[compressor]
+
+    def maybe_compress(data):
+        if compress is None:
+            return data, False
+        packed = compress(data)
+        return (packed, True) if len(packed) < len(data) else (data, False)
+
+    def metadata_block(payload):
+        assert len(payload) <= 8192
+        packed, compressed = maybe_compress(payload)
+        header = len(packed) if compressed else len(packed) | METADATA_UNCOMPRESSED
+        return struct.pack("<H", header) + packed
+
+    files = {"hello.txt": b"hello, squashfs\n", "sub/inner.txt": b"nested\n"}
+    if compress is not None:
+        files["big.bin"] = big_content()
+
+    # Data blocks straight after the 96-byte superblock. Without compression each file is one
+    # short block; with it, full blocks go here and tails go to the fragment block after them.
+    data = b""
+    blocks = {}  # name -> (blocks_start, [size words], fragment, fragment_offset)
+    fragment_block = b""
+    for name, content in files.items():
+        start = 96 + len(data)
+        if compress is None:
+            data += content
+            blocks[name] = (start, [len(content) | BLOCK_UNCOMPRESSED], NO_FRAGMENT, 0)
+            continue
+        full = len(content) // BLOCK_SIZE
+        sizes = []
+        for i in range(full):
+            packed, compressed = maybe_compress(content[i * BLOCK_SIZE : (i + 1) * BLOCK_SIZE])
+            data += packed
+            sizes.append(len(packed) if compressed else len(packed) | BLOCK_UNCOMPRESSED)
+        blocks[name] = (start if full else 0, sizes, 0, len(fragment_block))
+        fragment_block += content[full * BLOCK_SIZE :]
+    fragments = []
+    if fragment_block:
+        packed, compressed = maybe_compress(fragment_block)
+        fragments.append((96 + len(data), len(packed) if compressed else len(packed) | BLOCK_UNCOMPRESSED))
+        data += packed
+
+    # Inode numbers; the root comes last, as mksquashfs does.
+    leaves = ["big.bin"] * ("big.bin" in files) + ["hello.txt", "link", "sub/inner.txt"]
+    numbers = {name: i + 1 for i, name in enumerate(leaves)}
+    numbers["sub"] = len(numbers) + 1
+    numbers[""] = len(numbers) + 1
+
+    def inode_header(kind, mode, number):
+        return struct.pack("<HHHHII", kind, mode, 0, 0, MTIME, number)
+
+    def file_inode(name, mode):
+        start, sizes, fragment, fragment_offset = blocks[name]
+        return (
+            inode_header(FILE, mode, numbers[name])
+            + struct.pack("<IIII", start, fragment, fragment_offset, len(files[name]))
+            + b"".join(struct.pack("<I", s) for s in sizes)
+        )
+
+    def symlink_inode(name, target):
+        return inode_header(SYMLINK, 0o777, numbers[name]) + struct.pack(
+            "<II", 1, len(target)
+        ) + target
+
+    def dir_inode(name, mode, listing_offset, listing_size, links, parent):
+        return inode_header(DIR, mode, numbers[name]) + struct.pack(
+            "<IIHHI", 0, links, listing_size + 3, listing_offset, parent
+        )
+
+    def listing(entries):
+        # One header run: all inodes are in the first (only) inode table block.
+        out = struct.pack("<III", len(entries) - 1, 0, numbers[entries[0][0]])
+        for name, offset, kind in entries:
+            base = name.rsplit("/", 1)[-1].encode()
+            delta = numbers[name] - numbers[entries[0][0]]
+            out += struct.pack("<HhHH", offset, delta, kind, len(base) - 1) + base
+        return out
+
+    # Inode table: offsets are known once the earlier inodes are laid out.
+    inodes = b""
+    offsets = {}
+
+    def add(name, inode):
+        nonlocal inodes
+        offsets[name] = len(inodes)
+        inodes += inode
+
+    if "big.bin" in files:
+        add("big.bin", file_inode("big.bin", 0o644))
+    add("hello.txt", file_inode("hello.txt", 0o644))
+    add("link", symlink_inode("link", b"hello.txt"))
+    add("sub/inner.txt", file_inode("sub/inner.txt", 0o600))
+    sub_listing = listing([("sub/inner.txt", offsets["sub/inner.txt"], FILE)])
+    add("sub", dir_inode("sub", 0o755, 0, len(sub_listing), 2, numbers[""]))
+    root_entries = [
+        ("hello.txt", offsets["hello.txt"], FILE),
+        ("link", offsets["link"], SYMLINK),
+        ("sub", offsets["sub"], DIR),
+    ]
+    if "big.bin" in files:
+        root_entries.insert(0, ("big.bin", offsets["big.bin"], FILE))
+    root_listing = listing(root_entries)
+    add(
+        "",
+        dir_inode("", 0o755, len(sub_listing), len(root_listing), 3, len(numbers) + 1),
+    )
+
+    inode_table = 96 + len(data)
+    inode_blocks = metadata_block(inodes)
+    directory_table = inode_table + len(inode_blocks)
+    directory_blocks = metadata_block(sub_listing + root_listing)
+    tables = directory_table + len(directory_blocks)
+    fragment_tables = b""
+    fragment_table = 0xFFFFFFFFFFFFFFFF
+    if fragments:
+        entries = metadata_block(b"".join(struct.pack("<QII", s, n, 0) for s, n in fragments))
+        fragment_table = tables + len(entries)
+        fragment_tables = entries + struct.pack("<Q", tables)
+    id_block_pos = tables + len(fragment_tables)
+    id_block = metadata_block(struct.pack("<I", 0))
+    id_table = id_block_pos + len(id_block)
+    id_pointers = struct.pack("<Q", id_block_pos)
+    bytes_used = id_table + len(id_pointers)
+
+    superblock = struct.pack(
+        "<IIIIIHHHHHHQQQQQQQQ",
+        0x73717368,
+        len(numbers),
+        MTIME,
+        BLOCK_SIZE,
+        len(fragments),
+        compressor_id,
+        BLOCK_SIZE.bit_length() - 1,
+        FLAGS_UNCOMPRESSED if compress is None else FLAGS_COMPRESSED,
+        1,
+        4,
+        0,
+        offsets[""],  # root inode reference: block 0, this offset
+        bytes_used,
+        id_table,
+        0xFFFFFFFFFFFFFFFF,
+        inode_table,
+        directory_table,
+        fragment_table,
+        0xFFFFFFFFFFFFFFFF,
+    )
+    assert len(superblock) == 96
+
+    image = (
+        superblock + data + inode_blocks + directory_blocks + fragment_tables + id_block + id_pointers
+    )
+    image += b"\0" * (-len(image) % 4096)
+    return image
+
+
+def main():
+    for compressor in COMPRESSORS:
+        with open(f"{compressor or 'uncompressed'}.squashfs", "wb") as f:
+            f.write(build(compressor))
+
+
+if __name__ == "__main__":
+    main()
@@ -75,6 +75,10 @@ enum Command {
         #[arg(long)]
         json: bool,
     },
+
+    /// Print the JSON Schema of the doctor report and diff, for validating them in CI.
+    #[command(hide = true)]
+    EmitSchema,
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -132,6 +136,8 @@ fn main() -> Result<()> {
         Command::DoctorShow { snapshot, json } => {
             ops::doctor::show(snapshot, json).context("doctor show")?
         }
+
+        Command::EmitSchema => ops::doctor::emit_schema()?,
     }
 
     Ok(())
@@ -1,5 +1,6 @@
 use crate::ops::util;
 use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
@@ -7,7 +8,7 @@ use std::path::{Path, PathBuf};
 #[cfg(feature = "native-journald")]
 use systemd::{id128::Id128, journal};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Clone)]
 pub struct DoctorReport {
     pub timestamp: Option<String>,
     pub uname: Option<String>,
@@ -19,7 +20,7 @@ pub struct DoctorReport {
     pub skipped: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Clone)]
 pub struct CommandProbe {
     pub status: i32,
     pub stdout: String,
@@ -104,7 +105,7 @@ pub fn show(snapshot: PathBuf, json: bool) -> Result<()> {
     Ok(())
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct DoctorDiff {
     gsettings: MapDiff<String>,
     files: MapDiff<String>,
@@ -112,7 +113,7 @@ struct DoctorDiff {
     skipped: MapDiff<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct MapDiff<T>
 where
     T: Serialize,
@@ -122,7 +123,7 @@ where
     changed: BTreeMap<String, ValueChange<T>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct ValueChange<T>
 where
     T: Serialize,
@@ -131,6 +132,20 @@ where
     new: T,
 }
 
+/// Prints the JSON Schemas of the doctor report and of `doctor-diff --json`, for validating
+/// them in CI.
+pub fn emit_schema() -> Result<()> {
+    let schema = serde_json::json!({
+        "doctor-report": schemars::schema_for!(DoctorReport),
+        "doctor-diff": schemars::schema_for!(DoctorDiff),
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema).context("serialize schema")?
+    );
+    Ok(())
+}
+
 fn diff_reports(old: &DoctorReport, new: &DoctorReport) -> DoctorDiff {
     DoctorDiff {
         gsettings: diff_map(&old.gsettings, &new.gsettings),
@@ -0,0 +1,207 @@
+//! Test helper: puts a process into a state the stuck-snapshot code is meant to diagnose,
+//! prints `target_pid=<pid>` once that state is reached, then waits to be killed.
+//!
+//! Used by `tests/snapshot.rs`; also handy for eyeballing `--mode snapshot-pid` output.
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use std::fs;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+#[derive(Parser, Debug)]
+#[command(about = "Hold a process in a known /proc state for snapshot tests", long_about = None)]
+struct Cli {
+    /// Which state to enter.
+    #[arg(long, value_enum)]
+    state: State,
+
+    /// (many-threads) Number of extra parked threads.
+    #[arg(long, default_value_t = 64)]
+    threads: usize,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum State {
+    /// A thread blocked in ppoll on an eventfd + the read end of a pipe we also hold for writing.
+    PpollEventfd,
+    /// A background process group member stopped by SIGTTIN on its controlling TTY.
+    TtyStopped,
+    /// Many parked threads (exercises task sampling caps).
+    ManyThreads,
+}
+
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let target_pid = match cli.state {
+        State::PpollEventfd => enter_ppoll_eventfd()?,
+        State::TtyStopped => enter_tty_stopped()?,
+        State::ManyThreads => enter_many_threads(cli.threads)?,
+    };
+
+    let mut stdout = io::stdout();
+    writeln!(stdout, "target_pid={target_pid}")?;
+    stdout.flush()?;
+
+    loop {
+        std::thread::park();
+    }
+}
+
+fn wait_until(what: &str, mut ready: impl FnMut() -> bool) -> Result<()> {
+    let start = Instant::now();
+    while !ready() {
+        if start.elapsed() > READY_TIMEOUT {
+            bail!("timed out waiting for {what}");
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    Ok(())
+}
+
+fn enter_ppoll_eventfd() -> Result<u32> {
+    let efd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+    if efd < 0 {
+        return Err(io::Error::last_os_error()).context("eventfd");
+    }
+    let mut pipe_fds = [0 as libc::c_int; 2];
+    if unsafe { libc::pipe2(pipe_fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error()).context("pipe2");
+    }
+    // The write end stays open in this process, so we are the pipe's writer owner.
+    let read_fd = pipe_fds[0];
+
+    let (tid_tx, tid_rx) = std::sync::mpsc::channel::<libc::pid_t>();
+    std::thread::spawn(move || {
+        let _ = tid_tx.send(unsafe { libc::gettid() });
+        let mut fds = [
+            libc::pollfd {
+                fd: efd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: read_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+        loop {
+            unsafe {
+                libc::ppoll(
+                    fds.as_mut_ptr(),
+                    fds.len() as libc::nfds_t,
+                    std::ptr::null(),
+                    std::ptr::null(),
+                )
+            };
+        }
+    });
+    let tid = tid_rx.recv().context("ppoll thread tid")?;
+
+    let syscall_path = format!("/proc/self/task/{tid}/syscall");
+    wait_until("ppoll thread to block", || {
+        fs::read_to_string(&syscall_path)
+            .ok()
+            .and_then(|s| s.split_whitespace().next()?.parse::<libc::c_long>().ok())
+            == Some(libc::SYS_ppoll)
+    })?;
+    Ok(std::process::id())
+}
+
+fn enter_tty_stopped() -> Result<u32> {
+    // Layout: this process -> session leader (owns the pty) -> background member.
+    // The background member reads the tty and is stopped by SIGTTIN.
+    let mut report = [0 as libc::c_int; 2];
+    if unsafe { libc::pipe(report.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error()).context("pipe");
+    }
+
+    let leader = unsafe { libc::fork() };
+    if leader < 0 {
+        return Err(io::Error::last_os_error()).context("fork session leader");
+    }
+    if leader == 0 {
+        // Only async-signal-safe calls from here on; failures just exit.
+        unsafe { tty_session_leader(report[1]) }
+    }
+    unsafe { libc::close(report[1]) };
+
+    let mut buf = [0u8; 4];
+    let n = unsafe { libc::read(report[0], buf.as_mut_ptr().cast(), buf.len()) };
+    unsafe { libc::close(report[0]) };
+    if n != 4 {
+        bail!("session leader failed to set up the tty (read {n} bytes)");
+    }
+    let member = u32::from_ne_bytes(buf);
+
+    let stat_path = format!("/proc/{member}/stat");
+    wait_until("background member to stop", || {
+        fs::read_to_string(&stat_path)
+            .ok()
+            .and_then(|s| {
+                let after = s.get(s.rfind(')')? + 2..)?;
+                after.chars().next()
+            })
+            .is_some_and(|state| state == 'T')
+    })?;
+    Ok(member)
+}
+
+unsafe fn tty_session_leader(report_fd: libc::c_int) -> ! {
+    libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+    if libc::setsid() < 0 {
+        libc::_exit(10);
+    }
+    let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+    if master < 0 || libc::grantpt(master) != 0 || libc::unlockpt(master) != 0 {
+        libc::_exit(11);
+    }
+    let name = libc::ptsname(master);
+    if name.is_null() {
+        libc::_exit(12);
+    }
+    // Session leader without a ctty + open without O_NOCTTY => this becomes our ctty.
+    let slave = libc::open(name, libc::O_RDWR);
+    if slave < 0 {
+        libc::_exit(13);
+    }
+
+    let member = libc::fork();
+    if member < 0 {
+        libc::_exit(14);
+    }
+    if member == 0 {
+        libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+        // New process group in the same session: not the tty's foreground group.
+        libc::setpgid(0, 0);
+        let mut b = [0u8; 1];
+        loop {
+            libc::read(slave, b.as_mut_ptr().cast(), 1);
+        }
+    }
+    libc::setpgid(member, member);
+
+    let bytes = (member as u32).to_ne_bytes();
+    libc::write(report_fd, bytes.as_ptr().cast(), bytes.len());
+    libc::close(report_fd);
+    loop {
+        libc::pause();
+    }
+}
+
+fn enter_many_threads(threads: usize) -> Result<u32> {
+    for _ in 0..threads {
+        std::thread::spawn(|| loop {
+            std::thread::park();
+        });
+    }
+    wait_until("threads to start", || {
+        fs::read_dir("/proc/self/task")
+            .map(|d| d.count() > threads)
+            .unwrap_or(false)
+    })?;
+    Ok(std::process::id())
+}
@@ -1,12 +1,24 @@
 use anyhow::{bail, Context, Result};
 use clap::{Parser, ValueEnum};
-use std::collections::{HashMap, HashSet};
-use std::ffi::{CStr, CString};
+use elf_notes::{
+    elf64_le_machine, elf_abi_info, resolve_objcopy_path, strip_gnu_property_notes_under,
+    KernelVersion, StripReport, EM_AARCH64, EM_X86_64,
+};
+use runner_util::{
+    hash_file, is_sensitive_env_key, mount_for_path, parse_binfmt_entry, parse_mountinfo,
+    signal_name, FEX_BINFMT_PATH,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ffi::{CStr, CString, OsString};
 use std::fs;
 use std::io::{self, Write};
 use std::os::fd::RawFd;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 #[derive(Parser, Debug)]
@@ -20,20 +32,84 @@ struct Cli {
     #[arg(long, default_value = ".local/edge-muvm")]
     workdir: PathBuf,
 
-    /// Optional path to the Edge RPM (only used for metadata logging today).
+    /// Write runs and logs to a tmpfs of this size (e.g. `2G`) at `<workdir>/tmpfs` instead
+    /// of the workdir itself; the extracted Edge stays where it is.
+    ///
+    /// An existing tmpfs there is reused (the mount is left in place for later campaigns).
+    /// Mounting needs CAP_SYS_ADMIN; without it this warns and uses a regular directory.
+    #[arg(long, value_name = "SIZE")]
+    tmpfs_workdir: Option<String>,
+
+    /// Copy the runs and log this invocation wrote to the workdir, and what it added to
+    /// `--run-output-dir`, into this directory once the mode finishes, e.g. to persist
+    /// artifacts from `--tmpfs-workdir`. Warns when there was nothing to copy.
+    #[arg(long, value_name = "DIR")]
+    archive_dir: Option<PathBuf>,
+
+    /// (edge, preflight, muvm-true) Write the run's artifacts to exactly this directory
+    /// instead of a timestamped subdirectory of `--workdir`.
+    ///
+    /// Must be empty or missing unless `--force` is given.
+    #[arg(long, value_name = "PATH")]
+    run_output_dir: Option<PathBuf>,
+
+    /// Allow `--run-output-dir` to point at a non-empty directory.
+    ///
+    /// Existing files are left in place; same-named artifacts are overwritten.
+    #[arg(long)]
+    force: bool,
+
+    /// Optional path to the Edge RPM (logged; extracted when `--extract-rpm` is set).
     #[arg(long)]
     rpm: Option<PathBuf>,
 
+    /// Extract `--rpm` into the extracted root (via `rpm2cpio | cpio`) before running.
+    ///
+    /// Skipped when the extracted root already came from the same RPM (same path, size and
+    /// mtime) and still contains the Edge binary.
+    #[arg(long, default_value_t = false)]
+    extract_rpm: bool,
+
+    /// Strip `.note.gnu.property` (CET) from the Edge binary and its bundled `.so` files
+    /// under the extracted root before running (FEX rejects it). Edits the files in place.
+    #[arg(long, default_value_t = false)]
+    strip_edge_gnu_property: bool,
+
+    /// `objcopy` used by `--strip-edge-gnu-property` (default: first of `objcopy`,
+    /// `llvm-objcopy`, `eu-objcopy` in PATH).
+    #[arg(long)]
+    objcopy_path: Option<PathBuf>,
+
     /// Path to an already extracted RPM root.
     ///
     /// If omitted, defaults to `<workdir>/extracted`.
     #[arg(long)]
     extracted_root: Option<PathBuf>,
 
+    /// Edge binary path relative to the extracted root, for layouts other than the stable
+    /// RPM's `opt/microsoft/msedge/microsoft-edge` (e.g. beta/dev channels or repackaged
+    /// builds). When the binary is missing, the error lists candidates found under the root.
+    #[arg(long, value_name = "REL")]
+    edge_bin_subpath: Option<PathBuf>,
+
     /// Timeout in seconds for the muvm invocation.
     #[arg(long, default_value_t = 30)]
     timeout: u64,
 
+    /// Grace window in milliseconds between SIGTERM and SIGKILL when `--timeout` expires.
+    ///
+    /// muvm needs time to tear the VM down; too short a window leaks krun/virtiofsd children.
+    #[arg(long, default_value_t = 2000)]
+    kill_grace_ms: u64,
+
+    /// How often, in milliseconds, the watchdog loops poll the child for exit.
+    ///
+    /// Applies to the host-side muvm wait (PTY and inherit-tty) and to the guest-runner's
+    /// wait on Edge. Lower values tighten exit detection at the cost of more wakeups.
+    /// [default: 20 for the host loops, 50 for the guest-runner]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    poll_interval_ms: Option<u64>,
+
     /// Watchdog in seconds for the Edge process inside the guest.
     ///
     /// If Edge has not exited within this window, the guest-runner will capture a stuck
@@ -45,6 +121,24 @@ struct Cli {
     #[arg(long, default_value_t = 3)]
     matrix_runs: u32,
 
+    /// (muvm-true-matrix) Stop the sweep at the first run that times out or exits non-zero.
+    ///
+    /// The matrix summary still lists the runs done so far and notes where it stopped.
+    #[arg(long, default_value_t = false)]
+    fail_fast: bool,
+
+    /// (muvm-true-matrix) Also fail a run when this stderr classifier (a `summary.txt`
+    /// counter such as `stderr_ptrace_lines`) is above zero in its output. Implies
+    /// `--fail-fast`.
+    #[arg(
+        long,
+        value_name = "CLASSIFIER",
+        value_parser = clap::builder::PossibleValuesParser::new(
+            STDERR_SIGNATURES.iter().map(|(key, _)| *key)
+        )
+    )]
+    fail_on_classifier: Option<String>,
+
     /// URL to load for headless mode.
     #[arg(long, default_value = "https://example.com")]
     url: String,
@@ -62,12 +156,46 @@ struct Cli {
     #[arg(long, allow_hyphen_values = true)]
     edge_arg: Vec<String>,
 
+    /// File of extra Edge args, one per line (blank lines and `#` comments ignored), added
+    /// after any `--edge-arg`s.
+    ///
+    /// The args read from it are recorded as `EDGE_ARGS_FROM_FILE=` in `preflight.txt`.
+    #[arg(long, value_name = "PATH")]
+    edge_arg_file: Option<PathBuf>,
+
+    /// Drop the built-in Edge flags (`--disable-gpu`, `--password-store=basic`, ...), keeping
+    /// only the headless selection, `--user-data-dir`, `--proxy-server` and the trailing
+    /// `--dump-dom <url>`, so everything else comes from `--edge-arg`.
+    ///
+    /// The final flag list is recorded as `edge_flags:` in `preflight.txt`.
+    #[arg(long, default_value_t = false)]
+    no_default_edge_flags: bool,
+
     /// Extra environment variables to set for the Edge process (repeatable).
     ///
     /// Example: `--edge-env=CHROME_HEADLESS=1`.
     #[arg(long, value_name = "KEY=VALUE")]
     edge_env: Vec<String>,
 
+    /// Proxy for Edge, passed as `--proxy-server` (e.g. `http://192.168.1.10:8080`).
+    ///
+    /// The guest reaches the host network through muvm, so use an address of the host
+    /// (not 127.0.0.1) that the proxy listens on. Recorded in `preflight.txt`.
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Guest directory Edge is started from (created if missing), instead of whatever cwd
+    /// muvm lands in. Recorded in `preflight.txt`.
+    #[arg(long, value_name = "PATH")]
+    guest_cwd: Option<PathBuf>,
+
+    /// Env key whose value is replaced with `***` in the run dir's text artifacts other than
+    /// `stdout.txt` (repeatable), on top of keys containing TOKEN, SECRET, PASSWORD or KEY.
+    ///
+    /// The redacted keys are listed as `redacted_keys:` in `summary.txt`.
+    #[arg(long, value_name = "KEY")]
+    redact: Vec<String>,
+
     /// Preserve DBus/XDG environment variables when invoking `muvm`.
     ///
     /// By default we clear `DBUS_SESSION_BUS_ADDRESS` and `XDG_RUNTIME_DIR` to avoid
@@ -78,15 +206,72 @@ struct Cli {
     #[arg(long, default_value_t = false)]
     preserve_dbus_xdg_env: bool,
 
+    /// Host variable to forward to `muvm` (repeatable).
+    ///
+    /// With `--clear-env`, only these reach muvm (and so the guest). Without it, they are
+    /// exempt from the default DBus/XDG clearing.
+    #[arg(long, value_name = "KEY")]
+    env_passthrough: Vec<String>,
+
+    /// Start `muvm` from an empty environment plus the `--env-passthrough` variables, so the
+    /// guest env doesn't depend on the host session.
+    #[arg(long, default_value_t = false)]
+    clear_env: bool,
+
+    /// Extra flag passed to `muvm` ahead of `--emu=fex` (repeatable), e.g.
+    /// `--muvm-arg=--gpu-mode=drm`. In `--mode bisect` these are the baseline set.
+    #[arg(long, value_name = "ARG", allow_hyphen_values = true)]
+    muvm_arg: Vec<String>,
+
+    /// (bisect) muvm flag to try on top of the `--muvm-arg` baseline (repeatable).
+    ///
+    /// The search finds the smallest subset of these whose addition changes the
+    /// `--repeat-stop-on` outcome.
+    #[arg(long, value_name = "ARG", allow_hyphen_values = true)]
+    bisect_candidate: Vec<String>,
+
+    /// (bisect) Edge runs per flag set; a set counts as a hit if any run hits, so raise this
+    /// for outcomes that only show up some of the time.
+    #[arg(long, default_value_t = 1)]
+    bisect_attempts: u32,
+
     /// Best-effort guest sysctl writes to apply before spawning Edge.
     ///
     /// Example: `--guest-sysctl=vm.overcommit_memory=1`.
     ///
     /// Values are written inside the guest to `/proc/sys/...` and failures are
-    /// logged (runs continue even if a write fails).
+    /// logged (runs continue even if a write fails). Multi-field sysctls such as
+    /// `net.ipv4.tcp_rmem` take space- or tab-separated values:
+    /// `--guest-sysctl='net.ipv4.tcp_rmem=4096 131072 6291456'`. Other values are trimmed and
+    /// otherwise written as given.
     #[arg(long, value_name = "KEY=VALUE")]
     guest_sysctl: Vec<String>,
 
+    /// Inside the guest, run Edge in its own transient unit via
+    /// `systemd-run --user --pipe -p TasksMax=<N>` (when the guest has systemd-run).
+    ///
+    /// Unlike `--systemd-tasks-max` (which limits the whole muvm invocation on the host), this
+    /// limits only Edge's cgroup. The unit and cgroup path are recorded in `preflight.txt`.
+    #[arg(long, value_name = "N")]
+    guest_systemd_tasks_max: Option<u64>,
+
+    /// Inside the guest, raise the `RLIMIT_NOFILE` soft limit to N (capped at the hard limit)
+    /// before spawning Edge, to test whether fd exhaustion (EMFILE) is a factor.
+    ///
+    /// The before/after soft and hard limits are recorded in `preflight.txt`.
+    #[arg(long, value_name = "N")]
+    guest_nofile: Option<u64>,
+
+    /// Guest path muvm should run the guest-runner from, instead of copying this binary
+    /// into the run dir.
+    ///
+    /// `auto` derives `/run/muvm-host/<this binary>` and checks in the guest that it is
+    /// executable, falling back to the copy if not. The check boots muvm once; its result is
+    /// cached in `<workdir>/guest-runner-probe.txt` per muvm binary and host kernel. The
+    /// mechanism used is recorded in `summary.txt`.
+    #[arg(long, value_name = "GUEST_PATH")]
+    guest_runner_path: Option<String>,
+
     /// Where to place the Edge profile directory.
     ///
     /// `shared` uses `<run_dir>/profile` (virtio-fs/shared).
@@ -115,14 +300,116 @@ struct Cli {
     #[arg(long, value_enum, default_value_t = StraceMode::Minimal)]
     strace_mode: StraceMode,
 
+    /// Launch Edge untraced and attach `strace -p` to it after this many seconds instead of
+    /// wrapping it from the start (only relevant when `--strace` is enabled).
+    ///
+    /// Skips startup (thread creation etc.) so the `strace.<id>` files cover only the
+    /// steady state; they are picked up by the same analysis as wrapped traces.
+    #[arg(long, requires = "strace")]
+    strace_attach_after_seconds: Option<u64>,
+
+    /// Snapshot guest `/proc/meminfo` before spawning Edge and after it exits.
+    ///
+    /// Writes `meminfo-delta.txt` in the run dir and adds the deltas to `summary.txt`.
+    #[arg(long, default_value_t = false)]
+    mem_report: bool,
+
+    /// Dump `/proc/<edge pid>/environ` right after spawn to `edge-environ.txt`.
+    ///
+    /// Flags any `--edge-env` key that did not make it into Edge's environment.
+    #[arg(long, default_value_t = false)]
+    dump_environ: bool,
+
+    /// Tail `stderr.txt` inside the guest while Edge runs and keep a running `live-counts.txt`
+    /// (rewritten every second) with the same substring counters as `summary.txt`.
+    #[arg(long, default_value_t = false)]
+    live_classify: bool,
+
+    /// Kill Edge as soon as a stderr line contains this substring (repeatable).
+    ///
+    /// Implies `--live-classify`. The matching signature is recorded in `stop-signature.txt`.
+    #[arg(long, value_name = "SUBSTR")]
+    stop_on_signature: Vec<String>,
+
+    /// Maximum tasks (threads) sampled per process in the Edge and muvm-true-matrix stuck
+    /// snapshots.
+    ///
+    /// Raise this for a complete picture of a fully-spawned Edge; `task_elided` in
+    /// `stuck.txt` records how many were skipped.
+    #[arg(long, default_value_t = 24)]
+    snapshot_max_tasks: usize,
+
+    /// Maximum fds listed per process in the Edge and muvm-true-matrix stuck snapshots
+    /// (`fd_elided` records the rest).
+    #[arg(long, default_value_t = 64)]
+    snapshot_max_fds: usize,
+
+    /// How many hops the stuck snapshot's pipe wakeup graph follows through writer PIDs
+    /// that are themselves blocked in ppoll on an eventfd+pipe (0 disables recursion).
+    #[arg(long, default_value_t = 1)]
+    wakeup_depth: usize,
+
+    /// PIDs scanned under `/proc/*/fd` when resolving pipe/socket inode owners in stuck
+    /// snapshots. `scan_truncated: yes` means this (or `--owner-scan-max-fds`) ran out.
+    #[arg(long, default_value_t = 512)]
+    owner_scan_max_pids: usize,
+
+    /// Fds read per PID by the pipe/socket owner scans.
+    #[arg(long, default_value_t = 256)]
+    owner_scan_max_fds: usize,
+
+    /// On a stuck snapshot, also save the full `/proc/<pid>/maps` of Edge (and of a FEX
+    /// process under it, when found) to `maps.txt`, with mapped-bytes and region counts.
+    #[arg(long, default_value_t = false)]
+    capture_maps: bool,
+
+    /// Per-process cap on the maps text saved by `--capture-maps` (totals still cover all
+    /// regions).
+    #[arg(long, default_value_t = 4 * 1024 * 1024)]
+    maps_max_bytes: usize,
+
+    /// (edge / edge-repeat) Write the run's classifier counts to this file in Prometheus
+    /// text exposition format (e.g. for node_exporter's textfile collector).
+    #[arg(long)]
+    metrics_file: Option<PathBuf>,
+
+    /// How `--metrics-file` is updated.
+    ///
+    /// `overwrite` keeps only the latest run. `append` keeps earlier runs' samples too,
+    /// distinguished by a `run` label (the run dir name).
+    #[arg(long, value_enum, default_value_t = MetricsMode::Overwrite)]
+    metrics_mode: MetricsMode,
+
     /// (edge-repeat) Maximum attempts before stopping.
     #[arg(long, default_value_t = 6)]
     repeat_max_attempts: u32,
 
-    /// (edge-repeat) Stop condition.
+    /// (edge-repeat / bisect) Stop condition; in bisect mode, the outcome being compared.
     #[arg(long, value_enum, default_value_t = RepeatStopOn::PthreadCreate)]
     repeat_stop_on: RepeatStopOn,
 
+    /// On a failed or abnormal run, print the last N lines of the run's `muvm.txt` to stderr.
+    ///
+    /// Applies to preflight, muvm-true and the Edge modes. Off by default.
+    #[arg(long, value_name = "N")]
+    tail_muvm_output: Option<usize>,
+
+    /// (edge / edge-repeat) After each run, gzip its large logs in place (`strace.*`,
+    /// `host.strace.*`, `muvm.txt`, `run.log`): writes `<name>.gz`, removes the original and
+    /// points the run summary at the `.gz`. `analyze-run-dir` reads either form.
+    #[arg(long, default_value_t = false)]
+    compress_artifacts: bool,
+
+    /// Size threshold for `--compress-artifacts`; smaller files are left alone.
+    #[arg(long, default_value_t = 1024 * 1024, requires = "compress_artifacts")]
+    compress_min_bytes: u64,
+
+    /// (edge / edge-repeat / compare-headless / bisect) Which run summaries to write:
+    /// `summary.txt` (`key: value` lines), `summary.json` (the same fields, with the preflight
+    /// values and artifact paths as nested objects) or both.
+    #[arg(long, value_enum, default_value_t = SummaryFormat::Both)]
+    summary_format: SummaryFormat,
+
     /// Wrap `muvm` in `systemd-run --user --pty --wait -p TasksMax=<N> -- ...`.
     ///
     /// This is useful for testing whether a systemd cgroup task/thread limit is causing
@@ -134,13 +421,156 @@ struct Cli {
     #[arg(long)]
     edge_bin: Option<PathBuf>,
 
-    /// (guest-runner) Absolute run directory shared with host.
+    /// (guest-runner) Absolute run directory shared with host. (analyze-run-dir) The run dir to
+    /// analyze. (compare-run-dirs) Given twice, the two run dirs to compare.
     #[arg(long)]
-    run_dir: Option<PathBuf>,
+    run_dir: Vec<PathBuf>,
 
     /// (guest-runner) Headless implementation selector.
     #[arg(long, value_enum, default_value_t = HeadlessImpl::New)]
     guest_headless_impl: HeadlessImpl,
+
+    /// (decode-stat / decode-signals) Input file; reads stdin if omitted.
+    ///
+    /// decode-stat expects a saved `/proc/<pid>/stat` line; decode-signals expects a saved
+    /// `/proc/<pid>/status` (SigPnd/ShdPnd/SigBlk/SigIgn/SigCgt are decoded).
+    #[arg(long)]
+    file: Option<PathBuf>,
+
+    /// (analyze-strace) strace output to scan; a `.gz` file is decompressed.
+    #[arg(long, value_name = "PATH")]
+    strace_file: Option<PathBuf>,
+
+    /// (analyze-run-dir) Re-count the run's `stderr.txt` against the signatures in this file
+    /// and print the counts as JSON instead of running the pthread analysis.
+    ///
+    /// One `key: needle` per line (like `summary.txt`); blank lines and `#` comments are
+    /// ignored. A line counts once per signature whose needle it contains.
+    #[arg(long, value_name = "PATH")]
+    classifier_file: Option<PathBuf>,
+
+    /// (decode-signals) Raw hex signal mask to decode instead of reading a status file.
+    ///
+    /// Example: `--mask 0000000000014002` (a `SigBlk` value).
+    #[arg(long, value_name = "HEX")]
+    mask: Option<String>,
+
+    /// (snapshot-pid) Host PID to take a stuck snapshot of.
+    #[arg(long)]
+    pid: Option<u32>,
+
+    /// (index-runs / campaign-report) Only include runs stamped at or after this time (unix
+    /// milliseconds, as in the run dir names).
+    #[arg(long, value_name = "UNIX_MS")]
+    since: Option<u128>,
+
+    /// (index-runs) Only index runs produced by this mode (e.g. `edge` for `headless-*`).
+    #[arg(long, value_enum, value_name = "MODE")]
+    index_mode: Option<Mode>,
+
+    /// (compare-run-dirs) Where to write the comparison [default: `<workdir>/compare.txt`].
+    #[arg(long, value_name = "PATH")]
+    out: Option<PathBuf>,
+}
+
+impl Cli {
+    fn compress_artifacts(&self) -> Option<u64> {
+        self.compress_artifacts.then_some(self.compress_min_bytes)
+    }
+
+    fn watchdog(&self) -> Watchdog {
+        Watchdog {
+            kill_grace: Duration::from_millis(self.kill_grace_ms),
+            poll_interval: Duration::from_millis(
+                self.poll_interval_ms.unwrap_or(HOST_POLL_INTERVAL_MS),
+            ),
+            guest_poll_interval: Duration::from_millis(
+                self.poll_interval_ms.unwrap_or(GUEST_POLL_INTERVAL_MS),
+            ),
+        }
+    }
+
+    fn snapshot_limits(&self) -> SnapshotLimits {
+        SnapshotLimits {
+            max_tasks: self.snapshot_max_tasks,
+            max_fds: self.snapshot_max_fds,
+            wakeup_depth: self.wakeup_depth,
+            owner_scan_max_pids: self.owner_scan_max_pids,
+            owner_scan_max_fds: self.owner_scan_max_fds,
+            capture_maps_max_bytes: self.capture_maps.then_some(self.maps_max_bytes),
+        }
+    }
+}
+
+/// `--poll-interval-ms` defaults: the host loops also drain the PTY between polls, while the
+/// guest-runner only waits on Edge and snapshots it, so it can poll less often.
+const HOST_POLL_INTERVAL_MS: u64 = 20;
+const GUEST_POLL_INTERVAL_MS: u64 = 50;
+
+/// Host-side watchdog timing shared by the muvm wait loops.
+#[derive(Copy, Clone, Debug)]
+struct Watchdog {
+    /// Window between SIGTERM and SIGKILL once the timeout expires.
+    kill_grace: Duration,
+    /// Sleep between exit polls.
+    poll_interval: Duration,
+    /// Sleep between the guest-runner's polls of Edge, passed on as `--poll-interval-ms`.
+    guest_poll_interval: Duration,
+}
+
+/// Per-process caps for stuck snapshots; larger values mean larger `stuck.txt` files.
+#[derive(Copy, Clone, Debug)]
+struct SnapshotLimits {
+    max_tasks: usize,
+    max_fds: usize,
+    wakeup_depth: usize,
+    owner_scan_max_pids: usize,
+    owner_scan_max_fds: usize,
+    /// `Some(cap)` when `--capture-maps` is on.
+    capture_maps_max_bytes: Option<usize>,
+}
+
+impl Default for SnapshotLimits {
+    fn default() -> Self {
+        Self {
+            max_tasks: 24,
+            max_fds: 64,
+            wakeup_depth: 1,
+            owner_scan_max_pids: 512,
+            owner_scan_max_fds: 256,
+            capture_maps_max_bytes: None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum MetricsMode {
+    Overwrite,
+    Append,
+}
+
+/// Where (and how) run_edge exports Prometheus metrics.
+#[derive(Clone, Debug)]
+struct MetricsSink {
+    path: PathBuf,
+    mode: MetricsMode,
+    /// Value of the `run_mode` label (`edge` / `edge-repeat` / `bisect`).
+    run_mode: &'static str,
+}
+
+impl Cli {
+    fn metrics_sink(&self) -> Option<MetricsSink> {
+        let run_mode = match self.mode {
+            Mode::EdgeRepeat => "edge-repeat",
+            Mode::Bisect => "bisect",
+            _ => "edge",
+        };
+        self.metrics_file.as_ref().map(|path| MetricsSink {
+            path: path.clone(),
+            mode: self.metrics_mode,
+            run_mode,
+        })
+    }
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -153,6 +583,16 @@ enum RepeatStopOn {
     StdoutNonEmpty,
 }
 
+impl RepeatStopOn {
+    fn hit(self, res: &EdgeRunResult) -> bool {
+        match self {
+            RepeatStopOn::PthreadCreate => res.stderr_pthread_create_lines > 0,
+            RepeatStopOn::StackMprotectEnomem => res.pthread_stack_mprotect_enomem_events > 0,
+            RepeatStopOn::StdoutNonEmpty => res.stdout_bytes > 0,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, ValueEnum)]
 enum HeadlessImpl {
     New,
@@ -173,6 +613,23 @@ enum StraceMode {
     Hang,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum SummaryFormat {
+    Text,
+    Json,
+    Both,
+}
+
+impl SummaryFormat {
+    fn text(self) -> bool {
+        matches!(self, SummaryFormat::Text | SummaryFormat::Both)
+    }
+
+    fn json(self) -> bool {
+        matches!(self, SummaryFormat::Json | SummaryFormat::Both)
+    }
+}
+
 impl ProfileLocation {
     fn as_arg(&self) -> &'static str {
         match self {
@@ -182,15 +639,44 @@ impl ProfileLocation {
     }
 }
 
-#[derive(Copy, Clone, Debug, ValueEnum)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
 enum Mode {
     Preflight,
     MuvmTrue,
     MuvmTrueMatrix,
     Edge,
     EdgeRepeat,
+    /// Run Edge twice (`--headless`, then `--headless=old`) with identical settings and compare.
+    CompareHeadless,
+    /// Delta-debug which `--bisect-candidate` muvm flags, added to the `--muvm-arg` baseline,
+    /// change the `--repeat-stop-on` outcome; writes `edge-bisect-<stamp>.txt`.
+    Bisect,
     /// Analyze an existing run dir on the host (re-runs classifiers; does not invoke muvm).
     AnalyzeRunDir,
+    /// Re-run the classifiers on two `--run-dir`s and print the counters and preflight values
+    /// that differ side by side; writes `--out` (`<workdir>/compare.txt` by default) and leaves
+    /// both run dirs untouched. Does not invoke muvm.
+    CompareRunDirs,
+    /// Decode a saved `/proc/<pid>/stat` line (job control + controlling tty); does not invoke muvm.
+    DecodeStat,
+    /// Decode a hex signal mask or a saved `/proc/<pid>/status` into signal names; does not invoke muvm.
+    DecodeSignals,
+    /// Run the thread-stack `mmap(MAP_STACK)` -> `mprotect` ENOMEM correlation over a single
+    /// `--strace-file` from anywhere and print the event report; does not invoke muvm.
+    AnalyzeStrace,
+    /// Print a stuck snapshot of `--pid` on this host to stdout; does not invoke muvm.
+    SnapshotPid,
+    /// List the runs in `--workdir` (optionally filtered) and write `runs-index.tsv`; does not
+    /// invoke muvm. Includes runs under `<workdir>/tmpfs` and the `--run-output-dir` of each
+    /// run log.
+    IndexRuns,
+    /// Aggregate the summary of every Edge run `index-runs` finds into success rate, median
+    /// stdout, outcome frequencies and pthread event spread per mode / headless impl / profile
+    /// location; writes `campaign-report.txt` and `campaign-report.json`. Does not invoke muvm.
+    CampaignReport,
+    /// Print the JSON Schema of this tool's JSON output, keyed by artifact; does not invoke muvm.
+    #[value(hide = true)]
+    EmitSchema,
     GuestRunner,
 }
 
@@ -205,7 +691,8 @@ fn main() -> Result<()> {
             .context("--edge-bin is required in guest-runner mode")?;
         let run_dir = cli
             .run_dir
-            .as_deref()
+            .first()
+            .map(PathBuf::as_path)
             .context("--run-dir is required in guest-runner mode")?;
         return guest_runner(
             edge_bin,
@@ -213,18 +700,99 @@ fn main() -> Result<()> {
             &cli.url,
             cli.guest_headless_impl,
             &cli.edge_arg,
+            cli.edge_arg_file.as_deref(),
+            !cli.no_default_edge_flags,
             &cli.edge_env,
+            cli.proxy.as_deref(),
+            cli.guest_cwd.as_deref(),
             cli.profile_location,
             cli.preserve_dbus_xdg_env,
             &cli.guest_sysctl,
+            cli.guest_systemd_tasks_max,
+            cli.guest_nofile,
             cli.strace,
             cli.strace_mode,
+            cli.strace_attach_after_seconds.map(Duration::from_secs),
+            cli.mem_report,
+            cli.dump_environ,
+            cli.live_classify,
+            &cli.stop_on_signature,
+            cli.snapshot_limits(),
             Duration::from_secs(cli.edge_watchdog_seconds),
+            Duration::from_millis(cli.poll_interval_ms.unwrap_or(GUEST_POLL_INTERVAL_MS)),
+        );
+    }
+
+    // Offline decoders: no muvm, no workdir, no run log.
+    if let Mode::DecodeStat = cli.mode {
+        return run_decode_stat(cli.file.as_deref());
+    }
+    if let Mode::DecodeSignals = cli.mode {
+        return run_decode_signals(cli.mask.as_deref(), cli.file.as_deref());
+    }
+    if let Mode::AnalyzeStrace = cli.mode {
+        let strace_file = cli
+            .strace_file
+            .as_deref()
+            .context("--strace-file is required for --mode analyze-strace")?;
+        return run_analyze_strace(strace_file);
+    }
+    if let Mode::SnapshotPid = cli.mode {
+        let pid = cli
+            .pid
+            .context("--pid is required for --mode snapshot-pid")?;
+        print!(
+            "{}",
+            render_stuck_snapshot(pid, "target", cli.snapshot_limits())
         );
+        return Ok(());
+    }
+    if let Mode::IndexRuns = cli.mode {
+        return run_index_runs(&cli.workdir, cli.since, cli.index_mode);
+    }
+    if let Mode::CampaignReport = cli.mode {
+        return run_campaign_report(&cli.workdir, cli.since);
+    }
+    if let Mode::EmitSchema = cli.mode {
+        let schema = serde_json::json!({
+            "summary": schemars::schema_for!(EdgeSummary<'static>),
+            "classifier-counts": schemars::schema_for!(ClassifierCounts<'static>),
+            "campaign-report": schemars::schema_for!(CampaignReport),
+        });
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+    if let Mode::CompareRunDirs = cli.mode {
+        let [a, b] = cli.run_dir.as_slice() else {
+            bail!("--mode compare-run-dirs needs exactly two --run-dir");
+        };
+        let out = cli
+            .out
+            .clone()
+            .unwrap_or_else(|| cli.workdir.join("compare.txt"));
+        return run_compare_run_dirs(a, b, &out);
+    }
+    // Re-classifying saved evidence needs neither muvm nor a run log.
+    if let (Mode::AnalyzeRunDir, Some(classifier_file)) = (cli.mode, &cli.classifier_file) {
+        let run_dir = cli
+            .run_dir
+            .first()
+            .map(PathBuf::as_path)
+            .context("--run-dir is required for --mode analyze-run-dir")?;
+        return run_analyze_run_dir(run_dir, Some(classifier_file));
+    }
+
+    if let Some(proxy) = &cli.proxy {
+        check_proxy_url(proxy)?;
     }
 
     // Resolve host-side helpers up-front so PTY execution isn't dependent on PATH quirks.
-    let muvm_path = resolve_in_path("muvm").context("locate muvm in PATH")?;
+    let muvm = MuvmLauncher {
+        path: resolve_in_path("muvm").context("locate muvm in PATH")?,
+        clear_env: cli.clear_env,
+        env_passthrough: cli.env_passthrough.clone(),
+        extra_args: cli.muvm_arg.clone(),
+    };
     let systemd_run_path = if cli.systemd_tasks_max.is_some() {
         Some(resolve_in_path("systemd-run").context("locate systemd-run in PATH")?)
     } else {
@@ -232,18 +800,73 @@ fn main() -> Result<()> {
     };
 
     fs::create_dir_all(&cli.workdir).context("create workdir")?;
-    let workdir_abs = fs::canonicalize(&cli.workdir).context("canonicalize workdir")?;
+    let mut workdir_abs = fs::canonicalize(&cli.workdir).context("canonicalize workdir")?;
+    let tmpfs_workdir = match &cli.tmpfs_workdir {
+        Some(size) => {
+            let (dir, status) = prepare_tmpfs_workdir(&workdir_abs, size)?;
+            workdir_abs = dir;
+            Some(status)
+        }
+        None => None,
+    };
+    let workdir_entries_before = list_dir_names(&workdir_abs);
+    let run_output_dir = match &cli.run_output_dir {
+        Some(dir) if matches!(cli.mode, Mode::Edge | Mode::Preflight | Mode::MuvmTrue) => {
+            Some(prepare_run_output_dir(dir, cli.force)?)
+        }
+        Some(_) => bail!(
+            "--run-output-dir only applies to --mode edge, preflight and muvm-true (got {})",
+            mode_name(cli.mode)
+        ),
+        None => None,
+    };
+    // With --force the run output dir may already hold files; archive only what this run adds.
+    let run_output_entries_before = run_output_dir
+        .as_deref()
+        .map(list_dir_names)
+        .unwrap_or_default();
 
     let extracted_root = cli
         .extracted_root
         .clone()
         .unwrap_or_else(|| cli.workdir.join("extracted"));
+    let edge_bin_rel = cli
+        .edge_bin_subpath
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(EDGE_BIN_REL));
+    if edge_bin_rel.is_absolute() {
+        bail!(
+            "--edge-bin-subpath must be relative to the extracted root, got {}",
+            edge_bin_rel.display()
+        );
+    }
+    let rpm_extraction = if cli.extract_rpm {
+        let rpm = cli
+            .rpm
+            .as_deref()
+            .context("--rpm is required with --extract-rpm")?;
+        Some(extract_edge_rpm(rpm, &extracted_root, &edge_bin_rel)?)
+    } else {
+        None
+    };
     let extracted_root_abs = if extracted_root.exists() {
         fs::canonicalize(&extracted_root).context("canonicalize extracted root")?
     } else {
         extracted_root
     };
 
+    let edge_strip = if cli.strip_edge_gnu_property {
+        let objcopy =
+            resolve_objcopy_path(cli.objcopy_path.as_deref()).context("resolve objcopy")?;
+        Some(strip_edge_gnu_property(
+            &extracted_root_abs,
+            &edge_bin_rel,
+            &objcopy,
+        )?)
+    } else {
+        None
+    };
+
     let log_path = workdir_abs.join(format!("run-{}-{:?}.log", chrono_stamp(), cli.mode));
     {
         let mut f = fs::File::create(&log_path).context("create run log")?;
@@ -251,6 +874,12 @@ fn main() -> Result<()> {
         writeln!(f, "date: {}", iso_now())?;
         writeln!(f, "mode: {:?}", cli.mode)?;
         writeln!(f, "work: {}", workdir_abs.display())?;
+        if let Some(status) = &tmpfs_workdir {
+            writeln!(f, "tmpfs_workdir: {status}")?;
+        }
+        if let Some(dir) = &run_output_dir {
+            writeln!(f, "run_output_dir: {}", dir.display())?;
+        }
         writeln!(f, "extracted_root: {}", extracted_root_abs.display())?;
         writeln!(
             f,
@@ -272,6 +901,28 @@ fn main() -> Result<()> {
         } else {
             writeln!(f, "rpm: (none)")?;
         }
+        match &rpm_extraction {
+            Some(ex) => {
+                writeln!(
+                    f,
+                    "rpm_extract: {}",
+                    if ex.reused { "reused" } else { "extracted" }
+                )?;
+                writeln!(f, "rpm_extract_seconds: {}", ex.elapsed.as_secs())?;
+                writeln!(f, "rpm_extract_edge_bin: {}", ex.edge_bin.display())?;
+            }
+            None => writeln!(f, "rpm_extract: (not requested)")?,
+        }
+        match &edge_strip {
+            Some(r) => writeln!(
+                f,
+                "edge_gnu_property_strip: stripped={} failed={} remaining={}",
+                r.stripped_files.len(),
+                r.strip_failures.len(),
+                r.remaining_gnu_property_files.len()
+            )?,
+            None => writeln!(f, "edge_gnu_property_strip: (not requested)")?,
+        }
         writeln!(f)?;
         writeln!(f, "-- NOTE")?;
         writeln!(
@@ -280,146 +931,1134 @@ fn main() -> Result<()> {
         )?;
     }
 
+    let mut exit_code = 0;
     match cli.mode {
         Mode::Preflight => run_preflight(
-            &muvm_path,
+            &muvm,
             systemd_run_path.as_deref(),
             cli.systemd_tasks_max,
-            &workdir_abs,
+            run_output_dir
+                .clone()
+                .unwrap_or_else(|| workdir_abs.join(format!("preflight-{}", chrono_stamp()))),
             cli.timeout,
+            cli.watchdog(),
+            cli.tail_muvm_output,
         )?,
         Mode::MuvmTrue => run_muvm_true(
-            &muvm_path,
+            &muvm,
             systemd_run_path.as_deref(),
             cli.systemd_tasks_max,
-            &workdir_abs,
+            run_output_dir
+                .clone()
+                .unwrap_or_else(|| workdir_abs.join(format!("muvm-true-{}", chrono_stamp()))),
             cli.timeout,
+            cli.watchdog(),
+            cli.tail_muvm_output,
         )?,
         Mode::MuvmTrueMatrix => {
             let timeout_path = resolve_in_path("timeout").context("locate timeout in PATH")?;
             run_muvm_true_matrix(
-                &muvm_path,
-                &timeout_path,
+                &muvm,
                 systemd_run_path.as_deref(),
                 cli.systemd_tasks_max,
                 &workdir_abs,
                 cli.timeout,
-                cli.matrix_runs,
+                cli.watchdog(),
+                MatrixPlan {
+                    timeout_path: &timeout_path,
+                    runs_per_case: cli.matrix_runs,
+                    fail_fast: cli.fail_fast || cli.fail_on_classifier.is_some(),
+                    fail_on_classifier: cli.fail_on_classifier.as_deref(),
+                    snapshot_limits: cli.snapshot_limits(),
+                },
             )?
         }
         Mode::Edge => {
-            let _ = run_edge(
-                &muvm_path,
+            let res = run_edge(
+                &muvm,
                 systemd_run_path.as_deref(),
                 cli.systemd_tasks_max,
                 &workdir_abs,
+                run_output_dir.as_deref(),
                 &extracted_root_abs,
+                &edge_bin_rel,
                 cli.mem,
                 cli.muvm_privileged,
                 cli.strace,
                 cli.strace_mode,
+                cli.strace_attach_after_seconds.map(Duration::from_secs),
+                cli.mem_report,
+                cli.dump_environ,
+                cli.live_classify,
+                &cli.stop_on_signature,
+                cli.snapshot_limits(),
                 Duration::from_secs(cli.timeout),
+                cli.watchdog(),
                 Duration::from_secs(cli.edge_watchdog_seconds),
                 &cli.url,
                 cli.headless_impl,
                 &cli.edge_arg,
+                cli.edge_arg_file.as_deref(),
+                !cli.no_default_edge_flags,
                 &cli.edge_env,
+                cli.proxy.as_deref(),
+                cli.guest_cwd.as_deref(),
                 cli.profile_location,
                 cli.preserve_dbus_xdg_env,
                 &cli.guest_sysctl,
+                cli.guest_systemd_tasks_max,
+                cli.guest_nofile,
+                edge_strip.as_ref(),
+                cli.tail_muvm_output,
+                cli.metrics_sink().as_ref(),
+                &mode_name(cli.mode),
+                &cli.redact,
+                cli.guest_runner_path.as_deref(),
+                cli.compress_artifacts(),
+                cli.summary_format,
             )?;
+            exit_code = res.exit_code;
+        }
+        Mode::CompareHeadless => {
+            let mut runs: Vec<(HeadlessImpl, EdgeRunResult)> = Vec::new();
+            for headless_impl in [HeadlessImpl::New, HeadlessImpl::Old] {
+                eprintln!("compare-headless: running headless_impl={headless_impl:?}");
+                let res = run_edge(
+                    &muvm,
+                    systemd_run_path.as_deref(),
+                    cli.systemd_tasks_max,
+                    &workdir_abs,
+                    None,
+                    &extracted_root_abs,
+                    &edge_bin_rel,
+                    cli.mem,
+                    cli.muvm_privileged,
+                    cli.strace,
+                    cli.strace_mode,
+                    cli.strace_attach_after_seconds.map(Duration::from_secs),
+                    cli.mem_report,
+                    cli.dump_environ,
+                    cli.live_classify,
+                    &cli.stop_on_signature,
+                    cli.snapshot_limits(),
+                    Duration::from_secs(cli.timeout),
+                    cli.watchdog(),
+                    Duration::from_secs(cli.edge_watchdog_seconds),
+                    &cli.url,
+                    headless_impl,
+                    &cli.edge_arg,
+                    cli.edge_arg_file.as_deref(),
+                    !cli.no_default_edge_flags,
+                    &cli.edge_env,
+                    cli.proxy.as_deref(),
+                    cli.guest_cwd.as_deref(),
+                    cli.profile_location,
+                    cli.preserve_dbus_xdg_env,
+                    &cli.guest_sysctl,
+                    cli.guest_systemd_tasks_max,
+                    cli.guest_nofile,
+                    edge_strip.as_ref(),
+                    cli.tail_muvm_output,
+                    cli.metrics_sink().as_ref(),
+                    &mode_name(cli.mode),
+                    &cli.redact,
+                    cli.guest_runner_path.as_deref(),
+                    cli.compress_artifacts(),
+                    cli.summary_format,
+                )?;
+                runs.push((headless_impl, res));
+            }
+            write_headless_comparison(&workdir_abs, &runs)?;
         }
         Mode::EdgeRepeat => run_edge_repeat(
-            &muvm_path,
+            &muvm,
             systemd_run_path.as_deref(),
             cli.systemd_tasks_max,
             &workdir_abs,
             &extracted_root_abs,
+            &edge_bin_rel,
             cli.mem,
             cli.muvm_privileged,
             cli.strace,
             cli.strace_mode,
+            cli.strace_attach_after_seconds.map(Duration::from_secs),
+            cli.mem_report,
+            cli.dump_environ,
+            cli.live_classify,
+            &cli.stop_on_signature,
+            cli.snapshot_limits(),
             Duration::from_secs(cli.timeout),
+            cli.watchdog(),
             Duration::from_secs(cli.edge_watchdog_seconds),
             &cli.url,
             cli.headless_impl,
             &cli.edge_arg,
+            cli.edge_arg_file.as_deref(),
+            !cli.no_default_edge_flags,
             &cli.edge_env,
+            cli.proxy.as_deref(),
+            cli.guest_cwd.as_deref(),
             cli.profile_location,
             cli.preserve_dbus_xdg_env,
             &cli.guest_sysctl,
+            cli.guest_systemd_tasks_max,
+            cli.guest_nofile,
+            edge_strip.as_ref(),
+            cli.tail_muvm_output,
+            cli.metrics_sink().as_ref(),
+            &mode_name(cli.mode),
+            &cli.redact,
+            cli.guest_runner_path.as_deref(),
+            cli.compress_artifacts(),
+            cli.summary_format,
             cli.repeat_max_attempts,
             cli.repeat_stop_on,
         )?,
+        Mode::Bisect => {
+            let mut run = |muvm: &MuvmLauncher| {
+                run_edge(
+                    muvm,
+                    systemd_run_path.as_deref(),
+                    cli.systemd_tasks_max,
+                    &workdir_abs,
+                    None,
+                    &extracted_root_abs,
+                    &edge_bin_rel,
+                    cli.mem,
+                    cli.muvm_privileged,
+                    cli.strace,
+                    cli.strace_mode,
+                    cli.strace_attach_after_seconds.map(Duration::from_secs),
+                    cli.mem_report,
+                    cli.dump_environ,
+                    cli.live_classify,
+                    &cli.stop_on_signature,
+                    cli.snapshot_limits(),
+                    Duration::from_secs(cli.timeout),
+                    cli.watchdog(),
+                    Duration::from_secs(cli.edge_watchdog_seconds),
+                    &cli.url,
+                    cli.headless_impl,
+                    &cli.edge_arg,
+                    cli.edge_arg_file.as_deref(),
+                    !cli.no_default_edge_flags,
+                    &cli.edge_env,
+                    cli.proxy.as_deref(),
+                    cli.guest_cwd.as_deref(),
+                    cli.profile_location,
+                    cli.preserve_dbus_xdg_env,
+                    &cli.guest_sysctl,
+                    cli.guest_systemd_tasks_max,
+                    cli.guest_nofile,
+                    edge_strip.as_ref(),
+                    cli.tail_muvm_output,
+                    cli.metrics_sink().as_ref(),
+                    &mode_name(cli.mode),
+                    &cli.redact,
+                    cli.guest_runner_path.as_deref(),
+                    cli.compress_artifacts(),
+                    cli.summary_format,
+                )
+            };
+            run_bisect(
+                &workdir_abs,
+                &muvm,
+                &cli.bisect_candidate,
+                cli.repeat_stop_on,
+                cli.bisect_attempts,
+                &mut run,
+            )?;
+        }
         Mode::AnalyzeRunDir => {
             let run_dir = cli
                 .run_dir
-                .as_deref()
+                .first()
+                .map(PathBuf::as_path)
                 .context("--run-dir is required for --mode analyze-run-dir")?;
-            run_analyze_run_dir(run_dir)?;
+            run_analyze_run_dir(run_dir, None)?;
+        }
+        Mode::DecodeStat
+        | Mode::DecodeSignals
+        | Mode::AnalyzeStrace
+        | Mode::SnapshotPid
+        | Mode::IndexRuns
+        | Mode::CampaignReport
+        | Mode::CompareRunDirs
+        | Mode::EmitSchema
+        | Mode::GuestRunner => {
+            unreachable!("handled above")
         }
-        Mode::GuestRunner => unreachable!("handled above"),
     }
 
     eprintln!("Wrote log: {}", log_path.display());
+    if let Some(archive_dir) = &cli.archive_dir {
+        let new = archive_new_entries(&workdir_abs, &workdir_entries_before, archive_dir)?;
+        let mut copied = new.len();
+        // A run output dir outside the workdir (or one that already existed) isn't among the
+        // new workdir entries; archive it under its own name.
+        if let Some(dir) = run_output_dir.as_deref().filter(|dir| {
+            !new.iter()
+                .any(|name| dir.starts_with(workdir_abs.join(name)))
+        }) {
+            let name = dir
+                .file_name()
+                .context("--run-output-dir has no file name")?;
+            copied +=
+                archive_new_entries(dir, &run_output_entries_before, &archive_dir.join(name))?
+                    .len();
+        }
+        if copied == 0 {
+            eprintln!(
+                "WARNING: --archive-dir {}: this invocation wrote nothing to archive",
+                archive_dir.display()
+            );
+        } else {
+            eprintln!(
+                "Archived {copied} entr{} to {}",
+                if copied == 1 { "y" } else { "ies" },
+                archive_dir.display()
+            );
+        }
+    }
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
     Ok(())
 }
 
-fn run_analyze_run_dir(run_dir: &Path) -> Result<()> {
-    if !run_dir.is_dir() {
-        bail!("run dir does not exist: {}", run_dir.display());
-    }
+/// Workdir entry name prefixes (followed by a `chrono_stamp`) and the mode that creates them.
+/// Longer prefixes first so `muvm-true-matrix-` is not taken for `muvm-true-`.
+const RUN_NAME_PREFIXES: &[(&str, Mode)] = &[
+    ("muvm-true-matrix-", Mode::MuvmTrueMatrix),
+    ("muvm-true-", Mode::MuvmTrue),
+    ("compare-headless-", Mode::CompareHeadless),
+    ("edge-repeat-", Mode::EdgeRepeat),
+    ("edge-bisect-", Mode::Bisect),
+    ("headless-", Mode::Edge),
+    ("preflight-", Mode::Preflight),
+];
+
+struct IndexedRun {
+    stamp_ms: u128,
+    mode: Mode,
+    path: PathBuf,
+}
 
-    let stderr_path = run_dir.join("stderr.txt");
-    if !stderr_path.is_file() {
-        bail!("missing stderr.txt in run dir: {}", stderr_path.display());
+fn mode_name(mode: Mode) -> String {
+    mode.to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_else(|| format!("{mode:?}"))
+}
+
+/// Parses a workdir entry name (`headless-<ms>`, `edge-repeat-<ms>.txt`,
+/// `run-<ms>-<Mode>.log`, ...) into its stamp and originating mode.
+fn parse_run_entry_name(name: &str) -> Option<(u128, Mode)> {
+    if let Some(rest) = name.strip_prefix("run-") {
+        let (stamp, mode) = rest.strip_suffix(".log")?.split_once('-')?;
+        let mode = Mode::value_variants()
+            .iter()
+            .copied()
+            .find(|m| format!("{m:?}") == mode)?;
+        return Some((stamp.parse().ok()?, mode));
+    }
+    let (rest, mode) = RUN_NAME_PREFIXES
+        .iter()
+        .find_map(|(prefix, mode)| name.strip_prefix(prefix).map(|r| (r, *mode)))?;
+    let stamp = rest.strip_suffix(".txt").unwrap_or(rest);
+    Some((stamp.parse().ok()?, mode))
+}
+
+/// The runs of `workdir`: its stamped entries, those under `<workdir>/tmpfs`
+/// (`--tmpfs-workdir`), and the `--run-output-dir` each run log records, stamped like the log.
+fn workdir_runs(workdir: &Path) -> Result<Vec<IndexedRun>> {
+    let mut runs = Vec::new();
+    let mut seen = HashSet::new();
+    for dir in [workdir.to_path_buf(), workdir.join("tmpfs")] {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) if dir != workdir => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("read workdir {}", workdir.display()))
+            }
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some((stamp_ms, mode)) = parse_run_entry_name(&name) else {
+                continue;
+            };
+            let logged_output_dir = name
+                .starts_with("run-")
+                .then(|| logged_run_output_dir(&entry.path()))
+                .flatten();
+            for path in [Some(entry.path()), logged_output_dir]
+                .into_iter()
+                .flatten()
+            {
+                if seen.insert(path.clone()) {
+                    runs.push(IndexedRun {
+                        stamp_ms,
+                        mode,
+                        path,
+                    });
+                }
+            }
+        }
     }
+    Ok(runs)
+}
 
-    let report_path = run_dir.join("pthread.stack-mprotect-enomem.txt");
-    let analysis = analyze_pthread_stack_mprotect_enomem(run_dir, &stderr_path, &report_path)
-        .context("analyze pthread stack mprotect ENOMEM")?;
+/// The `run_output_dir: ` line of a run log, if that run wrote to `--run-output-dir`.
+fn logged_run_output_dir(log_path: &Path) -> Option<PathBuf> {
+    let log = fs::read_to_string(log_path).ok()?;
+    log.lines()
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| line.strip_prefix("run_output_dir: "))
+        .map(PathBuf::from)
+}
 
-    eprintln!("analysis_events_total: {}", analysis.events_total);
-    eprintln!("wrote_report: {}", report_path.display());
+fn run_index_runs(workdir: &Path, since: Option<u128>, mode: Option<Mode>) -> Result<()> {
+    let mut runs: Vec<IndexedRun> = workdir_runs(workdir)?
+        .into_iter()
+        .filter(|r| since.is_none_or(|s| r.stamp_ms >= s))
+        .filter(|r| mode.is_none_or(|m| m == r.mode))
+        .collect();
+    runs.sort_by(|a, b| a.stamp_ms.cmp(&b.stamp_ms).then(a.path.cmp(&b.path)));
+
+    let mut out = String::new();
+    out.push_str("stamp_ms\tmode\tname\toutcome\texit_code\texit_reason\telapsed_seconds\n");
+    for run in &runs {
+        let kvs = read_run_summary(&run.path).unwrap_or_default();
+        let get = |key: &str| kvs.get(key).cloned().unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            run.stamp_ms,
+            mode_name(run.mode),
+            run.path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            get("outcome"),
+            get("exit_code"),
+            get("exit_reason"),
+            get("elapsed_seconds"),
+        ));
+    }
+
+    let index_path = workdir.join("runs-index.tsv");
+    fs::write(&index_path, &out).context("write runs index")?;
+    print!("{out}");
+    eprintln!("Indexed {} run(s): {}", runs.len(), index_path.display());
     Ok(())
 }
 
-fn run_preflight(
-    muvm_path: &Path,
-    systemd_run_path: Option<&Path>,
-    systemd_tasks_max: Option<u64>,
-    workdir_abs: &Path,
-    timeout_secs: u64,
-) -> Result<()> {
-    let run_dir = workdir_abs.join(format!("preflight-{}", chrono_stamp()));
-    fs::create_dir_all(&run_dir).context("create preflight run dir")?;
+/// Top-level fields of a run's summary: the `key: value` lines of `summary.txt` (indented
+/// section lines skipped; the first occurrence of a key wins), or the scalar members of
+/// `summary.json` when there is no text summary. `None` when the run has neither.
+fn read_run_summary(run_dir: &Path) -> Option<HashMap<String, String>> {
+    let mut kvs = HashMap::new();
+    if let Ok(text) = fs::read_to_string(run_dir.join("summary.txt")) {
+        for line in text.lines() {
+            if line.starts_with(char::is_whitespace) {
+                continue;
+            }
+            if let Some((k, v)) = line.split_once(':') {
+                kvs.entry(k.trim().to_string())
+                    .or_insert_with(|| v.trim().to_string());
+            }
+        }
+        return Some(kvs);
+    }
+    let json = fs::read(run_dir.join("summary.json")).ok()?;
+    let fields: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&json).ok()?;
+    for (k, v) in fields {
+        if let Ok(value) = serde_json::from_value::<SummaryValue>(v) {
+            kvs.insert(k, value.to_string());
+        }
+    }
+    Some(kvs)
+}
 
-    let muvm_output_path = run_dir.join("muvm.txt");
-    let summary_path = run_dir.join("summary.txt");
+/// Lower median (no interpolation, so it's always an observed value).
+fn lower_median(values: &mut [u64]) -> Option<u64> {
+    values.sort_unstable();
+    values.get(values.len().saturating_sub(1) / 2).copied()
+}
 
-    let args: Vec<String> = wrap_muvm_args_if_requested(
-		vec![
-			muvm_path.display().to_string(),
-			"--emu=fex".into(),
-			"-e".into(),
-			format!("RUN_DIR={}", run_dir.display()),
-			"bash".into(),
-			"-lc".into(),
-			"set -euo pipefail; echo \"hello\" >\"$RUN_DIR/vm-ok.txt\"; echo \"wrote:$RUN_DIR/vm-ok.txt\"".into(),
-		],
-		systemd_run_path,
-		systemd_tasks_max,
-	)?;
+/// Spread of a per-run counter across a campaign group.
+#[derive(Serialize, JsonSchema)]
+struct CounterSpread {
+    runs_with_events: usize,
+    min: u64,
+    median: u64,
+    max: u64,
+}
 
-    let start = Instant::now();
-    let rc =
-        run_command_with_pty_to_file(&args, &muvm_output_path, Duration::from_secs(timeout_secs))
-            .context("run muvm preflight")?;
+impl CounterSpread {
+    fn of(mut values: Vec<u64>) -> Option<Self> {
+        let runs_with_events = values.iter().filter(|v| **v > 0).count();
+        let median = lower_median(&mut values)?;
+        Some(Self {
+            runs_with_events,
+            min: values[0],
+            median,
+            max: values[values.len() - 1],
+        })
+    }
 
-    let ok_exists = run_dir.join("vm-ok.txt").is_file();
+    fn text(&self) -> String {
+        format!(
+            "runs_with_events={} min={} median={} max={}",
+            self.runs_with_events, self.min, self.median, self.max
+        )
+    }
+}
 
-    let mut f = fs::File::create(&summary_path).context("write preflight summary")?;
+/// `campaign-report.json`: Edge run summaries aggregated per
+/// run_mode/headless_impl/profile_location.
+#[derive(Serialize, JsonSchema)]
+struct CampaignReport {
+    workdir: String,
+    runs: usize,
+    groups: Vec<CampaignGroup>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct CampaignGroup {
+    run_mode: String,
+    headless_impl: String,
+    profile_location: String,
+    runs: usize,
+    ok: usize,
+    /// `ok / runs`, rounded to four places.
+    success_rate: f64,
+    #[schemars(required)]
+    median_stdout_bytes: Option<u64>,
+    outcomes: std::collections::BTreeMap<String, usize>,
+    #[schemars(required)]
+    stderr_pthread_create_lines: Option<CounterSpread>,
+    #[schemars(required)]
+    pthread_stack_mprotect_enomem_events: Option<CounterSpread>,
+}
+
+/// Summary keys a campaign is grouped by; runs predating a key show up as `(unknown)`.
+const CAMPAIGN_GROUP_KEYS: [&str; 3] = ["run_mode", "headless_impl", "profile_location"];
+/// Per-run counters whose spread the campaign report gives.
+const CAMPAIGN_COUNTERS: [&str; 2] = [
+    "stderr_pthread_create_lines",
+    "pthread_stack_mprotect_enomem_events",
+];
+
+fn run_campaign_report(workdir: &Path, since: Option<u128>) -> Result<()> {
+    let mut groups: std::collections::BTreeMap<[String; 3], Vec<HashMap<String, String>>> =
+        std::collections::BTreeMap::new();
+    let mut total = 0;
+    for run in workdir_runs(workdir)? {
+        if run.mode != Mode::Edge || since.is_some_and(|s| run.stamp_ms < s) {
+            continue;
+        }
+        let Some(kvs) = read_run_summary(&run.path) else {
+            continue;
+        };
+        let key = CAMPAIGN_GROUP_KEYS.map(|k| {
+            kvs.get(k)
+                .cloned()
+                .unwrap_or_else(|| "(unknown)".to_string())
+        });
+        groups.entry(key).or_default().push(kvs);
+        total += 1;
+    }
+
+    let mut text = format!("workdir: {}\nruns: {total}\n", workdir.display());
+    let mut json_groups = Vec::new();
+    for (key, runs) in &groups {
+        let number =
+            |kvs: &HashMap<String, String>, k: &str| kvs.get(k).and_then(|v| v.parse::<u64>().ok());
+        let mut outcomes: std::collections::BTreeMap<&str, usize> = Default::default();
+        for kvs in runs {
+            *outcomes
+                .entry(kvs.get("outcome").map_or("(none)", String::as_str))
+                .or_default() += 1;
+        }
+        let ok = outcomes.get("ok").copied().unwrap_or(0);
+        let success_rate = ok as f64 / runs.len() as f64;
+        let mut stdout_bytes: Vec<u64> = runs
+            .iter()
+            .filter_map(|kvs| number(kvs, "stdout_bytes"))
+            .collect();
+        let median_stdout = lower_median(&mut stdout_bytes);
+        let spreads = CAMPAIGN_COUNTERS.map(|counter| {
+            CounterSpread::of(runs.iter().filter_map(|kvs| number(kvs, counter)).collect())
+        });
+
+        text.push_str(&format!(
+            "\n== run_mode={} headless_impl={} profile_location={} ==\n",
+            key[0], key[1], key[2]
+        ));
+        text.push_str(&format!("runs: {}\n", runs.len()));
+        text.push_str(&format!(
+            "success_rate: {ok}/{} ({:.0}%)\n",
+            runs.len(),
+            success_rate * 100.0
+        ));
+        text.push_str(&format!(
+            "median_stdout_bytes: {}\n",
+            median_stdout.map_or_else(|| "(unavailable)".to_string(), |v| v.to_string())
+        ));
+        text.push_str("outcomes:\n");
+        for (outcome, n) in &outcomes {
+            text.push_str(&format!("  {outcome}: {n}\n"));
+        }
+        for (counter, spread) in CAMPAIGN_COUNTERS.iter().zip(&spreads) {
+            text.push_str(&format!(
+                "{counter}: {}\n",
+                spread
+                    .as_ref()
+                    .map_or_else(|| "(unavailable)".to_string(), CounterSpread::text)
+            ));
+        }
+
+        let [stderr_pthread_create_lines, pthread_stack_mprotect_enomem_events] = spreads;
+        json_groups.push(CampaignGroup {
+            run_mode: key[0].clone(),
+            headless_impl: key[1].clone(),
+            profile_location: key[2].clone(),
+            runs: runs.len(),
+            ok,
+            success_rate: (success_rate * 1e4).round() / 1e4,
+            median_stdout_bytes: median_stdout,
+            outcomes: outcomes
+                .into_iter()
+                .map(|(outcome, n)| (outcome.to_string(), n))
+                .collect(),
+            stderr_pthread_create_lines,
+            pthread_stack_mprotect_enomem_events,
+        });
+    }
+    let report = CampaignReport {
+        workdir: workdir.display().to_string(),
+        runs: total,
+        groups: json_groups,
+    };
+    let mut json = serde_json::to_vec_pretty(&report).context("serialize campaign report")?;
+    json.push(b'\n');
+
+    let text_path = workdir.join("campaign-report.txt");
+    let json_path = workdir.join("campaign-report.json");
+    fs::write(&text_path, &text).context("write campaign report")?;
+    fs::write(&json_path, json).context("write campaign report json")?;
+    print!("{text}");
+    eprintln!(
+        "Wrote campaign report: {} ({})",
+        text_path.display(),
+        json_path.display()
+    );
+    Ok(())
+}
+
+/// Artifacts a finished Edge run dir should contain, and whether an empty file counts as
+/// missing (Edge's stdout legitimately can be empty).
+const RUN_DIR_ARTIFACTS: &[(&str, bool)] = &[
+    ("preflight.txt", true),
+    ("pid.txt", true),
+    ("stdout.txt", false),
+    ("stderr.txt", true),
+    ("edge-exit.txt", true),
+    ("summary.txt", true),
+];
+
+fn run_analyze_run_dir(run_dir: &Path, classifier_file: Option<&Path>) -> Result<()> {
+    if !run_dir.is_dir() {
+        bail!("run dir does not exist: {}", run_dir.display());
+    }
+
+    let mut incomplete = Vec::new();
+    for (name, must_be_non_empty) in RUN_DIR_ARTIFACTS {
+        // `--summary-format json` runs have only `summary.json`.
+        let name = match *name {
+            "summary.txt"
+                if !run_dir.join(name).is_file() && run_dir.join("summary.json").is_file() =>
+            {
+                &"summary.json"
+            }
+            _ => name,
+        };
+        let size = fs::metadata(run_dir.join(name))
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| m.len());
+        match size {
+            Some(n) => eprintln!("artifact: {name} size={n}"),
+            None => eprintln!("artifact: {name} MISSING"),
+        }
+        if size.is_none() || (*must_be_non_empty && size == Some(0)) {
+            incomplete.push(*name);
+        }
+    }
+
+    let stderr_path = run_dir.join("stderr.txt");
+    // Without Edge's stderr there is nothing to analyze: the run died before Edge launched.
+    let completeness = if incomplete.contains(&"stderr.txt") {
+        "empty"
+    } else if incomplete.is_empty() {
+        "complete"
+    } else {
+        "partial"
+    };
+    eprintln!("run_completeness: {completeness}");
+    if !incomplete.is_empty() {
+        eprintln!("incomplete_artifacts: {}", incomplete.join(","));
+    }
+    if completeness == "empty" {
+        eprintln!(
+            "skipping pthread analysis: stderr.txt is missing or empty in {} (the run likely \
+             ended before Edge launched; see muvm.txt)",
+            run_dir.display()
+        );
+        return Ok(());
+    }
+
+    if let Some(classifier_file) = classifier_file {
+        let text = fs::read_to_string(classifier_file)
+            .with_context(|| format!("read classifier file {}", classifier_file.display()))?;
+        let signatures = parse_classifier_file(&text)
+            .with_context(|| format!("parse classifier file {}", classifier_file.display()))?;
+        let (lines, counts) = classify_stderr_file(&stderr_path, &signatures)?;
+        println!(
+            "{}",
+            render_classifier_counts_json(
+                &stderr_path,
+                classifier_file,
+                lines,
+                &signatures,
+                &counts
+            )
+        );
+        return Ok(());
+    }
+
+    let report_path = run_dir.join("pthread.stack-mprotect-enomem.txt");
+    let analysis = analyze_pthread_stack_mprotect_enomem(run_dir, &stderr_path, Some(&report_path))
+        .context("analyze pthread stack mprotect ENOMEM")?;
+
+    eprintln!("analysis_events_total: {}", analysis.events_total);
+    eprintln!("wrote_report: {}", report_path.display());
+    Ok(())
+}
+
+/// Marks a compare-run-dirs value whose source artifact is missing from the run dir, so a run
+/// that never got a `stderr.txt` doesn't read as zero events.
+const ABSENT: &str = "(absent)";
+
+/// Preflight keys that differ on every run and would only add noise to compare-run-dirs.
+const PREFLIGHT_COMPARE_SKIP: &[&str] = &["date"];
+
+/// The counters compare-run-dirs re-derives from a run dir's raw artifacts, in table order.
+fn run_dir_counters(run_dir: &Path) -> Result<Vec<(&'static str, String)>> {
+    let stderr_path = run_dir.join("stderr.txt");
+    let stdout_bytes = fs::metadata(run_dir.join("stdout.txt"))
+        .ok()
+        .filter(|m| m.is_file())
+        .map_or_else(|| ABSENT.to_string(), |m| m.len().to_string());
+    if !stderr_path.is_file() {
+        return Ok(vec![
+            ("stdout_bytes", stdout_bytes),
+            ("stderr_lines", ABSENT.to_string()),
+            ("stderr_pthread_create_lines", ABSENT.to_string()),
+            ("pthread_stack_mprotect_enomem_events", ABSENT.to_string()),
+        ]);
+    }
+    let analysis = analyze_pthread_stack_mprotect_enomem(run_dir, &stderr_path, None)
+        .with_context(|| {
+            format!(
+                "analyze pthread stack mprotect ENOMEM in {}",
+                run_dir.display()
+            )
+        })?;
+    Ok(vec![
+        ("stdout_bytes", stdout_bytes),
+        ("stderr_lines", count_lines(&stderr_path)?.to_string()),
+        (
+            "stderr_pthread_create_lines",
+            count_substring_lines(&stderr_path, "pthread_create")?.to_string(),
+        ),
+        (
+            "pthread_stack_mprotect_enomem_events",
+            analysis.events_total.to_string(),
+        ),
+    ])
+}
+
+/// The guest-runner's own `snake_case_key: value` lines of `preflight.txt` (not the raw
+/// `/proc` dumps or `KEY=value` env lines); the first occurrence of a key wins. The run's own
+/// dir is replaced with `$RUN_DIR` so per-run paths (e.g. in `edge_flags`) compare equal.
+/// `None` when the run has no preflight.
+fn preflight_values(run_dir: &Path) -> Option<HashMap<String, String>> {
+    let text = fs::read_to_string(run_dir.join("preflight.txt")).ok()?;
+    let guest_run_dir = text
+        .lines()
+        .find_map(|line| line.strip_prefix("RUN_DIR="))
+        .filter(|dir| !dir.is_empty());
+    let mut kvs = HashMap::new();
+    for line in text.lines() {
+        let Some((k, v)) = line.split_once(": ") else {
+            continue;
+        };
+        let is_key = !k.is_empty()
+            && k.bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+        if is_key && !PREFLIGHT_COMPARE_SKIP.contains(&k) {
+            let v = match guest_run_dir {
+                Some(dir) => v.trim().replace(dir, "$RUN_DIR"),
+                None => v.trim().to_string(),
+            };
+            kvs.entry(k.to_string()).or_insert(v);
+        }
+    }
+    Some(kvs)
+}
+
+/// Rows of the compare-run-dirs table: every counter and preflight value that differs
+/// between the two runs.
+fn compare_run_dir_rows(
+    counters: [&[(&'static str, String)]; 2],
+    preflight: [Option<&HashMap<String, String>>; 2],
+) -> Vec<(String, String, String)> {
+    let mut rows: Vec<(String, String, String)> = counters[0]
+        .iter()
+        .zip(counters[1])
+        .filter(|((_, a), (_, b))| a != b)
+        .map(|((key, a), (_, b))| (key.to_string(), a.clone(), b.clone()))
+        .collect();
+    let keys: BTreeSet<&String> = preflight
+        .iter()
+        .flatten()
+        .flat_map(|kvs| kvs.keys())
+        .collect();
+    for key in keys {
+        let [a, b] = preflight.map(|kvs| {
+            kvs.and_then(|kvs| kvs.get(key))
+                .cloned()
+                .unwrap_or_else(|| ABSENT.to_string())
+        });
+        if a != b {
+            rows.push((format!("preflight.{key}"), a, b));
+        }
+    }
+    rows
+}
+
+fn run_compare_run_dirs(a: &Path, b: &Path, compare_path: &Path) -> Result<()> {
+    for run_dir in [a, b] {
+        if !run_dir.is_dir() {
+            bail!("run dir does not exist: {}", run_dir.display());
+        }
+    }
+    let counters = [run_dir_counters(a)?, run_dir_counters(b)?];
+    let preflight = [preflight_values(a), preflight_values(b)];
+    let rows = compare_run_dir_rows(
+        [&counters[0], &counters[1]],
+        [preflight[0].as_ref(), preflight[1].as_ref()],
+    );
+
+    let mut out = String::new();
+    out.push_str(&format!("date: {}\n", iso_now()));
+    out.push_str(&format!("run_dir_a: {}\n", a.display()));
+    out.push_str(&format!("run_dir_b: {}\n", b.display()));
+    out.push('\n');
+    if rows.is_empty() {
+        out.push_str("(no differences)\n");
+    } else {
+        let key_width = rows.iter().map(|(k, _, _)| k.len()).max().unwrap_or(0);
+        out.push_str(&format!(
+            "{:<key_width$}  {:<24}  {:<24}\n",
+            "key", "a", "b"
+        ));
+        for (key, va, vb) in &rows {
+            out.push_str(&format!("{key:<key_width$}  {va:<24}  {vb:<24}\n"));
+        }
+    }
+
+    if let Some(parent) = compare_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    fs::write(compare_path, &out).with_context(|| format!("write {}", compare_path.display()))?;
+    eprint!("{out}");
+    eprintln!("Wrote comparison: {}", compare_path.display());
+    Ok(())
+}
+
+/// Replaces the value of every sensitive `KEY=VALUE` in `text` with `***`.
+///
+/// Values end at whitespace, a quote or NUL, so this covers environ dumps, argv lines and
+/// JSON strings alike. Keys of non-sensitive pairs are skipped but their values are still
+/// scanned, so `EDGE_ENV=API_TOKEN=x` is redacted too.
+fn redact_env_values(text: &[u8], redact: &[String], keys: &mut BTreeSet<String>) -> Vec<u8> {
+    let is_key_char = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let ends_value = |b: u8| b.is_ascii_whitespace() || matches!(b, b'"' | b'\'' | 0);
+    let mut out = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        let starts_key = (text[i].is_ascii_alphabetic() || text[i] == b'_')
+            && (i == 0 || !is_key_char(text[i - 1]));
+        if !starts_key {
+            out.push(text[i]);
+            i += 1;
+            continue;
+        }
+        let mut j = i;
+        while j < text.len() && is_key_char(text[j]) {
+            j += 1;
+        }
+        if j == text.len() || text[j] != b'=' {
+            out.extend_from_slice(&text[i..j]);
+            i = j;
+            continue;
+        }
+        out.extend_from_slice(&text[i..=j]);
+        let key = String::from_utf8_lossy(&text[i..j]).into_owned();
+        let mut k = j + 1;
+        while k < text.len() && !ends_value(text[k]) {
+            k += 1;
+        }
+        let value = &text[j + 1..k];
+        if !value.is_empty() && is_sensitive_env_key(&key, redact) {
+            out.extend_from_slice(b"***");
+            if value != b"***" {
+                keys.insert(key);
+            }
+            i = k;
+        } else {
+            i = j + 1;
+        }
+    }
+    out
+}
+
+/// Redacts the top-level text artifacts of `run_dir` (`.txt`, `.json`, `.log`) and records
+/// the redacted keys in `summary` (which redacts itself when written).
+///
+/// `stdout.txt` is left alone: it is the page Edge dumped, not an env or argv dump, and
+/// `stdout_bytes`/`stdout_lines` have already been measured from it.
+fn redact_run_dir(run_dir: &Path, summary: &mut EdgeSummary, redact: &[String]) -> Result<()> {
+    let mut keys = BTreeSet::new();
+    for entry in fs::read_dir(run_dir).with_context(|| format!("read {}", run_dir.display()))? {
+        let path = entry?.path();
+        let is_text = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("txt" | "json" | "log")
+        );
+        if !is_text || !path.is_file() || path.file_name() == Some("stdout.txt".as_ref()) {
+            continue;
+        }
+        let text = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+        let redacted = redact_env_values(&text, redact, &mut keys);
+        if redacted != text {
+            fs::write(&path, redacted).with_context(|| format!("write {}", path.display()))?;
+        }
+    }
+
+    let keys: Vec<String> = keys.into_iter().collect();
+    summary.push(
+        "redacted_keys",
+        if keys.is_empty() {
+            "(none)".to_string()
+        } else {
+            keys.join(",")
+        },
+    );
+    Ok(())
+}
+
+/// Reads an `--edge-arg-file`: one Edge arg per line, surrounding whitespace trimmed, blank
+/// lines and `#` comments skipped.
+fn read_edge_arg_file(path: &Path) -> Result<Vec<String>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("read --edge-arg-file {}", path.display()))?;
+    let mut args = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let arg = line.trim();
+        if arg.is_empty() || arg.starts_with('#') {
+            continue;
+        }
+        if arg.contains('\0') {
+            bail!(
+                "invalid --edge-arg-file line (contains a NUL byte): {}:{}",
+                path.display(),
+                i + 1
+            );
+        }
+        args.push(arg.to_string());
+    }
+    Ok(args)
+}
+
+/// Rejects `--proxy` values Chromium's `--proxy-server` won't take, and warns about loopback
+/// addresses, which inside the guest don't reach the host.
+fn check_proxy_url(proxy: &str) -> Result<()> {
+    let Some((scheme, rest)) = proxy.split_once("://") else {
+        bail!("--proxy must be a URL like http://HOST:PORT, got {proxy}");
+    };
+    if !matches!(scheme, "http" | "https" | "socks4" | "socks5") {
+        bail!("--proxy scheme must be http, https, socks4 or socks5, got {scheme}");
+    }
+    let authority = rest.trim_end_matches('/');
+    let Some((host, port)) = authority.rsplit_once(':') else {
+        bail!("--proxy needs an explicit port, got {proxy}");
+    };
+    if host.is_empty() || port.parse::<u16>().is_err() {
+        bail!("--proxy needs HOST:PORT, got {proxy}");
+    }
+    if matches!(host, "localhost" | "127.0.0.1" | "[::1]") {
+        eprintln!(
+            "WARNING: --proxy {proxy} is a loopback address; inside the guest it won't reach \
+             the host's proxy"
+        );
+    }
+    Ok(())
+}
+
+/// Mounts (or reuses) a tmpfs at `<workdir>/tmpfs` for `--tmpfs-workdir` and returns it with
+/// a status for the run log: `existing`, `mounted size=...` or `fallback (...)`.
+fn prepare_tmpfs_workdir(workdir: &Path, size: &str) -> Result<(PathBuf, String)> {
+    if size.is_empty() || size.contains(',') {
+        bail!("--tmpfs-workdir expects a tmpfs size like 2G or 512M, got {size:?}");
+    }
+    let dir = workdir.join("tmpfs");
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    let mounts = parse_mountinfo(&fs::read_to_string("/proc/self/mountinfo").unwrap_or_default());
+    if mount_for_path(&mounts, &dir).is_some_and(|m| m.fs_type == "tmpfs") {
+        return Ok((dir, "existing".to_string()));
+    }
+
+    use std::os::unix::ffi::OsStrExt;
+    let target = CString::new(dir.as_os_str().as_bytes()).context("tmpfs dir path")?;
+    let data = CString::new(format!("size={size},mode=0755")).context("tmpfs options")?;
+    let rc = unsafe {
+        libc::mount(
+            c"tmpfs".as_ptr(),
+            target.as_ptr(),
+            c"tmpfs".as_ptr(),
+            libc::MS_NOSUID | libc::MS_NODEV,
+            data.as_ptr() as *const libc::c_void,
+        )
+    };
+    if rc == 0 {
+        return Ok((dir, format!("mounted size={size}")));
+    }
+    let err = io::Error::last_os_error();
+    eprintln!(
+        "WARNING: could not mount a tmpfs at {} ({err}); using it as a regular directory",
+        dir.display()
+    );
+    Ok((dir, format!("fallback ({err})")))
+}
+
+fn list_dir_names(dir: &Path) -> BTreeSet<OsString> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.file_name()).collect())
+        .unwrap_or_default()
+}
+
+/// Copies the entries of `dir` not in `before` into `archive_dir` (for `--archive-dir`) and
+/// returns their names.
+fn archive_new_entries(
+    dir: &Path,
+    before: &BTreeSet<OsString>,
+    archive_dir: &Path,
+) -> Result<Vec<OsString>> {
+    fs::create_dir_all(archive_dir)
+        .with_context(|| format!("create archive dir {}", archive_dir.display()))?;
+    let new: Vec<OsString> = list_dir_names(dir)
+        .into_iter()
+        .filter(|name| !before.contains(name))
+        .collect();
+    for name in &new {
+        copy_tree(&dir.join(name), &archive_dir.join(name))?;
+    }
+    Ok(new)
+}
+
+/// Recursive copy that recreates symlinks rather than following them.
+fn copy_tree(src: &Path, dst: &Path) -> Result<()> {
+    let meta = fs::symlink_metadata(src).with_context(|| format!("stat {}", src.display()))?;
+    if meta.is_dir() {
+        fs::create_dir_all(dst).with_context(|| format!("create {}", dst.display()))?;
+        for entry in fs::read_dir(src).with_context(|| format!("read {}", src.display()))? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+    } else if meta.file_type().is_symlink() {
+        let target = fs::read_link(src).with_context(|| format!("readlink {}", src.display()))?;
+        let _ = fs::remove_file(dst);
+        std::os::unix::fs::symlink(&target, dst)
+            .with_context(|| format!("symlink {}", dst.display()))?;
+    } else {
+        fs::copy(src, dst)
+            .with_context(|| format!("copy {} -> {}", src.display(), dst.display()))?;
+    }
+    Ok(())
+}
+
+/// Creates `--run-output-dir` and returns it canonicalized, refusing a non-empty
+/// directory unless `force` is set.
+fn prepare_run_output_dir(dir: &Path, force: bool) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let non_empty = fs::read_dir(dir)
+        .with_context(|| format!("read {}", dir.display()))?
+        .next()
+        .is_some();
+    if non_empty && !force {
+        bail!(
+            "--run-output-dir {} is not empty (pass --force to write into it anyway)",
+            dir.display()
+        );
+    }
+    fs::canonicalize(dir).with_context(|| format!("canonicalize {}", dir.display()))
+}
+
+fn run_preflight(
+    muvm: &MuvmLauncher,
+    systemd_run_path: Option<&Path>,
+    systemd_tasks_max: Option<u64>,
+    run_dir: PathBuf,
+    timeout_secs: u64,
+    watchdog: Watchdog,
+    tail_muvm_output: Option<usize>,
+) -> Result<()> {
+    fs::create_dir_all(&run_dir).context("create preflight run dir")?;
+
+    let muvm_output_path = run_dir.join("muvm.txt");
+    let summary_path = run_dir.join("summary.txt");
+
+    let mut muvm_argv = muvm.argv();
+    muvm_argv.extend([
+        "--emu=fex".into(),
+        "-e".into(),
+        format!("RUN_DIR={}", run_dir.display()),
+        "bash".into(),
+        "-lc".into(),
+        "set -euo pipefail; echo \"hello\" >\"$RUN_DIR/vm-ok.txt\"; echo \"wrote:$RUN_DIR/vm-ok.txt\"".into(),
+    ]);
+    let args: Vec<String> =
+        wrap_muvm_args_if_requested(muvm_argv, systemd_run_path, systemd_tasks_max)?;
+
+    let start = Instant::now();
+    let observed = run_command_with_pty_to_file_observed(
+        &args,
+        &muvm_output_path,
+        Duration::from_secs(timeout_secs),
+        watchdog,
+        None,
+        &|_| {},
+    )
+    .context("run muvm preflight")?;
+    let rc = observed.exit_code;
+
+    let ok_exists = run_dir.join("vm-ok.txt").is_file();
+
+    let mut f = fs::File::create(&summary_path).context("write preflight summary")?;
     writeln!(f, "exit_code: {rc}")?;
+    writeln!(f, "timeout_kill: {}", observed.timeout_kill)?;
     writeln!(f, "elapsed_seconds: {}", start.elapsed().as_secs())?;
     writeln!(f, "run_dir: {}", run_dir.display())?;
     writeln!(
@@ -430,36 +2069,48 @@ fn run_preflight(
             .unwrap_or_else(|| "(none)".to_string())
     )?;
     writeln!(f, "vm_ok_exists: {}", if ok_exists { "yes" } else { "no" })?;
+    writeln!(f, "host_env_passthrough: {}", muvm.passthrough_summary())?;
 
+    if let Some(n) = tail_muvm_output.filter(|_| rc != 0 || !ok_exists) {
+        eprint_muvm_tail(&muvm_output_path, n);
+    }
     Ok(())
 }
 
 fn run_muvm_true(
-    muvm_path: &Path,
+    muvm: &MuvmLauncher,
     systemd_run_path: Option<&Path>,
     systemd_tasks_max: Option<u64>,
-    workdir_abs: &Path,
+    run_dir: PathBuf,
     timeout_secs: u64,
+    watchdog: Watchdog,
+    tail_muvm_output: Option<usize>,
 ) -> Result<()> {
-    let run_dir = workdir_abs.join(format!("muvm-true-{}", chrono_stamp()));
     fs::create_dir_all(&run_dir).context("create muvm-true run dir")?;
 
     let muvm_output_path = run_dir.join("muvm.txt");
     let summary_path = run_dir.join("summary.txt");
 
-    let args: Vec<String> = wrap_muvm_args_if_requested(
-        vec![muvm_path.display().to_string(), "true".into()],
-        systemd_run_path,
-        systemd_tasks_max,
-    )?;
+    let mut muvm_argv = muvm.argv();
+    muvm_argv.push("true".into());
+    let args: Vec<String> =
+        wrap_muvm_args_if_requested(muvm_argv, systemd_run_path, systemd_tasks_max)?;
 
     let start = Instant::now();
-    let rc =
-        run_command_with_pty_to_file(&args, &muvm_output_path, Duration::from_secs(timeout_secs))
-            .context("run muvm true")?;
+    let observed = run_command_with_pty_to_file_observed(
+        &args,
+        &muvm_output_path,
+        Duration::from_secs(timeout_secs),
+        watchdog,
+        None,
+        &|_| {},
+    )
+    .context("run muvm true")?;
+    let rc = observed.exit_code;
 
     let mut f = fs::File::create(&summary_path).context("write muvm-true summary")?;
     writeln!(f, "exit_code: {rc}")?;
+    writeln!(f, "timeout_kill: {}", observed.timeout_kill)?;
     writeln!(f, "elapsed_seconds: {}", start.elapsed().as_secs())?;
     writeln!(f, "run_dir: {}", run_dir.display())?;
     writeln!(
@@ -469,7 +2120,11 @@ fn run_muvm_true(
             .map(|v| v.to_string())
             .unwrap_or_else(|| "(none)".to_string())
     )?;
+    writeln!(f, "host_env_passthrough: {}", muvm.passthrough_summary())?;
 
+    if let Some(n) = tail_muvm_output.filter(|_| rc != 0) {
+        eprint_muvm_tail(&muvm_output_path, n);
+    }
     Ok(())
 }
 
@@ -486,15 +2141,44 @@ enum KillMode {
     ExternalTimeoutForeground,
 }
 
+#[derive(Copy, Clone, Debug)]
+struct MatrixPlan<'a> {
+    /// `timeout(1)`, for the external-kill cases.
+    timeout_path: &'a Path,
+    runs_per_case: u32,
+    /// Stop at the first failing run instead of sweeping every case.
+    fail_fast: bool,
+    /// `STDERR_SIGNATURES` key whose count above zero also fails a run.
+    fail_on_classifier: Option<&'a str>,
+    /// Caps for each case's `stuck.txt`.
+    snapshot_limits: SnapshotLimits,
+}
+
 fn run_muvm_true_matrix(
-    muvm_path: &Path,
-    timeout_path: &Path,
+    muvm: &MuvmLauncher,
     systemd_run_path: Option<&Path>,
     systemd_tasks_max: Option<u64>,
     workdir_abs: &Path,
     timeout_secs: u64,
-    runs_per_case: u32,
+    watchdog: Watchdog,
+    plan: MatrixPlan<'_>,
 ) -> Result<()> {
+    let MatrixPlan {
+        timeout_path,
+        runs_per_case,
+        fail_fast,
+        fail_on_classifier,
+        snapshot_limits,
+    } = plan;
+    let classifier = fail_on_classifier
+        .map(|key| {
+            STDERR_SIGNATURES
+                .iter()
+                .find(|(k, _)| *k == key)
+                .copied()
+                .with_context(|| format!("unknown stderr classifier {key:?}"))
+        })
+        .transpose()?;
     let batch_dir = workdir_abs.join(format!("muvm-true-matrix-{}", chrono_stamp()));
     fs::create_dir_all(&batch_dir).context("create muvm-true matrix batch dir")?;
     let batch_summary_path = batch_dir.join("matrix-summary.txt");
@@ -520,6 +2204,13 @@ fn run_muvm_true_matrix(
     batch_summary.push_str(&format!("date: {}\n", iso_now()));
     batch_summary.push_str(&format!("timeout_secs: {timeout_secs}\n"));
     batch_summary.push_str(&format!("runs_per_case: {runs_per_case}\n"));
+    batch_summary.push_str(&format!(
+        "fail_fast: {}\n",
+        if fail_fast { "yes" } else { "no" }
+    ));
+    if let Some((key, _)) = classifier {
+        batch_summary.push_str(&format!("fail_on_classifier: {key}\n"));
+    }
     batch_summary.push_str(&format!(
         "systemd_tasks_max: {}\n",
         systemd_tasks_max
@@ -529,7 +2220,8 @@ fn run_muvm_true_matrix(
     batch_summary.push_str("\n## runs\n");
     batch_summary.push_str("case\trun\texit\telapsed\ttimed_out\tstuck_snapshot\n");
 
-    for (stdio_mode, kill_mode, case_name) in cases {
+    let mut aborted: Option<String> = None;
+    'cases: for (stdio_mode, kill_mode, case_name) in cases {
         for run_idx in 1..=runs_per_case {
             let run_dir = batch_dir.join(format!(
                 "case-{}-run-{}-{}",
@@ -557,7 +2249,7 @@ fn run_muvm_true_matrix(
             match kill_mode {
                 KillMode::Internal => {
                     argv = wrap_muvm_args_if_requested(
-                        vec![muvm_path.display().to_string(), "true".into()],
+                        [muvm.argv(), vec!["true".into()]].concat(),
                         systemd_run_path,
                         systemd_tasks_max,
                     )?;
@@ -567,9 +2259,11 @@ fn run_muvm_true_matrix(
                         vec![
                             timeout_path.display().to_string(),
                             format!("{timeout_secs}s"),
-                            muvm_path.display().to_string(),
-                            "true".into(),
-                        ],
+                        ]
+                        .into_iter()
+                        .chain(muvm.argv())
+                        .chain(["true".into()])
+                        .collect(),
                         systemd_run_path,
                         systemd_tasks_max,
                     )?;
@@ -580,9 +2274,11 @@ fn run_muvm_true_matrix(
                             timeout_path.display().to_string(),
                             "--foreground".into(),
                             format!("{timeout_secs}s"),
-                            muvm_path.display().to_string(),
-                            "true".into(),
-                        ],
+                        ]
+                        .into_iter()
+                        .chain(muvm.argv())
+                        .chain(["true".into()])
+                        .collect(),
                         systemd_run_path,
                         systemd_tasks_max,
                     )?;
@@ -590,7 +2286,7 @@ fn run_muvm_true_matrix(
             }
 
             let start = Instant::now();
-            let (rc, timed_out) = match stdio_mode {
+            let (rc, timed_out, timeout_kill) = match stdio_mode {
                 StdioMode::Pty => {
                     let hook = |child_pid: libc::pid_t| {
                         let root = child_pid as u32;
@@ -602,7 +2298,8 @@ fn run_muvm_true_matrix(
                         } else {
                             root
                         };
-                        write_stuck_snapshot_named(&stuck_path, target, "muvm").ok();
+                        write_stuck_snapshot_named(&stuck_path, target, "muvm", snapshot_limits)
+                            .ok();
                     };
 
                     let timeout = if matches!(
@@ -617,11 +2314,12 @@ fn run_muvm_true_matrix(
                         &argv,
                         &output_path,
                         timeout,
+                        watchdog,
                         snapshot_at,
                         &hook,
                     )
                     .context("run muvm matrix case (pty)")?;
-                    (res.exit_code, res.timed_out)
+                    (res.exit_code, res.timed_out, res.timeout_kill)
                 }
                 StdioMode::InheritTty => {
                     let hook = |child_pid: libc::pid_t| {
@@ -634,7 +2332,8 @@ fn run_muvm_true_matrix(
                         } else {
                             root
                         };
-                        write_stuck_snapshot_named(&stuck_path, target, "muvm").ok();
+                        write_stuck_snapshot_named(&stuck_path, target, "muvm", snapshot_limits)
+                            .ok();
                     };
 
                     let timeout = if matches!(
@@ -649,11 +2348,12 @@ fn run_muvm_true_matrix(
                         &argv,
                         &output_path,
                         timeout,
+                        watchdog,
                         snapshot_at,
                         &hook,
                     )
                     .context("run muvm matrix case (inherit tty)")?;
-                    (res.exit_code, res.timed_out)
+                    (res.exit_code, res.timed_out, res.timeout_kill)
                 }
             };
 
@@ -668,11 +2368,23 @@ fn run_muvm_true_matrix(
             writeln!(f, "exit_code: {rc}")?;
             writeln!(f, "elapsed_seconds: {elapsed}")?;
             writeln!(f, "timed_out: {}", if timed_out { "yes" } else { "no" })?;
+            writeln!(f, "timeout_kill: {timeout_kill}")?;
             writeln!(
                 f,
                 "stuck_snapshot: {}",
                 if stuck_exists { "yes" } else { "no" }
             )?;
+            let classifier_count = match classifier {
+                Some((key, needle)) => {
+                    let mut count = [0u64];
+                    for line in read_artifact(&output_path).unwrap_or_default().lines() {
+                        tally_signatures(line, std::iter::once(needle), &mut count);
+                    }
+                    writeln!(f, "{key}: {}", count[0])?;
+                    Some((key, count[0]))
+                }
+                None => None,
+            };
             writeln!(f, "run_dir: {}", run_dir.display())?;
             writeln!(f, "output_log: {}", output_path.display())?;
             writeln!(f, "stuck_log: {}", stuck_path.display())?;
@@ -682,85 +2394,392 @@ fn run_muvm_true_matrix(
                 if timed_out { "yes" } else { "no" },
                 if stuck_exists { "yes" } else { "no" }
             ));
+
+            if let Some(reason) =
+                matrix_run_failure(timed_out, rc, classifier_count).filter(|_| fail_fast)
+            {
+                aborted = Some(format!("{case_name} run {run_idx} ({reason}, exit {rc})"));
+                break 'cases;
+            }
         }
     }
 
+    if let Some(at) = &aborted {
+        batch_summary.push_str(&format!(
+            "\naborted_early: yes (--fail-fast stopped at {at}; later runs were not attempted)\n"
+        ));
+        eprintln!("muvm-true-matrix: --fail-fast stopped at {at}");
+    }
     fs::write(&batch_summary_path, batch_summary).context("write matrix summary")?;
     eprintln!("Run dir: {}", batch_dir.display());
     Ok(())
 }
 
-#[derive(Debug, Clone)]
-struct EdgeRunResult {
-    run_dir: PathBuf,
-    stdout_bytes: u64,
-    stderr_pthread_create_lines: u64,
-    pthread_stack_mprotect_enomem_events: u64,
+/// Why a muvm-true-matrix run counts as failed for `--fail-fast`: a timeout, a non-zero exit,
+/// or the `--fail-on-classifier` count (key, count) above zero.
+fn matrix_run_failure(timed_out: bool, rc: i32, classifier: Option<(&str, u64)>) -> Option<String> {
+    if timed_out {
+        Some("timed out".to_string())
+    } else if rc != 0 {
+        Some("non-zero exit".to_string())
+    } else {
+        classifier
+            .filter(|(_, count)| *count > 0)
+            .map(|(key, count)| format!("{key}={count}"))
+    }
 }
 
-fn run_edge(
-    muvm_path: &Path,
-    systemd_run_path: Option<&Path>,
-    systemd_tasks_max: Option<u64>,
-    workdir_abs: &Path,
-    extracted_root_abs: &Path,
-    mem: Option<u64>,
-    muvm_privileged: bool,
-    strace: bool,
-    strace_mode: StraceMode,
-    timeout: Duration,
-    edge_watchdog: Duration,
-    url: &str,
-    headless_impl: HeadlessImpl,
-    edge_args: &[String],
-    edge_env: &[String],
-    profile_location: ProfileLocation,
-    preserve_dbus_xdg_env: bool,
-    guest_sysctls: &[String],
-) -> Result<EdgeRunResult> {
-    if !extracted_root_abs.is_dir() {
-        bail!(
-            "No extracted root present; expected {}",
-            extracted_root_abs.display()
-        );
+/// One value of an Edge run summary: a bare number or string in `summary.json` and the text
+/// after `key: ` in `summary.txt`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum SummaryValue {
+    Count(u64),
+    Code(i64),
+    Text(String),
+    /// Space-separated in `summary.txt`, `(none)` when empty.
+    List(Vec<String>),
+}
+
+impl SummaryValue {
+    /// A value copied from another artifact's `key: value` line; numbers stay numbers in JSON.
+    /// Text that wouldn't read back the same as a number (`0022`, `+1`) stays text.
+    fn parse(text: String) -> Self {
+        match text.parse::<u64>() {
+            Ok(n) if n.to_string() == text => SummaryValue::Count(n),
+            _ => SummaryValue::Text(text),
+        }
     }
+}
 
-    let edge_bin = extracted_root_abs.join("opt/microsoft/msedge/microsoft-edge");
-    if !edge_bin.is_file() {
-        bail!("Edge binary missing at {}", edge_bin.display());
+impl std::fmt::Display for SummaryValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SummaryValue::Count(n) => write!(f, "{n}"),
+            SummaryValue::Code(n) => write!(f, "{n}"),
+            SummaryValue::Text(text) => f.write_str(text),
+            SummaryValue::List(items) if items.is_empty() => f.write_str("(none)"),
+            SummaryValue::List(items) => f.write_str(&items.join(" ")),
+        }
     }
+}
 
-    let run_dir = workdir_abs.join(format!("headless-{}", chrono_stamp()));
-    fs::create_dir_all(&run_dir).context("create run dir")?;
-    if matches!(profile_location, ProfileLocation::Shared) {
-        fs::create_dir_all(run_dir.join("profile")).context("create shared profile dir")?;
+impl From<u64> for SummaryValue {
+    fn from(n: u64) -> Self {
+        SummaryValue::Count(n)
     }
+}
 
-    let stdout_path = run_dir.join("stdout.txt");
-    let stderr_path = run_dir.join("stderr.txt");
+impl From<i32> for SummaryValue {
+    fn from(n: i32) -> Self {
+        SummaryValue::Code(n.into())
+    }
+}
+
+impl From<String> for SummaryValue {
+    fn from(text: String) -> Self {
+        SummaryValue::Text(text)
+    }
+}
+
+impl From<&str> for SummaryValue {
+    fn from(text: &str) -> Self {
+        SummaryValue::Text(text.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+struct CompressedArtifact {
+    name: String,
+    bytes_before: u64,
+    bytes_after: u64,
+}
+
+/// Everything `run_edge` reports about a run. `summary.txt` and `summary.json` are both
+/// rendered from it, so the two can't drift.
+#[derive(Debug, Default)]
+struct EdgeSummary<'a> {
+    /// Top-level fields, in `summary.txt` order.
+    fields: Vec<(String, SummaryValue)>,
+    /// cgroup v2 and sysctl values copied from `preflight.txt`.
+    preflight_kvs: Vec<(String, SummaryValue)>,
+    gnu_property_strip: Option<&'a StripReport>,
+    /// Paths of the run's other artifacts, keyed by role.
+    artifacts: Vec<(String, SummaryValue)>,
+    compressed_artifacts: Vec<CompressedArtifact>,
+}
+
+impl EdgeSummary<'_> {
+    fn push(&mut self, key: impl Into<String>, value: impl Into<SummaryValue>) {
+        self.fields.push((key.into(), value.into()));
+    }
+
+    fn artifact(&mut self, role: &str, path: &Path) {
+        self.artifacts
+            .push((role.to_string(), path.display().to_string().into()));
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = String::new();
+        for (k, v) in &self.fields {
+            out.push_str(&format!("{k}: {v}\n"));
+        }
+        if !self.preflight_kvs.is_empty() {
+            out.push_str("\npreflight_kvs:\n");
+            for (k, v) in &self.preflight_kvs {
+                out.push_str(&format!("  {k}: {v}\n"));
+            }
+        }
+        if let Some(r) = self.gnu_property_strip {
+            out.push_str("\ngnu_property_strip:\n");
+            out.push_str(&format!("  stripped_files: {}\n", r.stripped_files.len()));
+            for p in &r.stripped_files {
+                out.push_str(&format!("    {p}\n"));
+            }
+            out.push_str(&format!("  strip_failures: {}\n", r.strip_failures.len()));
+            for fail in &r.strip_failures {
+                out.push_str(&format!(
+                    "    {}: {}\n",
+                    fail.path,
+                    fail.error.lines().next().unwrap_or("").trim()
+                ));
+            }
+            out.push_str(&format!(
+                "  remaining_files: {}\n",
+                r.remaining_gnu_property_files.len()
+            ));
+            for p in &r.remaining_gnu_property_files {
+                out.push_str(&format!("    {p}\n"));
+            }
+        }
+        if !self.artifacts.is_empty() {
+            out.push_str("\nartifacts:\n");
+            for (k, v) in &self.artifacts {
+                out.push_str(&format!("  {k}: {v}\n"));
+            }
+        }
+        if !self.compressed_artifacts.is_empty() {
+            out.push_str("compressed_artifacts:\n");
+            for c in &self.compressed_artifacts {
+                out.push_str(&format!(
+                    "  {}: {} -> {} bytes\n",
+                    c.name, c.bytes_before, c.bytes_after
+                ));
+            }
+        }
+        out
+    }
+
+    /// Writes `summary.txt` and/or `summary.json` into `run_dir`, redacting them like the
+    /// other artifacts.
+    fn write(&self, run_dir: &Path, format: SummaryFormat, redact: &[String]) -> Result<()> {
+        let mut rendered = Vec::new();
+        if format.text() {
+            rendered.push(("summary.txt", self.render_text().into_bytes()));
+        }
+        if format.json() {
+            let mut json = serde_json::to_vec_pretty(self).context("serialize summary")?;
+            json.push(b'\n');
+            rendered.push(("summary.json", json));
+        }
+        for (name, bytes) in rendered {
+            let path = run_dir.join(name);
+            fs::write(
+                &path,
+                redact_env_values(&bytes, redact, &mut BTreeSet::new()),
+            )
+            .with_context(|| format!("write {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes `key: value` pairs as a JSON object, keeping their order.
+struct KvObject<'a, V = SummaryValue>(&'a [(String, V)]);
+
+impl<V: Serialize> Serialize for KvObject<'_, V> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+        s.collect_map(self.0.iter().map(|(k, v)| (k, v)))
+    }
+}
+
+impl Serialize for EdgeSummary<'_> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = s.serialize_map(None)?;
+        for (k, v) in &self.fields {
+            map.serialize_entry(k, v)?;
+        }
+        map.serialize_entry("preflight_kvs", &KvObject(&self.preflight_kvs))?;
+        if let Some(r) = self.gnu_property_strip {
+            map.serialize_entry("gnu_property_strip", r)?;
+        }
+        map.serialize_entry("artifacts", &KvObject(&self.artifacts))?;
+        if !self.compressed_artifacts.is_empty() {
+            map.serialize_entry("compressed_artifacts", &self.compressed_artifacts)?;
+        }
+        map.end()
+    }
+}
+
+/// Schema of `summary.json`: the top-level fields are open-ended, so anything not named here
+/// is a `SummaryValue`.
+impl JsonSchema for EdgeSummary<'_> {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "EdgeSummary".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        let value = generator.subschema_for::<SummaryValue>();
+        let kvs = schemars::json_schema!({
+            "type": "object",
+            "additionalProperties": value,
+        });
+        schemars::json_schema!({
+            "description": "Edge run summary.json; the same fields as summary.txt.",
+            "type": "object",
+            "properties": {
+                "outcome": { "type": "string" },
+                "preflight_kvs": kvs,
+                "gnu_property_strip": generator.subschema_for::<StripReport>(),
+                "artifacts": kvs,
+                "compressed_artifacts": generator.subschema_for::<Vec<CompressedArtifact>>(),
+            },
+            "required": ["outcome", "preflight_kvs", "artifacts"],
+            "additionalProperties": value,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EdgeRunResult {
+    run_dir: PathBuf,
+    stdout_bytes: u64,
+    stderr_pthread_create_lines: u64,
+    pthread_stack_mprotect_enomem_events: u64,
+    /// Process exit code for `--mode edge`: 0 ok, 124 timeout/watchdog, 125 muvm failure,
+    /// otherwise Edge's own exit code (128 + N for signal N).
+    exit_code: i32,
+}
+
+fn run_edge(
+    muvm: &MuvmLauncher,
+    systemd_run_path: Option<&Path>,
+    systemd_tasks_max: Option<u64>,
+    workdir_abs: &Path,
+    run_output_dir: Option<&Path>,
+    extracted_root_abs: &Path,
+    edge_bin_rel: &Path,
+    mem: Option<u64>,
+    muvm_privileged: bool,
+    strace: bool,
+    strace_mode: StraceMode,
+    strace_attach_after: Option<Duration>,
+    mem_report: bool,
+    dump_environ: bool,
+    live_classify: bool,
+    stop_on_signatures: &[String],
+    snapshot_limits: SnapshotLimits,
+    timeout: Duration,
+    watchdog: Watchdog,
+    edge_watchdog: Duration,
+    url: &str,
+    headless_impl: HeadlessImpl,
+    edge_args: &[String],
+    edge_arg_file: Option<&Path>,
+    default_edge_flags: bool,
+    edge_env: &[String],
+    proxy: Option<&str>,
+    guest_cwd: Option<&Path>,
+    profile_location: ProfileLocation,
+    preserve_dbus_xdg_env: bool,
+    guest_sysctls: &[String],
+    guest_systemd_tasks_max: Option<u64>,
+    guest_nofile: Option<u64>,
+    edge_strip: Option<&StripReport>,
+    tail_muvm_output: Option<usize>,
+    metrics: Option<&MetricsSink>,
+    run_mode: &str,
+    redact: &[String],
+    guest_runner_path: Option<&str>,
+    compress_artifacts: Option<u64>,
+    summary_format: SummaryFormat,
+) -> Result<EdgeRunResult> {
+    if !extracted_root_abs.is_dir() {
+        bail!(
+            "No extracted root present; expected {}",
+            extracted_root_abs.display()
+        );
+    }
+
+    let edge_bin = extracted_root_abs.join(edge_bin_rel);
+    if !edge_bin.is_file() {
+        return Err(edge_bin_missing(extracted_root_abs, edge_bin_rel));
+    }
+
+    let run_dir = run_output_dir
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| workdir_abs.join(format!("headless-{}", chrono_stamp())));
+    fs::create_dir_all(&run_dir).context("create run dir")?;
+    if matches!(profile_location, ProfileLocation::Shared) {
+        fs::create_dir_all(run_dir.join("profile")).context("create shared profile dir")?;
+    }
+
+    let stdout_path = run_dir.join("stdout.txt");
+    let stderr_path = run_dir.join("stderr.txt");
     let stderr_filtered_path = run_dir.join("stderr.filtered.txt");
     let ps_path = run_dir.join("ps.txt");
     let threads_path = run_dir.join("threads.txt");
     let preflight_path = run_dir.join("preflight.txt");
-    let summary_path = run_dir.join("summary.txt");
     let muvm_output_path = run_dir.join("muvm.txt");
+    let muvm_argv_path = run_dir.join("muvm-argv.txt");
+    let meminfo_delta_path = run_dir.join("meminfo-delta.txt");
+    let live_counts_path = run_dir.join("live-counts.txt");
+    let stop_signature_path = run_dir.join("stop-signature.txt");
+    let stuck_path = run_dir.join("stuck.txt");
+    let threads_timeseries_path = run_dir.join("threads-timeseries.txt");
+    let pressure_timeseries_path = run_dir.join("pressure-timeseries.txt");
+    let edge_environ_path = run_dir.join("edge-environ.txt");
+    let maps_path = run_dir.join("maps.txt");
+
+    // Read up front so a bad file fails before muvm starts. The guest can't see arbitrary host
+    // paths (its /tmp is its own tmpfs), so it gets the parsed args via the shared run dir.
+    let edge_args_from_file = match edge_arg_file {
+        Some(path) => Some(read_edge_arg_file(path)?),
+        None => None,
+    };
 
-    // Ensure the guest-runner binary is in a path that we know muvm shares.
     let self_exe = std::env::current_exe().context("locate current executable")?;
     let self_exe = fs::canonicalize(&self_exe).context("canonicalize current executable")?;
-    let guest_runner_path = run_dir.join("edge-muvm-guest-runner");
-    fs::copy(&self_exe, &guest_runner_path).context("copy guest-runner into run dir")?;
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&guest_runner_path)
-            .context("stat guest-runner")?
-            .permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&guest_runner_path, perms).context("chmod guest-runner")?;
-    }
+    // Recorded in the summary so runs on different machines can be shown to share inputs.
+    let muvm_sha256 = hash_file(&muvm.path)?;
+    let (guest_runner_path, guest_runner_mechanism) = resolve_guest_runner(
+        muvm,
+        &muvm_sha256,
+        guest_runner_path,
+        &self_exe,
+        &run_dir,
+        workdir_abs,
+        watchdog,
+    )?;
+    // Hash what the guest actually runs: an explicit path is usually a host file seen through
+    // `/run/muvm-host`, or the same path on the host; it is left out when neither is readable.
+    let guest_runner_sha256 = match guest_runner_mechanism {
+        "explicit" => {
+            let guest_path = Path::new(&guest_runner_path);
+            let host_path = guest_path
+                .strip_prefix("/run/muvm-host")
+                .map(|rel| Path::new("/").join(rel))
+                .unwrap_or_else(|_| guest_path.to_path_buf());
+            hash_file(&host_path).ok()
+        }
+        _ => Some(hash_file(&self_exe)?),
+    };
+    let edge_bin_sha256 = hash_file(&edge_bin)?;
 
-    let mut args: Vec<String> = vec![muvm_path.display().to_string(), "--emu=fex".into()];
+    let mut args: Vec<String> = muvm.argv();
+    args.push("--emu=fex".into());
     if let Some(mem) = mem {
         args.push(format!("--mem={mem}"));
     }
@@ -770,15 +2789,14 @@ fn run_edge(
 
     if !preserve_dbus_xdg_env {
         // Avoid inheriting host DBus session env into a VM that doesn't have that bus.
-        args.extend([
-            "-e".into(),
-            "DBUS_SESSION_BUS_ADDRESS=".into(),
-            "-e".into(),
-            "XDG_RUNTIME_DIR=".into(),
-        ]);
+        for key in ["DBUS_SESSION_BUS_ADDRESS", "XDG_RUNTIME_DIR"] {
+            if !muvm.env_passthrough.iter().any(|k| k == key) {
+                args.extend(["-e".into(), format!("{key}=")]);
+            }
+        }
     }
 
-    args.push(guest_runner_path.display().to_string());
+    args.push(guest_runner_path.clone());
     args.push("--mode".into());
     args.push("guest-runner".into());
     args.push("--edge-bin".into());
@@ -789,6 +2807,10 @@ fn run_edge(
     args.push(url.to_string());
     args.push("--edge-watchdog-seconds".into());
     args.push(edge_watchdog.as_secs().to_string());
+    args.push(format!(
+        "--poll-interval-ms={}",
+        watchdog.guest_poll_interval.as_millis()
+    ));
     args.push("--guest-headless-impl".into());
     args.push(match headless_impl {
         HeadlessImpl::New => "new".to_string(),
@@ -806,14 +2828,45 @@ fn run_edge(
         args.push(format!("--guest-sysctl={kv}"));
     }
 
+    if let Some(n) = guest_systemd_tasks_max {
+        args.push(format!("--guest-systemd-tasks-max={n}"));
+    }
+
+    if let Some(n) = guest_nofile {
+        args.push(format!("--guest-nofile={n}"));
+    }
+
+    if !default_edge_flags {
+        args.push("--no-default-edge-flags".into());
+    }
+
     for a in edge_args {
         args.push(format!("--edge-arg={a}"));
     }
 
+    if let Some(file_args) = &edge_args_from_file {
+        let path = run_dir.join("edge-args.txt");
+        let mut text = String::new();
+        for a in file_args {
+            text.push_str(a);
+            text.push('\n');
+        }
+        fs::write(&path, text).with_context(|| format!("write {}", path.display()))?;
+        args.push(format!("--edge-arg-file={}", path.display()));
+    }
+
     for kv in edge_env {
         args.push(format!("--edge-env={kv}"));
     }
 
+    if let Some(proxy) = proxy {
+        args.push(format!("--proxy={proxy}"));
+    }
+
+    if let Some(dir) = guest_cwd {
+        args.push(format!("--guest-cwd={}", dir.display()));
+    }
+
     if strace {
         args.push("--strace".into());
         args.push("--strace-mode".into());
@@ -821,25 +2874,110 @@ fn run_edge(
             StraceMode::Minimal => "minimal".to_string(),
             StraceMode::Hang => "hang".to_string(),
         });
+        if let Some(after) = strace_attach_after {
+            args.push(format!("--strace-attach-after-seconds={}", after.as_secs()));
+        }
+    }
+
+    if mem_report {
+        args.push("--mem-report".into());
+    }
+
+    if dump_environ {
+        args.push("--dump-environ".into());
+    }
+
+    if live_classify {
+        args.push("--live-classify".into());
+    }
+
+    for sig in stop_on_signatures {
+        args.push(format!("--stop-on-signature={sig}"));
+    }
+
+    args.push(format!(
+        "--snapshot-max-tasks={}",
+        snapshot_limits.max_tasks
+    ));
+    args.push(format!("--snapshot-max-fds={}", snapshot_limits.max_fds));
+    args.push(format!("--wakeup-depth={}", snapshot_limits.wakeup_depth));
+    args.push(format!(
+        "--owner-scan-max-pids={}",
+        snapshot_limits.owner_scan_max_pids
+    ));
+    args.push(format!(
+        "--owner-scan-max-fds={}",
+        snapshot_limits.owner_scan_max_fds
+    ));
+    if let Some(max_bytes) = snapshot_limits.capture_maps_max_bytes {
+        args.push("--capture-maps".into());
+        args.push(format!("--maps-max-bytes={max_bytes}"));
     }
 
     let args = wrap_muvm_args_if_requested(args, systemd_run_path, systemd_tasks_max)?;
+    // The exact invocation, so a run can be repeated by hand outside this tool.
+    let mut argv_text = args.join("\n");
+    argv_text.push('\n');
+    fs::write(&muvm_argv_path, argv_text).context("write muvm argv")?;
 
     let start = Instant::now();
-    let rc = run_command_with_pty_to_file(&args, &muvm_output_path, timeout).context("run muvm")?;
+    let observed = run_command_with_pty_to_file_observed(
+        &args,
+        &muvm_output_path,
+        timeout,
+        watchdog,
+        None,
+        &|_| {},
+    )
+    .context("run muvm")?;
+    let rc = observed.exit_code;
 
     if !stdout_path.is_file() || !stderr_path.is_file() {
-        let mut f = fs::File::create(&summary_path).context("write missing-artifact summary")?;
-        writeln!(f, "exit_code: {rc}")?;
-        writeln!(f, "elapsed_seconds: {}", start.elapsed().as_secs())?;
-        writeln!(f, "note: expected artifacts missing")?;
-        writeln!(f, "run_dir: {}", run_dir.display())?;
-        writeln!(f, "muvm_output: {}", muvm_output_path.display())?;
+        let (exit_code, exit_reason) = if observed.timed_out {
+            (EXIT_TIMEOUT, "timeout")
+        } else {
+            (EXIT_MUVM_FAILED, "muvm_failed")
+        };
+        let outcome = if observed.timed_out {
+            "timeout"
+        } else {
+            "unknown"
+        };
+        let mut summary = EdgeSummary::default();
+        summary.push("outcome", outcome);
+        summary.push("exit_code", rc);
+        summary.push("exit_reason", exit_reason);
+        summary.push(
+            "exit_reason_detail",
+            if observed.timed_out {
+                "muvm-timeout".to_string()
+            } else {
+                format!("muvm-exited-{rc}")
+            },
+        );
+        summary.push("timeout_kill", observed.timeout_kill);
+        summary.push("run_mode", run_mode);
+        summary.push("runner_exit_code", exit_code);
+        summary.push("elapsed_seconds", start.elapsed().as_secs());
+        summary.push("note", "expected artifacts missing");
+        summary.push("run_dir", run_dir.display().to_string());
+        summary.push("muvm_output", muvm_output_path.display().to_string());
+        summary.push("muvm_argv", muvm_argv_path.display().to_string());
+        redact_run_dir(&run_dir, &mut summary, redact)?;
+        if let Some(n) = tail_muvm_output {
+            eprint_muvm_tail(&muvm_output_path, n);
+        }
+        if let Some(min_bytes) = compress_artifacts {
+            compress_run_artifacts(&run_dir, &mut summary, min_bytes)?;
+        }
+        summary.write(&run_dir, summary_format, redact)?;
+        write_artifacts_readme(&run_dir)?;
         return Ok(EdgeRunResult {
             run_dir,
             stdout_bytes: 0,
             stderr_pthread_create_lines: 0,
             pthread_stack_mprotect_enomem_events: 0,
+            exit_code,
         });
     }
 
@@ -847,23 +2985,37 @@ fn run_edge(
     filter_stderr(&stderr_path, &stderr_filtered_path).ok();
 
     let stdout_bytes = fs::metadata(&stdout_path).map(|m| m.len()).unwrap_or(0);
+    let stdout_lines = count_lines(&stdout_path).unwrap_or(0);
+    // `--dump-dom` / `--print-to-pdf=-` output need not end in a newline; an empty file is
+    // reported as such rather than as "no trailing newline".
+    let stdout_trailing_newline = match fs::read(&stdout_path) {
+        Ok(bytes) if bytes.is_empty() => "(empty)",
+        Ok(bytes) if bytes.ends_with(b"\n") => "yes",
+        Ok(_) => "no",
+        Err(_) => "(unreadable)",
+    };
     let stderr_lines = count_lines(&stderr_path).unwrap_or(0);
     let ptrace_lines = count_substring_lines(&stderr_path, "ptrace:").unwrap_or(0);
     let pthread_lines = count_substring_lines(&stderr_path, "pthread_create").unwrap_or(0);
-    let dbus_lines =
-        count_substring_lines(&stderr_path, "Failed to connect to the bus").unwrap_or(0);
     let ssl_lines =
         count_substring_lines(&stderr_path, "ssl_client_socket_impl.cc:930").unwrap_or(0);
     let handshake_lines = count_substring_lines(&stderr_path, "handshake failed").unwrap_or(0);
 
     let pthread_stack_report_path = run_dir.join("pthread.stack-mprotect-enomem.txt");
-    let pthread_analysis =
-        analyze_pthread_stack_mprotect_enomem(&run_dir, &stderr_path, &pthread_stack_report_path)
-            .unwrap_or_else(|_e| PthreadStackAnalysis {
-                pthread_ids: Vec::new(),
-                pthread_pids: Vec::new(),
-                events_total: 0,
-            });
+    let pthread_analysis = analyze_pthread_stack_mprotect_enomem(
+        &run_dir,
+        &stderr_path,
+        Some(&pthread_stack_report_path),
+    )
+    .unwrap_or_else(|_e| PthreadStackAnalysis {
+        pthread_ids: Vec::new(),
+        pthread_pids: Vec::new(),
+        events_total: 0,
+    });
+
+    let dbus_report_path = run_dir.join("dbus.failures.txt");
+    let dbus_analysis = analyze_dbus_failures(&stderr_path, &dbus_report_path).unwrap_or_default();
+    let dbus_lines = dbus_analysis.total;
 
     let preflight_kvs = extract_preflight_kvs(
         &preflight_path,
@@ -883,110 +3035,899 @@ fn run_edge(
         ],
     );
 
-    let mut f = fs::File::create(&summary_path).context("write headless summary")?;
-    writeln!(f, "exit_code: {rc}")?;
-    writeln!(f, "elapsed_seconds: {}", start.elapsed().as_secs())?;
-    writeln!(
-        f,
-        "systemd_tasks_max: {}",
-        systemd_tasks_max
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "(none)".to_string())
-    )?;
     let edge_exit = fs::read_to_string(run_dir.join("edge-exit.txt"))
         .unwrap_or_else(|e| format!("(unavailable: {e})"));
-    writeln!(f, "edge_exit: {}", edge_exit.trim())?;
-    writeln!(
-        f,
-        "headless_impl: {}",
+    let (exit_code, exit_reason) = if observed.timed_out {
+        (EXIT_TIMEOUT, "timeout")
+    } else if stuck_path.is_file() && !stop_signature_path.is_file() {
+        // The guest watchdog only snapshots (and kills) Edge when it outlived its window.
+        (EXIT_TIMEOUT, "edge_watchdog")
+    } else {
+        match parse_edge_exit_code(&edge_exit) {
+            Some(0) => (0, "ok"),
+            Some(code) => (code, "guest_exit"),
+            None if rc != 0 => (rc, "guest_exit"),
+            None => (0, "ok"),
+        }
+    };
+
+    let muvm_output = fs::read_to_string(&muvm_output_path).unwrap_or_default();
+    let outcome = classify_outcome(&OutcomeCounters {
+        exit_reason,
+        exit_code,
+        stdout_bytes,
+        pthread_create_lines: pthread_lines,
+        pthread_stack_enomem_events: pthread_analysis.events_total,
+        dbus_lines,
+        ssl_lines: ssl_lines + handshake_lines,
+        oom_reported: muvm_output.contains("Out of memory"),
+        stopped_on_signature: stop_signature_path.is_file(),
+    });
+
+    let mut summary = EdgeSummary {
+        gnu_property_strip: edge_strip,
+        ..EdgeSummary::default()
+    };
+    summary.push("outcome", outcome);
+    summary.push("exit_code", rc);
+    summary.push("exit_reason", exit_reason);
+    summary.push(
+        "exit_reason_detail",
+        exit_reason_detail(&EndEvidence {
+            edge_exit: &edge_exit,
+            muvm_exit_code: rc,
+            muvm_timed_out: observed.timed_out,
+            watchdog_fired: stuck_path.is_file(),
+            stopped_on_signature: stop_signature_path.is_file(),
+        }),
+    );
+    summary.push("timeout_kill", observed.timeout_kill);
+    summary.push("runner_exit_code", exit_code);
+    summary.push("elapsed_seconds", start.elapsed().as_secs());
+    summary.push(
+        "systemd_tasks_max",
+        systemd_tasks_max.map_or_else(|| "(none)".into(), SummaryValue::Count),
+    );
+    summary.push("edge_exit", edge_exit.trim());
+    summary.push(
+        "headless_impl",
         match headless_impl {
             HeadlessImpl::New => "new",
             HeadlessImpl::Old => "old",
+        },
+    );
+    summary.push("run_mode", run_mode);
+    summary.push("profile_location", profile_location.as_arg());
+    for (k, v) in extract_preflight_kvs(
+        &preflight_path,
+        &[
+            "guest_nproc",
+            "guest_cpuinfo_processors",
+            "guest_cpuset",
+            "profile_writable",
+            "profile_write_error",
+            "run_dir_fs",
+            "profile_dir_fs",
+            "fex_binfmt_registered",
+            "fex_binfmt_enabled",
+            "fex_binfmt_interpreter",
+            "xdg_runtime_dir_exists",
+            "dbus_socket_exists",
+            "dbus_env_dangling",
+            "fexserver_socket_present",
+            "fexserver_running",
+            "fexserver_socket_stale",
+            "proxy",
+            "guest_cwd",
+            "edge_default_flags",
+            "guest_nofile_before",
+            "guest_nofile_after",
+            "guest_nofile_error",
+            "guest_kernel_version",
+            "guest_kernel",
+            "edge_min_kernel",
+            "kernel_too_old",
+        ],
+    ) {
+        if k == "kernel_too_old" && v == "yes" {
+            eprintln!(
+                "WARNING: the guest kernel is older than Edge's .note.ABI-tag minimum ({})",
+                preflight_path.display()
+            );
         }
-    )?;
-    writeln!(f, "stdout_bytes: {stdout_bytes}")?;
-    writeln!(f, "stderr_lines: {stderr_lines}")?;
-    writeln!(f, "stderr_ptrace_lines: {ptrace_lines}")?;
-    writeln!(f, "stderr_pthread_create_lines: {pthread_lines}")?;
-    writeln!(
-        f,
-        "pthread_stack_mprotect_enomem_events: {}",
-        pthread_analysis.events_total
-    )?;
-    writeln!(
-        f,
-        "pthread_pids_from_stderr: {}",
-        if pthread_analysis.pthread_pids.is_empty() {
-            "(none)".to_string()
-        } else {
+        if k == "profile_writable" && v == "no" {
+            eprintln!(
+                "WARNING: the guest could not write the Edge profile dir ({}); this run does not \
+                 reflect a working --profile-location {}",
+                preflight_path.display(),
+                profile_location.as_arg()
+            );
+        }
+        summary.push(k, SummaryValue::parse(v));
+    }
+    summary.push("guest_runner_mechanism", guest_runner_mechanism);
+    summary.push("guest_runner_path", guest_runner_path);
+    if let Some(sha256) = guest_runner_sha256 {
+        summary.push("guest_runner_sha256", sha256);
+    }
+    summary.push("muvm_sha256", muvm_sha256);
+    summary.push("edge_bin_sha256", edge_bin_sha256);
+    summary.push("host_env_passthrough", muvm.passthrough_summary());
+    if !muvm.extra_args.is_empty() {
+        summary.push("muvm_extra_args", muvm.extra_args.join(" "));
+    }
+    summary.push("stdout_bytes", stdout_bytes);
+    summary.push("stdout_lines", stdout_lines);
+    summary.push("stdout_has_trailing_newline", stdout_trailing_newline);
+    summary.push("stderr_lines", stderr_lines);
+    summary.push("stderr_ptrace_lines", ptrace_lines);
+    summary.push("stderr_pthread_create_lines", pthread_lines);
+    summary.push(
+        "pthread_stack_mprotect_enomem_events",
+        pthread_analysis.events_total,
+    );
+    summary.push(
+        "pthread_pids_from_stderr",
+        SummaryValue::List(
             pthread_analysis
                 .pthread_pids
                 .iter()
                 .map(|v| v.to_string())
-                .collect::<Vec<_>>()
-                .join(" ")
-        }
-    )?;
-    writeln!(
-        f,
-        "pthread_ids_from_stderr: {}",
-        if pthread_analysis.pthread_ids.is_empty() {
-            "(none)".to_string()
-        } else {
+                .collect(),
+        ),
+    );
+    summary.push(
+        "pthread_ids_from_stderr",
+        SummaryValue::List(
             pthread_analysis
                 .pthread_ids
                 .iter()
                 .map(|(pid, tid)| format!("{pid}:{tid}"))
-                .collect::<Vec<_>>()
-                .join(" ")
+                .collect(),
+        ),
+    );
+    summary.push("stderr_dbus_lines", dbus_lines);
+    summary.push("dbus_failure_pattern", dbus_analysis.pattern());
+    summary.push(
+        "dbus_first_offset_seconds",
+        dbus_analysis
+            .first_offset_seconds
+            .map(|s| format!("{s:.3}"))
+            .unwrap_or_else(|| "(unavailable)".to_string()),
+    );
+    summary.push(
+        "dbus_first_line",
+        dbus_analysis
+            .first_line
+            .unwrap_or_else(|| "(none)".to_string()),
+    );
+    summary.push("stderr_ssl_client_socket_lines", ssl_lines);
+    summary.push("stderr_handshake_failed_lines", handshake_lines);
+    if mem_report {
+        for (k, v) in extract_preflight_kvs(
+            &meminfo_delta_path,
+            &[
+                "meminfo_memavailable_delta_kb",
+                "meminfo_memfree_delta_kb",
+                "committed_as_delta_kb",
+            ],
+        ) {
+            summary.push(k, SummaryValue::parse(v));
         }
-    )?;
-    writeln!(f, "stderr_dbus_lines: {dbus_lines}")?;
-    writeln!(f, "stderr_ssl_client_socket_lines: {ssl_lines}")?;
-    writeln!(f, "stderr_handshake_failed_lines: {handshake_lines}")?;
-    if !preflight_kvs.is_empty() {
-        writeln!(f)?;
-        writeln!(f, "preflight_kvs:")?;
-        for (k, v) in preflight_kvs {
-            writeln!(f, "  {k}: {v}")?;
-        }
-    }
-    writeln!(f)?;
-    writeln!(f, "artifacts:")?;
-    writeln!(f, "  preflight: {}", preflight_path.display())?;
-    writeln!(f, "  ps: {}", ps_path.display())?;
-    writeln!(f, "  threads: {}", threads_path.display())?;
-    writeln!(f, "  stdout: {}", stdout_path.display())?;
-    writeln!(f, "  stderr: {}", stderr_path.display())?;
-    writeln!(f, "  stderr_filtered: {}", stderr_filtered_path.display())?;
-    writeln!(f, "  muvm: {}", muvm_output_path.display())?;
-    writeln!(
-        f,
-        "  pthread_stack_report: {}",
-        pthread_stack_report_path.display()
-    )?;
-
+    }
+    if live_classify || !stop_on_signatures.is_empty() {
+        let stopped = extract_preflight_kvs(&stop_signature_path, &["stopped_on_signature"]);
+        summary.push(
+            "stopped_on_signature",
+            stopped
+                .first()
+                .map(|(_, v)| v.clone())
+                .unwrap_or_else(|| "(none)".to_string()),
+        );
+    }
+    if dump_environ {
+        let missing = extract_preflight_kvs(&edge_environ_path, &["missing_edge_env_keys"]);
+        summary.push(
+            "missing_edge_env_keys",
+            missing
+                .first()
+                .map(|(_, v)| v.clone())
+                .unwrap_or_else(|| "(no edge-environ.txt)".to_string()),
+        );
+    }
+    // Totals for the first (Edge) process in maps.txt; absent unless --capture-maps hit a stuck run.
+    for (k, v) in extract_preflight_kvs(
+        &maps_path,
+        &[
+            "maps_regions",
+            "maps_total_mapped_bytes",
+            "maps_anon_regions",
+            "maps_file_regions",
+        ],
+    ) {
+        summary.push(k, SummaryValue::parse(v));
+    }
+    let thread_peak = extract_preflight_kvs(&threads_timeseries_path, &["max_threads_observed"]);
+    summary.push(
+        "max_threads_observed",
+        thread_peak
+            .into_iter()
+            .next()
+            .map_or_else(|| "(unavailable)".into(), |(_, v)| SummaryValue::parse(v)),
+    );
+    let pressure_peaks = extract_preflight_kvs(&pressure_timeseries_path, PSI_PEAK_KEYS);
+    for key in PSI_PEAK_KEYS {
+        let value = pressure_peaks
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or("(unavailable)");
+        summary.push(*key, value);
+    }
+    summary.push(
+        "tty_stop_suspected",
+        match fs::read_to_string(&stuck_path) {
+            Ok(text) => match detect_tty_stop(&text) {
+                Some((label, sig)) => format!("yes signal={sig} snapshot={label}"),
+                None => "no".to_string(),
+            },
+            Err(_) => "(no stuck snapshot)".to_string(),
+        },
+    );
+    let crashpad_handlers = extract_preflight_kvs(&ps_path, &["crashpad_handlers_spawned"])
+        .first()
+        .and_then(|(_, v)| v.parse::<u64>().ok());
+    match crashpad_handlers {
+        Some(n) => {
+            summary.push("crashpad_handlers_spawned", n);
+            let disabled = default_edge_flags
+                || edge_args
+                    .iter()
+                    .chain(edge_args_from_file.iter().flatten())
+                    .any(|a| CRASHPAD_DISABLE_FLAGS.contains(&a.as_str()));
+            if n > 0 && disabled {
+                summary.push("crashpad_despite_disable_flags", "yes");
+                eprintln!(
+                    "WARNING: {n} crashpad handler(s) spawned despite the crash reporting \
+                     disable flags (see {})",
+                    ps_path.display()
+                );
+            }
+        }
+        None => summary.push("crashpad_handlers_spawned", "(unavailable)"),
+    }
+    summary.preflight_kvs = preflight_kvs
+        .into_iter()
+        .map(|(k, v)| (k, SummaryValue::parse(v)))
+        .collect();
+    summary.artifact("preflight", &preflight_path);
+    summary.artifact("ps", &ps_path);
+    summary.artifact("threads", &threads_path);
+    summary.artifact("threads_timeseries", &threads_timeseries_path);
+    summary.artifact("pressure_timeseries", &pressure_timeseries_path);
+    summary.artifact("stdout", &stdout_path);
+    summary.artifact("stderr", &stderr_path);
+    summary.artifact("stderr_filtered", &stderr_filtered_path);
+    summary.artifact("muvm", &muvm_output_path);
+    summary.artifact("muvm_argv", &muvm_argv_path);
+    if mem_report {
+        summary.artifact("meminfo_delta", &meminfo_delta_path);
+    }
+    if live_classify || !stop_on_signatures.is_empty() {
+        summary.artifact("live_counts", &live_counts_path);
+    }
+    if dump_environ {
+        summary.artifact("edge_environ", &edge_environ_path);
+    }
+    if maps_path.is_file() {
+        summary.artifact("maps", &maps_path);
+    }
+    summary.artifact("pthread_stack_report", &pthread_stack_report_path);
+    summary.artifact("dbus_failures", &dbus_report_path);
+
+    if let Some(sink) = metrics {
+        let headless = match headless_impl {
+            HeadlessImpl::New => "new",
+            HeadlessImpl::Old => "old",
+        };
+        let mut labels = vec![
+            ("run_mode", sink.run_mode.to_string()),
+            ("headless_impl", headless.to_string()),
+        ];
+        if sink.mode == MetricsMode::Append {
+            let run_name = run_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            labels.push(("run", run_name));
+        }
+        let samples: [(&str, &str, u64); 7] = [
+            ("stdout_bytes", "Bytes Edge wrote to stdout.", stdout_bytes),
+            (
+                "elapsed_seconds",
+                "Wall-clock seconds for the muvm invocation.",
+                start.elapsed().as_secs(),
+            ),
+            (
+                "stderr_pthread_create_lines",
+                "Edge stderr lines mentioning pthread_create.",
+                pthread_lines,
+            ),
+            (
+                "pthread_stack_mprotect_enomem_events",
+                "Thread-stack mprotect ENOMEM events found by the T1 classifier.",
+                pthread_analysis.events_total,
+            ),
+            (
+                "stderr_dbus_lines",
+                "Edge stderr lines reporting DBus connection failures.",
+                dbus_lines,
+            ),
+            (
+                "stderr_ssl_client_socket_lines",
+                "Edge stderr lines from ssl_client_socket_impl.cc:930.",
+                ssl_lines,
+            ),
+            (
+                "stderr_handshake_failed_lines",
+                "Edge stderr lines mentioning a failed handshake.",
+                handshake_lines,
+            ),
+        ];
+        write_prometheus_metrics(sink, &labels, &samples)
+            .with_context(|| format!("write metrics {}", sink.path.display()))?;
+        summary.artifact("metrics", &sink.path);
+    }
+
+    redact_run_dir(&run_dir, &mut summary, redact)?;
+
+    if let Some(n) = tail_muvm_output.filter(|_| exit_code != 0 || outcome != "ok") {
+        eprint_muvm_tail(&muvm_output_path, n);
+    }
+    if let Some(min_bytes) = compress_artifacts {
+        compress_run_artifacts(&run_dir, &mut summary, min_bytes)?;
+    }
+    summary.write(&run_dir, summary_format, redact)?;
+    write_artifacts_readme(&run_dir)?;
     eprintln!("Run dir: {}", run_dir.display());
     Ok(EdgeRunResult {
         run_dir,
         stdout_bytes,
         stderr_pthread_create_lines: pthread_lines,
         pthread_stack_mprotect_enomem_events: pthread_analysis.events_total,
+        exit_code,
     })
 }
 
-fn extract_preflight_kvs(preflight_path: &Path, keys: &[&str]) -> Vec<(String, String)> {
-    let Ok(s) = fs::read_to_string(preflight_path) else {
-        return Vec::new();
-    };
-    let want: HashSet<&str> = keys.iter().copied().collect();
-    let mut seen = HashSet::new();
-    let mut out = Vec::new();
-    for line in s.lines() {
-        let Some((k, v)) = line.split_once(':') else {
-            continue;
-        };
-        let k = k.trim();
+/// Whether `--compress-artifacts` applies to a run dir file name.
+fn is_compressible_artifact(name: &str) -> bool {
+    name.starts_with("strace.")
+        || name.starts_with("host.strace.")
+        || name == "muvm.txt"
+        || name == "run.log"
+}
+
+/// Gzips the large logs of `run_dir` in place and rewrites their paths in `summary`.
+fn compress_run_artifacts(run_dir: &Path, summary: &mut EdgeSummary, min_bytes: u64) -> Result<()> {
+    use flate2::write::GzEncoder;
+
+    let mut compressed: Vec<(PathBuf, u64, u64)> = Vec::new();
+    let mut entries: Vec<PathBuf> = fs::read_dir(run_dir)
+        .with_context(|| format!("read {}", run_dir.display()))?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.ends_with(".gz") || !is_compressible_artifact(&name) {
+            continue;
+        }
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if !meta.is_file() || meta.len() < min_bytes {
+            continue;
+        }
+        let gz_path = gz_sibling(&path);
+        let mut src = fs::File::open(&path).with_context(|| format!("open {}", path.display()))?;
+        let dst =
+            fs::File::create(&gz_path).with_context(|| format!("create {}", gz_path.display()))?;
+        let mut enc = GzEncoder::new(dst, flate2::Compression::default());
+        io::copy(&mut src, &mut enc).with_context(|| format!("gzip {}", path.display()))?;
+        enc.finish()
+            .with_context(|| format!("finish {}", gz_path.display()))?;
+        fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+        let gz_len = fs::metadata(&gz_path).map(|m| m.len()).unwrap_or(0);
+        compressed.push((path, meta.len(), gz_len));
+    }
+    if compressed.is_empty() {
+        return Ok(());
+    }
+
+    for (path, bytes_before, bytes_after) in compressed {
+        let plain = path.display().to_string();
+        let gz = format!("{plain}.gz");
+        for value in summary
+            .fields
+            .iter_mut()
+            .chain(summary.artifacts.iter_mut())
+            .map(|(_, v)| v)
+        {
+            if let SummaryValue::Text(text) = value {
+                if let Some(prefix) = text.strip_suffix(plain.as_str()) {
+                    *text = format!("{prefix}{gz}");
+                }
+            }
+        }
+        summary.compressed_artifacts.push(CompressedArtifact {
+            name: gz_sibling(&path)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            bytes_before,
+            bytes_after,
+        });
+    }
+    Ok(())
+}
+
+fn gz_sibling(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".gz");
+    path.with_file_name(name)
+}
+
+/// Reads a run artifact, falling back to its `--compress-artifacts` `.gz` form. A path that
+/// itself ends in `.gz` is decompressed.
+fn read_artifact(path: &Path) -> io::Result<String> {
+    use std::io::Read;
+
+    let read_gz = |gz: &Path| {
+        let mut bytes = Vec::new();
+        flate2::read::GzDecoder::new(fs::File::open(gz)?).read_to_end(&mut bytes)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    };
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        return read_gz(path);
+    }
+    match fs::read(path) {
+        Ok(bytes) => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => read_gz(&gz_sibling(path)),
+        Err(e) => Err(e),
+    }
+}
+
+/// What each file an Edge run dir can contain holds, for `ARTIFACTS.md`. A trailing `*` matches
+/// a prefix and a trailing `/` a directory; the first matching row wins.
+const RUN_ARTIFACTS: &[(&str, &str)] = &[
+    (
+        "summary.txt",
+        "Host-side `key: value` digest of the run: outcome, exit codes, stderr signature \
+         counts, selected preflight values and paths to the other artifacts.",
+    ),
+    (
+        "summary.json",
+        "The same digest as `summary.txt` as one JSON object, with `preflight_kvs` and \
+         `artifacts` nested.",
+    ),
+    (
+        "muvm.txt",
+        "Everything muvm printed on the host PTY, including guest-runner output.",
+    ),
+    (
+        "muvm-argv.txt",
+        "The full host command that started the run (`systemd-run`/`env -i` wrappers, muvm \
+         and its flags, the guest-runner argv), one argument per line.",
+    ),
+    (
+        "edge-args.txt",
+        "The args parsed from `--edge-arg-file`, one per line; the guest-runner reads this \
+         copy since the original path may not exist in the guest.",
+    ),
+    (
+        "preflight.txt",
+        "Guest state recorded by the guest-runner before Edge starts: limits, cgroup v2 \
+         counters, sysctls, mounts, kernel, FEX binfmt and FEXServer state.",
+    ),
+    (
+        "stdout.txt",
+        "Edge stdout (the `--dump-dom` output when the page loaded).",
+    ),
+    ("stderr.txt", "Edge stderr, unfiltered."),
+    (
+        "stderr.filtered.txt",
+        "`stderr.txt` without the crashpad/ptrace noise, for quick reading.",
+    ),
+    (
+        "pid.txt",
+        "PID the guest-runner spawned (`wrapper_pid`) and the Edge PID it tracked; they \
+         differ when Edge is wrapped in strace or a guest systemd unit.",
+    ),
+    (
+        "edge-exit.txt",
+        "Edge's exit status as seen by the guest-runner (`unknown` if it had to be killed).",
+    ),
+    (
+        "ps.txt",
+        "Guest process tree under the tracked Edge PID, taken when the wait ended, with any \
+         crashpad handlers in it (`crashpad_handlers_spawned`).",
+    ),
+    (
+        "threads.txt",
+        "Per-thread listing of the tracked Edge PID, taken when the wait ended.",
+    ),
+    (
+        "threads-timeseries.txt",
+        "Thread count of the Edge tree sampled while it ran, with `max_threads_observed`.",
+    ),
+    (
+        "pressure-timeseries.txt",
+        "Guest `/proc/pressure/{memory,cpu,io}` avg10 sampled every second while Edge ran, \
+         with the peaks (only when the guest kernel has PSI).",
+    ),
+    (
+        "stuck.txt",
+        "Snapshot of a run that hit the Edge watchdog: per-task state, wchan, syscall, \
+         kernel stack, fds and futex wakeup candidates, taken before the kill.",
+    ),
+    (
+        "maps.txt",
+        "`/proc/<pid>/maps` of the stuck tree (`--capture-maps`).",
+    ),
+    (
+        "meminfo-delta.txt",
+        "Guest `/proc/meminfo` before and after Edge, with deltas (`--mem-report`).",
+    ),
+    (
+        "edge-environ.txt",
+        "Edge's environment right after spawn, with any `--edge-env` key that did not \
+         arrive (`--dump-environ`).",
+    ),
+    (
+        "live-counts.txt",
+        "Stderr signature counts kept while Edge ran (`--live-classify`).",
+    ),
+    (
+        "stop-signature.txt",
+        "Signature that stopped the run early and when (`--stop-on-signature`).",
+    ),
+    (
+        "guest-sysctl.txt",
+        "Guest sysctls before and after applying `--guest-sysctl`.",
+    ),
+    (
+        "strace.enabled.txt",
+        "Whether `--strace` found strace in the guest, and whether it wrapped or attached.",
+    ),
+    (
+        "strace.attach.log",
+        "strace's own messages when `--strace-attach-after-seconds` attached it to the running Edge.",
+    ),
+    (
+        "strace.*",
+        "Guest strace output, one file per traced thread/process id (`--strace`).",
+    ),
+    ("host.strace.*", "Host-side strace output, one file per id."),
+    (
+        "pthread.stack-mprotect-enomem.txt",
+        "Host analysis matching stderr `pthread_create` failures to the strace \
+         mmap/mprotect ENOMEM events behind them.",
+    ),
+    (
+        "dbus.failures.txt",
+        "Host analysis of stderr `Failed to connect to the bus` lines: count, pids, one-shot \
+         vs repeating, and the first/last failure with its offset from Edge's first log line.",
+    ),
+    (
+        "guest-runner-detect.txt",
+        "Output of the check for the guest-runner under `/run/muvm-host` \
+         (`--guest-runner-path auto`).",
+    ),
+    (
+        "edge-muvm-guest-runner",
+        "Copy of this tool that muvm ran inside the guest.",
+    ),
+    (
+        "profile/",
+        "Edge user-data dir (`--profile-location shared`).",
+    ),
+];
+
+/// Writes `ARTIFACTS.md` into `run_dir`, describing the files this run actually produced.
+fn write_artifacts_readme(run_dir: &Path) -> Result<()> {
+    let mut names: Vec<String> = fs::read_dir(run_dir)
+        .with_context(|| format!("read {}", run_dir.display()))?
+        .flatten()
+        .map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            if e.path().is_dir() {
+                format!("{name}/")
+            } else {
+                name
+            }
+        })
+        .filter(|name| name != "ARTIFACTS.md")
+        .collect();
+    names.sort();
+    fs::write(
+        run_dir.join("ARTIFACTS.md"),
+        render_artifacts_readme(&names),
+    )
+    .context("write ARTIFACTS.md")
+}
+
+fn render_artifacts_readme(names: &[String]) -> String {
+    let row_for = |name: &str| {
+        let plain = name.strip_suffix(".gz").unwrap_or(name);
+        RUN_ARTIFACTS
+            .iter()
+            .position(|(pattern, _)| match pattern.strip_suffix('*') {
+                Some(prefix) => plain.starts_with(prefix),
+                None => plain == *pattern,
+            })
+    };
+    let mut rows: Vec<(usize, Vec<&str>)> = Vec::new();
+    let mut other: Vec<&str> = Vec::new();
+    for name in names {
+        match row_for(name) {
+            Some(i) => match rows.iter_mut().find(|(r, _)| *r == i) {
+                Some((_, files)) => files.push(name),
+                None => rows.push((i, vec![name])),
+            },
+            None => other.push(name),
+        }
+    }
+    rows.sort_by_key(|(i, _)| *i);
+
+    let mut out = String::from(
+        "# Run artifacts\n\nGenerated by edge-muvm-experiment for the files in this directory. \
+         `.gz` files were compressed by `--compress-artifacts`.\n\n",
+    );
+    for (i, files) in &rows {
+        let (pattern, description) = RUN_ARTIFACTS[*i];
+        let label = if pattern.ends_with('*') && files.len() > 1 {
+            format!("`{pattern}` ({} files)", files.len())
+        } else {
+            files
+                .iter()
+                .map(|f| format!("`{f}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        out.push_str(&format!("- {label}: {description}\n"));
+    }
+    if !other.is_empty() {
+        out.push_str("\nNot described above:\n\n");
+        for name in other {
+            out.push_str(&format!("- `{name}`\n"));
+        }
+    }
+    out
+}
+
+fn eprint_muvm_tail(muvm_output_path: &Path, n: usize) {
+    let text = match fs::read(muvm_output_path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) => {
+            eprintln!("(cannot read {}: {e})", muvm_output_path.display());
+            return;
+        }
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    let tail = &lines[lines.len().saturating_sub(n)..];
+    eprintln!(
+        "--- last {} line(s) of {} ---",
+        tail.len(),
+        muvm_output_path.display()
+    );
+    for line in tail {
+        eprintln!("{line}");
+    }
+    eprintln!("--- end of muvm output ---");
+}
+
+/// Inputs to `classify_outcome`, all already computed for `summary.txt`.
+struct OutcomeCounters<'a> {
+    exit_reason: &'a str,
+    exit_code: i32,
+    stdout_bytes: u64,
+    pthread_create_lines: u64,
+    pthread_stack_enomem_events: u64,
+    dbus_lines: u64,
+    ssl_lines: u64,
+    /// The guest kernel's OOM killer reported in `muvm.txt`.
+    oom_reported: bool,
+    /// `--stop-on-signature` fired, so a SIGKILL exit was ours.
+    stopped_on_signature: bool,
+}
+
+/// One-word triage verdict, the first field of `summary.txt` and `summary.json`. First match
+/// wins:
+///
+/// 1. `timeout`: host `--timeout` or the guest Edge watchdog fired.
+/// 2. `oom`: the guest OOM killer fired, or Edge died of SIGKILL we did not send.
+/// 3. `fex-crash`: Edge died of SIGSEGV/SIGBUS/SIGILL/SIGTRAP/SIGABRT.
+/// 4. `pthread-enomem`: any `pthread_create` failure or stack mprotect ENOMEM.
+/// 5. `dbus-blocked`: no stdout and DBus connection failures in stderr.
+/// 6. `ssl-stall`: no stdout and SSL socket/handshake failures in stderr.
+/// 7. `ok`: stdout is non-empty and no DBus or SSL failures were logged.
+/// 8. `unknown`: anything else, including stdout alongside DBus or SSL failures.
+fn classify_outcome(c: &OutcomeCounters) -> &'static str {
+    const SIGKILL_EXIT: i32 = 128 + 9;
+    const CRASH_EXITS: &[i32] = &[128 + 4, 128 + 5, 128 + 6, 128 + 7, 128 + 11];
+    if c.exit_reason == "timeout" || c.exit_reason == "edge_watchdog" {
+        "timeout"
+    } else if c.oom_reported
+        || (c.exit_reason == "guest_exit" && c.exit_code == SIGKILL_EXIT && !c.stopped_on_signature)
+    {
+        "oom"
+    } else if c.exit_reason == "guest_exit" && CRASH_EXITS.contains(&c.exit_code) {
+        "fex-crash"
+    } else if c.pthread_create_lines > 0 || c.pthread_stack_enomem_events > 0 {
+        "pthread-enomem"
+    } else if c.stdout_bytes == 0 && c.dbus_lines > 0 {
+        "dbus-blocked"
+    } else if c.stdout_bytes == 0 && c.ssl_lines > 0 {
+        "ssl-stall"
+    } else if c.stdout_bytes > 0 && c.dbus_lines == 0 && c.ssl_lines == 0 {
+        "ok"
+    } else {
+        "unknown"
+    }
+}
+
+/// Exit code for a timed-out run (matches GNU `timeout`): the host `--timeout` or the guest
+/// Edge watchdog fired.
+const EXIT_TIMEOUT: i32 = 124;
+/// Exit code when muvm failed before the guest-runner produced Edge's artifacts.
+const EXIT_MUVM_FAILED: i32 = 125;
+
+/// Where a run's end shows up: `edge-exit.txt`, muvm's exit and the watchdog/stop artifacts.
+struct EndEvidence<'a> {
+    edge_exit: &'a str,
+    muvm_exit_code: i32,
+    muvm_timed_out: bool,
+    /// `stuck.txt` exists: the guest Edge watchdog snapshotted and killed Edge.
+    watchdog_fired: bool,
+    /// `stop-signature.txt` exists: `--stop-on-signature` killed Edge.
+    stopped_on_signature: bool,
+}
+
+/// How the run ended, in one value for `summary.txt`'s `exit_reason_detail:`, e.g.
+/// `edge-exited-0`, `edge-crashed-SIGSEGV`, `edge-killed-SIGKILL-by-watchdog`, `muvm-timeout`.
+/// Unlike the coarse `exit_reason:`, this keeps exit codes and signal names.
+fn exit_reason_detail(e: &EndEvidence) -> String {
+    const CRASH_SIGNALS: &[u32] = &[4, 5, 6, 7, 8, 11];
+    if e.muvm_timed_out {
+        return "muvm-timeout".to_string();
+    }
+    let status = e.edge_exit.trim().strip_prefix("edge_exit:").map(str::trim);
+    let signal = status
+        .and_then(|v| v.strip_prefix("signal:"))
+        .and_then(|v| {
+            v.trim()
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>()
+                .parse::<u32>()
+                .ok()
+        });
+    let code = status
+        .and_then(|v| v.strip_prefix("exit status:"))
+        .and_then(|v| v.trim().parse::<i32>().ok());
+    match (signal, code) {
+        (Some(sig), _) if e.stopped_on_signature => {
+            format!("edge-killed-{}-on-signature", signal_name(sig))
+        }
+        (Some(sig), _) if e.watchdog_fired => {
+            format!("edge-killed-{}-by-watchdog", signal_name(sig))
+        }
+        (Some(sig), _) if CRASH_SIGNALS.contains(&sig) => {
+            format!("edge-crashed-{}", signal_name(sig))
+        }
+        (Some(sig), _) => format!("edge-killed-{}", signal_name(sig)),
+        (None, Some(code)) => format!("edge-exited-{code}"),
+        (None, None) if e.watchdog_fired => "edge-killed-by-watchdog".to_string(),
+        (None, None) if e.muvm_exit_code != 0 => format!("muvm-exited-{}", e.muvm_exit_code),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+/// Exit code from `edge-exit.txt` (`edge_exit: exit status: N` / `edge_exit: signal: N (...)`);
+/// signals map to `128 + N`.
+fn parse_edge_exit_code(edge_exit: &str) -> Option<i32> {
+    let v = edge_exit.trim().strip_prefix("edge_exit:")?.trim();
+    if let Some(code) = v.strip_prefix("exit status:") {
+        return code.trim().parse().ok();
+    }
+    let sig = v.strip_prefix("signal:")?.trim();
+    let digits: String = sig.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse::<i32>().ok().map(|n| 128 + n)
+}
+
+const METRIC_PREFIX: &str = "edge_muvm_";
+
+/// Write `samples` as Prometheus gauges. In append mode, samples already in the file are kept
+/// (a sample with an identical label set is replaced) and each family is re-emitted contiguously,
+/// as the text format requires. The file is replaced atomically for scrapers.
+fn write_prometheus_metrics(
+    sink: &MetricsSink,
+    labels: &[(&str, String)],
+    samples: &[(&str, &str, u64)],
+) -> Result<()> {
+    let label_text = labels
+        .iter()
+        .map(|(k, v)| {
+            let v = v.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("{k}=\"{v}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    // family name -> sample lines (in order)
+    let mut families: Vec<(String, Vec<String>)> = Vec::new();
+    if sink.mode == MetricsMode::Append {
+        let existing = fs::read_to_string(&sink.path).unwrap_or_default();
+        for line in existing.lines() {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            let name_end = line.find(['{', ' ']).unwrap_or(line.len());
+            let name = &line[..name_end];
+            match families.iter_mut().find(|(n, _)| n == name) {
+                Some((_, lines)) => lines.push(line.to_string()),
+                None => families.push((name.to_string(), vec![line.to_string()])),
+            }
+        }
+    }
+
+    let mut help: HashMap<String, &str> = HashMap::new();
+    for (name, description, value) in samples {
+        let name = format!("{METRIC_PREFIX}{name}");
+        let series = format!("{name}{{{label_text}}}");
+        let line = format!("{series} {value}");
+        help.insert(name.clone(), description);
+        match families.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, lines)) => {
+                lines.retain(|l| !l.starts_with(&format!("{series} ")));
+                lines.push(line);
+            }
+            None => families.push((name, vec![line])),
+        }
+    }
+
+    let mut out = String::new();
+    for (name, lines) in &families {
+        if let Some(description) = help.get(name) {
+            out.push_str(&format!("# HELP {name} {description}\n"));
+        }
+        out.push_str(&format!("# TYPE {name} gauge\n"));
+        for l in lines {
+            out.push_str(l);
+            out.push('\n');
+        }
+    }
+
+    let tmp_path = sink.path.with_extension("prom.tmp");
+    fs::write(&tmp_path, out).with_context(|| format!("write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, &sink.path)
+        .with_context(|| format!("rename {} -> {}", tmp_path.display(), sink.path.display()))
+}
+
+fn extract_preflight_kvs(preflight_path: &Path, keys: &[&str]) -> Vec<(String, String)> {
+    let Ok(s) = fs::read_to_string(preflight_path) else {
+        return Vec::new();
+    };
+    let want: HashSet<&str> = keys.iter().copied().collect();
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for line in s.lines() {
+        let Some((k, v)) = line.split_once(':') else {
+            continue;
+        };
+        let k = k.trim();
         if !want.contains(k) {
             continue;
         }
@@ -999,24 +3940,46 @@ fn extract_preflight_kvs(preflight_path: &Path, keys: &[&str]) -> Vec<(String, S
 }
 
 fn run_edge_repeat(
-    muvm_path: &Path,
+    muvm: &MuvmLauncher,
     systemd_run_path: Option<&Path>,
     systemd_tasks_max: Option<u64>,
     workdir_abs: &Path,
     extracted_root_abs: &Path,
+    edge_bin_rel: &Path,
     mem: Option<u64>,
     muvm_privileged: bool,
     strace: bool,
     strace_mode: StraceMode,
+    strace_attach_after: Option<Duration>,
+    mem_report: bool,
+    dump_environ: bool,
+    live_classify: bool,
+    stop_on_signatures: &[String],
+    snapshot_limits: SnapshotLimits,
     timeout: Duration,
+    watchdog: Watchdog,
     edge_watchdog: Duration,
     url: &str,
     headless_impl: HeadlessImpl,
     edge_args: &[String],
+    edge_arg_file: Option<&Path>,
+    default_edge_flags: bool,
     edge_env: &[String],
+    proxy: Option<&str>,
+    guest_cwd: Option<&Path>,
     profile_location: ProfileLocation,
     preserve_dbus_xdg_env: bool,
     guest_sysctls: &[String],
+    guest_systemd_tasks_max: Option<u64>,
+    guest_nofile: Option<u64>,
+    edge_strip: Option<&StripReport>,
+    tail_muvm_output: Option<usize>,
+    metrics: Option<&MetricsSink>,
+    run_mode: &str,
+    redact: &[String],
+    guest_runner_path: Option<&str>,
+    compress_artifacts: Option<u64>,
+    summary_format: SummaryFormat,
     max_attempts: u32,
     stop_on: RepeatStopOn,
 ) -> Result<()> {
@@ -1026,10 +3989,20 @@ fn run_edge_repeat(
     log.push_str(&format!("max_attempts: {max_attempts}\n"));
     log.push_str(&format!("stop_on: {:?}\n", stop_on));
     log.push_str(&format!("strace: {}\n", if strace { "yes" } else { "no" }));
+    if let (true, Some(after)) = (strace, strace_attach_after) {
+        log.push_str(&format!(
+            "strace_attach_after_seconds: {}\n",
+            after.as_secs()
+        ));
+    }
     log.push_str(&format!(
         "edge_watchdog_seconds: {}\n",
         edge_watchdog.as_secs()
     ));
+    log.push_str(&format!(
+        "poll_interval_ms: {}\n",
+        watchdog.poll_interval.as_millis()
+    ));
     log.push_str(&format!("url: {url}\n"));
     log.push_str(&format!("headless_impl: {:?}\n", headless_impl));
     log.push_str(&format!(
@@ -1044,24 +4017,47 @@ fn run_edge_repeat(
         attempts = i;
         eprintln!("edge-repeat: attempt {i}/{max_attempts}");
         let res = run_edge(
-            muvm_path,
+            muvm,
             systemd_run_path,
             systemd_tasks_max,
             workdir_abs,
+            None,
             extracted_root_abs,
+            edge_bin_rel,
             mem,
             muvm_privileged,
             strace,
             strace_mode,
+            strace_attach_after,
+            mem_report,
+            dump_environ,
+            live_classify,
+            stop_on_signatures,
+            snapshot_limits,
             timeout,
+            watchdog,
             edge_watchdog,
             url,
             headless_impl,
             edge_args,
+            edge_arg_file,
+            default_edge_flags,
             edge_env,
+            proxy,
+            guest_cwd,
             profile_location,
             preserve_dbus_xdg_env,
             guest_sysctls,
+            guest_systemd_tasks_max,
+            guest_nofile,
+            edge_strip,
+            tail_muvm_output,
+            metrics,
+            run_mode,
+            redact,
+            guest_runner_path,
+            compress_artifacts,
+            summary_format,
         )?;
 
         log.push_str(&format!(
@@ -1072,13 +4068,7 @@ fn run_edge_repeat(
             res.pthread_stack_mprotect_enomem_events
         ));
 
-        let should_stop = match stop_on {
-            RepeatStopOn::PthreadCreate => res.stderr_pthread_create_lines > 0,
-            RepeatStopOn::StackMprotectEnomem => res.pthread_stack_mprotect_enomem_events > 0,
-            RepeatStopOn::StdoutNonEmpty => res.stdout_bytes > 0,
-        };
-
-        if should_stop {
+        if stop_on.hit(&res) {
             log.push_str(&format!(
                 "\nstop: hit on attempt {i}: {}\n",
                 res.run_dir.display()
@@ -1102,6 +4092,320 @@ fn run_edge_repeat(
     Ok(())
 }
 
+/// Runs Edge with the baseline flags plus subsets of `candidates`, caching the outcome of
+/// each subset (by candidate index) and logging every run.
+struct BisectRunner<'a> {
+    muvm: &'a MuvmLauncher,
+    candidates: &'a [String],
+    stop_on: RepeatStopOn,
+    attempts: u32,
+    run: &'a mut dyn FnMut(&MuvmLauncher) -> Result<EdgeRunResult>,
+    outcomes: HashMap<Vec<usize>, bool>,
+    runs_log: String,
+}
+
+impl BisectRunner<'_> {
+    fn hit(&mut self, subset: &[usize]) -> Result<bool> {
+        if let Some(&hit) = self.outcomes.get(subset) {
+            return Ok(hit);
+        }
+        let mut muvm = self.muvm.clone();
+        muvm.extra_args
+            .extend(subset.iter().map(|&i| self.candidates[i].clone()));
+        let mut hit = false;
+        for attempt in 1..=self.attempts {
+            eprintln!(
+                "bisect: {} attempt {attempt}/{}",
+                format_subset(subset),
+                self.attempts
+            );
+            let res = (self.run)(&muvm)?;
+            hit = self.stop_on.hit(&res);
+            self.runs_log.push_str(&format!(
+                "{} attempt {attempt}: hit={} dir={}\n",
+                format_subset(subset),
+                if hit { "yes" } else { "no" },
+                res.run_dir.display()
+            ));
+            if hit {
+                break;
+            }
+        }
+        self.outcomes.insert(subset.to_vec(), hit);
+        Ok(hit)
+    }
+}
+
+fn format_subset(subset: &[usize]) -> String {
+    let items: Vec<String> = subset.iter().map(|i| i.to_string()).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Zeller's ddmin: shrinks `all` to a 1-minimal subset for which `changed` holds (it must hold
+/// for `all`). Appends the search tree to `log`, one line per split and per test.
+fn ddmin(
+    all: Vec<usize>,
+    changed: &mut dyn FnMut(&[usize]) -> Result<bool>,
+    log: &mut String,
+) -> Result<Vec<usize>> {
+    let mut current = all;
+    let mut n = 2;
+    let mut depth = 0;
+    while current.len() >= 2 {
+        let indent = "  ".repeat(depth);
+        log.push_str(&format!(
+            "{indent}split {} into {n}\n",
+            format_subset(&current)
+        ));
+        let chunk_len = current.len().div_ceil(n);
+        let chunks: Vec<Vec<usize>> = current.chunks(chunk_len).map(<[usize]>::to_vec).collect();
+        let mut tests: Vec<(&str, Vec<usize>)> =
+            chunks.iter().map(|c| ("subset", c.clone())).collect();
+        // With two chunks each complement is the other chunk, already tested above.
+        if chunks.len() > 2 {
+            tests.extend(chunks.iter().map(|c| {
+                let rest = current.iter().copied().filter(|i| !c.contains(i));
+                ("complement", rest.collect())
+            }));
+        }
+        let mut reduced = None;
+        for (kind, test) in tests {
+            let hit = changed(&test)?;
+            log.push_str(&format!(
+                "{indent}  test {kind} {} -> {}\n",
+                format_subset(&test),
+                if hit { "changed" } else { "same" }
+            ));
+            if hit {
+                reduced = Some((kind, test));
+                break;
+            }
+        }
+        match reduced {
+            Some((kind, test)) => {
+                n = if kind == "subset" { 2 } else { (n - 1).max(2) };
+                current = test;
+                depth += 1;
+            }
+            None if n >= current.len() => break,
+            None => n = (n * 2).min(current.len()),
+        }
+    }
+    Ok(current)
+}
+
+fn run_bisect(
+    workdir_abs: &Path,
+    muvm: &MuvmLauncher,
+    candidates: &[String],
+    stop_on: RepeatStopOn,
+    attempts: u32,
+    run: &mut dyn FnMut(&MuvmLauncher) -> Result<EdgeRunResult>,
+) -> Result<()> {
+    if candidates.is_empty() {
+        bail!("--mode bisect needs at least one --bisect-candidate");
+    }
+    let log_path = workdir_abs.join(format!("edge-bisect-{}.txt", chrono_stamp()));
+    let mut log = String::new();
+    log.push_str(&format!("date: {}\n", iso_now()));
+    log.push_str(&format!("stop_on: {stop_on:?}\n"));
+    log.push_str(&format!("attempts_per_set: {attempts}\n"));
+    log.push_str(&format!("baseline: {}\n", muvm.extra_args.join(" ")));
+    log.push_str("candidates:\n");
+    for (i, flag) in candidates.iter().enumerate() {
+        log.push_str(&format!("  {i}: {flag}\n"));
+    }
+
+    let mut runner = BisectRunner {
+        muvm,
+        candidates,
+        stop_on,
+        attempts: attempts.max(1),
+        run,
+        outcomes: HashMap::new(),
+        runs_log: String::new(),
+    };
+    let all: Vec<usize> = (0..candidates.len()).collect();
+    let baseline_hit = runner.hit(&[])?;
+    let all_hit = runner.hit(&all)?;
+    let yes_no = |hit: bool| if hit { "hit" } else { "no hit" };
+    log.push_str(&format!(
+        "\nbaseline: {}\nbaseline + all candidates: {}\n",
+        yes_no(baseline_hit),
+        yes_no(all_hit)
+    ));
+
+    let minimal = if baseline_hit == all_hit {
+        log.push_str("\nresult: adding all candidates does not change the outcome\n");
+        None
+    } else {
+        log.push_str("\nsearch:\n");
+        let mut changed = |subset: &[usize]| Ok(runner.hit(subset)? != baseline_hit);
+        let minimal = ddmin(all, &mut changed, &mut log)?;
+        let flags: Vec<&str> = minimal.iter().map(|&i| candidates[i].as_str()).collect();
+        log.push_str(&format!(
+            "\nresult: minimal difference {} = {} ({} -> {})\n",
+            format_subset(&minimal),
+            flags.join(" "),
+            yes_no(baseline_hit),
+            yes_no(all_hit)
+        ));
+        Some(flags.join(" "))
+    };
+    log.push_str(&format!(
+        "\nruns ({} flag sets):\n{}",
+        runner.outcomes.len(),
+        runner.runs_log
+    ));
+    fs::write(&log_path, log).context("write bisect log")?;
+
+    match minimal {
+        Some(flags) => eprintln!("bisect: minimal flag difference: {flags}"),
+        None => eprintln!("bisect: candidates don't change the outcome"),
+    }
+    eprintln!("bisect: log: {}", log_path.display());
+    Ok(())
+}
+
+/// Summary keys shown side by side by `--mode compare-headless`.
+const HEADLESS_COMPARE_KEYS: &[&str] = &[
+    "outcome",
+    "exit_code",
+    "exit_reason_detail",
+    "elapsed_seconds",
+    "edge_exit",
+    "stdout_bytes",
+    "stderr_lines",
+    "stderr_pthread_create_lines",
+    "pthread_stack_mprotect_enomem_events",
+    "stderr_dbus_lines",
+    "stderr_ssl_client_socket_lines",
+    "stderr_handshake_failed_lines",
+];
+
+fn write_headless_comparison(
+    workdir_abs: &Path,
+    runs: &[(HeadlessImpl, EdgeRunResult)],
+) -> Result<()> {
+    let columns: Vec<(String, HashMap<String, String>)> = runs
+        .iter()
+        .map(|(headless_impl, res)| {
+            let kvs = read_run_summary(&res.run_dir).unwrap_or_default();
+            (format!("{headless_impl:?}").to_lowercase(), kvs)
+        })
+        .collect();
+
+    let mut out = String::new();
+    out.push_str(&format!("date: {}\n", iso_now()));
+    for ((_, res), (name, _)) in runs.iter().zip(&columns) {
+        out.push_str(&format!("run_dir_{name}: {}\n", res.run_dir.display()));
+    }
+    out.push('\n');
+
+    let key_width = HEADLESS_COMPARE_KEYS
+        .iter()
+        .map(|k| k.len())
+        .max()
+        .unwrap_or(0);
+    out.push_str(&format!("{:<key_width$}", "key"));
+    for (name, _) in &columns {
+        out.push_str(&format!("  {name:<24}"));
+    }
+    out.push('\n');
+    for key in HEADLESS_COMPARE_KEYS {
+        out.push_str(&format!("{key:<key_width$}"));
+        for (_, kvs) in &columns {
+            let v = kvs.get(*key).map(String::as_str).unwrap_or("(missing)");
+            out.push_str(&format!("  {v:<24}"));
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+
+    let with_output: Vec<&str> = runs
+        .iter()
+        .zip(&columns)
+        .filter(|((_, res), _)| res.stdout_bytes > 0)
+        .map(|(_, (name, _))| name.as_str())
+        .collect();
+    let verdict = if with_output.len() == runs.len() {
+        "both produced output".to_string()
+    } else if !with_output.is_empty() {
+        format!("only {} produced output", with_output.join(", "))
+    } else {
+        // Neither produced output: same failure if the exit and classifier counts agree.
+        let failure_keys = [
+            "edge_exit",
+            "stderr_pthread_create_lines",
+            "pthread_stack_mprotect_enomem_events",
+        ];
+        let same = columns.windows(2).all(|w| {
+            failure_keys
+                .iter()
+                .all(|k| w[0].1.get(*k) == w[1].1.get(*k))
+        });
+        if same {
+            "neither produced output; both failed the same way".to_string()
+        } else {
+            "neither produced output; failures differ (see table)".to_string()
+        }
+    };
+    out.push_str(&format!("verdict: {verdict}\n"));
+
+    let path = workdir_abs.join(format!("compare-headless-{}.txt", chrono_stamp()));
+    fs::write(&path, &out).context("write headless comparison")?;
+    eprint!("{out}");
+    eprintln!("Wrote comparison: {}", path.display());
+    Ok(())
+}
+
+/// How `muvm` is started: its path, which host environment it gets and any extra flags.
+#[derive(Clone)]
+struct MuvmLauncher {
+    path: PathBuf,
+    clear_env: bool,
+    env_passthrough: Vec<String>,
+    /// `--muvm-arg` flags, placed right after the muvm path.
+    extra_args: Vec<String>,
+}
+
+impl MuvmLauncher {
+    /// argv that starts muvm; with `--clear-env` this is `env -i KEY=VALUE... muvm`.
+    fn argv(&self) -> Vec<String> {
+        let mut argv = Vec::new();
+        if self.clear_env {
+            argv.push("env".into());
+            argv.push("-i".into());
+            for key in &self.env_passthrough {
+                if let Ok(val) = std::env::var(key) {
+                    argv.push(format!("{key}={val}"));
+                }
+            }
+        }
+        argv.push(self.path.display().to_string());
+        argv.extend(self.extra_args.iter().cloned());
+        argv
+    }
+
+    /// Host vars muvm gets: `inherit` (everything) or the forwarded keys.
+    fn passthrough_summary(&self) -> String {
+        if !self.clear_env {
+            return "inherit".to_string();
+        }
+        let keys: Vec<&str> = self
+            .env_passthrough
+            .iter()
+            .filter(|k| std::env::var_os(k).is_some())
+            .map(String::as_str)
+            .collect();
+        if keys.is_empty() {
+            "(none)".to_string()
+        } else {
+            keys.join(",")
+        }
+    }
+}
+
 fn wrap_muvm_args_if_requested(
     argv: Vec<String>,
     systemd_run_path: Option<&Path>,
@@ -1128,40 +4432,483 @@ fn wrap_muvm_args_if_requested(
     Ok(out)
 }
 
-fn guest_runner(
-    edge_bin: &Path,
-    run_dir: &Path,
-    url: &str,
-    headless_impl: HeadlessImpl,
-    edge_args: &[String],
-    edge_env: &[String],
-    profile_location: ProfileLocation,
-    preserve_dbus_xdg_env: bool,
-    guest_sysctls: &[String],
-    strace: bool,
-    strace_mode: StraceMode,
-    edge_watchdog: Duration,
-) -> Result<()> {
-    if !edge_bin.is_file() {
-        bail!("Edge binary missing at {}", edge_bin.display());
+/// Creates, reads back and removes a probe file in the Edge profile dir.
+///
+/// A shared profile lives on virtiofs, where permission quirks can make writes fail without
+/// Edge saying so; this surfaces that before Edge starts.
+fn probe_profile_writable(profile_dir: &Path) -> std::result::Result<(), String> {
+    let probe = profile_dir.join(".edge-muvm-write-probe");
+    let expected = format!("probe {}\n", std::process::id());
+    fs::write(&probe, &expected).map_err(|e| format!("write {}: {e}", probe.display()))?;
+    let got = fs::read_to_string(&probe).map_err(|e| format!("read back {}: {e}", probe.display()));
+    let removed = fs::remove_file(&probe).map_err(|e| format!("remove {}: {e}", probe.display()));
+    if got? != expected {
+        return Err(format!("read back {}: contents differ", probe.display()));
+    }
+    removed
+}
+
+/// An rlimit as `(soft, hard)`.
+type Rlimit = (libc::rlim_t, libc::rlim_t);
+
+/// `RLIMIT_NOFILE` of this process.
+fn nofile_limit() -> io::Result<Rlimit> {
+    let mut lim = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut lim) } != 0 {
+        return Err(io::Error::last_os_error());
     }
-    let profile_dir = match profile_location {
-        ProfileLocation::Shared => run_dir.join("profile"),
-        ProfileLocation::GuestTmp => {
-            PathBuf::from(format!("/tmp/edge-muvm-profile-{}", chrono_stamp()))
+    Ok((lim.rlim_cur, lim.rlim_max))
+}
+
+/// Sets the `RLIMIT_NOFILE` soft limit to `want`, capped at the hard limit (raising the hard
+/// limit needs privileges the guest runner may not have). Returns the limits before and after.
+fn raise_nofile_limit(want: u64) -> io::Result<(Rlimit, Rlimit)> {
+    let before = nofile_limit()?;
+    let lim = libc::rlimit {
+        rlim_cur: (want as libc::rlim_t).min(before.1),
+        rlim_max: before.1,
+    };
+    if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &lim) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok((before, nofile_limit()?))
+}
+
+fn format_rlimit((soft, hard): Rlimit) -> String {
+    let fmt = |v: libc::rlim_t| {
+        if v == libc::RLIM_INFINITY {
+            "unlimited".to_string()
+        } else {
+            v.to_string()
         }
     };
-    fs::create_dir_all(&profile_dir).context("create profile dir")?;
+    format!("soft={} hard={}", fmt(soft), fmt(hard))
+}
 
-    let stdout_path = run_dir.join("stdout.txt");
-    let stderr_path = run_dir.join("stderr.txt");
-    let ps_path = run_dir.join("ps.txt");
-    let threads_path = run_dir.join("threads.txt");
-    let preflight_path = run_dir.join("preflight.txt");
+/// Whether x86_64 ELFs route through FEX via binfmt_misc in the guest, and with which
+/// interpreter; a wrong interpreter there breaks x86_64 execs that are not launched via FEX.
+fn write_fex_binfmt_kvs(f: &mut fs::File) -> Result<()> {
+    let Ok(text) = fs::read_to_string(FEX_BINFMT_PATH) else {
+        writeln!(f, "fex_binfmt_registered: no")?;
+        return Ok(());
+    };
+    let entry = parse_binfmt_entry(&text);
+    writeln!(f, "fex_binfmt_registered: yes")?;
+    writeln!(
+        f,
+        "fex_binfmt_enabled: {}",
+        if entry.enabled { "yes" } else { "no" }
+    )?;
+    writeln!(
+        f,
+        "fex_binfmt_interpreter: {}",
+        entry.interpreter.as_deref().unwrap_or("(unknown)")
+    )?;
+    writeln!(
+        f,
+        "fex_binfmt_flags: {}",
+        entry.flags.as_deref().unwrap_or("(none)")
+    )?;
+    Ok(())
+}
+
+/// Whether the runtime dir and DBus session socket Edge inherits exist in the guest. A
+/// preserved `DBUS_SESSION_BUS_ADDRESS` pointing at no socket is `dbus_env_dangling`, the
+/// suspected cause of Edge blocking on DBus.
+fn write_dbus_env_kvs(f: &mut fs::File, preserve_dbus_xdg_env: bool) -> Result<()> {
+    let var = |k: &str| std::env::var(k).ok().filter(|v| !v.is_empty());
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+
+    match var("XDG_RUNTIME_DIR") {
+        Some(dir) => writeln!(
+            f,
+            "xdg_runtime_dir_exists: {}",
+            yes_no(Path::new(&dir).is_dir())
+        )?,
+        None => writeln!(f, "xdg_runtime_dir_exists: (unset)")?,
+    }
+
+    let Some(address) = var("DBUS_SESSION_BUS_ADDRESS") else {
+        writeln!(f, "dbus_socket_exists: (unset)")?;
+        return Ok(());
+    };
+    let Some(socket) = dbus_unix_socket(&address) else {
+        writeln!(f, "dbus_socket_exists: (not a unix: address)")?;
+        return Ok(());
+    };
+    let exists = match socket.strip_prefix('@') {
+        // Abstract sockets have no path; look for a listener in /proc/net/unix instead.
+        Some(_) => fs::read_to_string("/proc/net/unix").is_ok_and(|t| {
+            t.lines()
+                .any(|l| l.split_whitespace().last() == Some(&socket))
+        }),
+        None => Path::new(&socket).exists(),
+    };
+    writeln!(f, "dbus_socket: {socket}")?;
+    writeln!(f, "dbus_socket_exists: {}", yes_no(exists))?;
+    if preserve_dbus_xdg_env && !exists {
+        writeln!(f, "dbus_env_dangling: yes")?;
+    }
+    Ok(())
+}
+
+/// First unix socket in a DBus address list: `unix:path=P` gives `P`, `unix:abstract=N` gives
+/// `@N` (as `/proc/net/unix` shows it).
+fn dbus_unix_socket(address: &str) -> Option<String> {
+    address.split(';').find_map(|entry| {
+        entry
+            .trim()
+            .strip_prefix("unix:")?
+            .split(',')
+            .find_map(|kv| match kv.split_once('=')? {
+                ("path", p) => Some(p.to_string()),
+                ("abstract", n) => Some(format!("@{n}")),
+                _ => None,
+            })
+    })
+}
+
+/// FEXServer socket under the runtime dir and whether a FEXServer process is running; a
+/// socket with no server behind it is stale and makes FEX hang or fail on connect.
+fn write_fexserver_kvs(f: &mut fs::File) -> Result<()> {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("/run/user/{}", unsafe { libc::getuid() })));
+    let mut sockets: Vec<String> = fs::read_dir(&runtime_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .filter(|name| name.ends_with(".FEXServer.Socket"))
+                .collect()
+        })
+        .unwrap_or_default();
+    sockets.sort();
+    let running: Vec<u32> = fs::read_dir("/proc")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+                .filter(|pid| {
+                    fs::read_to_string(format!("/proc/{pid}/comm"))
+                        .is_ok_and(|c| c.trim() == "FEXServer")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    writeln!(f, "fexserver_runtime_dir: {}", runtime_dir.display())?;
+    writeln!(
+        f,
+        "fexserver_socket_present: {}",
+        if sockets.is_empty() { "no" } else { "yes" }
+    )?;
+    if !sockets.is_empty() {
+        writeln!(f, "fexserver_sockets: {}", sockets.join(" "))?;
+    }
+    writeln!(
+        f,
+        "fexserver_running: {}",
+        if running.is_empty() {
+            "no".to_string()
+        } else {
+            format!(
+                "yes (pid {})",
+                running
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )
+        }
+    )?;
+    if !sockets.is_empty() && running.is_empty() {
+        writeln!(f, "fexserver_socket_stale: yes")?;
+    }
+    Ok(())
+}
+
+/// Guest `uname -r` (parsed) and Edge's `.note.ABI-tag` minimum, plus `kernel_too_old`
+/// when both are known.
+fn write_guest_kernel_kvs(f: &mut fs::File, edge_bin: &Path) -> Result<()> {
+    let release = fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default();
+    let guest = KernelVersion::parse_release(&release);
+    let min = match elf_abi_info(edge_bin) {
+        Ok(info) => info.and_then(|i| i.abi_tag).map(|t| t.min_kernel),
+        Err(e) => {
+            writeln!(f, "edge_abi_tag_error: {e:#}")?;
+            None
+        }
+    };
+    writeln!(f, "guest_kernel_version: {release}")?;
+    if let Some(guest) = guest {
+        writeln!(f, "guest_kernel: {guest}")?;
+    }
+    if let Some(min) = min {
+        writeln!(f, "edge_min_kernel: {min}")?;
+    }
+    if let (Some(guest), Some(min)) = (guest, min) {
+        writeln!(
+            f,
+            "kernel_too_old: {}",
+            if guest < min { "yes" } else { "no" }
+        )?;
+    }
+    Ok(())
+}
+
+/// Results of the `--guest-runner-path auto` probe, one `<muvm sha256> <host kernel>
+/// <guest path> <mechanism>` line each, kept in the workdir so only the first run with a given
+/// muvm, kernel and guest-runner location boots a VM to probe. Delete it to probe again.
+const GUEST_RUNNER_PROBE_CACHE: &str = "guest-runner-probe.txt";
+
+/// Guest path muvm runs the guest-runner from, and how it got there: `explicit`
+/// (`--guest-runner-path`), `host-mount` (`auto` found it under `/run/muvm-host`) or `copy`
+/// (copied into the run dir, which muvm shares).
+fn resolve_guest_runner(
+    muvm: &MuvmLauncher,
+    muvm_sha256: &str,
+    requested: Option<&str>,
+    self_exe: &Path,
+    run_dir: &Path,
+    workdir: &Path,
+    watchdog: Watchdog,
+) -> Result<(String, &'static str)> {
+    match requested {
+        Some("auto") => {
+            let guest_path = format!("/run/muvm-host{}", self_exe.display());
+            let kernel = fs::read_to_string("/proc/sys/kernel/osrelease")
+                .map(|s| s.trim().to_string())
+                .unwrap_or_else(|_| "unknown".into());
+            let key = format!("{muvm_sha256} {kernel} {guest_path}");
+            let cache_path = workdir.join(GUEST_RUNNER_PROBE_CACHE);
+            let cached = fs::read_to_string(&cache_path).ok().and_then(|text| {
+                text.lines()
+                    .rev()
+                    .find_map(|line| line.strip_prefix(key.as_str())?.strip_prefix(' '))
+                    .map(str::to_string)
+            });
+            match cached.as_deref() {
+                Some("host-mount") => return Ok((guest_path, "host-mount")),
+                Some(_) => {}
+                None => {
+                    let mechanism = probe_guest_runner(muvm, &guest_path, run_dir, watchdog)?;
+                    let mut cache = fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&cache_path)
+                        .with_context(|| format!("open {}", cache_path.display()))?;
+                    writeln!(cache, "{key} {mechanism}")
+                        .with_context(|| format!("write {}", cache_path.display()))?;
+                    if mechanism == "host-mount" {
+                        return Ok((guest_path, mechanism));
+                    }
+                }
+            }
+        }
+        Some(path) => return Ok((path.to_string(), "explicit")),
+        None => {}
+    }
+
+    let guest_runner_path = run_dir.join("edge-muvm-guest-runner");
+    fs::copy(self_exe, &guest_runner_path).context("copy guest-runner into run dir")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&guest_runner_path)
+            .context("stat guest-runner")?
+            .permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&guest_runner_path, perms).context("chmod guest-runner")?;
+    }
+    Ok((guest_runner_path.display().to_string(), "copy"))
+}
+
+/// Boots muvm once to check that `guest_path` is executable in the guest: `host-mount` if it
+/// is, `copy` (with a warning) if not.
+fn probe_guest_runner(
+    muvm: &MuvmLauncher,
+    guest_path: &str,
+    run_dir: &Path,
+    watchdog: Watchdog,
+) -> Result<&'static str> {
+    let mut argv = muvm.argv();
+    argv.extend(["test".into(), "-x".into(), guest_path.to_string()]);
+    let observed = run_command_with_pty_to_file_observed(
+        &argv,
+        &run_dir.join("guest-runner-detect.txt"),
+        Duration::from_secs(120),
+        watchdog,
+        None,
+        &|_| {},
+    )
+    .context("check guest-runner under /run/muvm-host")?;
+    if observed.exit_code == 0 {
+        return Ok("host-mount");
+    }
+    eprintln!(
+        "WARNING: {guest_path} is not executable in the guest (exit {}); copying the \
+         guest-runner into the run dir instead",
+        observed.exit_code
+    );
+    Ok("copy")
+}
+
+/// Flags (all among the defaults) that should keep Edge from starting a crashpad handler.
+const CRASHPAD_DISABLE_FLAGS: &[&str] = &[
+    "--disable-breakpad",
+    "--disable-crash-reporter",
+    "--disable-features=Crashpad",
+];
+
+/// Chromium flags guest_runner passes to Edge unless `--no-default-edge-flags`.
+const DEFAULT_EDGE_FLAGS: &[&str] = &[
+    "--disable-gpu",
+    "--no-first-run",
+    "--no-default-browser-check",
+    // Avoid keychain prompts during repeated headless runs.
+    "--password-store=basic",
+    "--use-mock-keychain",
+    "--disable-extensions",
+    "--disable-component-extensions-with-background-pages",
+    "--disable-dev-shm-usage",
+    "--disable-breakpad",
+    "--disable-crash-reporter",
+    "--no-crash-upload",
+    "--disable-features=Crashpad",
+];
+
+/// Sysctls that take several whitespace-separated fields, written tab-separated the way the
+/// kernel prints them. A trailing `*` matches any key with that prefix.
+const MULTI_FIELD_SYSCTLS: &[&str] = &[
+    "kernel.printk",
+    "kernel.sched_*",
+    "kernel.sem",
+    "net.ipv4.ip_local_port_range",
+    "net.ipv4.ping_group_range",
+    "net.ipv4.tcp_mem",
+    "net.ipv4.tcp_rmem",
+    "net.ipv4.tcp_wmem",
+    "net.ipv4.udp_mem",
+    "vm.lowmem_reserve_ratio",
+];
+
+fn is_multi_field_sysctl(key: &str) -> bool {
+    MULTI_FIELD_SYSCTLS
+        .iter()
+        .any(|pat| match pat.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == *pat,
+        })
+}
+
+/// Splits a `--guest-sysctl` `KEY=VALUE` into the key and the value to write. Values of
+/// `MULTI_FIELD_SYSCTLS` may be space or tab separated and are re-joined with tabs; any other
+/// value is trimmed and otherwise written as given (e.g. `kernel.core_pattern=|/usr/bin/x %p`).
+/// Errors say why it's invalid.
+fn parse_guest_sysctl(kv: &str) -> std::result::Result<(&str, String), &'static str> {
+    let (k, v) = kv.split_once('=').ok_or("expected KEY=VALUE")?;
+    let k = k.trim();
+    if k.is_empty() {
+        return Err("empty key");
+    }
+    if v.contains('\n') || v.contains('\r') {
+        return Err("newline in value");
+    }
+    let valid_key = k
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '.')
+        && k.split('.').all(|part| !part.is_empty());
+    if !valid_key {
+        return Err("bad key");
+    }
+    if !is_multi_field_sysctl(k) {
+        return Ok((k, v.trim().to_string()));
+    }
+    let fields: Vec<&str> = v.split([' ', '\t']).filter(|f| !f.is_empty()).collect();
+    Ok((k, fields.join("\t")))
+}
+
+fn guest_runner(
+    edge_bin: &Path,
+    run_dir: &Path,
+    url: &str,
+    headless_impl: HeadlessImpl,
+    edge_args: &[String],
+    edge_arg_file: Option<&Path>,
+    default_edge_flags: bool,
+    edge_env: &[String],
+    proxy: Option<&str>,
+    guest_cwd: Option<&Path>,
+    profile_location: ProfileLocation,
+    preserve_dbus_xdg_env: bool,
+    guest_sysctls: &[String],
+    guest_systemd_tasks_max: Option<u64>,
+    guest_nofile: Option<u64>,
+    strace: bool,
+    strace_mode: StraceMode,
+    strace_attach_after: Option<Duration>,
+    mem_report: bool,
+    dump_environ: bool,
+    live_classify: bool,
+    stop_on_signatures: &[String],
+    snapshot_limits: SnapshotLimits,
+    edge_watchdog: Duration,
+    poll_interval: Duration,
+) -> Result<()> {
+    if !edge_bin.is_file() {
+        bail!("Edge binary missing at {}", edge_bin.display());
+    }
+    let edge_args_from_file = match edge_arg_file {
+        Some(path) => read_edge_arg_file(path)?,
+        None => Vec::new(),
+    };
+    // Everything below (strace, systemd-run --same-dir, Edge) inherits this cwd.
+    if let Some(dir) = guest_cwd {
+        fs::create_dir_all(dir)
+            .and_then(|()| std::env::set_current_dir(dir))
+            .with_context(|| format!("enter --guest-cwd {}", dir.display()))?;
+    }
+    let profile_dir = match profile_location {
+        ProfileLocation::Shared => run_dir.join("profile"),
+        ProfileLocation::GuestTmp => {
+            PathBuf::from(format!("/tmp/edge-muvm-profile-{}", chrono_stamp()))
+        }
+    };
+    fs::create_dir_all(&profile_dir).context("create profile dir")?;
+    let profile_probe = probe_profile_writable(&profile_dir);
+    if let Err(e) = &profile_probe {
+        eprintln!(
+            "WARNING: profile dir {} is not writable ({e}); Edge profile writes will fail",
+            profile_dir.display()
+        );
+    }
+
+    // Raised on this process so Edge (and strace) inherit it.
+    let nofile = guest_nofile.map(|want| (want, raise_nofile_limit(want)));
+
+    let stdout_path = run_dir.join("stdout.txt");
+    let stderr_path = run_dir.join("stderr.txt");
+    let ps_path = run_dir.join("ps.txt");
+    let threads_path = run_dir.join("threads.txt");
+    let preflight_path = run_dir.join("preflight.txt");
     let pid_path = run_dir.join("pid.txt");
     let exit_path = run_dir.join("edge-exit.txt");
     let stuck_path = run_dir.join("stuck.txt");
     let guest_sysctl_path = run_dir.join("guest-sysctl.txt");
+    let meminfo_delta_path = run_dir.join("meminfo-delta.txt");
+    let live_counts_path = run_dir.join("live-counts.txt");
+    let stop_signature_path = run_dir.join("stop-signature.txt");
+    let threads_timeseries_path = run_dir.join("threads-timeseries.txt");
+    let pressure_timeseries_path = run_dir.join("pressure-timeseries.txt");
+    let edge_environ_path = run_dir.join("edge-environ.txt");
+    let maps_path = run_dir.join("maps.txt");
 
     {
         let mut f = fs::File::create(&preflight_path).context("write preflight")?;
@@ -1171,12 +4918,56 @@ fn guest_runner(
         writeln!(f, "RUN_DIR={}", run_dir.display())?;
         writeln!(f, "PROFILE_LOCATION={}", profile_location.as_arg())?;
         writeln!(f, "PROFILE_DIR={}", profile_dir.display())?;
+        match &profile_probe {
+            Ok(()) => writeln!(f, "profile_writable: yes")?,
+            Err(e) => {
+                writeln!(f, "profile_writable: no")?;
+                writeln!(f, "profile_write_error: {e}")?;
+            }
+        }
+        // virtiofs vs tmpfs vs overlay explains most I/O and permission surprises.
+        let mounts =
+            parse_mountinfo(&fs::read_to_string("/proc/self/mountinfo").unwrap_or_default());
+        for (key, dir) in [
+            ("run_dir_fs", run_dir),
+            ("profile_dir_fs", profile_dir.as_path()),
+        ] {
+            let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+            match mount_for_path(&mounts, &dir) {
+                Some(m) => writeln!(
+                    f,
+                    "{key}: {} (source={} mount_point={})",
+                    m.fs_type, m.source, m.mount_point
+                )?,
+                None => writeln!(f, "{key}: (unknown)")?,
+            }
+        }
+        write_guest_kernel_kvs(&mut f, edge_bin)?;
+        write_fex_binfmt_kvs(&mut f)?;
+        write_fexserver_kvs(&mut f)?;
+        if let Some((want, result)) = &nofile {
+            writeln!(f, "guest_nofile_requested: {want}")?;
+            match result {
+                Ok((before, after)) => {
+                    writeln!(f, "guest_nofile_before: {}", format_rlimit(*before))?;
+                    writeln!(f, "guest_nofile_after: {}", format_rlimit(*after))?;
+                }
+                Err(e) => writeln!(f, "guest_nofile_error: {e}")?,
+            }
+        }
         if !edge_args.is_empty() {
             writeln!(f, "EDGE_ARGS={}", edge_args.join(" "))?;
         }
+        if edge_arg_file.is_some() {
+            writeln!(f, "EDGE_ARGS_FROM_FILE={}", edge_args_from_file.join(" "))?;
+        }
         if !edge_env.is_empty() {
             writeln!(f, "EDGE_ENV={}", edge_env.join(" "))?;
         }
+        writeln!(f, "proxy: {}", proxy.unwrap_or("(none)"))?;
+        if let Some(dir) = guest_cwd {
+            writeln!(f, "guest_cwd: {}", dir.display())?;
+        }
         writeln!(
             f,
             "PRESERVE_DBUS_XDG_ENV={}",
@@ -1192,6 +4983,7 @@ fn guest_runner(
             "ENV_XDG_RUNTIME_DIR={}",
             std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "(unset)".into())
         )?;
+        write_dbus_env_kvs(&mut f, preserve_dbus_xdg_env)?;
         writeln!(f, "URL={}", url)?;
         writeln!(
             f,
@@ -1202,6 +4994,7 @@ fn guest_runner(
             }
         )?;
         writeln!(f, "EDGE_WATCHDOG_SECONDS={}", edge_watchdog.as_secs())?;
+        writeln!(f, "POLL_INTERVAL_MS={}", poll_interval.as_millis())?;
         writeln!(f)?;
         writeln!(f, "proc_self_status:")?;
         writeln!(
@@ -1292,6 +5085,58 @@ fn guest_runner(
             )
         )?;
         writeln!(f)?;
+        writeln!(f, "guest_mounts:")?;
+        for m in &mounts {
+            writeln!(
+                f,
+                "{} {} {} {}",
+                m.mount_point, m.fs_type, m.source, m.options
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "cpus:")?;
+        // Chromium sizes its thread pools from the CPU count, so record what the guest exposes.
+        writeln!(
+            f,
+            "guest_nproc: {}",
+            std::thread::available_parallelism()
+                .map(|n| n.to_string())
+                .unwrap_or_else(|e| format!("(unavailable: {e})"))
+        )?;
+        writeln!(
+            f,
+            "guest_cpuinfo_processors: {}",
+            fs::read_to_string("/proc/cpuinfo")
+                .map(|s| s
+                    .lines()
+                    .filter(|l| l.starts_with("processor"))
+                    .count()
+                    .to_string())
+                .unwrap_or_else(|e| format!("(unavailable: {e})"))
+        )?;
+        let cpuset_dir = parse_cgroup_v2_relative_path(&proc_self_cgroup)
+            .map(|rel| cgroup_v2_dir_from_relative_path(&rel))
+            .unwrap_or_else(|| PathBuf::from("/sys/fs/cgroup"));
+        writeln!(
+            f,
+            "guest_cpuset: {}",
+            read_first_line_best_effort(&cpuset_dir.join("cpuset.cpus.effective"))
+        )?;
+        writeln!(
+            f,
+            "guest_cpu_max: {}",
+            read_first_line_best_effort(&cpuset_dir.join("cpu.max"))
+        )?;
+        let cpus_allowed = fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|st| {
+                st.lines()
+                    .find_map(|l| l.strip_prefix("Cpus_allowed_list:"))
+                    .map(|v| v.trim().to_string())
+            })
+            .unwrap_or_else(|| "(unknown)".to_string());
+        writeln!(f, "guest_cpus_allowed_list: {cpus_allowed}")?;
+        writeln!(f)?;
         writeln!(f, "kernel_threads_max:")?;
         writeln!(
             f,
@@ -1429,229 +5274,971 @@ fn guest_runner(
         let mut report = String::new();
         report.push_str(&format!("date: {}\n", iso_now()));
         for kv in guest_sysctls {
-            let Some((k, v)) = kv.split_once('=') else {
-                report.push_str(&format!(
-                    "requested: {kv}\nresult: invalid (expected KEY=VALUE)\n\n"
-                ));
-                continue;
+            let (k, v) = match parse_guest_sysctl(kv) {
+                Ok(parsed) => parsed,
+                Err(why) => {
+                    report.push_str(&format!("requested: {kv}\nresult: invalid ({why})\n\n"));
+                    continue;
+                }
             };
-            let k = k.trim();
-            let v = v.trim();
-            if k.is_empty() {
-                report.push_str(&format!("requested: {kv}\nresult: invalid (empty key)\n\n"));
-                continue;
+
+            let path = PathBuf::from("/proc/sys").join(k.replace('.', "/"));
+            let before = read_first_line_best_effort(&path);
+            let write_res = fs::write(&path, format!("{v}\n"));
+            let after = read_first_line_best_effort(&path);
+
+            report.push_str(&format!(
+                "requested: {k}={}\npath: {}\nbefore: {before}\n",
+                v.replace('\t', " "),
+                path.display()
+            ));
+            match write_res {
+                Ok(_) => report.push_str("write: ok\n"),
+                Err(e) => report.push_str(&format!("write: error: {e}\n")),
             }
-            if v.contains('\n') || v.contains('\r') {
-                report.push_str(&format!(
-                    "requested: {kv}\nresult: invalid (newline in value)\n\n"
-                ));
-                continue;
+            report.push_str(&format!("after: {after}\n\n"));
+        }
+        let _ = fs::write(&guest_sysctl_path, report);
+    }
+
+    let stdout_file = fs::File::create(&stdout_path).context("create stdout")?;
+    let stderr_file = fs::File::create(&stderr_path).context("create stderr")?;
+
+    // Optionally prefix Edge with strace, or (with an attach delay) resolve it now and attach
+    // to the running Edge later.
+    let strace_enabled_path = run_dir.join("strace.enabled.txt");
+    let strace_path = if strace {
+        match resolve_in_path("strace") {
+            Ok(p) => {
+                let mode = match strace_attach_after {
+                    Some(after) => format!("attach\nattach_after_seconds: {}", after.as_secs()),
+                    None => "wrap".to_string(),
+                };
+                let _ = fs::write(
+                    &strace_enabled_path,
+                    format!("strace: yes\npath: {}\nmode: {mode}\n", p.display()),
+                );
+                Some(p)
+            }
+            Err(e) => {
+                let _ = fs::write(
+                    &strace_enabled_path,
+                    format!("strace: requested but not available ({e})\n"),
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let wrapped_in_strace = strace_path.is_some() && strace_attach_after.is_none();
+    let mut cmd = match &strace_path {
+        Some(p) if wrapped_in_strace => {
+            let mut c = strace_command(p, run_dir, strace_mode);
+            c.arg(edge_bin);
+            c
+        }
+        _ => Command::new(edge_bin),
+    };
+
+    // Apply requested environment variables. This sets the env for the direct Edge process
+    // and also works when wrapped in strace (Edge inherits strace's environment).
+    for kv in edge_env {
+        let Some((k, v)) = kv.split_once('=') else {
+            bail!("invalid --edge-env value (expected KEY=VALUE): {kv}");
+        };
+        if k.is_empty() {
+            bail!("invalid --edge-env value (empty KEY): {kv}");
+        }
+        cmd.env(k, v);
+    }
+
+    let meminfo_before =
+        mem_report.then(|| read_text_best_effort(Path::new("/proc/meminfo"), 64 * 1024));
+
+    // Use newer headless implementation to avoid legacy headless limitations.
+    let mut edge_flags = vec![match headless_impl {
+        HeadlessImpl::New => "--headless".to_string(),
+        HeadlessImpl::Old => "--headless=old".to_string(),
+    }];
+    if default_edge_flags {
+        edge_flags.extend(DEFAULT_EDGE_FLAGS.iter().map(|f| f.to_string()));
+    }
+    edge_flags.push(format!("--user-data-dir={}", profile_dir.display()));
+    edge_flags.extend(proxy.map(|p| format!("--proxy-server={p}")));
+    edge_flags.extend(edge_args.iter().cloned());
+    edge_flags.extend(edge_args_from_file.iter().cloned());
+    edge_flags.push("--dump-dom".into());
+    edge_flags.push(url.to_string());
+    append_preflight(
+        &preflight_path,
+        &format!(
+            "edge_default_flags: {}\nedge_flags: {}\n",
+            if default_edge_flags { "yes" } else { "no" },
+            edge_flags.join(" ")
+        ),
+    );
+    cmd.args(&edge_flags);
+
+    let guest_unit = match guest_systemd_tasks_max {
+        Some(tasks_max) => match resolve_in_path("systemd-run") {
+            Ok(systemd_run) => Some(GuestSystemdUnit {
+                systemd_run,
+                unit: format!("edge-muvm-{}.service", chrono_stamp()),
+                tasks_max,
+                // The unit is spawned by the user manager, so it doesn't inherit our rlimit.
+                nofile_soft: match &nofile {
+                    Some((_, Ok((_, (soft, _))))) => Some(*soft),
+                    _ => None,
+                },
+            }),
+            Err(e) => {
+                append_preflight(
+                    &preflight_path,
+                    &format!("guest_systemd_run: unavailable ({e}); running Edge directly\n"),
+                );
+                None
             }
+        },
+        None => None,
+    };
+    if let Some(unit) = &guest_unit {
+        cmd = unit.wrap(&cmd);
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(stdout_file)
+        .stderr(stderr_file)
+        .spawn()
+        .context("spawn Edge")?;
+
+    let pid = child.id();
 
-            let mut valid = true;
-            let mut prev_dot = false;
-            for ch in k.chars() {
-                let ok = ch.is_ascii_alphanumeric() || ch == '_' || ch == '.';
-                if !ok {
-                    valid = false;
+    // When wrapping Edge in `strace`, `child.id()` is the `strace` PID (not Edge).
+    // For artifacts (ps/threads/stuck), we want the actual Edge/browser PID.
+    let wrapper_pid = pid;
+    // Under a guest systemd unit, Edge (or strace) is the unit's main process, not our child.
+    let spawn_root = match &guest_unit {
+        Some(unit) => {
+            let main_pid = unit.wait_main_pid(Duration::from_secs(2));
+            let cgroup = unit.control_group();
+            append_preflight(
+                &preflight_path,
+                &format!(
+                    "\nguest_systemd_tasks_max: {}\nguest_systemd_unit: {}\nguest_systemd_main_pid: {}\nguest_systemd_cgroup: {}\nguest_systemd_cgroup_pids_max: {}\n",
+                    unit.tasks_max,
+                    unit.unit,
+                    main_pid
+                        .map(|p| p.to_string())
+                        .unwrap_or_else(|| "(unknown)".to_string()),
+                    cgroup.as_deref().unwrap_or("(unknown)"),
+                    cgroup
+                        .as_deref()
+                        .map(|cg| read_first_line_best_effort(
+                            &cgroup_v2_dir_from_relative_path(cg).join("pids.max")
+                        ))
+                        .unwrap_or_else(|| "(unknown)".to_string()),
+                ),
+            );
+            main_pid.unwrap_or(wrapper_pid)
+        }
+        None => wrapper_pid,
+    };
+    let tracked_pid = if wrapped_in_strace {
+        let start = Instant::now();
+        let deadline = start + Duration::from_secs(2);
+        let mut edge_pid = None;
+        while Instant::now() < deadline {
+            if let Ok(children) = pids_by_ppid(spawn_root) {
+                if let Some(p) = children.first().copied() {
+                    edge_pid = Some(p);
                     break;
                 }
-                if ch == '.' {
-                    if prev_dot {
-                        valid = false;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        edge_pid.unwrap_or(spawn_root)
+    } else {
+        spawn_root
+    };
+
+    let _ = fs::write(
+        &pid_path,
+        format!(
+            "wrapper_pid={wrapper_pid}\ntracked_pid={tracked_pid}\nwrapped_in_strace={}\nguest_systemd_unit={}\n",
+            if wrapped_in_strace { "yes" } else { "no" },
+            guest_unit.as_ref().map(|u| u.unit.as_str()).unwrap_or("(none)")
+        ),
+    );
+
+    if dump_environ {
+        write_edge_environ(&edge_environ_path, tracked_pid, edge_env).ok();
+    }
+
+    let live = (live_classify || !stop_on_signatures.is_empty()).then(|| {
+        spawn_live_classifier(
+            stderr_path.clone(),
+            live_counts_path.clone(),
+            stop_on_signatures.to_vec(),
+        )
+    });
+    let thread_sampler = spawn_thread_sampler(tracked_pid, threads_timeseries_path);
+    let pressure_sampler = spawn_pressure_sampler(pressure_timeseries_path);
+
+    // Wait for a bounded time for Edge to finish dumping the DOM.
+    let started = Instant::now();
+    let deadline = started + edge_watchdog;
+    let mut status = None;
+    let mut stopped_on: Option<String> = None;
+    let mut attach_at = strace_path
+        .as_ref()
+        .zip(strace_attach_after)
+        .map(|(p, after)| (p, started + after));
+    let mut attached: Option<std::process::Child> = None;
+    while Instant::now() < deadline {
+        if let Some(s) = child.try_wait().context("poll Edge")? {
+            status = Some(s);
+            break;
+        }
+        if let Some((p, at)) = attach_at {
+            if Instant::now() >= at {
+                attach_at = None;
+                attached =
+                    attach_strace(p, run_dir, strace_mode, tracked_pid, &strace_enabled_path);
+            }
+        }
+        if let Some(sig) = live.as_ref().and_then(|l| l.stop_hit()) {
+            stopped_on = Some(sig);
+            break;
+        }
+        std::thread::sleep(poll_interval);
+    }
+    // Stop sampling before any teardown so the series ends on the live process tree.
+    thread_sampler.finish();
+    if let Some(sampler) = pressure_sampler {
+        sampler.finish();
+    }
+
+    write_ps(&ps_path, tracked_pid).ok();
+    write_threads(&threads_path, tracked_pid).ok();
+
+    if let Some(sig) = &stopped_on {
+        // A requested signature showed up: this is an intentional early stop, not a hang,
+        // so skip the stuck snapshot and just tear the tree down.
+        let _ = fs::write(
+            &stop_signature_path,
+            format!(
+                "stopped_on_signature: {sig}\nelapsed_ms: {}\n",
+                started.elapsed().as_millis()
+            ),
+        );
+        if let Some(unit) = &guest_unit {
+            unit.kill();
+        }
+        #[cfg(unix)]
+        {
+            kill_process_tree(wrapper_pid, libc::SIGKILL, 4096);
+        }
+        let _ = child.kill();
+        status = child.wait().ok();
+    }
+
+    if status.is_none() {
+        // Capture a best-effort snapshot of what the process is doing before we kill it.
+        write_stuck_snapshot(&stuck_path, tracked_pid, snapshot_limits).ok();
+        if let Some(max_bytes) = snapshot_limits.capture_maps_max_bytes {
+            write_maps_capture(&maps_path, tracked_pid, max_bytes).ok();
+        }
+
+        // Keep runs bounded.
+        // Kill the strace wrapper's process tree to ensure Edge (and any children)
+        // are terminated as well.
+        if let Some(unit) = &guest_unit {
+            unit.kill();
+        }
+        #[cfg(unix)]
+        {
+            kill_process_tree(wrapper_pid, libc::SIGKILL, 4096);
+        }
+        let _ = child.kill();
+        status = child.wait().ok();
+    }
+
+    if let Some(mut tracer) = attached {
+        // Edge is gone by now, so strace normally exits on its own; SIGTERM detaches it
+        // otherwise (e.g. a guest systemd unit outlived our child).
+        #[cfg(unix)]
+        unsafe {
+            libc::kill(tracer.id() as i32, libc::SIGTERM);
+        }
+        let _ = tracer.wait();
+    }
+
+    if let Some(live) = live {
+        live.finish();
+    }
+
+    if let Some(before) = meminfo_before {
+        let after = read_text_best_effort(Path::new("/proc/meminfo"), 64 * 1024);
+        write_meminfo_delta(&meminfo_delta_path, &before, &after).ok();
+    }
+
+    let mut f = fs::File::create(&exit_path).context("write edge exit")?;
+    writeln!(
+        f,
+        "edge_exit: {}",
+        status
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    )?;
+    Ok(())
+}
+
+/// `strace` with the run's output prefix and `--strace-mode` trace set; the caller appends
+/// either the Edge command line or `-p <pid>`.
+fn strace_command(strace_path: &Path, run_dir: &Path, strace_mode: StraceMode) -> Command {
+    let mut c = Command::new(strace_path);
+    let trace_set = match strace_mode {
+        StraceMode::Minimal => {
+            "clone,clone3,mmap,mprotect,munmap,mremap,brk,futex,prlimit64,setrlimit"
+        }
+        StraceMode::Hang => "process,signal,network,ipc,desc,memory",
+    };
+    // NOTE: `-s 0` makes string output useless (empty/abbreviated).
+    // Use a moderate cap and `-v` so execve argv/etc. aren't shown as `[...]`.
+    let strace_string_limit = match strace_mode {
+        StraceMode::Minimal => "128",
+        StraceMode::Hang => "256",
+    };
+    c.arg("-ff")
+        .arg("-tt")
+        .arg("-T")
+        .arg("-s")
+        .arg(strace_string_limit)
+        .arg("-v")
+        .arg("-o")
+        .arg(run_dir.join("strace"))
+        .arg("-e")
+        .arg(format!("trace={trace_set}"));
+    c
+}
+
+/// Attaches `strace` to the live Edge process (`-ff` follows all of its existing threads and
+/// new children), writing the same `strace.<id>` files a wrapped run would. strace's own
+/// messages go to `strace.attach.log`; an strace that exits right away (e.g. ptrace refused)
+/// is recorded as an `attach_error:` in `strace.enabled.txt` rather than `attached_pid:`.
+fn attach_strace(
+    strace_path: &Path,
+    run_dir: &Path,
+    strace_mode: StraceMode,
+    pid: u32,
+    strace_enabled_path: &Path,
+) -> Option<std::process::Child> {
+    let mut c = strace_command(strace_path, run_dir, strace_mode);
+    c.arg("-p")
+        .arg(pid.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null());
+    let log_path = run_dir.join("strace.attach.log");
+    match fs::File::create(&log_path) {
+        Ok(log) => c.stderr(log),
+        Err(_) => c.stderr(Stdio::null()),
+    };
+    let (note, child) = match c.spawn() {
+        Ok(mut child) => {
+            std::thread::sleep(Duration::from_millis(200));
+            match child.try_wait() {
+                Ok(Some(status)) => (
+                    format!(
+                        "attach_error: strace exited ({status}) right after attaching to {pid}, \
+                         see {}\n",
+                        log_path.display()
+                    ),
+                    None,
+                ),
+                _ => (format!("attached_pid: {pid}\n"), Some(child)),
+            }
+        }
+        Err(e) => (format!("attach_error: {e}\n"), None),
+    };
+    if let Ok(mut f) = fs::OpenOptions::new()
+        .append(true)
+        .open(strace_enabled_path)
+    {
+        let _ = f.write_all(note.as_bytes());
+    }
+    child
+}
+
+/// Substring signatures counted in Edge stderr, keyed by their `summary.txt` name.
+const STDERR_SIGNATURES: &[(&str, &str)] = &[
+    ("stderr_ptrace_lines", "ptrace:"),
+    ("stderr_pthread_create_lines", "pthread_create"),
+    ("stderr_dbus_lines", DBUS_FAILURE_NEEDLE),
+    (
+        "stderr_ssl_client_socket_lines",
+        "ssl_client_socket_impl.cc:930",
+    ),
+    ("stderr_handshake_failed_lines", "handshake failed"),
+];
+
+/// Adds one to `counts[i]` for each needle `line` contains; shared by the live classifier and
+/// `analyze-run-dir --classifier-file`.
+fn tally_signatures<'a>(line: &str, needles: impl Iterator<Item = &'a str>, counts: &mut [u64]) {
+    for (i, needle) in needles.enumerate() {
+        if line.contains(needle) {
+            counts[i] += 1;
+        }
+    }
+}
+
+/// Parses a `--classifier-file` into `(key, needle)` signatures, in file order.
+fn parse_classifier_file(text: &str) -> Result<Vec<(String, String)>> {
+    let mut signatures: Vec<(String, String)> = Vec::new();
+    for (n, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, needle)) = line.split_once(':') else {
+            bail!("line {}: expected `key: needle`, got {raw:?}", n + 1);
+        };
+        let (key, needle) = (key.trim(), needle.trim());
+        if key.is_empty() || needle.is_empty() {
+            bail!("line {}: empty key or needle in {raw:?}", n + 1);
+        }
+        if signatures.iter().any(|(k, _)| k == key) {
+            bail!("line {}: duplicate key {key:?}", n + 1);
+        }
+        signatures.push((key.to_string(), needle.to_string()));
+    }
+    if signatures.is_empty() {
+        bail!("no signatures found");
+    }
+    Ok(signatures)
+}
+
+/// Counts `stderr_path` lines and, per signature, the lines containing its needle.
+fn classify_stderr_file(
+    stderr_path: &Path,
+    signatures: &[(String, String)],
+) -> Result<(u64, Vec<u64>)> {
+    let bytes = fs::read(stderr_path).with_context(|| format!("read {}", stderr_path.display()))?;
+    let mut lines = 0u64;
+    let mut counts = vec![0u64; signatures.len()];
+    for line in String::from_utf8_lossy(&bytes).lines() {
+        lines += 1;
+        tally_signatures(
+            line,
+            signatures.iter().map(|(_, n)| n.as_str()),
+            &mut counts,
+        );
+    }
+    Ok((lines, counts))
+}
+
+/// `analyze-run-dir --classifier-file` output: stderr line counts per classifier key.
+#[derive(Serialize, JsonSchema)]
+struct ClassifierCounts<'a> {
+    stderr: String,
+    classifier_file: String,
+    stderr_lines: u64,
+    /// In classifier file order.
+    #[schemars(with = "std::collections::BTreeMap<String, u64>")]
+    counts: KvObject<'a, u64>,
+}
+
+fn render_classifier_counts_json(
+    stderr_path: &Path,
+    classifier_file: &Path,
+    lines: u64,
+    signatures: &[(String, String)],
+    counts: &[u64],
+) -> String {
+    let counts: Vec<(String, u64)> = signatures
+        .iter()
+        .zip(counts)
+        .map(|((key, _), n)| (key.clone(), *n))
+        .collect();
+    let report = ClassifierCounts {
+        stderr: stderr_path.display().to_string(),
+        classifier_file: classifier_file.display().to_string(),
+        stderr_lines: lines,
+        counts: KvObject(&counts),
+    };
+    serde_json::to_string_pretty(&report).expect("classifier counts serialize")
+}
+
+struct LiveClassifier {
+    done: Arc<AtomicBool>,
+    stop_hit: Arc<Mutex<Option<String>>>,
+    handle: std::thread::JoinHandle<()>,
+}
+
+impl LiveClassifier {
+    fn stop_hit(&self) -> Option<String> {
+        self.stop_hit.lock().ok().and_then(|g| g.clone())
+    }
+
+    fn finish(self) {
+        self.done.store(true, Ordering::SeqCst);
+        let _ = self.handle.join();
+    }
+}
+
+fn spawn_live_classifier(
+    stderr_path: PathBuf,
+    counts_path: PathBuf,
+    stop_signatures: Vec<String>,
+) -> LiveClassifier {
+    let done = Arc::new(AtomicBool::new(false));
+    let stop_hit: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+
+    let thread_done = Arc::clone(&done);
+    let thread_stop_hit = Arc::clone(&stop_hit);
+    let handle = std::thread::spawn(move || {
+        use std::io::Read;
+
+        let mut file: Option<fs::File> = None;
+        let mut pending: Vec<u8> = Vec::new();
+        let mut lines_total = 0u64;
+        let mut counts = vec![0u64; STDERR_SIGNATURES.len()];
+        let mut last_write: Option<Instant> = None;
+        let mut buf = [0u8; 64 * 1024];
+
+        loop {
+            // Sample `done` before reading so the final pass sees everything Edge wrote.
+            let finished = thread_done.load(Ordering::SeqCst);
+
+            if file.is_none() {
+                file = fs::File::open(&stderr_path).ok();
+            }
+            if let Some(f) = file.as_mut() {
+                while let Ok(n) = f.read(&mut buf) {
+                    if n == 0 {
                         break;
                     }
-                    prev_dot = true;
-                } else {
-                    prev_dot = false;
+                    pending.extend_from_slice(&buf[..n]);
                 }
             }
-            if k.starts_with('.') || k.ends_with('.') {
-                valid = false;
+
+            // Only classify complete lines; keep a trailing partial line for the next pass
+            // (or flush it once Edge is gone).
+            let split_at = if finished {
+                pending.len()
+            } else {
+                pending
+                    .iter()
+                    .rposition(|b| *b == b'\n')
+                    .map(|i| i + 1)
+                    .unwrap_or(0)
+            };
+            let complete: Vec<u8> = pending.drain(..split_at).collect();
+            for line in String::from_utf8_lossy(&complete).lines() {
+                lines_total += 1;
+                tally_signatures(line, STDERR_SIGNATURES.iter().map(|(_, n)| *n), &mut counts);
+                if let Some(sig) = stop_signatures
+                    .iter()
+                    .find(|sig| line.contains(sig.as_str()))
+                {
+                    if let Ok(mut hit) = thread_stop_hit.lock() {
+                        hit.get_or_insert_with(|| sig.clone());
+                    }
+                }
             }
-            if !valid {
-                report.push_str(&format!("requested: {kv}\nresult: invalid (bad key)\n\n"));
-                continue;
+
+            if finished || last_write.is_none_or(|t| t.elapsed() >= Duration::from_secs(1)) {
+                let mut out = String::new();
+                out.push_str(&format!("date: {}\n", iso_now()));
+                out.push_str(&format!("stderr_lines: {lines_total}\n"));
+                for (i, (key, _)) in STDERR_SIGNATURES.iter().enumerate() {
+                    out.push_str(&format!("{key}: {}\n", counts[i]));
+                }
+                let hit = thread_stop_hit.lock().ok().and_then(|g| g.clone());
+                out.push_str(&format!(
+                    "stopped_on_signature: {}\n",
+                    hit.unwrap_or_else(|| "(none)".to_string())
+                ));
+                out.push_str(&format!("final: {}\n", if finished { "yes" } else { "no" }));
+                let _ = fs::write(&counts_path, out);
+                last_write = Some(Instant::now());
             }
 
-            let path = PathBuf::from("/proc/sys").join(k.replace('.', "/"));
-            let before = read_first_line_best_effort(&path);
-            let write_res = fs::write(&path, format!("{v}\n"));
-            let after = read_first_line_best_effort(&path);
+            if finished {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    });
 
-            report.push_str(&format!(
-                "requested: {k}={v}\npath: {}\nbefore: {before}\n",
-                path.display()
-            ));
-            match write_res {
-                Ok(_) => report.push_str("write: ok\n"),
-                Err(e) => report.push_str(&format!("write: error: {e}\n")),
+    LiveClassifier {
+        done,
+        stop_hit,
+        handle,
+    }
+}
+
+const THREAD_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+const THREAD_SAMPLE_MAX_PIDS: usize = 512;
+
+/// One `/proc` sample of the tracked process and its descendants.
+struct ThreadSample {
+    elapsed_ms: u128,
+    tracked_threads: u64,
+    tree_threads: u64,
+    tree_pids: usize,
+    /// Process in the tree with the most threads, as `comm(pid)=threads`.
+    top: String,
+}
+
+struct ThreadSampler {
+    done: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<()>,
+}
+
+impl ThreadSampler {
+    fn finish(self) {
+        self.done.store(true, Ordering::SeqCst);
+        let _ = self.handle.join();
+    }
+}
+
+/// Samples thread counts every `THREAD_SAMPLE_INTERVAL` until finished, then writes the time
+/// series (and its maxima) to `out_path`. Complements the one-shot `threads.txt`.
+fn spawn_thread_sampler(tracked_pid: u32, out_path: PathBuf) -> ThreadSampler {
+    let done = Arc::new(AtomicBool::new(false));
+    let thread_done = Arc::clone(&done);
+    let handle = std::thread::spawn(move || {
+        let started = Instant::now();
+        let mut samples = Vec::new();
+        loop {
+            // Sample `done` first so we always take one last sample after finish().
+            let finished = thread_done.load(Ordering::SeqCst);
+            if let Some(sample) = sample_thread_tree(tracked_pid, started) {
+                samples.push(sample);
+            }
+            if finished {
+                break;
+            }
+            let next = Instant::now() + THREAD_SAMPLE_INTERVAL;
+            while Instant::now() < next && !thread_done.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(25));
+            }
+        }
+        let _ = fs::write(&out_path, render_thread_timeseries(tracked_pid, &samples));
+    });
+    ThreadSampler { done, handle }
+}
+
+const PSI_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const PSI_RESOURCES: [&str; 3] = ["memory", "cpu", "io"];
+/// Peak keys written at the top of `pressure-timeseries.txt` and copied into the summary.
+const PSI_PEAK_KEYS: &[&str] = &[
+    "psi_memory_some_avg10_max",
+    "psi_memory_full_avg10_max",
+    "psi_cpu_some_avg10_max",
+    "psi_cpu_full_avg10_max",
+    "psi_io_some_avg10_max",
+    "psi_io_full_avg10_max",
+];
+
+/// `some` and `full` avg10 of one `/proc/pressure/<resource>` file (`full` is absent for
+/// cpu on older kernels).
+fn parse_psi_avg10(text: &str) -> (Option<f64>, Option<f64>) {
+    let avg10 = |kind: &str| {
+        text.lines()
+            .find_map(|l| l.strip_prefix(kind)?.strip_prefix(' '))?
+            .split_whitespace()
+            .find_map(|field| field.strip_prefix("avg10="))?
+            .parse()
+            .ok()
+    };
+    (avg10("some"), avg10("full"))
+}
+
+struct PressureSample {
+    elapsed_ms: u128,
+    /// `(some, full)` avg10 per entry of `PSI_RESOURCES`.
+    avg10: [(Option<f64>, Option<f64>); 3],
+}
+
+/// Samples guest PSI every `PSI_SAMPLE_INTERVAL` until finished, then writes the time series
+/// (and its peaks) to `out_path`. `None` when the kernel has no `/proc/pressure`.
+fn spawn_pressure_sampler(out_path: PathBuf) -> Option<ThreadSampler> {
+    if !Path::new("/proc/pressure/memory").exists() {
+        return None;
+    }
+    let done = Arc::new(AtomicBool::new(false));
+    let thread_done = Arc::clone(&done);
+    let handle = std::thread::spawn(move || {
+        let started = Instant::now();
+        let mut samples = Vec::new();
+        loop {
+            let finished = thread_done.load(Ordering::SeqCst);
+            samples.push(PressureSample {
+                elapsed_ms: started.elapsed().as_millis(),
+                avg10: PSI_RESOURCES.map(|r| {
+                    fs::read_to_string(format!("/proc/pressure/{r}"))
+                        .map(|text| parse_psi_avg10(&text))
+                        .unwrap_or_default()
+                }),
+            });
+            if finished {
+                break;
+            }
+            let next = Instant::now() + PSI_SAMPLE_INTERVAL;
+            while Instant::now() < next && !thread_done.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(25));
             }
-            report.push_str(&format!("after: {after}\n\n"));
         }
-        let _ = fs::write(&guest_sysctl_path, report);
+        let _ = fs::write(&out_path, render_pressure_timeseries(&samples));
+    });
+    Some(ThreadSampler { done, handle })
+}
+
+fn render_pressure_timeseries(samples: &[PressureSample]) -> String {
+    let fmt = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+    let mut out = String::new();
+    out.push_str(&format!(
+        "interval_ms: {}\n",
+        PSI_SAMPLE_INTERVAL.as_millis()
+    ));
+    out.push_str(&format!("samples: {}\n", samples.len()));
+    for (i, key) in PSI_PEAK_KEYS.iter().enumerate() {
+        let peak = samples
+            .iter()
+            .filter_map(|s| {
+                let (some, full) = s.avg10[i / 2];
+                if i % 2 == 0 {
+                    some
+                } else {
+                    full
+                }
+            })
+            .reduce(f64::max);
+        out.push_str(&format!(
+            "{key}: {}\n",
+            peak.map_or_else(|| "(no samples)".to_string(), |v| format!("{v:.2}"))
+        ));
+    }
+    out.push('\n');
+    out.push_str("elapsed_ms");
+    for r in PSI_RESOURCES {
+        out.push_str(&format!("\t{r}_some\t{r}_full"));
+    }
+    out.push('\n');
+    for s in samples {
+        out.push_str(&s.elapsed_ms.to_string());
+        for (some, full) in s.avg10 {
+            out.push_str(&format!("\t{}\t{}", fmt(some), fmt(full)));
+        }
+        out.push('\n');
     }
+    out
+}
 
-    let stdout_file = fs::File::create(&stdout_path).context("create stdout")?;
-    let stderr_file = fs::File::create(&stderr_path).context("create stderr")?;
+fn read_status_threads(pid: u32) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    status
+        .lines()
+        .find_map(|l| l.strip_prefix("Threads:"))
+        .and_then(|v| v.trim().parse().ok())
+}
 
-    // Optionally prefix Edge with strace.
-    let strace_enabled_path = run_dir.join("strace.enabled.txt");
-    let mut cmd = if strace {
-        match resolve_in_path("strace") {
-            Ok(p) => {
-                let _ = fs::write(
-                    &strace_enabled_path,
-                    format!("strace: yes\npath: {}\n", p.display()),
-                );
-                let mut c = Command::new(p);
-                let trace_set = match strace_mode {
-                    StraceMode::Minimal => {
-                        "clone,clone3,mmap,mprotect,munmap,mremap,brk,futex,prlimit64,setrlimit"
-                    }
-                    StraceMode::Hang => "process,signal,network,ipc,desc,memory",
-                };
-                // NOTE: `-s 0` makes string output useless (empty/abbreviated).
-                // Use a moderate cap and `-v` so execve argv/etc. aren't shown as `[...]`.
-                let strace_string_limit = match strace_mode {
-                    StraceMode::Minimal => "128",
-                    StraceMode::Hang => "256",
-                };
-                c.arg("-ff")
-                    .arg("-tt")
-                    .arg("-T")
-                    .arg("-s")
-                    .arg(strace_string_limit)
-                    .arg("-v")
-                    .arg("-o")
-                    .arg(run_dir.join("strace"))
-                    .arg("-e")
-                    .arg(format!("trace={trace_set}"))
-                    .arg(edge_bin);
-                c
-            }
-            Err(e) => {
-                let _ = fs::write(
-                    &strace_enabled_path,
-                    format!("strace: requested but not available ({e})\n"),
-                );
-                Command::new(edge_bin)
-            }
-        }
-    } else {
-        Command::new(edge_bin)
-    };
+fn sample_thread_tree(root_pid: u32, started: Instant) -> Option<ThreadSample> {
+    let tracked_threads = read_status_threads(root_pid)?;
+    let elapsed_ms = started.elapsed().as_millis();
 
-    // Apply requested environment variables. This sets the env for the direct Edge process
-    // and also works when wrapped in strace (Edge inherits strace's environment).
-    for kv in edge_env {
-        let Some((k, v)) = kv.split_once('=') else {
-            bail!("invalid --edge-env value (expected KEY=VALUE): {kv}");
+    let mut tree_threads = 0u64;
+    let mut tree_pids = 0usize;
+    let mut top = (0u64, root_pid);
+    for pid in process_tree(root_pid, usize::MAX, THREAD_SAMPLE_MAX_PIDS) {
+        let Some(threads) = read_status_threads(pid) else {
+            continue;
         };
-        if k.is_empty() {
-            bail!("invalid --edge-env value (empty KEY): {kv}");
+        tree_pids += 1;
+        tree_threads += threads;
+        if threads > top.0 {
+            top = (threads, pid);
         }
-        cmd.env(k, v);
     }
 
-    // Use newer headless implementation to avoid legacy headless limitations.
-    let mut child = cmd
-        .arg(match headless_impl {
-            HeadlessImpl::New => "--headless",
-            HeadlessImpl::Old => "--headless=old",
-        })
-        .arg("--disable-gpu")
-        .arg("--no-first-run")
-        .arg("--no-default-browser-check")
-        // Avoid keychain prompts during repeated headless runs.
-        .arg("--password-store=basic")
-        .arg("--use-mock-keychain")
-        .arg("--disable-extensions")
-        .arg("--disable-component-extensions-with-background-pages")
-        .arg("--disable-dev-shm-usage")
-        .arg("--disable-breakpad")
-        .arg("--disable-crash-reporter")
-        .arg("--no-crash-upload")
-        .arg("--disable-features=Crashpad")
-        .arg(format!("--user-data-dir={}", profile_dir.display()))
-        .args(edge_args)
-        .arg("--dump-dom")
-        .arg(url)
-        .stdin(Stdio::null())
-        .stdout(stdout_file)
-        .stderr(stderr_file)
-        .spawn()
-        .context("spawn Edge")?;
+    let (top_threads, top_pid) = top;
+    let top_comm = read_proc_comm(top_pid).unwrap_or_else(|| "(unknown)".to_string());
+    Some(ThreadSample {
+        elapsed_ms,
+        tracked_threads,
+        tree_threads,
+        tree_pids,
+        top: format!("{top_comm}({top_pid})={top_threads}"),
+    })
+}
 
-    let pid = child.id();
+fn render_thread_timeseries(tracked_pid: u32, samples: &[ThreadSample]) -> String {
+    let max_tracked = samples.iter().map(|s| s.tracked_threads).max();
+    let peak = samples.iter().max_by_key(|s| s.tree_threads);
+    let fmt = |v: Option<String>| v.unwrap_or_else(|| "(no samples)".to_string());
 
-    // When wrapping Edge in `strace`, `child.id()` is the `strace` PID (not Edge).
-    // For artifacts (ps/threads/stuck), we want the actual Edge/browser PID.
-    let wrapper_pid = pid;
-    let tracked_pid = if strace {
-        let start = Instant::now();
-        let deadline = start + Duration::from_secs(2);
-        let mut edge_pid = None;
-        while Instant::now() < deadline {
-            if let Ok(children) = pids_by_ppid(wrapper_pid) {
-                if let Some(p) = children.first().copied() {
-                    edge_pid = Some(p);
-                    break;
-                }
+    let mut out = String::new();
+    out.push_str(&format!("tracked_pid: {tracked_pid}\n"));
+    out.push_str(&format!(
+        "interval_ms: {}\n",
+        THREAD_SAMPLE_INTERVAL.as_millis()
+    ));
+    out.push_str(&format!("samples: {}\n", samples.len()));
+    out.push_str(&format!(
+        "max_tracked_threads: {}\n",
+        fmt(max_tracked.map(|v| v.to_string()))
+    ));
+    out.push_str(&format!(
+        "max_threads_observed: {}\n",
+        fmt(peak.map(|s| s.tree_threads.to_string()))
+    ));
+    out.push_str(&format!(
+        "max_threads_observed_at_ms: {}\n",
+        fmt(peak.map(|s| s.elapsed_ms.to_string()))
+    ));
+    out.push('\n');
+    out.push_str("elapsed_ms\ttracked_threads\ttree_threads\ttree_pids\ttop\n");
+    for s in samples {
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            s.elapsed_ms, s.tracked_threads, s.tree_threads, s.tree_pids, s.top
+        ));
+    }
+    out
+}
+
+/// Transient guest-side systemd unit Edge runs in for `--guest-systemd-tasks-max`.
+struct GuestSystemdUnit {
+    systemd_run: PathBuf,
+    unit: String,
+    tasks_max: u64,
+    nofile_soft: Option<u64>,
+}
+
+impl GuestSystemdUnit {
+    /// Re-targets `cmd` through `systemd-run`. The unit does not inherit our environment, so
+    /// every variable is forwarded with `-E` (explicit `cmd` envs, i.e. `--edge-env`, win).
+    fn wrap(&self, cmd: &Command) -> Command {
+        let mut wrapped = Command::new(&self.systemd_run);
+        wrapped
+            .arg("--user")
+            // `--pipe`, not `--pty`: a pty would merge Edge's stderr into stdout.txt (with CRLFs)
+            // and leave stderr.txt empty for the classifiers.
+            .arg("--pipe")
+            .arg("--wait")
+            .arg("--collect")
+            .arg("--quiet")
+            .arg(format!("--unit={}", self.unit))
+            .arg("-p")
+            .arg(format!("TasksMax={}", self.tasks_max))
+            .arg("--same-dir");
+        if let Some(soft) = self.nofile_soft {
+            wrapped.arg("-p").arg(format!("LimitNOFILESoft={soft}"));
+        }
+        let explicit: HashSet<&std::ffi::OsStr> = cmd.get_envs().map(|(k, _)| k).collect();
+        for (k, _) in std::env::vars_os() {
+            if !explicit.contains(k.as_os_str()) {
+                wrapped.arg("-E").arg(k);
             }
-            std::thread::sleep(Duration::from_millis(10));
         }
-        edge_pid.unwrap_or(wrapper_pid)
-    } else {
-        wrapper_pid
-    };
+        for (k, v) in cmd.get_envs() {
+            if let Some(v) = v {
+                let mut kv = k.to_os_string();
+                kv.push("=");
+                kv.push(v);
+                wrapped.arg("-E").arg(kv);
+            }
+        }
+        wrapped
+            .arg("--")
+            .arg(cmd.get_program())
+            .args(cmd.get_args());
+        wrapped
+    }
 
-    let _ = fs::write(
-        &pid_path,
-        format!(
-            "wrapper_pid={wrapper_pid}\ntracked_pid={tracked_pid}\nwrapped_in_strace={}\n",
-            if strace { "yes" } else { "no" }
-        ),
-    );
+    fn show(&self, property: &str) -> Option<String> {
+        let out = Command::new("systemctl")
+            .args(["--user", "show", "-p", property, "--value", &self.unit])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        let v = String::from_utf8_lossy(&out.stdout).trim().to_string();
+        (out.status.success() && !v.is_empty()).then_some(v)
+    }
 
-    // Wait for a bounded time for Edge to finish dumping the DOM.
-    let deadline = Instant::now() + edge_watchdog;
-    let mut status = None;
-    while Instant::now() < deadline {
-        if let Some(s) = child.try_wait().context("poll Edge")? {
-            status = Some(s);
-            break;
+    fn wait_main_pid(&self, timeout: Duration) -> Option<u32> {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if let Some(pid) = self
+                .show("MainPID")
+                .and_then(|v| v.parse::<u32>().ok())
+                .filter(|p| *p != 0)
+            {
+                return Some(pid);
+            }
+            std::thread::sleep(Duration::from_millis(20));
         }
-        std::thread::sleep(Duration::from_millis(50));
+        None
     }
 
-    write_ps(&ps_path, tracked_pid).ok();
-    write_threads(&threads_path, tracked_pid).ok();
+    fn control_group(&self) -> Option<String> {
+        self.show("ControlGroup")
+    }
 
-    if status.is_none() {
-        // Capture a best-effort snapshot of what the process is doing before we kill it.
-        write_stuck_snapshot(&stuck_path, tracked_pid).ok();
+    /// Killing `systemd-run` does not stop the unit, so SIGKILL the unit itself.
+    fn kill(&self) {
+        let _ = Command::new("systemctl")
+            .args(["--user", "kill", "--signal=SIGKILL", &self.unit])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
 
-        // Keep runs bounded.
-        // Kill the strace wrapper's process tree to ensure Edge (and any children)
-        // are terminated as well.
-        #[cfg(unix)]
-        {
-            kill_process_tree(wrapper_pid, libc::SIGKILL, 4096);
+fn append_preflight(preflight_path: &Path, text: &str) {
+    if let Ok(mut f) = fs::OpenOptions::new().append(true).open(preflight_path) {
+        let _ = f.write_all(text.as_bytes());
+    }
+}
+
+fn parse_meminfo_kb(meminfo: &str, key: &str) -> Option<u64> {
+    // Lines look like: "MemAvailable:    1234567 kB"
+    for line in meminfo.lines() {
+        let Some((k, v)) = line.split_once(':') else {
+            continue;
+        };
+        if k.trim() == key {
+            return v.split_whitespace().next()?.parse().ok();
         }
-        let _ = child.kill();
-        status = child.wait().ok();
     }
+    None
+}
 
-    let mut f = fs::File::create(&exit_path).context("write edge exit")?;
-    writeln!(
-        f,
-        "edge_exit: {}",
-        status
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| "unknown".to_string())
-    )?;
-    Ok(())
+fn write_meminfo_delta(path: &Path, before: &str, after: &str) -> Result<()> {
+    let mut out = String::new();
+    out.push_str(&format!("date: {}\n", iso_now()));
+    for (meminfo_key, prefix) in [
+        ("MemAvailable", "meminfo_memavailable"),
+        ("MemFree", "meminfo_memfree"),
+        ("Committed_AS", "committed_as"),
+    ] {
+        let b = parse_meminfo_kb(before, meminfo_key);
+        let a = parse_meminfo_kb(after, meminfo_key);
+        let fmt = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_else(|| "?".into());
+        out.push_str(&format!("{prefix}_before_kb: {}\n", fmt(b)));
+        out.push_str(&format!("{prefix}_after_kb: {}\n", fmt(a)));
+        match (b, a) {
+            (Some(b), Some(a)) => {
+                out.push_str(&format!("{prefix}_delta_kb: {}\n", a as i64 - b as i64))
+            }
+            _ => out.push_str(&format!("{prefix}_delta_kb: ?\n")),
+        }
+    }
+    fs::write(path, out).context("write meminfo delta")
 }
 
 fn parse_cgroup_v2_relative_path(proc_self_cgroup: &str) -> Option<String> {
@@ -1764,76 +6351,260 @@ fn parse_bracket_pid_tid(line: &str) -> Option<(u32, u32)> {
             break;
         }
     }
-    if !saw_tid {
-        return None;
+    if !saw_tid {
+        return None;
+    }
+    Some((pid, tid))
+}
+
+fn unique_pids(ids: &[(u32, u32)]) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for (pid, _tid) in ids {
+        if seen.insert(*pid) {
+            out.push(*pid);
+        }
+    }
+    out
+}
+
+fn pick_strace_path(run_dir: &Path, pid: u32, tid: u32) -> Option<(PathBuf, String)> {
+    // Prefer thread ID (strace -ff usually keys files by tid), but keep compatibility
+    // with either `strace.<pid>` or host-side `host.strace.<pid>`.
+    let candidates: [(u32, &str); 2] = [(tid, "tid"), (pid, "pid")];
+    for (ident, kind) in candidates {
+        for prefix in ["strace.", "host.strace."] {
+            let p = run_dir.join(format!("{prefix}{ident}"));
+            if p.is_file() || gz_sibling(&p).is_file() {
+                return Some((p, format!("matched {kind}={ident}")));
+            }
+        }
+    }
+    None
+}
+
+fn extract_hex_after_equals(line: &str) -> Option<String> {
+    // Example: mmap(...)= 0x7fffdfea0000
+    let eq = line.rfind("=")?;
+    let tail = line[eq + 1..].trim_start();
+    if !tail.starts_with("0x") {
+        return None;
+    }
+    let mut end = 2;
+    for c in tail[2..].chars() {
+        if c.is_ascii_hexdigit() {
+            end += c.len_utf8();
+        } else {
+            break;
+        }
+    }
+    Some(tail[..end].to_string())
+}
+
+/// Writes the report to `report_path` unless it's `None` (compare-run-dirs only wants the
+/// counts and leaves the run dirs untouched).
+fn analyze_pthread_stack_mprotect_enomem(
+    run_dir: &Path,
+    stderr_path: &Path,
+    report_path: Option<&Path>,
+) -> Result<PthreadStackAnalysis> {
+    let stderr = read_artifact(stderr_path).unwrap_or_default();
+    let mut ids: Vec<(u32, u32)> = Vec::new();
+    let mut seen = HashSet::new();
+    for line in stderr.lines() {
+        if !line.contains("pthread_create") {
+            continue;
+        }
+        if let Some((pid, tid)) = parse_bracket_pid_tid(line) {
+            if seen.insert((pid, tid)) {
+                ids.push((pid, tid));
+            }
+        }
+    }
+    let pids = unique_pids(&ids);
+
+    let mut report = String::new();
+    report.push_str("pthread_ids_from_stderr: ");
+    if ids.is_empty() {
+        report.push_str("(none)\n");
+    } else {
+        report.push_str(
+            &ids.iter()
+                .map(|(pid, tid)| format!("{pid}:{tid}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        report.push('\n');
+    }
+    report.push_str("pthread_pids_from_stderr: ");
+    if pids.is_empty() {
+        report.push_str("(none)\n");
+    } else {
+        report.push_str(
+            &pids
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        report.push('\n');
+    }
+
+    let mut events_total: u64 = 0;
+    for (pid, tid) in &ids {
+        report.push_str(&format!("\n== pid {pid} tid {tid} ==\n"));
+        let Some((strace_path, match_note)) = pick_strace_path(run_dir, *pid, *tid) else {
+            report.push_str("strace: (missing)\n");
+            continue;
+        };
+        report.push_str(&format!(
+            "strace: {} ({match_note})\n",
+            strace_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        ));
+
+        let text = read_artifact(&strace_path).unwrap_or_default();
+        let pid_events = scan_stack_mprotect_enomem(&text, &mut report);
+        events_total += pid_events;
+
+        report.push_str(&format!("stack_mprotect_enomem_events: {pid_events}\n"));
+    }
+
+    report.push_str(&format!(
+        "\nstack_mprotect_enomem_events_total: {events_total}\n"
+    ));
+
+    if let Some(report_path) = report_path {
+        fs::write(report_path, report).context("write pthread stack report")?;
     }
-    Some((pid, tid))
+
+    Ok(PthreadStackAnalysis {
+        pthread_ids: ids,
+        pthread_pids: pids,
+        events_total,
+    })
 }
 
-fn unique_pids(ids: &[(u32, u32)]) -> Vec<u32> {
-    let mut seen = HashSet::new();
-    let mut out = Vec::new();
-    for (pid, _tid) in ids {
-        if seen.insert(*pid) {
-            out.push(*pid);
-        }
-    }
-    out
+/// Edge stderr line marking a failed DBus session bus connection.
+const DBUS_FAILURE_NEEDLE: &str = "Failed to connect to the bus";
+
+#[derive(Debug, Default)]
+struct DbusFailureAnalysis {
+    total: u64,
+    pids: Vec<u32>,
+    first_line: Option<String>,
+    last_line: Option<String>,
+    /// Seconds from the first timestamped stderr line (roughly Edge's start) to the first
+    /// timestamped bus failure.
+    first_offset_seconds: Option<f64>,
 }
 
-fn pick_strace_path(run_dir: &Path, pid: u32, tid: u32) -> Option<(PathBuf, String)> {
-    // Prefer thread ID (strace -ff usually keys files by tid), but keep compatibility
-    // with either `strace.<pid>` or host-side `host.strace.<pid>`.
-    let candidates: [(u32, &str); 2] = [(tid, "tid"), (pid, "pid")];
-    for (ident, kind) in candidates {
-        for prefix in ["strace.", "host.strace."] {
-            let p = run_dir.join(format!("{prefix}{ident}"));
-            if p.is_file() {
-                return Some((p, format!("matched {kind}={ident}")));
-            }
+impl DbusFailureAnalysis {
+    /// `none`, `one-shot` (a single failure) or `repeating` (a storm).
+    fn pattern(&self) -> &'static str {
+        match self.total {
+            0 => "none",
+            1 => "one-shot",
+            _ => "repeating",
         }
     }
-    None
 }
 
-fn extract_hex_after_equals(line: &str) -> Option<String> {
-    // Example: mmap(...)= 0x7fffdfea0000
-    let eq = line.rfind("=")?;
-    let tail = line[eq + 1..].trim_start();
-    if !tail.starts_with("0x") {
+/// Seconds since midnight of a Chromium `[PID:TID:MMDD/HHMMSS.ffffff:...]` log prefix.
+fn parse_bracket_timestamp(line: &str) -> Option<f64> {
+    let start = line.find('[')?;
+    let inner = &line[start + 1..];
+    let inner = &inner[..inner.find(']')?];
+    let (_, time) = inner.split(':').nth(2)?.split_once('/')?;
+    let (hms, frac) = time.split_once('.').unwrap_or((time, "0"));
+    if hms.len() != 6 || !hms.bytes().all(|b| b.is_ascii_digit()) {
         return None;
     }
-    let mut end = 2;
-    for c in tail[2..].chars() {
-        if c.is_ascii_hexdigit() {
-            end += c.len_utf8();
-        } else {
-            break;
-        }
-    }
-    Some(tail[..end].to_string())
+    let field = |i: usize| hms[i..i + 2].parse::<u32>().ok();
+    let seconds = field(0)? * 3600 + field(2)? * 60 + field(4)?;
+    let frac: f64 = format!("0.{frac}").parse().ok()?;
+    Some(f64::from(seconds) + frac)
 }
 
-fn analyze_pthread_stack_mprotect_enomem(
-    run_dir: &Path,
-    stderr_path: &Path,
-    report_path: &Path,
-) -> Result<PthreadStackAnalysis> {
-    let stderr = fs::read_to_string(stderr_path).unwrap_or_default();
-    let mut ids: Vec<(u32, u32)> = Vec::new();
-    let mut seen = HashSet::new();
+/// Scans Edge stderr for DBus bus connection failures: how many, from which pids, and when
+/// the first one appeared. Writes the findings to `report_path`.
+fn analyze_dbus_failures(stderr_path: &Path, report_path: &Path) -> Result<DbusFailureAnalysis> {
+    let stderr = read_artifact(stderr_path).unwrap_or_default();
+    let mut analysis = DbusFailureAnalysis::default();
+    let mut ids = Vec::new();
+    let mut start_ts = None;
+    let mut first_ts = None;
     for line in stderr.lines() {
-        if !line.contains("pthread_create") {
+        let ts = parse_bracket_timestamp(line);
+        if start_ts.is_none() {
+            start_ts = ts;
+        }
+        if !line.contains(DBUS_FAILURE_NEEDLE) {
             continue;
         }
-        if let Some((pid, tid)) = parse_bracket_pid_tid(line) {
-            if seen.insert((pid, tid)) {
-                ids.push((pid, tid));
-            }
+        analysis.total += 1;
+        let Some(id) = parse_bracket_pid_tid(line) else {
+            continue;
+        };
+        ids.push(id);
+        if analysis.first_line.is_none() {
+            analysis.first_line = Some(line.to_string());
+            first_ts = ts;
         }
+        analysis.last_line = Some(line.to_string());
+    }
+    analysis.pids = unique_pids(&ids);
+    if let (Some(start), Some(first)) = (start_ts, first_ts) {
+        // Prefixes carry no date past MMDD; a negative offset means the run crossed midnight.
+        let offset = first - start;
+        analysis.first_offset_seconds = Some(if offset < 0.0 {
+            offset + 86400.0
+        } else {
+            offset
+        });
     }
-    let pids = unique_pids(&ids);
 
+    let mut report = String::new();
+    report.push_str(&format!("dbus_failures_total: {}\n", analysis.total));
+    report.push_str(&format!("dbus_failure_pattern: {}\n", analysis.pattern()));
+    report.push_str(&format!(
+        "dbus_failure_pids: {}\n",
+        if analysis.pids.is_empty() {
+            "(none)".to_string()
+        } else {
+            analysis
+                .pids
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    ));
+    report.push_str(&format!(
+        "dbus_first_offset_seconds: {}\n",
+        analysis
+            .first_offset_seconds
+            .map(|s| format!("{s:.3}"))
+            .unwrap_or_else(|| "(unavailable)".to_string())
+    ));
+    report.push_str(&format!(
+        "dbus_first_line: {}\n",
+        analysis.first_line.as_deref().unwrap_or("(none)")
+    ));
+    report.push_str(&format!(
+        "dbus_last_line: {}\n",
+        analysis.last_line.as_deref().unwrap_or("(none)")
+    ));
+    fs::write(report_path, report).context("write dbus failures report")?;
+    Ok(analysis)
+}
+
+/// Finds thread-stack setups in one strace file: an `mmap(..., MAP_STACK)` followed within 250
+/// lines by an RW `mprotect` of (part of) that mapping failing with ENOMEM. Appends each event
+/// with context to `report` and returns the count. Works on any strace output; pids aren't used.
+fn scan_stack_mprotect_enomem(text: &str, report: &mut String) -> u64 {
     fn parse_u64_hex(s: &str) -> Option<u64> {
         let t = s.trim();
         let t = t.strip_prefix("0x").unwrap_or(t);
@@ -1878,114 +6649,65 @@ fn analyze_pthread_stack_mprotect_enomem(
         Some((addr, len))
     }
 
-    let mut report = String::new();
-    report.push_str("pthread_ids_from_stderr: ");
-    if ids.is_empty() {
-        report.push_str("(none)\n");
-    } else {
-        report.push_str(
-            &ids.iter()
-                .map(|(pid, tid)| format!("{pid}:{tid}"))
-                .collect::<Vec<_>>()
-                .join(" "),
-        );
-        report.push('\n');
-    }
-    report.push_str("pthread_pids_from_stderr: ");
-    if pids.is_empty() {
-        report.push_str("(none)\n");
-    } else {
-        report.push_str(
-            &pids
-                .iter()
-                .map(|v| v.to_string())
-                .collect::<Vec<_>>()
-                .join(" "),
-        );
-        report.push('\n');
-    }
+    let lines: Vec<&str> = text.lines().collect();
+    let mut events: u64 = 0;
 
-    let mut events_total: u64 = 0;
-    for (pid, tid) in &ids {
-        report.push_str(&format!("\n== pid {pid} tid {tid} ==\n"));
-        let Some((strace_path, match_note)) = pick_strace_path(run_dir, *pid, *tid) else {
-            report.push_str("strace: (missing)\n");
+    for (i, line) in lines.iter().enumerate() {
+        let Some((mmap_base, mmap_len)) = parse_strace_mmap_stack(line) else {
             continue;
         };
-        report.push_str(&format!(
-            "strace: {} ({match_note})\n",
-            strace_path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-        ));
-
-        let text = fs::read_to_string(&strace_path).unwrap_or_default();
-        let lines: Vec<&str> = text.lines().collect();
-        let mut pid_events: u64 = 0;
+        let mmap_end = mmap_base.saturating_add(mmap_len);
 
-        for (i, line) in lines.iter().enumerate() {
-            let Some((mmap_base, mmap_len)) = parse_strace_mmap_stack(line) else {
+        let end = (i + 250).min(lines.len());
+        for j in (i + 1)..end {
+            let l = lines[j];
+            let Some((mp_addr, mp_len)) = parse_strace_mprotect_enomem(l) else {
                 continue;
             };
-            let mmap_end = mmap_base.saturating_add(mmap_len);
-
-            let end = (i + 250).min(lines.len());
-            for j in (i + 1)..end {
-                let l = lines[j];
-                let Some((mp_addr, mp_len)) = parse_strace_mprotect_enomem(l) else {
-                    continue;
-                };
-                let mp_end = mp_addr.saturating_add(mp_len);
-
-                // Typical stack setup: mmap(PROT_NONE, MAP_STACK) returns base,
-                // then mprotect(base + page_size, len - page_size, RW) to leave a guard page.
-                // Don't require exact base address match; accept any mprotect range that falls
-                // within the mapping.
-                let within_mapping = mp_addr >= mmap_base && mp_end <= mmap_end;
-                let page_size: u64 = 4096;
-                let guard_page_shape = mp_addr == mmap_base.saturating_add(page_size)
-                    && (mp_len == mmap_len.saturating_sub(page_size)
-                        || mp_len == mmap_len.saturating_sub(page_size * 2));
-
-                if within_mapping || guard_page_shape {
-                    pid_events += 1;
-                    events_total += 1;
-                    report.push_str(&format!(
-                        "\n-- stack mprotect ENOMEM event #{pid_events} --\n"
-                    ));
-                    report.push_str(&format!(
-                        "mmap_base: 0x{mmap_base:x} mmap_len: {mmap_len} mmap_end: 0x{mmap_end:x}\n"
-                    ));
-                    report.push_str(&format!(
-                        "mprotect_addr: 0x{mp_addr:x} mprotect_len: {mp_len} mprotect_end: 0x{mp_end:x}\n"
-                    ));
+            let mp_end = mp_addr.saturating_add(mp_len);
+
+            // Typical stack setup: mmap(PROT_NONE, MAP_STACK) returns base,
+            // then mprotect(base + page_size, len - page_size, RW) to leave a guard page.
+            // Don't require exact base address match; accept any mprotect range that falls
+            // within the mapping.
+            let within_mapping = mp_addr >= mmap_base && mp_end <= mmap_end;
+            let page_size: u64 = 4096;
+            let guard_page_shape = mp_addr == mmap_base.saturating_add(page_size)
+                && (mp_len == mmap_len.saturating_sub(page_size)
+                    || mp_len == mmap_len.saturating_sub(page_size * 2));
+
+            if within_mapping || guard_page_shape {
+                events += 1;
+                report.push_str(&format!("\n-- stack mprotect ENOMEM event #{events} --\n"));
+                report.push_str(&format!(
+                    "mmap_base: 0x{mmap_base:x} mmap_len: {mmap_len} mmap_end: 0x{mmap_end:x}\n"
+                ));
+                report.push_str(&format!(
+                    "mprotect_addr: 0x{mp_addr:x} mprotect_len: {mp_len} mprotect_end: 0x{mp_end:x}\n"
+                ));
 
-                    let lo = j.saturating_sub(5);
-                    let hi = (j + 4).min(lines.len());
-                    for ctx in &lines[lo..hi] {
-                        report.push_str(ctx);
-                        report.push('\n');
-                    }
-                    break;
+                let lo = j.saturating_sub(5);
+                let hi = (j + 4).min(lines.len());
+                for ctx in &lines[lo..hi] {
+                    report.push_str(ctx);
+                    report.push('\n');
                 }
+                break;
             }
         }
-
-        report.push_str(&format!("stack_mprotect_enomem_events: {pid_events}\n"));
     }
 
-    report.push_str(&format!(
-        "\nstack_mprotect_enomem_events_total: {events_total}\n"
-    ));
-
-    fs::write(report_path, report).context("write pthread stack report")?;
+    events
+}
 
-    Ok(PthreadStackAnalysis {
-        pthread_ids: ids,
-        pthread_pids: pids,
-        events_total,
-    })
+fn run_analyze_strace(strace_file: &Path) -> Result<()> {
+    let text =
+        read_artifact(strace_file).with_context(|| format!("read {}", strace_file.display()))?;
+    let mut report = format!("strace: {}\n", strace_file.display());
+    let events = scan_stack_mprotect_enomem(&text, &mut report);
+    report.push_str(&format!("\nstack_mprotect_enomem_events_total: {events}\n"));
+    print!("{report}");
+    Ok(())
 }
 
 fn run_cmd_best_effort(program: &str, args: &[&str], max_bytes: usize) -> String {
@@ -2045,14 +6767,28 @@ fn sample_and_count_lines(path: &Path, sample: usize) -> String {
     }
 }
 
-fn write_stuck_snapshot(path: &Path, pid: u32) -> Result<()> {
-    write_stuck_snapshot_named(path, pid, "edge")
+fn write_stuck_snapshot(path: &Path, pid: u32, limits: SnapshotLimits) -> Result<()> {
+    write_stuck_snapshot_named(path, pid, "edge", limits)
+}
+
+fn write_stuck_snapshot_named(
+    path: &Path,
+    pid: u32,
+    label: &str,
+    limits: SnapshotLimits,
+) -> Result<()> {
+    fs::write(path, render_stuck_snapshot(pid, label, limits)).context("write stuck snapshot")
 }
 
-fn write_stuck_snapshot_named(path: &Path, pid: u32, label: &str) -> Result<()> {
+fn render_stuck_snapshot(pid: u32, label: &str, limits: SnapshotLimits) -> String {
     let mut out = String::new();
     out.push_str("### stuck snapshot\n");
     out.push_str(&format!("pid: {pid}\n"));
+    let (abi, abi_source) = SyscallAbi::detect(pid);
+    out.push_str(&format!(
+        "syscall_abi: {} (from {abi_source})\n",
+        abi.name()
+    ));
     out.push_str(&format!("date: {}\n\n", iso_now()));
 
     // Time series: take two close snapshots to distinguish "stuck but progressing" from
@@ -2066,11 +6802,11 @@ fn write_stuck_snapshot_named(path: &Path, pid: u32, label: &str) -> Result<()>
         }
     }
 
-    snapshot_proc(&mut out, pid, &format!("{label}_t0"));
+    snapshot_proc(&mut out, pid, &format!("{label}_t0"), limits);
     let parent_pid = read_parent_pid(pid).filter(|ppid| *ppid > 1 && *ppid != pid);
     if let Some(ppid) = parent_pid {
         out.push_str(&format!("\n--- {label}_parent (ppid={ppid}) ---\n"));
-        snapshot_proc(&mut out, ppid, &format!("{label}_parent"));
+        snapshot_proc(&mut out, ppid, &format!("{label}_parent"), limits);
     }
 
     // Compact, side-by-side view for upstream/debugging: shows whether the target and its
@@ -2079,7 +6815,7 @@ fn write_stuck_snapshot_named(path: &Path, pid: u32, label: &str) -> Result<()>
     append_job_control_compare(&mut out, pid, parent_pid);
     out.push_str(&format!("\n--- {label}_timeseries_sleep_ms: 250 ---\n"));
     std::thread::sleep(Duration::from_millis(250));
-    snapshot_proc(&mut out, pid, &format!("{label}_t1"));
+    snapshot_proc(&mut out, pid, &format!("{label}_t1"), limits);
 
     // After t1 snapshot, emit a compact diff-like summary for the writer PIDs we identified at t0.
     if !writer_pids_t0.is_empty() {
@@ -2129,22 +6865,26 @@ fn write_stuck_snapshot_named(path: &Path, pid: u32, label: &str) -> Result<()>
     if let Ok(children) = pids_by_ppid(pid) {
         for (i, child_pid) in children.into_iter().take(3).enumerate() {
             out.push_str(&format!("\n--- child[{i}] ---\n"));
-            snapshot_proc(&mut out, child_pid, "child");
+            snapshot_proc(&mut out, child_pid, "child", limits);
         }
     }
 
-    fs::write(path, out).context("write stuck snapshot")
+    out
 }
 
 struct ObservedRun {
     exit_code: i32,
     timed_out: bool,
+    /// How a timed-out child went away: `none` (no timeout), `exited_in_grace` after SIGTERM,
+    /// or `sigkill` once the grace window ran out.
+    timeout_kill: &'static str,
 }
 
 fn run_command_inherit_tty_observed(
     args: &[String],
     log_path: &Path,
     timeout: Duration,
+    watchdog: Watchdog,
     snapshot_at: Option<Duration>,
     on_snapshot: &dyn Fn(libc::pid_t),
 ) -> Result<ObservedRun> {
@@ -2184,6 +6924,7 @@ fn run_command_inherit_tty_observed(
     let start = Instant::now();
     let mut did_snapshot = false;
     let mut timed_out = false;
+    let mut timeout_kill = "none";
     let exit_code;
     loop {
         if let Ok(Some(code)) = waitpid_nonblocking(pid) {
@@ -2207,14 +6948,16 @@ fn run_command_inherit_tty_observed(
             kill_process_tree(pid as u32, libc::SIGTERM, 2048);
             let grace_start = Instant::now();
             let mut code: Option<i32> = None;
-            while grace_start.elapsed() < Duration::from_millis(500) {
+            timeout_kill = "exited_in_grace";
+            while grace_start.elapsed() < watchdog.kill_grace {
                 if let Ok(Some(c)) = waitpid_nonblocking(pid) {
                     code = Some(c);
                     break;
                 }
-                std::thread::sleep(Duration::from_millis(20));
+                std::thread::sleep(watchdog.poll_interval);
             }
             if code.is_none() {
+                timeout_kill = "sigkill";
                 kill_process_tree(pid as u32, libc::SIGKILL, 2048);
                 code = waitpid_blocking(pid).ok();
             }
@@ -2222,12 +6965,13 @@ fn run_command_inherit_tty_observed(
             break;
         }
 
-        std::thread::sleep(Duration::from_millis(20));
+        std::thread::sleep(watchdog.poll_interval);
     }
 
     Ok(ObservedRun {
         exit_code,
         timed_out,
+        timeout_kill,
     })
 }
 
@@ -2235,6 +6979,7 @@ fn run_command_with_pty_to_file_observed(
     args: &[String],
     log_path: &Path,
     timeout: Duration,
+    watchdog: Watchdog,
     snapshot_at: Option<Duration>,
     on_snapshot: &dyn Fn(libc::pid_t),
 ) -> Result<ObservedRun> {
@@ -2327,6 +7072,7 @@ fn run_command_with_pty_to_file_observed(
     let mut exit_code: Option<i32> = None;
     let mut did_snapshot = false;
     let mut timed_out = false;
+    let mut timeout_kill = "none";
 
     loop {
         // Drain any PTY output.
@@ -2360,23 +7106,26 @@ fn run_command_with_pty_to_file_observed(
             on_snapshot(pid);
             // Graceful stop, then hard kill.
             kill_process_group(pid, libc::SIGTERM);
-            // Brief grace window.
+            // Grace window for muvm to tear the VM down.
             let grace_start = Instant::now();
-            while grace_start.elapsed() < Duration::from_millis(500) {
+            timeout_kill = "exited_in_grace";
+            while grace_start.elapsed() < watchdog.kill_grace {
                 drain_master(master, &mut log).ok();
                 if let Ok(Some(code)) = waitpid_nonblocking(pid) {
                     exit_code = Some(code);
                     break;
                 }
+                std::thread::sleep(watchdog.poll_interval);
             }
             if exit_code.is_none() {
+                timeout_kill = "sigkill";
                 kill_process_group(pid, libc::SIGKILL);
                 let _ = waitpid_blocking(pid).map(|c| exit_code = Some(c));
             }
             break;
         }
 
-        std::thread::sleep(Duration::from_millis(20));
+        std::thread::sleep(watchdog.poll_interval);
     }
 
     // Final drain.
@@ -2386,6 +7135,7 @@ fn run_command_with_pty_to_file_observed(
     Ok(ObservedRun {
         exit_code: exit_code.unwrap_or(124),
         timed_out,
+        timeout_kill,
     })
 }
 
@@ -2413,6 +7163,7 @@ fn collect_ppoll_eventfd_pipe_inodes(pid: u32, max_tasks: usize) -> Vec<u64> {
     }
     tids.sort_unstable();
 
+    let ppoll_nr = SyscallAbi::detect(pid).0.ppoll_nr();
     let mut out: Vec<u64> = Vec::new();
     for tid in tids.into_iter().take(max_tasks) {
         let syscall = read_text_best_effort(&task_dir.join(format!("{tid}/syscall")), 4096)
@@ -2421,7 +7172,7 @@ fn collect_ppoll_eventfd_pipe_inodes(pid: u32, max_tasks: usize) -> Vec<u64> {
         let Some(sc) = parse_proc_syscall_line(&syscall) else {
             continue;
         };
-        if sc.nr != 73 {
+        if sc.nr != ppoll_nr {
             continue;
         }
         let pollfd_ptr = sc.args[0];
@@ -2617,18 +7368,54 @@ fn pids_by_ppid(ppid: u32) -> Result<Vec<u32>> {
     Ok(pids)
 }
 
-fn read_parent_pid(pid: u32) -> Option<u32> {
-    let stat_text = read_text_best_effort(&PathBuf::from(format!("/proc/{pid}/stat")), 64 * 1024);
-    if stat_text.starts_with("(unavailable:") {
-        return None;
+/// `root_pid` and its descendants, breadth-first (parents before their children), at most
+/// `max_depth` levels below the root and `max_pids` pids in total. Pids whose children can't
+/// be listed are kept as leaves.
+fn process_tree(root_pid: u32, max_depth: usize, max_pids: usize) -> Vec<u32> {
+    let mut tree = Vec::new();
+    let mut queue = std::collections::VecDeque::from([(root_pid, 0)]);
+    let mut seen = HashSet::new();
+    while let Some((pid, depth)) = queue.pop_front() {
+        if tree.len() >= max_pids {
+            break;
+        }
+        if !seen.insert(pid) {
+            continue;
+        }
+        tree.push(pid);
+        if depth < max_depth {
+            if let Ok(children) = pids_by_ppid(pid) {
+                queue.extend(children.into_iter().map(|c| (c, depth + 1)));
+            }
+        }
     }
-    parse_proc_stat_job_control(&stat_text).map(|jc| jc.ppid)
+    tree
+}
+
+fn read_parent_pid(pid: u32) -> Option<u32> {
+    read_job_control(pid).map(|jc| jc.ppid)
 }
 
 fn read_proc_comm(pid: u32) -> Option<String> {
     let p = PathBuf::from(format!("/proc/{pid}/comm"));
-    let s = fs::read_to_string(p).ok()?;
-    Some(s.trim().to_string())
+    let bytes = fs::read(p).ok()?;
+    // The kernel appends exactly one '\n'; anything else is part of the name.
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(&bytes);
+    Some(escape_comm(&String::from_utf8_lossy(bytes)))
+}
+
+/// Escapes control characters so a `comm` set via `prctl(PR_SET_NAME)` cannot break
+/// line-oriented snapshot output.
+fn escape_comm(comm: &str) -> String {
+    comm.chars()
+        .map(|c| {
+            if c.is_control() {
+                c.escape_default().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
 }
 
 fn read_proc_cmdline(pid: u32, max_bytes: usize) -> Option<String> {
@@ -2661,44 +7448,62 @@ fn read_proc_cmdline(pid: u32, max_bytes: usize) -> Option<String> {
 }
 
 fn find_vm_like_descendant_pid(root_pid: u32, max_depth: usize, max_nodes: usize) -> Option<u32> {
-    use std::collections::VecDeque;
-    let mut q: VecDeque<(u32, usize)> = VecDeque::new();
-    q.push_back((root_pid, 0));
-    let mut visited = 0usize;
-
-    while let Some((pid, depth)) = q.pop_front() {
-        visited += 1;
-        if visited > max_nodes {
-            break;
-        }
+    process_tree(root_pid, max_depth, max_nodes)
+        .into_iter()
+        .find(|pid| read_proc_comm(*pid).is_some_and(|comm| comm.starts_with("VM:")))
+}
 
-        if let Some(comm) = read_proc_comm(pid) {
-            if comm.starts_with("VM:") {
-                return Some(pid);
+/// Scan a stuck snapshot for a process stopped by background TTY access: `state=T`,
+/// `is_foreground_pgrp=no` and SIGTTIN/SIGTTOU pending. Returns `(label, signal)`.
+fn detect_tty_stop(snapshot_text: &str) -> Option<(String, String)> {
+    let mut label = String::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut in_pending_mask = false;
+    for line in snapshot_text.lines() {
+        if let Some(rest) = line.strip_prefix('[') {
+            in_pending_mask = false;
+            if let Some(l) = rest.strip_suffix("] status_signals_decoded") {
+                label = l.to_string();
+                pending.clear();
             }
+            continue;
         }
-
-        if depth >= max_depth {
+        if line.starts_with("SigPnd:") || line.starts_with("ShdPnd:") {
+            in_pending_mask = true;
             continue;
         }
-
-        let Ok(children) = pids_by_ppid(pid) else {
+        if line.starts_with("SigBlk:") || line.starts_with("SigIgn:") || line.starts_with("SigCgt:")
+        {
+            in_pending_mask = false;
             continue;
-        };
-        for c in children {
-            q.push_back((c, depth + 1));
+        }
+        if in_pending_mask && line.starts_with("  ") {
+            pending.extend(line.split_whitespace().map(|s| s.to_string()));
+            continue;
+        }
+        if line.starts_with("state=") {
+            let field = |key: &str| {
+                line.split_whitespace()
+                    .find_map(|tok| tok.strip_prefix(key))
+                    .unwrap_or("")
+            };
+            if field("state=") != "T" || field("is_foreground_pgrp=") != "no" {
+                continue;
+            }
+            if let Some(sig) = pending
+                .iter()
+                .find(|s| s.as_str() == "SIGTTIN" || s.as_str() == "SIGTTOU")
+            {
+                return Some((label.clone(), sig.clone()));
+            }
         }
     }
-
     None
 }
 
 fn read_job_control(pid: u32) -> Option<ProcStatJobControl> {
-    let stat_text = read_text_best_effort(&PathBuf::from(format!("/proc/{pid}/stat")), 64 * 1024);
-    if stat_text.starts_with("(unavailable:") {
-        return None;
-    }
-    parse_proc_stat_job_control(&stat_text)
+    let stat = fs::read(format!("/proc/{pid}/stat")).ok()?;
+    parse_proc_stat_job_control(&stat)
 }
 
 fn is_foreground_pgrp(jc: &ProcStatJobControl) -> Option<bool> {
@@ -2790,7 +7595,82 @@ fn append_job_control_compare(out: &mut String, pid: u32, parent_pid: Option<u32
     }
 }
 
-fn snapshot_proc(out: &mut String, pid: u32, label: &str) {
+/// Writes `/proc/<pid>/maps` for `pid` and for the first FEX process among its descendants
+/// (up to two levels), each capped at `max_bytes` of text.
+fn write_maps_capture(path: &Path, pid: u32, max_bytes: usize) -> Result<()> {
+    let mut out = String::new();
+    out.push_str("### maps capture\n");
+    out.push_str(&format!("date: {}\n", iso_now()));
+    out.push_str(&format!("max_bytes_per_pid: {max_bytes}\n\n"));
+    append_maps(&mut out, pid, "edge", max_bytes);
+
+    let fex_pid = process_tree(pid, 2, usize::MAX)
+        .into_iter()
+        .skip(1)
+        .find(|p| read_proc_comm(*p).is_some_and(|c| c.to_ascii_lowercase().contains("fex")));
+    match fex_pid {
+        Some(fex_pid) => append_maps(&mut out, fex_pid, "fex", max_bytes),
+        None => out.push_str("--- fex: (no FEX process found under the tracked pid) ---\n"),
+    }
+    fs::write(path, out).context("write maps capture")
+}
+
+fn append_maps(out: &mut String, pid: u32, label: &str, max_bytes: usize) {
+    let comm = read_proc_comm(pid).unwrap_or_else(|| "(unknown)".to_string());
+    out.push_str(&format!("--- {label} pid={pid} comm={comm} ---\n"));
+    let maps = match fs::read_to_string(format!("/proc/{pid}/maps")) {
+        Ok(m) => m,
+        Err(e) => {
+            out.push_str(&format!("(unavailable: {e})\n\n"));
+            return;
+        }
+    };
+
+    let (mut total, mut anon, mut file, mut special) = (0u64, 0usize, 0usize, 0usize);
+    for line in maps.lines() {
+        let mut fields = line.split_whitespace();
+        let Some((start, end)) = fields.next().and_then(|r| r.split_once('-')) else {
+            continue;
+        };
+        if let (Ok(start), Ok(end)) = (u64::from_str_radix(start, 16), u64::from_str_radix(end, 16))
+        {
+            total += end.saturating_sub(start);
+        }
+        // perms, offset, dev, inode, then the (optional) pathname.
+        match fields.nth(4) {
+            None => anon += 1,
+            Some(p) if p.starts_with('/') => file += 1,
+            Some(p)
+                if p.starts_with("[heap]") || p.starts_with("[stack") || p.starts_with("[anon") =>
+            {
+                anon += 1
+            }
+            Some(_) => special += 1,
+        }
+    }
+
+    out.push_str(&format!("maps_regions: {}\n", anon + file + special));
+    out.push_str(&format!("maps_total_mapped_bytes: {total}\n"));
+    out.push_str(&format!("maps_anon_regions: {anon}\n"));
+    out.push_str(&format!("maps_file_regions: {file}\n"));
+    out.push_str(&format!("maps_special_regions: {special}\n"));
+    let truncated = maps.len() > max_bytes;
+    out.push_str(&format!(
+        "maps_truncated: {}\n",
+        if truncated { "yes" } else { "no" }
+    ));
+    let mut cut = maps.len().min(max_bytes);
+    while !maps.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    out.push_str(&maps[..cut]);
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+fn snapshot_proc(out: &mut String, pid: u32, label: &str, limits: SnapshotLimits) {
     out.push_str(&format!("[{label}] /proc/{pid}/status\n"));
     append_proc_file(out, pid, "status", 64 * 1024);
     out.push_str("\n");
@@ -2821,13 +7701,10 @@ fn snapshot_proc(out: &mut String, pid: u32, label: &str) {
     out.push_str(&format!(
         "[{label}] job_control (from /proc/{pid}/stat + stdio)\n"
     ));
-    let stat_text = read_text_best_effort(&PathBuf::from(format!("/proc/{pid}/stat")), 64 * 1024);
-    if stat_text.starts_with("(unavailable:") {
-        out.push_str(&stat_text);
-        if !out.ends_with('\n') {
-            out.push('\n');
-        }
-    } else if let Some(jc) = parse_proc_stat_job_control(&stat_text) {
+    let stat = fs::read(format!("/proc/{pid}/stat"));
+    if let Err(e) = &stat {
+        out.push_str(&format!("(unavailable: {e})\n"));
+    } else if let Some(jc) = stat.as_deref().ok().and_then(parse_proc_stat_job_control) {
         let fg = if jc.tty_nr != 0 && jc.tpgid > 0 {
             if jc.pgrp == jc.tpgid {
                 "yes"
@@ -2871,14 +7748,22 @@ fn snapshot_proc(out: &mut String, pid: u32, label: &str) {
     out.push_str("\n");
 
     out.push_str(&format!("[{label}] /proc/{pid}/task/* (sample)\n"));
-    let task_discovered = snapshot_tasks(out, pid, 24);
+    let task_discovered = snapshot_tasks(out, pid, limits.max_tasks);
     out.push_str("\n");
 
     if !task_discovered.ppoll_pipe_inodes.is_empty() {
         out.push_str(&format!(
             "[{label}] pipe_wakeup_path (from ppoll eventfd+pipe)\n"
         ));
-        emit_pipe_wakeup_path(out, &task_discovered.ppoll_pipe_inodes, 4, 512, 256, 10);
+        emit_pipe_wakeup_path(
+            out,
+            &task_discovered.ppoll_pipe_inodes,
+            4,
+            limits.owner_scan_max_pids,
+            limits.owner_scan_max_fds,
+            10,
+            limits.wakeup_depth,
+        );
         out.push_str("\n");
     }
 
@@ -2906,7 +7791,7 @@ fn snapshot_proc(out: &mut String, pid: u32, label: &str) {
     snapshot_fds(
         out,
         pid,
-        64,
+        limits,
         &task_discovered.socket_inodes,
         &task_discovered.pipe_inodes,
     );
@@ -2923,11 +7808,25 @@ struct ProcStatJobControl {
     tpgid: i32,
 }
 
-fn parse_proc_stat_job_control(stat_text: &str) -> Option<ProcStatJobControl> {
+/// Splits raw `/proc/<pid>/stat` bytes into `(pid, comm, fields after comm)`.
+///
+/// `comm` may contain spaces, parens and even newlines, so the only reliable delimiter is the
+/// last `)` in the buffer: every field after it is a number or the single state letter.
+fn split_proc_stat(stat: &[u8]) -> Option<(&str, String, &str)> {
+    let lparen = stat.iter().position(|&b| b == b'(')?;
+    let rparen = stat.iter().rposition(|&b| b == b')')?;
+    if rparen < lparen {
+        return None;
+    }
+    let pid = std::str::from_utf8(&stat[..lparen]).ok()?.trim();
+    let comm = String::from_utf8_lossy(&stat[lparen + 1..rparen]).into_owned();
+    let rest = std::str::from_utf8(&stat[rparen + 1..]).ok()?;
+    Some((pid, comm, rest))
+}
+
+fn parse_proc_stat_job_control(stat: &[u8]) -> Option<ProcStatJobControl> {
     // /proc/<pid>/stat format: pid (comm) state ppid pgrp session tty_nr tpgid ...
-    let s = stat_text.trim();
-    let rparen = s.rfind(')')?;
-    let after = s.get(rparen + 2..)?; // skip ") "
+    let (_, _, after) = split_proc_stat(stat)?;
     let mut it = after.split_whitespace();
     let state_s = it.next()?;
     let state = state_s.chars().next()?;
@@ -2964,6 +7863,68 @@ fn format_tty_nr_details(tty_nr: i32) -> String {
     format!(" dev=0x{dev:08x} major={maj} minor={min}")
 }
 
+fn read_file_or_stdin(path: Option<&Path>) -> Result<String> {
+    match path {
+        Some(p) => fs::read_to_string(p).with_context(|| format!("read {}", p.display())),
+        None => {
+            let mut text = String::new();
+            io::Read::read_to_string(&mut io::stdin(), &mut text).context("read stdin")?;
+            Ok(text)
+        }
+    }
+}
+
+fn run_decode_stat(file: Option<&Path>) -> Result<()> {
+    let text = read_file_or_stdin(file)?;
+    // Parse the input as a whole rather than line by line: comm may contain a newline.
+    let stat = text.trim();
+    if stat.is_empty() {
+        bail!("no stat line found in input");
+    }
+    let jc = parse_proc_stat_job_control(stat.as_bytes())
+        .with_context(|| format!("not a /proc/<pid>/stat line: {stat}"))?;
+    let (pid, comm, _) = split_proc_stat(stat.as_bytes()).context("split stat line")?;
+
+    println!("pid={pid} comm={}", escape_comm(&comm));
+    println!("state={}", jc.state);
+    println!("ppid={}", jc.ppid);
+    println!("pgrp={}", jc.pgrp);
+    println!("session={}", jc.session);
+    println!("tpgid={}", jc.tpgid);
+    println!("tty_nr={}{}", jc.tty_nr, format_tty_nr_details(jc.tty_nr));
+    println!(
+        "fg={}",
+        match is_foreground_pgrp(&jc) {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "(n/a)",
+        }
+    );
+    Ok(())
+}
+
+fn run_decode_signals(mask: Option<&str>, file: Option<&Path>) -> Result<()> {
+    let mut out = String::new();
+    if let Some(mask) = mask {
+        let hex = mask.trim();
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        let mask = u128::from_str_radix(hex, 16)
+            .with_context(|| format!("--mask is not a hex signal mask: {hex}"))?;
+        let names = decode_signal_mask(mask);
+        out.push_str(&format!("mask: 0x{mask:x}\n"));
+        if names.is_empty() {
+            out.push_str("  (none)\n");
+        } else {
+            out.push_str(&format!("  {}\n", names.join(" ")));
+        }
+    } else {
+        let status_text = read_file_or_stdin(file)?;
+        append_decoded_status_signals(&mut out, &status_text);
+    }
+    print!("{out}");
+    Ok(())
+}
+
 fn append_decoded_status_signals(out: &mut String, status_text: &str) {
     let fields = ["SigPnd", "ShdPnd", "SigBlk", "SigIgn", "SigCgt"];
     let mut any = false;
@@ -3008,47 +7969,6 @@ fn decode_signal_mask(mask: u128) -> Vec<String> {
     out
 }
 
-fn signal_name(sig: u32) -> String {
-    match sig {
-        1 => "SIGHUP".into(),
-        2 => "SIGINT".into(),
-        3 => "SIGQUIT".into(),
-        4 => "SIGILL".into(),
-        5 => "SIGTRAP".into(),
-        6 => "SIGABRT".into(),
-        7 => "SIGBUS".into(),
-        8 => "SIGFPE".into(),
-        9 => "SIGKILL".into(),
-        10 => "SIGUSR1".into(),
-        11 => "SIGSEGV".into(),
-        12 => "SIGUSR2".into(),
-        13 => "SIGPIPE".into(),
-        14 => "SIGALRM".into(),
-        15 => "SIGTERM".into(),
-        16 => "SIGSTKFLT".into(),
-        17 => "SIGCHLD".into(),
-        18 => "SIGCONT".into(),
-        19 => "SIGSTOP".into(),
-        20 => "SIGTSTP".into(),
-        21 => "SIGTTIN".into(),
-        22 => "SIGTTOU".into(),
-        23 => "SIGURG".into(),
-        24 => "SIGXCPU".into(),
-        25 => "SIGXFSZ".into(),
-        26 => "SIGVTALRM".into(),
-        27 => "SIGPROF".into(),
-        28 => "SIGWINCH".into(),
-        29 => "SIGIO".into(),
-        30 => "SIGPWR".into(),
-        31 => "SIGSYS".into(),
-        // Linux SIGRTMIN is typically 34; 32/33 are reserved by glibc/NPTL.
-        32 => "SIGRTMIN-2".into(),
-        33 => "SIGRTMIN-1".into(),
-        34..=64 => format!("SIGRTMIN+{}", sig - 34),
-        _ => format!("SIG{sig}"),
-    }
-}
-
 #[derive(Default)]
 struct TaskDiscoveredInodes {
     socket_inodes: Vec<u64>,
@@ -3076,7 +7996,16 @@ fn snapshot_tasks(out: &mut String, pid: u32, max_tasks: usize) -> TaskDiscovere
     }
     tids.sort_unstable();
 
+    let (abi, abi_source) = SyscallAbi::detect(pid);
     out.push_str(&format!("task_count: {}\n", tids.len()));
+    out.push_str(&format!(
+        "task_elided: {}\n",
+        tids.len().saturating_sub(max_tasks)
+    ));
+    out.push_str(&format!(
+        "syscall_abi: {} (from {abi_source})\n",
+        abi.name()
+    ));
     out.push_str("task_sample:\n");
     let mut discovered = TaskDiscoveredInodes::default();
     for tid in tids.into_iter().take(max_tasks) {
@@ -3091,13 +8020,18 @@ fn snapshot_tasks(out: &mut String, pid: u32, max_tasks: usize) -> TaskDiscovere
             .to_string();
         let stack = read_text_best_effort(&task_dir.join(format!("{tid}/stack")), 8 * 1024);
         let stack_top = stack.lines().take(2).collect::<Vec<_>>().join(" | ");
+        let sc = parse_proc_syscall_line(&syscall);
+        let syscall_name = sc
+            .and_then(|sc| abi.syscall_name(sc.nr))
+            .map(|name| format!(" syscall_name={name}"))
+            .unwrap_or_default();
         out.push_str(&format!(
-            "  tid {tid}: comm={comm} wchan={wchan} syscall={syscall} stack_top={stack_top}\n"
+            "  tid {tid}: comm={comm} wchan={wchan} syscall={syscall}{syscall_name} \
+             stack_top={stack_top}\n"
         ));
 
-        if let Some(sc) = parse_proc_syscall_line(&syscall) {
-            // On aarch64, syscall 73 is ppoll.
-            if sc.nr == 73 {
+        if let Some(sc) = sc {
+            if sc.nr == abi.ppoll_nr() {
                 let pollfd_ptr = sc.args[0];
                 let nfds = sc.args[1] as usize;
                 if (1..=8).contains(&nfds) {
@@ -3182,6 +8116,7 @@ fn emit_pipe_wakeup_path(
     max_pids: usize,
     max_fds_per_pid: usize,
     max_hits_per_inode: usize,
+    wakeup_depth: usize,
 ) {
     let mut inodes: Vec<u64> = ppoll_pipe_inodes.to_vec();
     inodes.sort_unstable();
@@ -3218,9 +8153,12 @@ fn emit_pipe_wakeup_path(
         let mut skipped_pids = 0usize;
         let mut proc_errs = 0usize;
         let mut writer_pids: Vec<u32> = Vec::new();
+        let mut pid_budget_hit = false;
+        let mut fd_capped_pids = 0usize;
 
         for other_pid in proc_pids.iter().copied() {
             if scanned_pids >= max_pids {
+                pid_budget_hit = true;
                 break;
             }
             scanned_pids += 1;
@@ -3237,6 +8175,7 @@ fn emit_pipe_wakeup_path(
             let mut scanned_fds = 0usize;
             for fd_ent in fds.flatten() {
                 if scanned_fds >= max_fds_per_pid {
+                    fd_capped_pids += 1;
                     break;
                 }
                 scanned_fds += 1;
@@ -3297,24 +8236,47 @@ fn emit_pipe_wakeup_path(
         writer_pids.dedup();
         if writer_pids.is_empty() {
             out.push_str("  (no writer owners found within scan bounds)\n");
+            let satisfied = hit_counts
+                .get(&inode)
+                .is_some_and(|c| *c >= max_hits_per_inode);
+            out.push_str("  ");
+            out.push_str(&format_scan_truncated(
+                pid_budget_hit,
+                fd_capped_pids,
+                satisfied,
+                max_pids,
+                max_fds_per_pid,
+            ));
         } else {
             out.push_str("  writer_pid_task_samples:\n");
-            for wp in writer_pids.into_iter().take(6) {
+            let writer_pids: Vec<u32> = writer_pids.into_iter().take(6).collect();
+            // Shared across hops so cycles (A waits on B waits on A) terminate.
+            let mut visited: HashSet<u32> = writer_pids.iter().copied().collect();
+            let bounds = PipeScanBounds {
+                max_pids,
+                max_fds_per_pid,
+                max_hits_per_inode,
+            };
+            for wp in writer_pids {
                 out.push_str(&format!("  --- writer_pid {wp} ---\n"));
                 emit_pid_status_key_fields(out, wp);
                 let _ = snapshot_tasks(out, wp, 12);
-                // One-hop recursion: if the writer PID is itself waiting on an eventfd+pipe
-                // ppoll set, follow that pipe inode to its writer owners.
+                // If the writer PID is itself waiting on an eventfd+pipe ppoll set, follow
+                // that pipe inode to its writer owners, up to `wakeup_depth` hops.
+                if wakeup_depth == 0 {
+                    continue;
+                }
                 let next_pipe_inodes = collect_ppoll_eventfd_pipe_inodes(wp, 24);
                 if !next_pipe_inodes.is_empty() {
-                    out.push_str("  writer_wait_graph_one_hop:\n");
-                    emit_one_hop_pipe_wait_graph(
+                    out.push_str(&format!("  writer_wait_graph (depth={wakeup_depth}):\n"));
+                    emit_pipe_wait_graph(
                         out,
                         wp,
                         &next_pipe_inodes,
-                        max_pids,
-                        max_fds_per_pid,
-                        max_hits_per_inode,
+                        bounds,
+                        1,
+                        wakeup_depth,
+                        &mut visited,
                     );
                 }
             }
@@ -3326,35 +8288,59 @@ fn emit_pipe_wakeup_path(
     }
 }
 
-fn emit_one_hop_pipe_wait_graph(
-    out: &mut String,
-    pid: u32,
-    pipe_inodes: &[u64],
+#[derive(Copy, Clone, Debug)]
+struct PipeScanBounds {
     max_pids: usize,
     max_fds_per_pid: usize,
     max_hits_per_inode: usize,
+}
+
+/// Follow `pid`'s eventfd+pipe ppoll waits to the pipe writers, recursing into writers that
+/// are themselves waiting, until `max_hops` is reached. `visited` doubles as a cycle guard and
+/// a total-work bound (capped at `bounds.max_pids`).
+fn emit_pipe_wait_graph(
+    out: &mut String,
+    pid: u32,
+    pipe_inodes: &[u64],
+    bounds: PipeScanBounds,
+    hop: usize,
+    max_hops: usize,
+    visited: &mut HashSet<u32>,
 ) {
+    let indent = "  ".repeat(hop + 1);
     let mut inodes: Vec<u64> = pipe_inodes.to_vec();
     inodes.sort_unstable();
     inodes.dedup();
     out.push_str(&format!(
-        "    pid={pid} waits_on_eventfd_pipe_inodes: {inodes:?}\n"
+        "{indent}hop={hop} pid={pid} waits_on_eventfd_pipe_inodes: {inodes:?}\n"
     ));
     for inode in inodes.into_iter().take(3) {
-        out.push_str(&format!("    -- waits_on pipe_inode {inode} --\n"));
-        let writer_pids =
-            collect_pipe_writer_pids(&[inode], max_pids, max_fds_per_pid, max_hits_per_inode);
+        out.push_str(&format!("{indent}-- waits_on pipe_inode {inode} --\n"));
+        let writer_pids = collect_pipe_writer_pids(
+            &[inode],
+            bounds.max_pids,
+            bounds.max_fds_per_pid,
+            bounds.max_hits_per_inode,
+        );
         if writer_pids.is_empty() {
-            out.push_str("      (no writer owners found within scan bounds)\n");
+            out.push_str(&format!(
+                "{indent}  (no writer owners found within scan bounds)\n"
+            ));
             continue;
         }
-        out.push_str(&format!("      writer_pids: {writer_pids:?}\n"));
+        out.push_str(&format!("{indent}  writer_pids: {writer_pids:?}\n"));
         for wp in writer_pids.into_iter().take(4) {
-            out.push_str(&format!("      --- owner_pid {wp} ---\n"));
+            out.push_str(&format!("{indent}  --- owner_pid {wp} ---\n"));
+            if !visited.insert(wp) {
+                out.push_str(&format!(
+                    "{indent}  (already visited; cycle or shared owner)\n"
+                ));
+                continue;
+            }
             emit_pid_status_key_fields(out, wp);
             if let Some(sig) = sample_task_signature(wp, 8) {
                 out.push_str(&format!(
-					"      signature: tasks={} leader_wchan={} leader_syscall_nr={} digest=0x{:x}\n",
+					"{indent}  signature: tasks={} leader_wchan={} leader_syscall_nr={} digest=0x{:x}\n",
 					sig.task_count,
 					sig.leader_wchan,
 					sig.leader_syscall_nr
@@ -3363,6 +8349,29 @@ fn emit_one_hop_pipe_wait_graph(
 					sig.digest
 				));
             }
+
+            if hop >= max_hops {
+                continue;
+            }
+            if visited.len() >= bounds.max_pids {
+                out.push_str(&format!(
+                    "{indent}  (wait graph pid budget exhausted: {})\n",
+                    bounds.max_pids
+                ));
+                return;
+            }
+            let next_pipe_inodes = collect_ppoll_eventfd_pipe_inodes(wp, 24);
+            if !next_pipe_inodes.is_empty() {
+                emit_pipe_wait_graph(
+                    out,
+                    wp,
+                    &next_pipe_inodes,
+                    bounds,
+                    hop + 1,
+                    max_hops,
+                    visited,
+                );
+            }
         }
     }
 }
@@ -3370,7 +8379,7 @@ fn emit_one_hop_pipe_wait_graph(
 fn snapshot_fds(
     out: &mut String,
     pid: u32,
-    max_fds: usize,
+    limits: SnapshotLimits,
     extra_socket_inodes: &[u64],
     extra_pipe_inodes: &[u64],
 ) {
@@ -3404,16 +8413,23 @@ fn snapshot_fds(
     }
 
     out.push_str(&format!("fd_count: {}\n", fds.len()));
+    out.push_str(&format!(
+        "fd_elided: {}\n",
+        fds.len().saturating_sub(limits.max_fds)
+    ));
     out.push_str("fd_targets:\n");
-    for fd in fds.iter().copied().take(max_fds) {
+    for fd in fds.iter().copied().take(limits.max_fds) {
         let target = targets_by_fd
             .get(&fd)
             .cloned()
             .unwrap_or_else(|| "(unknown)".to_string());
         out.push_str(&format!("  fd {fd}: {target}\n"));
     }
-    if fds.len() > max_fds {
-        out.push_str(&format!("  … ({} more fds) …\n", fds.len() - max_fds));
+    if fds.len() > limits.max_fds {
+        out.push_str(&format!(
+            "  … ({} more fds) …\n",
+            fds.len() - limits.max_fds
+        ));
     }
 
     let epoll_fds: Vec<u32> = fds
@@ -3476,7 +8492,13 @@ fn snapshot_fds(
     pipe_inodes.sort_unstable();
     pipe_inodes.dedup();
     if !pipe_inodes.is_empty() {
-        emit_pipe_inode_fd_owners(out, &pipe_inodes, 512, 256, 10);
+        emit_pipe_inode_fd_owners(
+            out,
+            &pipe_inodes,
+            limits.owner_scan_max_pids,
+            limits.owner_scan_max_fds,
+            10,
+        );
     }
 
     // Resolve any observed socket:[inode] entries via /proc/net/*.
@@ -3515,7 +8537,13 @@ fn snapshot_fds(
 
         // Best-effort: resolve which processes own these socket inodes by scanning /proc/*/fd.
         // This stays "all Rust" (no external tooling) and is bounded for performance.
-        emit_socket_inode_fd_owners(out, &socket_inodes, 512, 256, 10);
+        emit_socket_inode_fd_owners(
+            out,
+            &socket_inodes,
+            limits.owner_scan_max_pids,
+            limits.owner_scan_max_fds,
+            10,
+        );
     }
 
     out.push_str("fdinfo_sample:\n");
@@ -3561,10 +8589,10 @@ fn emit_socket_inode_fd_owners(
     let mut skipped_pids = 0usize;
     let mut proc_errs = 0usize;
 
+    let mut pid_budget_hit = false;
+    let mut fd_capped_pids = 0usize;
+
     for ent in proc_entries.flatten() {
-        if scanned_pids >= max_pids {
-            break;
-        }
         let name = ent.file_name();
         let s = name.to_string_lossy();
         let Ok(other_pid) = s.parse::<u32>() else {
@@ -3575,6 +8603,10 @@ fn emit_socket_inode_fd_owners(
         if hit_counts.values().all(|c| *c >= max_hits_per_inode) {
             break;
         }
+        if scanned_pids >= max_pids {
+            pid_budget_hit = true;
+            break;
+        }
 
         scanned_pids += 1;
         let fd_dir = PathBuf::from(format!("/proc/{other_pid}/fd"));
@@ -3591,6 +8623,7 @@ fn emit_socket_inode_fd_owners(
         let mut scanned_fds = 0usize;
         for fd_ent in fds.flatten() {
             if scanned_fds >= max_fds_per_pid {
+                fd_capped_pids += 1;
                 break;
             }
             scanned_fds += 1;
@@ -3629,8 +8662,40 @@ fn emit_socket_inode_fd_owners(
     }
 
     out.push_str(&format!(
-		"socket_inode_fd_owners_stats: scanned_pids={scanned_pids} skipped_pids={skipped_pids} fd_read_errors={proc_errs}\n"
+		"socket_inode_fd_owners_stats: scanned_pids={scanned_pids} skipped_pids={skipped_pids} fd_read_errors={proc_errs} fd_capped_pids={fd_capped_pids}\n"
 	));
+    let satisfied = hit_counts.values().all(|c| *c >= max_hits_per_inode);
+    out.push_str(&format_scan_truncated(
+        pid_budget_hit,
+        fd_capped_pids,
+        satisfied,
+        max_pids,
+        max_fds_per_pid,
+    ));
+}
+
+/// `scan_truncated:` line for the bounded `/proc/*/fd` owner scans. Only "yes" when a scan
+/// budget ran out before every inode reached its hit cap, i.e. owners may have been missed.
+fn format_scan_truncated(
+    pid_budget_hit: bool,
+    fd_capped_pids: usize,
+    satisfied: bool,
+    max_pids: usize,
+    max_fds_per_pid: usize,
+) -> String {
+    if satisfied || (!pid_budget_hit && fd_capped_pids == 0) {
+        return "scan_truncated: no\n".to_string();
+    }
+    let mut reasons = Vec::new();
+    if pid_budget_hit {
+        reasons.push(format!("max_pids={max_pids} reached"));
+    }
+    if fd_capped_pids > 0 {
+        reasons.push(format!(
+            "{fd_capped_pids} pid(s) over max_fds_per_pid={max_fds_per_pid}"
+        ));
+    }
+    format!("scan_truncated: yes ({})\n", reasons.join("; "))
 }
 
 fn parse_socket_inode(target: &str) -> Option<u64> {
@@ -3678,10 +8743,10 @@ fn emit_pipe_inode_fd_owners(
     let mut skipped_pids = 0usize;
     let mut proc_errs = 0usize;
 
+    let mut pid_budget_hit = false;
+    let mut fd_capped_pids = 0usize;
+
     for ent in proc_entries.flatten() {
-        if scanned_pids >= max_pids {
-            break;
-        }
         let name = ent.file_name();
         let s = name.to_string_lossy();
         let Ok(other_pid) = s.parse::<u32>() else {
@@ -3691,6 +8756,10 @@ fn emit_pipe_inode_fd_owners(
         if hit_counts.values().all(|c| *c >= max_hits_per_inode) {
             break;
         }
+        if scanned_pids >= max_pids {
+            pid_budget_hit = true;
+            break;
+        }
 
         scanned_pids += 1;
         let fd_dir = PathBuf::from(format!("/proc/{other_pid}/fd"));
@@ -3706,6 +8775,7 @@ fn emit_pipe_inode_fd_owners(
         let mut scanned_fds = 0usize;
         for fd_ent in fds.flatten() {
             if scanned_fds >= max_fds_per_pid {
+                fd_capped_pids += 1;
                 break;
             }
             scanned_fds += 1;
@@ -3758,8 +8828,99 @@ fn emit_pipe_inode_fd_owners(
     }
 
     out.push_str(&format!(
-		"pipe_inode_fd_owners_stats: scanned_pids={scanned_pids} skipped_pids={skipped_pids} fd_read_errors={proc_errs}\n"
+		"pipe_inode_fd_owners_stats: scanned_pids={scanned_pids} skipped_pids={skipped_pids} fd_read_errors={proc_errs} fd_capped_pids={fd_capped_pids}\n"
 	));
+    let satisfied = hit_counts.values().all(|c| *c >= max_hits_per_inode);
+    out.push_str(&format_scan_truncated(
+        pid_budget_hit,
+        fd_capped_pids,
+        satisfied,
+        max_pids,
+        max_fds_per_pid,
+    ));
+}
+
+/// `(name, aarch64 nr, x86_64 nr)` for the syscalls stuck snapshots decode.
+const SYSCALL_NRS: &[(&str, u64, u64)] = &[
+    ("read", 63, 0),
+    ("write", 64, 1),
+    ("mmap", 222, 9),
+    ("mprotect", 226, 10),
+    ("munmap", 215, 11),
+    ("nanosleep", 101, 35),
+    ("clock_nanosleep", 115, 230),
+    ("clone", 220, 56),
+    ("wait4", 260, 61),
+    ("futex", 98, 202),
+    ("ppoll", 73, 271),
+    ("epoll_pwait", 22, 281),
+];
+
+/// Which syscall number table a task's `/proc/<pid>/syscall` uses. Under FEX the kernel
+/// reports the aarch64 numbers of the interpreter, so that is the fallback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyscallAbi {
+    Aarch64,
+    X86_64,
+}
+
+impl SyscallAbi {
+    /// From the ELF machine of `/proc/<pid>/exe`, else `uname -m`, else aarch64; the second
+    /// value says which source decided.
+    fn detect(pid: u32) -> (Self, &'static str) {
+        let exe = PathBuf::from(format!("/proc/{pid}/exe"));
+        let from_exe = match elf64_le_machine(&exe) {
+            Ok(Some(EM_AARCH64)) => Some(SyscallAbi::Aarch64),
+            Ok(Some(EM_X86_64)) => Some(SyscallAbi::X86_64),
+            _ => None,
+        };
+        if let Some(abi) = from_exe {
+            return (abi, "exe");
+        }
+        match uname_machine().as_deref() {
+            Some("aarch64" | "arm64") => (SyscallAbi::Aarch64, "uname"),
+            Some("x86_64") => (SyscallAbi::X86_64, "uname"),
+            _ => (SyscallAbi::Aarch64, "default"),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SyscallAbi::Aarch64 => "aarch64",
+            SyscallAbi::X86_64 => "x86_64",
+        }
+    }
+
+    fn nr(self, name: &str) -> Option<u64> {
+        SYSCALL_NRS
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, aarch64, x86_64)| match self {
+                SyscallAbi::Aarch64 => *aarch64,
+                SyscallAbi::X86_64 => *x86_64,
+            })
+    }
+
+    fn ppoll_nr(self) -> u64 {
+        self.nr("ppoll").expect("ppoll is in SYSCALL_NRS")
+    }
+
+    fn syscall_name(self, nr: u64) -> Option<&'static str> {
+        SYSCALL_NRS
+            .iter()
+            .map(|(n, _, _)| *n)
+            .find(|n| self.nr(n) == Some(nr))
+    }
+}
+
+/// The `machine` field of uname(2), i.e. `uname -m`.
+fn uname_machine() -> Option<String> {
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return None;
+    }
+    let machine = unsafe { CStr::from_ptr(uts.machine.as_ptr()) };
+    Some(machine.to_string_lossy().into_owned())
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -3940,9 +9101,75 @@ fn write_ps(path: &Path, pid: u32) -> Result<()> {
             }
         }
     }
+    let handlers = crashpad_handlers_in_tree(pid);
+    out.push_str("\n### crashpad handlers in edge tree\n");
+    for (handler, cmdline) in &handlers {
+        out.push_str(&format!("{handler} {cmdline}\n"));
+    }
+    out.push_str(&format!("crashpad_handlers_spawned: {}\n", handlers.len()));
     fs::write(path, out).context("write ps")
 }
 
+/// Whether a cmdline is a crashpad handler (`msedge_crashpad_handler`, `chrome_crashpad_handler`),
+/// looking at argv[0] and, for processes run through FEX, argv[1].
+fn is_crashpad_handler(cmdline: &str) -> bool {
+    cmdline.split(' ').take(2).any(|arg| {
+        let name = arg.rsplit('/').next().unwrap_or(arg);
+        name.contains("crashpad_handler") || name.contains("chrome_crashpad")
+    })
+}
+
+/// Crashpad handler processes (pid, cmdline) in the `process_tree` of `root_pid`.
+fn crashpad_handlers_in_tree(root_pid: u32) -> Vec<(u32, String)> {
+    process_tree(root_pid, usize::MAX, THREAD_SAMPLE_MAX_PIDS)
+        .into_iter()
+        .filter_map(|pid| {
+            read_proc_cmdline(pid, 512)
+                .filter(|c| is_crashpad_handler(c))
+                .map(|cmdline| (pid, cmdline))
+        })
+        .collect()
+}
+
+/// Writes the NUL-split `/proc/<pid>/environ` (unredacted) and which `--edge-env` keys are
+/// absent from it, to catch env vars lost between the host, muvm `-e` and Edge.
+fn write_edge_environ(path: &Path, pid: u32, edge_env: &[String]) -> Result<()> {
+    let raw = fs::read(format!("/proc/{pid}/environ"))
+        .with_context(|| format!("read /proc/{pid}/environ"))?;
+    let entries: Vec<String> = raw
+        .split(|b| *b == 0)
+        .filter(|e| !e.is_empty())
+        .map(|e| String::from_utf8_lossy(e).into_owned())
+        .collect();
+    let present: HashSet<&str> = entries
+        .iter()
+        .map(|e| e.split_once('=').map(|(k, _)| k).unwrap_or(e))
+        .collect();
+    let missing: Vec<&str> = edge_env
+        .iter()
+        .filter_map(|kv| kv.split_once('=').map(|(k, _)| k))
+        .filter(|k| !present.contains(k))
+        .collect();
+
+    let mut out = String::new();
+    out.push_str(&format!("pid: {pid}\n"));
+    out.push_str(&format!("entries: {}\n", entries.len()));
+    out.push_str(&format!(
+        "missing_edge_env_keys: {}\n",
+        if missing.is_empty() {
+            "(none)".to_string()
+        } else {
+            missing.join(" ")
+        }
+    ));
+    out.push_str("\nenviron:\n");
+    for e in &entries {
+        out.push_str(e);
+        out.push('\n');
+    }
+    fs::write(path, out).context("write edge environ")
+}
+
 fn write_threads(path: &Path, pid: u32) -> Result<()> {
     let mut out = String::new();
     out.push_str("### thread_count_total\n");
@@ -3989,13 +9216,12 @@ fn filter_stderr(input: &Path, output: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Counts lines, including a final line without a trailing newline.
 fn count_lines(path: &Path) -> Result<u64> {
     let content = fs::read(path).context("read file for line count")?;
-    let mut lines = 0u64;
-    for b in content {
-        if b == b'\n' {
-            lines += 1;
-        }
+    let mut lines = content.iter().filter(|b| **b == b'\n').count() as u64;
+    if content.last().is_some_and(|b| *b != b'\n') {
+        lines += 1;
     }
     Ok(lines)
 }
@@ -4025,15 +9251,6 @@ fn count_substring_lines(path: &Path, needle: &str) -> Result<u64> {
     Ok(s.lines().filter(|l| l.contains(needle)).count() as u64)
 }
 
-fn run_command_with_pty_to_file(
-    args: &[String],
-    log_path: &Path,
-    timeout: Duration,
-) -> Result<i32> {
-    let res = run_command_with_pty_to_file_observed(args, log_path, timeout, None, &|_| {})?;
-    Ok(res.exit_code)
-}
-
 fn ptsname(master: RawFd) -> Result<CString> {
     let mut buf = [0 as libc::c_char; 256];
     let rc = unsafe { libc::ptsname_r(master, buf.as_mut_ptr(), buf.len()) };
@@ -4117,29 +9334,9 @@ fn kill_process_group(pid: libc::pid_t, signal: libc::c_int) {
     }
 }
 
+/// Signals every pid in the `process_tree` of `root`, deepest first.
 fn kill_process_tree(root: u32, signal: libc::c_int, max_pids: usize) {
-    let mut queue: Vec<u32> = vec![root];
-    let mut seen: HashSet<u32> = HashSet::new();
-    let mut all: Vec<u32> = Vec::new();
-
-    while let Some(pid) = queue.pop() {
-        if all.len() >= max_pids {
-            break;
-        }
-        if !seen.insert(pid) {
-            continue;
-        }
-        all.push(pid);
-        if let Ok(children) = pids_by_ppid(pid) {
-            for c in children {
-                if !seen.contains(&c) {
-                    queue.push(c);
-                }
-            }
-        }
-    }
-
-    for pid in all.into_iter().rev() {
+    for pid in process_tree(root, usize::MAX, max_pids).into_iter().rev() {
         unsafe {
             libc::kill(pid as libc::pid_t, signal);
         }
@@ -4179,6 +9376,194 @@ fn iso_now() -> String {
     format!("unix-seconds:{ts}")
 }
 
+/// Default Edge binary location inside the extracted RPM root (`--edge-bin-subpath`).
+const EDGE_BIN_REL: &str = "opt/microsoft/msedge/microsoft-edge";
+
+/// "Edge binary missing" error for `root/rel`, suggesting a `--edge-bin-subpath` when a
+/// `microsoft-edge*` binary exists elsewhere under `root`.
+fn edge_bin_missing(root: &Path, rel: &Path) -> anyhow::Error {
+    let found = find_edge_binaries(root);
+    let mut msg = format!("Edge binary missing at {}", root.join(rel).display());
+    if let Some(first) = found.first() {
+        let listed: Vec<String> = found.iter().map(|p| p.display().to_string()).collect();
+        msg.push_str(&format!(
+            "; found {} under {}; try --edge-bin-subpath {}",
+            listed.join(", "),
+            root.display(),
+            first.display()
+        ));
+    }
+    anyhow::anyhow!(msg)
+}
+
+/// Executable regular files named `msedge` or `microsoft-edge*` under `root` (relative
+/// paths), to point at the binary of a beta/dev or repackaged layout. The exec bit keeps
+/// `.desktop` files, man pages and menu entries of the same name out of the list, and `etc/`
+/// (cron jobs, alternatives) is not searched. ELF files under `opt/` and launchers sitting
+/// next to an `msedge` ELF come first; the rest follow, shallowest first.
+fn find_edge_binaries(root: &Path) -> Vec<PathBuf> {
+    use std::io::Read;
+    use std::os::unix::fs::PermissionsExt;
+
+    const MAX_DEPTH: usize = 6;
+    let mut found = Vec::new();
+    let mut queue = std::collections::VecDeque::from([(PathBuf::new(), 0)]);
+    while let Some((rel, depth)) = queue.pop_front() {
+        let Ok(entries) = fs::read_dir(root.join(&rel)) else {
+            continue;
+        };
+        let mut entries: Vec<_> = entries.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let Ok(ft) = entry.file_type() else {
+                continue;
+            };
+            let name = entry.file_name();
+            let child = rel.join(&name);
+            if ft.is_dir() && depth < MAX_DEPTH {
+                if child != Path::new("etc") {
+                    queue.push_back((child, depth + 1));
+                }
+            } else if ft.is_file()
+                && (name == "msedge" || name.to_string_lossy().starts_with("microsoft-edge"))
+                && entry
+                    .metadata()
+                    .is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+            {
+                found.push(child);
+            }
+        }
+    }
+    let is_elf = |rel: &Path| {
+        let mut magic = [0u8; 4];
+        fs::File::open(root.join(rel))
+            .and_then(|mut f| f.read_exact(&mut magic))
+            .is_ok_and(|()| &magic == b"\x7fELF")
+    };
+    // Stable sort: within each group the breadth-first (shallowest first) order is kept.
+    found.sort_by_key(|rel| {
+        let preferred = (rel.starts_with("opt") && is_elf(rel))
+            || rel.parent().is_some_and(|dir| is_elf(&dir.join("msedge")));
+        !preferred
+    });
+    found
+}
+
+fn strip_edge_gnu_property(
+    extracted_root: &Path,
+    edge_bin_rel: &Path,
+    objcopy: &OsString,
+) -> Result<StripReport> {
+    if !extracted_root.join(edge_bin_rel).is_file() {
+        return Err(edge_bin_missing(extracted_root, edge_bin_rel))
+            .context("--strip-edge-gnu-property");
+    }
+    // Edge's bundled libraries live next to the binary.
+    let edge_dir = edge_bin_rel
+        .parent()
+        .and_then(Path::to_str)
+        .context("--edge-bin-subpath has no parent directory")?;
+    strip_gnu_property_notes_under(extracted_root, &[edge_dir], objcopy)
+        .context("strip .note.gnu.property under the extracted Edge root")
+}
+
+/// Marker in the extracted root recording which RPM it was extracted from.
+const RPM_EXTRACT_MARKER: &str = ".extracted-from-rpm";
+
+struct RpmExtraction {
+    reused: bool,
+    elapsed: Duration,
+    edge_bin: PathBuf,
+}
+
+/// Extracts `rpm` into `dest` with `rpm2cpio | cpio`, staging in `<dest>.partial` so an
+/// interrupted extraction never leaves a half-populated root behind.
+fn extract_edge_rpm(rpm: &Path, dest: &Path, edge_bin_rel: &Path) -> Result<RpmExtraction> {
+    let start = Instant::now();
+    let rpm = fs::canonicalize(rpm).with_context(|| format!("canonicalize {}", rpm.display()))?;
+    let meta = fs::metadata(&rpm).context("stat rpm")?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let marker = format!(
+        "rpm: {}\nsize: {}\nmtime: {mtime}\n",
+        rpm.display(),
+        meta.len()
+    );
+
+    let edge_bin = dest.join(edge_bin_rel);
+    let marker_path = dest.join(RPM_EXTRACT_MARKER);
+    if edge_bin.is_file() && fs::read_to_string(&marker_path).ok().as_deref() == Some(&marker) {
+        return Ok(RpmExtraction {
+            reused: true,
+            elapsed: start.elapsed(),
+            edge_bin,
+        });
+    }
+
+    // Only a tree this function extracted (it has the marker) is ever replaced; anything else
+    // at `--extracted-root` may be the user's own and is left alone.
+    let dest_is_empty_dir = fs::read_dir(dest).is_ok_and(|mut entries| entries.next().is_none());
+    if dest.exists() && !dest_is_empty_dir && !marker_path.is_file() {
+        bail!(
+            "{} exists but was not extracted by --extract-rpm (no {RPM_EXTRACT_MARKER}); \
+             refusing to replace it. Remove it or pass a different --extracted-root",
+            dest.display()
+        );
+    }
+
+    let rpm2cpio = resolve_in_path("rpm2cpio").context("locate rpm2cpio in PATH")?;
+    let cpio = resolve_in_path("cpio").context("locate cpio in PATH")?;
+
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    if partial.exists() {
+        fs::remove_dir_all(&partial).context("remove stale partial extraction")?;
+    }
+    fs::create_dir_all(&partial).context("create partial extraction dir")?;
+
+    let mut producer = Command::new(&rpm2cpio)
+        .arg(&rpm)
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("spawn rpm2cpio")?;
+    let producer_out = producer.stdout.take().context("rpm2cpio stdout")?;
+    let cpio_status = Command::new(&cpio)
+        .args(["-idm", "--quiet", "--no-absolute-filenames"])
+        .current_dir(&partial)
+        .stdin(producer_out)
+        .status()
+        .context("run cpio")?;
+    let producer_status = producer.wait().context("wait for rpm2cpio")?;
+    if !producer_status.success() {
+        bail!("rpm2cpio failed for {}: {producer_status}", rpm.display());
+    }
+    if !cpio_status.success() {
+        bail!("cpio failed extracting {}: {cpio_status}", rpm.display());
+    }
+
+    if !partial.join(edge_bin_rel).is_file() {
+        return Err(edge_bin_missing(&partial, edge_bin_rel))
+            .with_context(|| format!("{} is not the expected Edge RPM layout", rpm.display()));
+    }
+
+    if dest.exists() {
+        fs::remove_dir_all(dest).context("remove previous extracted root")?;
+    }
+    fs::rename(&partial, dest).context("move extraction into place")?;
+    fs::write(&marker_path, marker).context("write rpm extraction marker")?;
+
+    Ok(RpmExtraction {
+        reused: false,
+        elapsed: start.elapsed(),
+        edge_bin,
+    })
+}
+
 fn resolve_in_path(program: &str) -> Result<PathBuf> {
     let candidate = Path::new(program);
     if candidate.components().count() > 1 {
@@ -4209,3 +9594,540 @@ fn resolve_in_path(program: &str) -> Result<PathBuf> {
 
     bail!("{program} not found in PATH")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_campaign_counters() {
+        let spread = CounterSpread::of(vec![0, 7, 0, 3]).unwrap();
+        assert_eq!(spread.text(), "runs_with_events=2 min=0 median=0 max=7");
+        assert!(CounterSpread::of(Vec::new()).is_none());
+        assert_eq!(lower_median(&mut [5, 1, 9]), Some(5));
+    }
+
+    #[test]
+    fn ok_outcome_needs_clean_stderr() {
+        let mut c = OutcomeCounters {
+            exit_reason: "ok",
+            exit_code: 0,
+            stdout_bytes: 512,
+            pthread_create_lines: 0,
+            pthread_stack_enomem_events: 0,
+            dbus_lines: 0,
+            ssl_lines: 0,
+            oom_reported: false,
+            stopped_on_signature: false,
+        };
+        assert_eq!(classify_outcome(&c), "ok");
+        c.dbus_lines = 3;
+        assert_eq!(classify_outcome(&c), "unknown");
+        c.stdout_bytes = 0;
+        assert_eq!(classify_outcome(&c), "dbus-blocked");
+    }
+
+    #[test]
+    fn parses_multi_field_guest_sysctls() {
+        assert_eq!(
+            parse_guest_sysctl("vm.overcommit_memory=1"),
+            Ok(("vm.overcommit_memory", "1".to_string()))
+        );
+        assert_eq!(
+            parse_guest_sysctl("net.ipv4.tcp_rmem=4096\t131072  6291456"),
+            Ok(("net.ipv4.tcp_rmem", "4096\t131072\t6291456".to_string()))
+        );
+        // Pasted from `cat /proc/sys/kernel/printk`, which prints the fields tab-separated.
+        assert_eq!(
+            parse_guest_sysctl("kernel.printk=4\t4\t1\t7"),
+            Ok(("kernel.printk", "4\t4\t1\t7".to_string()))
+        );
+        assert_eq!(
+            parse_guest_sysctl("kernel.sched_rt_runtime_us= 950000"),
+            Ok(("kernel.sched_rt_runtime_us", "950000".to_string()))
+        );
+        assert_eq!(
+            parse_guest_sysctl("vm.swappiness= 10 "),
+            Ok(("vm.swappiness", "10".to_string()))
+        );
+        assert_eq!(
+            parse_guest_sysctl("kernel.core_pattern=|/usr/bin/x %p"),
+            Ok(("kernel.core_pattern", "|/usr/bin/x %p".to_string()))
+        );
+        assert_eq!(
+            parse_guest_sysctl("net.ipv4.tcp_rmem=4096\n131072"),
+            Err("newline in value")
+        );
+        assert_eq!(parse_guest_sysctl("vm..x=1"), Err("bad key"));
+    }
+
+    #[test]
+    fn scans_stack_mprotect_enomem_in_plain_strace() {
+        let text = "\
+123 mmap(NULL, 8392704, PROT_NONE, MAP_PRIVATE|MAP_ANONYMOUS|MAP_STACK, -1, 0) = 0x7f0000000000
+123 mprotect(0x7f0000001000, 8388608, PROT_READ|PROT_WRITE) = -1 ENOMEM (Cannot allocate memory)
+123 mmap(NULL, 8392704, PROT_NONE, MAP_PRIVATE|MAP_ANONYMOUS|MAP_STACK, -1, 0) = 0x7f1000000000
+123 mprotect(0x7f1000001000, 8388608, PROT_READ|PROT_WRITE) = 0
+";
+        let mut report = String::new();
+        assert_eq!(scan_stack_mprotect_enomem(text, &mut report), 1);
+        assert!(report.contains("mmap_base: 0x7f0000000000 mmap_len: 8392704"));
+    }
+
+    #[test]
+    fn detects_crashpad_handlers() {
+        assert!(is_crashpad_handler(
+            "/opt/microsoft/msedge/msedge_crashpad_handler --monitor-self --database=/x"
+        ));
+        assert!(is_crashpad_handler(
+            "/usr/bin/FEXInterpreter /opt/microsoft/msedge/msedge_crashpad_handler --no-periodic-tasks"
+        ));
+        assert!(!is_crashpad_handler(
+            "/opt/microsoft/msedge/msedge --type=renderer --crashpad-handler-pid=42"
+        ));
+        assert!(!is_crashpad_handler(
+            "/opt/microsoft/msedge/msedge --headless --disable-features=Crashpad"
+        ));
+    }
+
+    #[test]
+    fn parses_psi_avg10() {
+        let memory = "some avg10=12.50 avg60=3.00 avg300=0.70 total=123456\n\
+                      full avg10=4.25 avg60=1.00 avg300=0.20 total=45678\n";
+        assert_eq!(parse_psi_avg10(memory), (Some(12.5), Some(4.25)));
+        let old_cpu = "some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n";
+        assert_eq!(parse_psi_avg10(old_cpu), (Some(0.0), None));
+    }
+
+    #[test]
+    fn ddmin_finds_minimal_interacting_flags() {
+        let mut tested = 0;
+        let mut changed = |subset: &[usize]| {
+            tested += 1;
+            Ok(subset.contains(&3) && subset.contains(&5))
+        };
+        let mut log = String::new();
+        let minimal = ddmin((0..8).collect(), &mut changed, &mut log).unwrap();
+        assert_eq!(minimal, vec![3, 5]);
+        assert!(tested < 30, "{tested} tests");
+        assert!(log.starts_with("split [0,1,2,3,4,5,6,7] into 2\n"));
+        assert!(log.contains("-> changed"));
+    }
+
+    #[test]
+    fn renders_text_and_json_summaries_from_one_struct() {
+        let dir = std::env::temp_dir().join(format!("edge-muvm-summary-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut summary = EdgeSummary::default();
+        summary.push("exit_code", -1);
+        summary.push("stdout_bytes", 42u64);
+        summary.push("pthread_pids_from_stderr", SummaryValue::List(Vec::new()));
+        summary.push("edge_exit", "exit_code=0");
+        summary.preflight_kvs = vec![
+            (
+                "vm_max_map_count".into(),
+                SummaryValue::parse("65530".into()),
+            ),
+            ("umask".into(), SummaryValue::parse("0022".into())),
+        ];
+        summary.artifact("stdout", Path::new("/run/stdout.txt"));
+        summary.write(&dir, SummaryFormat::Both, &[]).unwrap();
+
+        let text = fs::read_to_string(dir.join("summary.txt")).unwrap();
+        assert!(
+            text.starts_with("exit_code: -1\nstdout_bytes: 42\npthread_pids_from_stderr: (none)\n")
+        );
+        assert!(text.contains("\npreflight_kvs:\n  vm_max_map_count: 65530\n"));
+        let json: serde_json::Value =
+            serde_json::from_slice(&fs::read(dir.join("summary.json")).unwrap()).unwrap();
+        assert_eq!(json["stdout_bytes"], 42);
+        assert_eq!(json["pthread_pids_from_stderr"], serde_json::json!([]));
+        assert_eq!(json["preflight_kvs"]["vm_max_map_count"], 65530);
+        assert_eq!(json["preflight_kvs"]["umask"], "0022");
+        assert_eq!(json["artifacts"]["stdout"], "/run/stdout.txt");
+
+        fs::remove_file(dir.join("summary.txt")).unwrap();
+        let kvs = read_run_summary(&dir).unwrap();
+        assert_eq!(kvs["exit_code"], "-1");
+        assert_eq!(kvs["pthread_pids_from_stderr"], "(none)");
+        assert!(!kvs.contains_key("artifacts"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_edge_arg_file_skipping_comments() {
+        let dir = std::env::temp_dir().join(format!("edge-muvm-argfile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("args.txt");
+        fs::write(
+            &path,
+            "# flags\n--no-sandbox\n\n  --lang=de  \n#--disable-gpu\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_edge_arg_file(&path).unwrap(),
+            vec!["--no-sandbox", "--lang=de"]
+        );
+        fs::write(&path, "--ok\n--bad\0arg\n").unwrap();
+        let err = read_edge_arg_file(&path).unwrap_err().to_string();
+        assert!(err.contains("NUL byte") && err.ends_with(":2"), "{err}");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn times_first_dbus_failure_from_stderr() {
+        let dir = std::env::temp_dir().join(format!("edge-muvm-dbus-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let stderr = dir.join("stderr.txt");
+        fs::write(
+            &stderr,
+            "[100:100:1231/235959.500000:INFO:content_main.cc(1)] start\n\
+             Failed to connect to the bus: unbracketed\n\
+             [101:7:0101/000001.250000:ERROR:bus.cc(407)] Failed to connect to the bus: a\n\
+             [102:9:0101/000003.000000:ERROR:bus.cc(407)] Failed to connect to the bus: b\n",
+        )
+        .unwrap();
+        let analysis = analyze_dbus_failures(&stderr, &dir.join("dbus.failures.txt")).unwrap();
+        assert_eq!(analysis.total, 3);
+        assert_eq!(analysis.pattern(), "repeating");
+        assert_eq!(analysis.pids, vec![101, 102]);
+        assert!((analysis.first_offset_seconds.unwrap() - 1.75).abs() < 1e-6);
+        assert!(analysis.first_line.unwrap().ends_with("bus: a"));
+        assert!(analysis.last_line.unwrap().ends_with("bus: b"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn maps_syscall_numbers_per_abi() {
+        assert_eq!(SyscallAbi::Aarch64.ppoll_nr(), 73);
+        assert_eq!(SyscallAbi::X86_64.ppoll_nr(), 271);
+        assert_eq!(SyscallAbi::Aarch64.syscall_name(226), Some("mprotect"));
+        assert_eq!(SyscallAbi::X86_64.syscall_name(9), Some("mmap"));
+        assert_eq!(SyscallAbi::X86_64.syscall_name(73), None);
+    }
+
+    #[test]
+    fn compares_run_dirs_marking_absent_stderr() {
+        let with_stderr = [
+            ("stdout_bytes", "0".to_string()),
+            ("stderr_pthread_create_lines", "12".to_string()),
+        ];
+        let without_stderr = [
+            ("stdout_bytes", "0".to_string()),
+            ("stderr_pthread_create_lines", ABSENT.to_string()),
+        ];
+        let a: HashMap<String, String> = [("vm_overcommit_memory", "1"), ("guest_nproc", "4")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mut b = a.clone();
+        b.insert("vm_overcommit_memory".into(), "0".into());
+        b.insert("kernel_too_old".into(), "no".into());
+
+        let rows = compare_run_dir_rows([&with_stderr, &without_stderr], [Some(&a), Some(&b)]);
+        let row = |k: &str, va: &str, vb: &str| (k.to_string(), va.to_string(), vb.to_string());
+        assert_eq!(
+            rows,
+            vec![
+                row("stderr_pthread_create_lines", "12", ABSENT),
+                row("preflight.kernel_too_old", ABSENT, "no"),
+                row("preflight.vm_overcommit_memory", "1", "0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn compresses_artifacts_and_reads_them_back() {
+        let dir = std::env::temp_dir().join(format!("edge-muvm-compress-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let muvm = dir.join("muvm.txt");
+        fs::write(&muvm, "muvm output\n".repeat(64)).unwrap();
+        fs::write(dir.join("stderr.txt"), "kept\n").unwrap();
+        let mut summary = EdgeSummary::default();
+        summary.push("muvm_output", muvm.display().to_string());
+
+        compress_run_artifacts(&dir, &mut summary, 16).unwrap();
+        assert!(!muvm.exists());
+        assert!(dir.join("stderr.txt").is_file());
+        assert_eq!(read_artifact(&muvm).unwrap(), "muvm output\n".repeat(64));
+        // A `.gz` path is decompressed even when the plain file is back next to it.
+        fs::write(&muvm, "stale\n").unwrap();
+        let gz = gz_sibling(&muvm);
+        assert_eq!(read_artifact(&gz).unwrap(), "muvm output\n".repeat(64));
+        let text = summary.render_text();
+        assert!(text.starts_with(&format!("muvm_output: {}.gz\n", muvm.display())));
+        assert!(text.contains("compressed_artifacts:\n  muvm.txt.gz: 768 -> "));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn describes_only_present_artifacts() {
+        let names: Vec<String> = [
+            "profile/",
+            "strace.12",
+            "strace.13.gz",
+            "strace.enabled.txt",
+            "summary.txt",
+            "unknown.bin",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let text = render_artifacts_readme(&names);
+        assert!(text.contains("\n- `summary.txt`: Host-side"));
+        assert!(text.contains("\n- `strace.enabled.txt`: Whether"));
+        assert!(text.contains("\n- `strace.*` (2 files): Guest strace"));
+        assert!(text.contains("\n- `profile/`: Edge user-data"));
+        assert!(!text.contains("stuck.txt"));
+        assert!(text.ends_with("Not described above:\n\n- `unknown.bin`\n"));
+    }
+
+    #[test]
+    fn derives_exit_reason_detail() {
+        let detail = |edge_exit: &str, rc: i32, timed_out: bool, stuck: bool| {
+            exit_reason_detail(&EndEvidence {
+                edge_exit,
+                muvm_exit_code: rc,
+                muvm_timed_out: timed_out,
+                watchdog_fired: stuck,
+                stopped_on_signature: false,
+            })
+        };
+        assert_eq!(
+            detail("edge_exit: exit status: 0\n", 0, false, false),
+            "edge-exited-0"
+        );
+        assert_eq!(
+            detail(
+                "edge_exit: signal: 11 (SIGSEGV) (core dumped)\n",
+                139,
+                false,
+                false
+            ),
+            "edge-crashed-SIGSEGV"
+        );
+        assert_eq!(
+            detail("edge_exit: signal: 9 (SIGKILL)\n", 0, false, true),
+            "edge-killed-SIGKILL-by-watchdog"
+        );
+        assert_eq!(
+            detail("edge_exit: signal: 9 (SIGKILL)\n", 0, false, false),
+            "edge-killed-SIGKILL"
+        );
+        assert_eq!(detail("(unavailable: x)", 0, true, false), "muvm-timeout");
+        assert_eq!(detail("(unavailable: x)", 1, false, false), "muvm-exited-1");
+    }
+
+    #[test]
+    fn finds_dbus_unix_socket() {
+        assert_eq!(
+            dbus_unix_socket("unix:path=/run/user/1000/bus").as_deref(),
+            Some("/run/user/1000/bus")
+        );
+        assert_eq!(
+            dbus_unix_socket("tcp:host=x,port=1;unix:abstract=/tmp/dbus-ab,guid=0f").as_deref(),
+            Some("@/tmp/dbus-ab")
+        );
+        assert_eq!(dbus_unix_socket("tcp:host=x,port=1"), None);
+    }
+
+    #[test]
+    fn parses_fex_binfmt_entry() {
+        let entry = parse_binfmt_entry(
+            "enabled\ninterpreter /usr/bin/FEXInterpreter\nflags: POCF\noffset 0\nmagic 7f454c46\n",
+        );
+        assert!(entry.enabled);
+        assert_eq!(
+            entry.interpreter.as_deref(),
+            Some("/usr/bin/FEXInterpreter")
+        );
+        assert_eq!(entry.flags.as_deref(), Some("POCF"));
+        assert!(!parse_binfmt_entry("disabled\n").enabled);
+    }
+
+    #[test]
+    fn parses_classifier_file_and_renders_json() {
+        let text = "# fex\nptrace: ptrace:\n\nssl: ssl_client_socket_impl.cc:930\n";
+        let sigs = parse_classifier_file(text).unwrap();
+        assert_eq!(
+            sigs,
+            vec![
+                ("ptrace".to_string(), "ptrace:".to_string()),
+                (
+                    "ssl".to_string(),
+                    "ssl_client_socket_impl.cc:930".to_string()
+                ),
+            ]
+        );
+        assert!(parse_classifier_file("ptrace: a\nptrace: b\n").is_err());
+        assert!(parse_classifier_file("no separator\n").is_err());
+
+        let json = render_classifier_counts_json(
+            Path::new("run/stderr.txt"),
+            Path::new("sigs \"v2\".txt"),
+            7,
+            &sigs,
+            &[3, 0],
+        );
+        assert_eq!(
+            json,
+            "{\n  \"stderr\": \"run/stderr.txt\",\n  \"classifier_file\": \"sigs \\\"v2\\\".txt\",\n  \
+             \"stderr_lines\": 7,\n  \"counts\": {\n    \"ptrace\": 3,\n    \"ssl\": 0\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn proc_stat_survives_pathological_comm() {
+        let stat = b"4242 ((evil) )\n () S 1 4242 4242 34817 4242 4194560 0 0\n";
+        let (pid, comm, _) = split_proc_stat(stat).unwrap();
+        assert_eq!(pid, "4242");
+        assert_eq!(comm, "(evil) )\n (");
+        assert_eq!(escape_comm(&comm), "(evil) )\\n (");
+
+        let jc = parse_proc_stat_job_control(stat).unwrap();
+        assert_eq!(jc.state, 'S');
+        assert_eq!(jc.ppid, 1);
+        assert_eq!(jc.pgrp, 4242);
+        assert_eq!(jc.session, 4242);
+        assert_eq!(jc.tty_nr, 34817);
+        assert_eq!(jc.tpgid, 4242);
+    }
+
+    #[test]
+    fn redacts_sensitive_env_values() {
+        let text = b"HOME=/root\0GH_TOKEN=abc\0EDGE_ENV=API_KEY=k1 MY_VAR=x\n\"MY_VAR=y\"";
+        let mut keys = BTreeSet::new();
+        let out = redact_env_values(text, &["MY_VAR".to_string()], &mut keys);
+        assert_eq!(
+            out,
+            b"HOME=/root\0GH_TOKEN=***\0EDGE_ENV=API_KEY=*** MY_VAR=***\n\"MY_VAR=***\"".to_vec()
+        );
+        assert_eq!(
+            keys.into_iter().collect::<Vec<_>>(),
+            ["API_KEY", "GH_TOKEN", "MY_VAR"]
+        );
+    }
+
+    #[test]
+    fn matrix_run_fails_on_timeout_exit_or_classifier() {
+        assert_eq!(matrix_run_failure(false, 0, None), None);
+        assert_eq!(
+            matrix_run_failure(false, 0, Some(("stderr_ptrace_lines", 0))),
+            None
+        );
+        assert_eq!(
+            matrix_run_failure(false, 0, Some(("stderr_ptrace_lines", 2))).as_deref(),
+            Some("stderr_ptrace_lines=2")
+        );
+        assert_eq!(
+            matrix_run_failure(true, 124, Some(("stderr_ptrace_lines", 2))).as_deref(),
+            Some("timed out")
+        );
+        assert_eq!(
+            matrix_run_failure(false, 1, None).as_deref(),
+            Some("non-zero exit")
+        );
+    }
+
+    #[test]
+    fn detects_background_tty_stop_in_snapshot() {
+        // Laid out as snapshot_proc writes it; SigPnd has SIGTTOU (22) pending.
+        let snapshot = |label: &str, sigpnd: &str, state: char, fg: &str| {
+            let mut out = format!("[{label}] status_signals_decoded\n");
+            append_decoded_status_signals(
+                &mut out,
+                &format!(
+                    "SigPnd:\t{sigpnd}\nShdPnd:\t0000000000000000\nSigBlk:\t0000000000000000\n"
+                ),
+            );
+            out.push_str(&format!(
+                "\n[{label}] job_control (from /proc/1/stat + stdio)\n\
+                 state={state} ppid=1 pgrp=7 session=1 tpgid=9 tty_nr=34817 is_foreground_pgrp={fg}\n"
+            ));
+            out
+        };
+        let ttou = "0000000000200000";
+
+        let text =
+            snapshot("edge", "0000000000000000", 'S', "yes") + &snapshot("child", ttou, 'T', "no");
+        assert_eq!(
+            detect_tty_stop(&text),
+            Some(("child".to_string(), "SIGTTOU".to_string()))
+        );
+        // Stopped in the foreground, or pending without being stopped, is not a TTY stop.
+        assert_eq!(detect_tty_stop(&snapshot("edge", ttou, 'T', "yes")), None);
+        assert_eq!(detect_tty_stop(&snapshot("edge", ttou, 'S', "no")), None);
+        // A stop signal pending on an earlier process doesn't carry over to the next one.
+        let text =
+            snapshot("edge", ttou, 'S', "no") + &snapshot("child", "0000000000000000", 'T', "no");
+        assert_eq!(detect_tty_stop(&text), None);
+    }
+
+    #[test]
+    fn process_tree_lists_parents_before_children() {
+        let mut sh = Command::new("sh")
+            .args(["-c", "sleep 30 & wait"])
+            .spawn()
+            .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let sleep_pid = loop {
+            match pids_by_ppid(sh.id()).unwrap_or_default().first() {
+                Some(&pid) => break pid,
+                None if Instant::now() > deadline => panic!("sleep never started"),
+                None => std::thread::sleep(Duration::from_millis(20)),
+            }
+        };
+        let tree = process_tree(std::process::id(), usize::MAX, 64);
+        let shallow = process_tree(std::process::id(), 1, 64);
+        kill_process_tree(sh.id(), libc::SIGKILL, 64);
+        let _ = sh.wait();
+
+        let at = |pid| tree.iter().position(|&p| p == pid).unwrap();
+        assert_eq!(tree[0], std::process::id());
+        assert!(at(sh.id()) < at(sleep_pid), "{tree:?}");
+        assert!(shallow.contains(&sh.id()) && !shallow.contains(&sleep_pid));
+        assert_eq!(
+            process_tree(std::process::id(), 0, 64),
+            [std::process::id()]
+        );
+    }
+
+    #[test]
+    fn suggests_edge_binaries_from_an_rpm_shaped_tree() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("edge-muvm-rpm-tree-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let put = |rel: &str, contents: &[u8], mode: u32| {
+            let path = root.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, contents).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        };
+        let elf = b"\x7fELF\x02\x01\x01\0";
+        put("etc/cron.daily/microsoft-edge", b"#!/bin/sh\n", 0o755);
+        put("usr/bin/microsoft-edge-stable", b"#!/bin/sh\n", 0o755);
+        put(
+            "usr/share/applications/microsoft-edge.desktop",
+            b"[Desktop Entry]\n",
+            0o644,
+        );
+        put(
+            "opt/microsoft/msedge/microsoft-edge",
+            b"#!/bin/bash\n",
+            0o755,
+        );
+        put("opt/microsoft/msedge/msedge", elf, 0o755);
+
+        assert_eq!(
+            find_edge_binaries(&root),
+            [
+                "opt/microsoft/msedge/microsoft-edge",
+                "opt/microsoft/msedge/msedge",
+                "usr/bin/microsoft-edge-stable",
+            ]
+            .map(PathBuf::from)
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,94 @@
+//! Spawns `snapshot-target` in a known state and checks what `--mode snapshot-pid` reports.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+
+struct Target {
+    child: Child,
+    pid: u32,
+}
+
+impl Drop for Target {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn spawn_target(args: &[&str]) -> Target {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_snapshot-target"))
+        .args(args)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn snapshot-target");
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .expect("read target_pid");
+    let pid = line
+        .trim()
+        .strip_prefix("target_pid=")
+        .and_then(|p| p.parse().ok())
+        .unwrap_or_else(|| panic!("unexpected snapshot-target output: {line:?}"));
+    Target { child, pid }
+}
+
+fn snapshot(pid: u32, extra: &[&str]) -> String {
+    let out = Command::new(env!("CARGO_BIN_EXE_edge-muvm-experiment"))
+        .args(["--mode", "snapshot-pid", "--pid", &pid.to_string()])
+        .args(extra)
+        .output()
+        .expect("run snapshot-pid");
+    assert!(
+        out.status.success(),
+        "snapshot-pid failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).expect("utf-8 snapshot")
+}
+
+fn first_value(text: &str, key: &str) -> usize {
+    text.lines()
+        .find_map(|l| l.strip_prefix(key))
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or_else(|| panic!("missing {key} in snapshot"))
+}
+
+#[test]
+fn many_threads_reports_elided_tasks() {
+    let target = spawn_target(&["--state", "many-threads", "--threads", "40"]);
+    let text = snapshot(target.pid, &["--snapshot-max-tasks", "8"]);
+
+    let count = first_value(&text, "task_count:");
+    assert!(count > 40, "task_count={count}");
+    assert_eq!(first_value(&text, "task_elided:"), count - 8);
+}
+
+#[test]
+fn tty_stopped_shows_background_stop() {
+    let target = spawn_target(&["--state", "tty-stopped"]);
+    let text = snapshot(target.pid, &[]);
+
+    let jc = text
+        .lines()
+        .find(|l| l.starts_with("state="))
+        .expect("job_control line");
+    assert!(jc.starts_with("state=T "), "{jc}");
+    assert!(jc.contains("is_foreground_pgrp=no"), "{jc}");
+    assert!(!jc.contains("tty_nr=0 "), "{jc}");
+}
+
+#[test]
+fn ppoll_eventfd_exposes_eventfd_and_pipe() {
+    let target = spawn_target(&["--state", "ppoll-eventfd"]);
+    let text = snapshot(target.pid, &[]);
+
+    assert!(text.contains("anon_inode:[eventfd]"), "{text}");
+    assert!(text.contains("pipe:["), "{text}");
+    assert!(text.contains("ppoll decoded: nfds=2"), "{text}");
+    assert!(text.contains("pipe_wakeup_path:"), "{text}");
+    assert!(
+        text.contains(&format!("--- writer_pid {} ---", target.pid)),
+        "{text}"
+    );
+}
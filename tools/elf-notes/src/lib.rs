@@ -0,0 +1,524 @@
+//! ELF helpers shared by the FEX-facing tools: detecting x86_64 ELFs and stripping the
+//! `.note.gnu.property` (CET) section that FEX rejects.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+pub const GNU_PROPERTY_SECTION: &str = ".note.gnu.property";
+
+#[derive(Default, Debug, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StripReport {
+    pub stripped_files: Vec<String>,
+    pub strip_failures: Vec<StripFailure>,
+    pub remaining_gnu_property_files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StripFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Strips `.note.gnu.property` under each existing `root/<rel>` directory, then re-checks the
+/// x86_64 ELFs found while stripping so `remaining_gnu_property_files` reflects what is still
+/// left.
+pub fn strip_gnu_property_notes_under(
+    root: &Path,
+    rel_dirs: &[&str],
+    objcopy: &OsString,
+) -> Result<StripReport> {
+    let mut report = StripReport::default();
+    let mut x86_64_elfs = Vec::new();
+
+    for rel in rel_dirs {
+        let dir = root.join(rel);
+        if dir.exists() {
+            x86_64_elfs.extend(
+                strip_gnu_property_notes_in_tree(&dir, &mut report, objcopy)
+                    .with_context(|| format!("Stripping notes under {}", dir.display()))?,
+            );
+        }
+    }
+
+    // Verify: stripping does not change which files are x86_64 ELFs, so only those need
+    // re-checking for the note.
+    x86_64_elfs.sort();
+    x86_64_elfs.dedup();
+    report.remaining_gnu_property_files =
+        files_with_gnu_property(&x86_64_elfs, true).context("Scanning remaining notes")?;
+
+    report.stripped_files.sort();
+    report.strip_failures.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(report)
+}
+
+/// Resolves the `objcopy` used for stripping: `explicit` if given, else the first of
+/// `objcopy`, `llvm-objcopy`, `eu-objcopy` that runs.
+pub fn resolve_objcopy_path(explicit: Option<&Path>) -> Result<OsString> {
+    if let Some(p) = explicit {
+        return Ok(p.as_os_str().to_os_string());
+    }
+
+    fn works(candidate: &str, arg: &str) -> bool {
+        Command::new(candidate)
+            .arg(arg)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    for candidate in ["objcopy", "llvm-objcopy", "eu-objcopy"] {
+        if works(candidate, "--version") || works(candidate, "-V") {
+            return Ok(OsString::from(candidate));
+        }
+    }
+
+    bail!(
+        "No usable objcopy found (tried: objcopy, llvm-objcopy, eu-objcopy). Install binutils (or llvm/eu-binutils) or pass --objcopy-path."
+    )
+}
+
+/// Strips `.note.gnu.property` in place from every x86_64 ELF under `root`, recording
+/// successes and per-file failures in `report`. Returns every x86_64 ELF it classified.
+pub fn strip_gnu_property_notes_in_tree(
+    root: &Path,
+    report: &mut StripReport,
+    objcopy: &OsString,
+) -> Result<Vec<PathBuf>> {
+    let mut x86_64_elfs = Vec::new();
+    walk_files(root, &mut |path| {
+        if !is_elf_x86_64(path)? {
+            return Ok(());
+        }
+        x86_64_elfs.push(path.to_path_buf());
+        if !elf_has_section(path, GNU_PROPERTY_SECTION.as_bytes())? {
+            return Ok(());
+        }
+
+        // objcopy edits the file in-place.
+        let out = Command::new(objcopy)
+            .arg("--remove-section")
+            .arg(GNU_PROPERTY_SECTION)
+            .arg(path)
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("objcopy on {}", path.display()))?;
+        if !out.status.success() {
+            // Don't hard-fail on a single file; keep going but surface stderr.
+            report.strip_failures.push(StripFailure {
+                path: path.display().to_string(),
+                error: String::from_utf8_lossy(&out.stderr).to_string(),
+            });
+        } else {
+            report.stripped_files.push(path.display().to_string());
+        }
+        Ok(())
+    })?;
+    Ok(x86_64_elfs)
+}
+
+/// Records x86_64 ELFs under `root` that still carry `.note.gnu.property`.
+pub fn collect_remaining_gnu_property_files(root: &Path, report: &mut StripReport) -> Result<()> {
+    let mut files = Vec::new();
+    walk_files(root, &mut |path| {
+        files.push(path.to_path_buf());
+        Ok(())
+    })?;
+    report
+        .remaining_gnu_property_files
+        .extend(files_with_gnu_property(&files, false)?);
+    report.remaining_gnu_property_files.sort();
+    report.remaining_gnu_property_files.dedup();
+    Ok(())
+}
+
+fn walk_files(dir: &Path, f: &mut dyn FnMut(&Path) -> Result<()>) -> Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("read_dir {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        let meta = std::fs::symlink_metadata(&path)
+            .with_context(|| format!("symlink_metadata {}", path.display()))?;
+        if meta.is_dir() {
+            walk_files(&path, f)?;
+        } else if meta.is_file() {
+            f(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Upper bound on verification threads; the work is mostly small reads.
+const MAX_SCAN_WORKERS: usize = 8;
+
+/// The x86_64 ELFs among `files` that carry `.note.gnu.property`, sorted. Files are checked
+/// by a bounded pool of scoped threads; the first error wins. `known_x86_64` skips
+/// re-classifying files the caller already knows are x86_64 ELFs.
+fn files_with_gnu_property(files: &[PathBuf], known_x86_64: bool) -> Result<Vec<String>> {
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_SCAN_WORKERS)
+        .min(files.len().max(1));
+    let next = AtomicUsize::new(0);
+    let found = Mutex::new(Vec::new());
+    let first_err: Mutex<Option<anyhow::Error>> = Mutex::new(None);
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = files.get(idx) else {
+                    break;
+                };
+                let is_x86 = if known_x86_64 {
+                    Ok(true)
+                } else {
+                    is_elf_x86_64(path)
+                };
+                let hit = is_x86.and_then(|is_x86| {
+                    Ok(is_x86 && elf_has_section(path, GNU_PROPERTY_SECTION.as_bytes())?)
+                });
+                match hit {
+                    Ok(true) => found.lock().unwrap().push(path.display().to_string()),
+                    Ok(false) => {}
+                    Err(e) => {
+                        first_err.lock().unwrap().get_or_insert(e);
+                        // Stop handing out work; other workers finish their current file.
+                        next.store(files.len(), Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(e) = first_err.into_inner().unwrap() {
+        return Err(e);
+    }
+    let mut found = found.into_inner().unwrap();
+    found.sort();
+    Ok(found)
+}
+
+pub const EM_X86_64: u16 = 62;
+pub const EM_AARCH64: u16 = 183;
+
+pub fn is_elf_x86_64(path: &Path) -> Result<bool> {
+    Ok(elf64_le_machine(path)? == Some(EM_X86_64))
+}
+
+/// Returns `e_machine` for ELF64 little-endian files, `None` for anything else.
+pub fn elf64_le_machine(path: &Path) -> Result<Option<u16>> {
+    use std::io::Read;
+    let mut f = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut hdr = [0u8; 64];
+    let n = f
+        .read(&mut hdr)
+        .with_context(|| format!("read {}", path.display()))?;
+    if n < 20 {
+        return Ok(None);
+    }
+    if &hdr[0..4] != b"\x7fELF" {
+        return Ok(None);
+    }
+    // Only handle ELF64 little-endian here (fits our target).
+    if hdr[4] != 2 || hdr[5] != 1 {
+        return Ok(None);
+    }
+    Ok(Some(u16::from_le_bytes([hdr[18], hdr[19]])))
+}
+
+/// One entry of an ELF section header table.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ElfSection {
+    pub name: String,
+    pub sh_type: u32,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Whether the ELF64 little-endian file at `path` has a section named `section_name`.
+pub fn elf_has_section(path: &Path, section_name: &[u8]) -> Result<bool> {
+    Ok(elf_sections(path)?
+        .is_some_and(|sections| sections.iter().any(|s| s.name.as_bytes() == section_name)))
+}
+
+/// Reads the section header table of an ELF64 little-endian file; `None` for anything else
+/// (including ELFs without section headers).
+pub fn elf_sections(path: &Path) -> Result<Option<Vec<ElfSection>>> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut f = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+
+    let mut ehdr = [0u8; 64];
+    f.read_exact(&mut ehdr)
+        .with_context(|| format!("read ELF header {}", path.display()))?;
+    if &ehdr[0..4] != b"\x7fELF" {
+        return Ok(None);
+    }
+    if ehdr[4] != 2 || ehdr[5] != 1 {
+        return Ok(None);
+    }
+
+    let e_shoff = u64::from_le_bytes(ehdr[40..48].try_into().unwrap());
+    let e_shentsize = u16::from_le_bytes(ehdr[58..60].try_into().unwrap()) as u64;
+    let e_shnum = u16::from_le_bytes(ehdr[60..62].try_into().unwrap()) as u64;
+    let e_shstrndx = u16::from_le_bytes(ehdr[62..64].try_into().unwrap()) as u64;
+    if e_shoff == 0 || e_shentsize < 64 || e_shnum == 0 || e_shstrndx >= e_shnum {
+        return Ok(None);
+    }
+
+    // Read the section header string table header.
+    f.seek(SeekFrom::Start(e_shoff + e_shentsize * e_shstrndx))
+        .with_context(|| format!("seek shstrndx {}", path.display()))?;
+    let mut sh = vec![0u8; e_shentsize as usize];
+    f.read_exact(&mut sh)
+        .with_context(|| format!("read shstr header {}", path.display()))?;
+
+    // sh_offset/sh_size in ELF64 section header: offsets 24..32, 32..40.
+    let shstr_off = u64::from_le_bytes(sh[24..32].try_into().unwrap());
+    let shstr_size = u64::from_le_bytes(sh[32..40].try_into().unwrap());
+    // Cap to something sane to avoid huge allocations on corrupt binaries.
+    let cap = shstr_size.min(16 * 1024 * 1024);
+    f.seek(SeekFrom::Start(shstr_off))
+        .with_context(|| format!("seek shstrtab {}", path.display()))?;
+    let mut shstr = vec![0u8; cap as usize];
+    f.read_exact(&mut shstr)
+        .with_context(|| format!("read shstrtab {}", path.display()))?;
+
+    let mut sections = Vec::with_capacity(e_shnum as usize);
+    for idx in 0..e_shnum {
+        f.seek(SeekFrom::Start(e_shoff + e_shentsize * idx))
+            .with_context(|| format!("seek section header {}", path.display()))?;
+        f.read_exact(&mut sh)
+            .with_context(|| format!("read section header {}", path.display()))?;
+        let name_off = u32::from_le_bytes(sh[0..4].try_into().unwrap()) as usize;
+        let name = if name_off < shstr.len() {
+            let end = shstr[name_off..]
+                .iter()
+                .position(|&b| b == 0)
+                .map(|p| name_off + p)
+                .unwrap_or(shstr.len());
+            String::from_utf8_lossy(&shstr[name_off..end]).into_owned()
+        } else {
+            String::new()
+        };
+        sections.push(ElfSection {
+            name,
+            sh_type: u32::from_le_bytes(sh[4..8].try_into().unwrap()),
+            offset: u64::from_le_bytes(sh[24..32].try_into().unwrap()),
+            size: u64::from_le_bytes(sh[32..40].try_into().unwrap()),
+        });
+    }
+
+    Ok(Some(sections))
+}
+
+/// `.note.ABI-tag`: the OS and minimum kernel version a binary was built for.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AbiTag {
+    pub os: u32,
+    pub min_kernel: KernelVersion,
+}
+
+/// A kernel `major.minor.patch`, ordered so a binary's minimum can be compared to a release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct KernelVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl KernelVersion {
+    /// Leading `major.minor.patch` of a release like `6.13.5-400.asahi.fc41.aarch64+16k`
+    /// (as printed by `uname -r`); missing components count as 0.
+    pub fn parse_release(release: &str) -> Option<Self> {
+        let numeric = release.trim().split(['-', '+']).next()?;
+        let mut parts = numeric.split('.').map(|p| p.parse::<u32>().ok());
+        Some(Self {
+            major: parts.next()??,
+            minor: parts.next().flatten().unwrap_or(0),
+            patch: parts.next().flatten().unwrap_or(0),
+        })
+    }
+}
+
+impl std::fmt::Display for KernelVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// ABI requirements of an ELF64 little-endian file beyond its machine type.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ElfAbiInfo {
+    /// `e_ident[EI_OSABI]`.
+    pub osabi: u8,
+    pub abi_tag: Option<AbiTag>,
+}
+
+pub const ELFOSABI_SYSV: u8 = 0;
+pub const ELFOSABI_GNU: u8 = 3;
+
+/// `NT_GNU_ABI_TAG`, the note type of `.note.ABI-tag`.
+const NT_GNU_ABI_TAG: u32 = 1;
+
+/// Reads `e_ident[EI_OSABI]` and `.note.ABI-tag`; `None` for anything but ELF64 little-endian.
+pub fn elf_abi_info(path: &Path) -> Result<Option<ElfAbiInfo>> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut f = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut ident = [0u8; 16];
+    if f.read_exact(&mut ident).is_err() {
+        return Ok(None);
+    }
+    if &ident[0..4] != b"\x7fELF" || ident[4] != 2 || ident[5] != 1 {
+        return Ok(None);
+    }
+    let osabi = ident[7];
+
+    let section = elf_sections(path)?
+        .and_then(|sections| sections.into_iter().find(|s| s.name == ".note.ABI-tag"));
+    let abi_tag = match section {
+        Some(s) if s.sh_type == 7 => {
+            // The note is 32 bytes; cap reads on corrupt headers.
+            let mut buf = vec![0u8; s.size.min(4096) as usize];
+            f.seek(SeekFrom::Start(s.offset))
+                .with_context(|| format!("seek .note.ABI-tag {}", path.display()))?;
+            f.read_exact(&mut buf)
+                .with_context(|| format!("read .note.ABI-tag {}", path.display()))?;
+            parse_abi_tag_note(&buf)
+        }
+        _ => None,
+    };
+
+    Ok(Some(ElfAbiInfo { osabi, abi_tag }))
+}
+
+/// Finds the GNU `NT_GNU_ABI_TAG` entry in the contents of a note section.
+fn parse_abi_tag_note(buf: &[u8]) -> Option<AbiTag> {
+    let word = |off: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(
+            buf.get(off..off + 4)?.try_into().unwrap(),
+        ))
+    };
+    let mut off = 0;
+    while off + 12 <= buf.len() {
+        let namesz = word(off)? as usize;
+        let descsz = word(off + 4)? as usize;
+        let ntype = word(off + 8)?;
+        let name_off = off + 12;
+        let desc_off = name_off + namesz.div_ceil(4) * 4;
+        let name = buf.get(name_off..name_off + namesz)?;
+        if ntype == NT_GNU_ABI_TAG && name == b"GNU\0" && descsz >= 16 {
+            return Some(AbiTag {
+                os: word(desc_off)?,
+                min_kernel: KernelVersion {
+                    major: word(desc_off + 4)?,
+                    minor: word(desc_off + 8)?,
+                    patch: word(desc_off + 12)?,
+                },
+            });
+        }
+        off = desc_off + descsz.div_ceil(4) * 4;
+    }
+    None
+}
+
+/// Symbolic name for `e_ident[EI_OSABI]`, e.g. `GNU` for 3.
+pub fn osabi_name(osabi: u8) -> String {
+    let name = match osabi {
+        ELFOSABI_SYSV => "SYSV",
+        1 => "HPUX",
+        2 => "NetBSD",
+        ELFOSABI_GNU => "GNU",
+        6 => "Solaris",
+        9 => "FreeBSD",
+        12 => "OpenBSD",
+        _ => return format!("0x{osabi:x}"),
+    };
+    name.to_string()
+}
+
+/// Symbolic name for the OS word of `.note.ABI-tag`.
+pub fn abi_tag_os_name(os: u32) -> String {
+    let name = match os {
+        0 => "Linux",
+        1 => "Hurd",
+        2 => "Solaris",
+        3 => "FreeBSD",
+        _ => return format!("{os}"),
+    };
+    name.to_string()
+}
+
+/// Symbolic name for an ELF `sh_type`, e.g. `NOTE` for 7.
+pub fn section_type_name(sh_type: u32) -> String {
+    let name = match sh_type {
+        0 => "NULL",
+        1 => "PROGBITS",
+        2 => "SYMTAB",
+        3 => "STRTAB",
+        4 => "RELA",
+        5 => "HASH",
+        6 => "DYNAMIC",
+        7 => "NOTE",
+        8 => "NOBITS",
+        9 => "REL",
+        11 => "DYNSYM",
+        14 => "INIT_ARRAY",
+        15 => "FINI_ARRAY",
+        16 => "PREINIT_ARRAY",
+        17 => "GROUP",
+        18 => "SYMTAB_SHNDX",
+        19 => "RELR",
+        0x6fff_fff5 => "GNU_ATTRIBUTES",
+        0x6fff_fff6 => "GNU_HASH",
+        0x6fff_fffd => "GNU_verdef",
+        0x6fff_fffe => "GNU_verneed",
+        0x6fff_ffff => "GNU_versym",
+        _ => return format!("0x{sh_type:x}"),
+    };
+    name.to_string()
+}
+
+/// `elf-sections` output shared by the CLIs: one `index name type size` row per section.
+pub fn format_elf_sections(path: &Path) -> Result<String> {
+    let Some(sections) = elf_sections(path)? else {
+        bail!(
+            "{} is not an ELF64 little-endian file with section headers",
+            path.display()
+        );
+    };
+    let width = sections
+        .iter()
+        .map(|s| s.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut out = format!(
+        "{:>3}  {:<width$}  {:<14}  {:>12}\n",
+        "idx", "name", "type", "size"
+    );
+    for (idx, s) in sections.iter().enumerate() {
+        out.push_str(&format!(
+            "{idx:>3}  {:<width$}  {:<14}  {:>12}\n",
+            s.name,
+            section_type_name(s.sh_type),
+            s.size
+        ));
+    }
+    Ok(out)
+}
@@ -1,11 +1,16 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use cmd_lib::run_cmd;
-use std::path::PathBuf;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Output EROFS image path
     #[arg(short, long, default_value = "fedora-base.erofs")]
     output: PathBuf,
@@ -32,6 +37,13 @@ struct Cli {
     vm: bool,
 }
 
+#[derive(Subcommand)]
+enum Commands {
+    /// Print the JSON Schema of `build.report.json` (`--vm`), for validating it in CI
+    #[command(hide = true)]
+    EmitSchema,
+}
+
 fn cleanup_mounts(rootfs_dir: &std::path::Path) {
     let mounts = vec![
         "run/user/0",
@@ -58,6 +70,12 @@ fn cleanup_mounts(rootfs_dir: &std::path::Path) {
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(Commands::EmitSchema) = cli.command {
+        let schema = serde_json::json!({ "build-report": schemars::schema_for!(BuildReport) });
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
     if cli.vm {
         return run_in_vm(&cli);
     }
@@ -619,14 +637,92 @@ fn run_in_vm(cli: &Cli) -> Result<()> {
         .arg(script)
         .status()?;
 
+    let host_output = project_root.join(output_filename.as_ref());
+    let report = build_vm_report(
+        status.code(),
+        &project_root.join("vm_debug.log"),
+        &host_output,
+    );
+    let report_path = project_root.join("build.report.json");
+    std::fs::write(&report_path, serde_json::to_string_pretty(&report)? + "\n")?;
+    println!("Wrote build report: {}", report_path.display());
+
     if !status.success() {
         anyhow::bail!("VM execution failed");
     }
+    if !report.success {
+        anyhow::bail!(
+            "VM exited 0 but the build did not complete (see {})",
+            project_root.join("vm_debug.log").display()
+        );
+    }
 
     println!("Success! Artifact available at {}", cli.output.display());
     Ok(())
 }
 
+/// Machine-readable verdict for a `--vm` build, written as `build.report.json`.
+#[derive(Serialize, JsonSchema)]
+struct BuildReport {
+    success: bool,
+    vm_exit_code: Option<i32>,
+    /// `ok`, `ok_noscripts`, `failed`, or `not_reached` (the VM failed before dnf ran).
+    dnf_outcome: &'static str,
+    noscripts_fallback: bool,
+    package_count: Option<usize>,
+    image_path: PathBuf,
+    image_size_bytes: Option<u64>,
+    vm_debug_log: PathBuf,
+}
+
+fn build_vm_report(vm_exit_code: Option<i32>, vm_log: &Path, image: &Path) -> BuildReport {
+    let log = std::fs::read(vm_log)
+        .map(|b| String::from_utf8_lossy(&b).into_owned())
+        .unwrap_or_default();
+    let build_complete = log.contains("Build complete inside VM.");
+    let noscripts_fallback = log.contains("Retrying with --setopt=tsflags=noscripts");
+    let dnf_outcome = if log.contains("dnf install failed") {
+        "failed"
+    } else if log.contains("Cleaning up DNF metadata...") {
+        if noscripts_fallback {
+            "ok_noscripts"
+        } else {
+            "ok"
+        }
+    } else {
+        "not_reached"
+    };
+    let package_count = dnf_install_count(&log);
+
+    BuildReport {
+        success: vm_exit_code == Some(0) && build_complete,
+        vm_exit_code,
+        dnf_outcome,
+        noscripts_fallback,
+        package_count,
+        image_path: image.to_path_buf(),
+        image_size_bytes: std::fs::metadata(image).ok().map(|m| m.len()),
+        vm_debug_log: vm_log.to_path_buf(),
+    }
+}
+
+/// Packages dnf's transaction summary says it installed: `Installing: N packages` (dnf5) or
+/// `Install  N Packages` (dnf4). The last summary wins, so a noscripts retry is counted.
+fn dnf_install_count(log: &str) -> Option<usize> {
+    log.lines().rev().find_map(|line| {
+        let rest = line
+            .trim()
+            .strip_prefix("Installing:")
+            .or_else(|| line.trim().strip_prefix("Install "))?;
+        let mut words = rest.split_whitespace();
+        let n = words.next()?.parse().ok()?;
+        words
+            .next()
+            .filter(|w| w.eq_ignore_ascii_case("packages") || w.eq_ignore_ascii_case("package"))
+            .map(|_| n)
+    })
+}
+
 fn bundle_fex(output_dir: &std::path::Path) -> Result<()> {
     use std::fs;
     use std::process::Command;
@@ -755,3 +851,64 @@ fn bundle_fex(output_dir: &std::path::Path) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_dnf4_transaction_summary() {
+        let log = "\
+Installing:
+ bash                x86_64    5.2.26-3.fc40     fedora    1.8 M
+Installing dependencies:
+ filesystem          x86_64    3.18-8.fc40       fedora    1.1 M
+
+Transaction Summary
+================================================================================
+Install  187 Packages
+
+  Installing       : filesystem-3.18-8.fc40.x86_64                        1/187
+";
+        assert_eq!(dnf_install_count(log), Some(187));
+        assert_eq!(dnf_install_count("Install  1 Package\n"), Some(1));
+    }
+
+    #[test]
+    fn counts_dnf5_transaction_summary() {
+        let log = "\
+Package                 Arch   Version            Repository      Size
+Installing:
+ bash                   x86_64 5.2.26-3.fc40      fedora       8.1 MiB
+
+Transaction Summary:
+ Installing:       187 packages
+
+[  1/189] Installing filesystem-0:3.18-8.fc40.x86_64 100% |   1.2 MiB/s
+";
+        assert_eq!(dnf_install_count(log), Some(187));
+    }
+
+    #[test]
+    fn bare_installing_header_is_not_a_count() {
+        assert_eq!(
+            dnf_install_count("Installing:\n bash x86_64 5.2.26\n"),
+            None
+        );
+        assert_eq!(dnf_install_count("Nothing to do.\n"), None);
+    }
+
+    #[test]
+    fn noscripts_retry_summary_wins() {
+        let log = "\
+Transaction Summary:
+ Installing:       190 packages
+Error: Transaction failed: scriptlet failed
+Retrying with --setopt=tsflags=noscripts...
+Transaction Summary:
+ Installing:       187 packages
+Complete!
+";
+        assert_eq!(dnf_install_count(log), Some(187));
+    }
+}
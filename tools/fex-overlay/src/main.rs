@@ -1,14 +1,20 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use regex::Regex;
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// List of packages to include in the overlay
     #[arg(required = true)]
     packages: Vec<String>,
@@ -38,9 +44,59 @@ struct Cli {
     /// Fedora packages may mark CET (IBT/SHSTK) via this note, which FEX can reject.
     #[arg(long, default_value_t = true)]
     strip_gnu_property: bool,
+
+    /// Compare the staged tree against a manifest from an earlier build and report the
+    /// size delta plus added/removed files.
+    #[arg(long)]
+    compare_manifest: Option<PathBuf>,
+
+    /// Extract into this existing directory instead of a fresh temp tree, writing only files
+    /// that are not already present, then image the whole directory.
+    #[arg(long, value_name = "DIR")]
+    into: Option<PathBuf>,
+
+    /// Retries for the RPM download after a failure, with exponential backoff (1s, 2s, 4s, ...).
+    #[arg(long, default_value_t = 3)]
+    download_retries: u32,
+
+    /// Resolve the packages against repo metadata and print the plan (would-download,
+    /// would-skip-abi, not-found) plus the manifest a build would write, without downloading.
+    /// Fails if any requested package is not found. would-skip-abi is advisory: it comes from
+    /// the repo's file lists, while a build checks the extracted payload itself.
+    #[arg(long)]
+    check_only: bool,
+}
+
+/// RPM name prefixes that make up the ABI boundary; deps overlays must not ship them.
+const ABI_BOUNDARY_NAME_RE: &str = r"^(glibc|glibc-common|glibc-minimal-langpack|glibc-langpack|gcc-libs|libgcc|libstdc\+\+|libgomp|libatomic|libasan|libubsan)-";
+
+/// Loader/libc/toolchain-runtime paths (relative to the root) that deps overlays must not ship.
+const ABI_BOUNDARY_PATHS: [&str; 8] = [
+    "lib64/ld-linux-x86-64.so.2",
+    "usr/lib64/ld-linux-x86-64.so.2",
+    "lib64/libc.so.6",
+    "usr/lib64/libc.so.6",
+    "lib64/libstdc++.so.6",
+    "usr/lib64/libstdc++.so.6",
+    "lib64/libgcc_s.so.1",
+    "usr/lib64/libgcc_s.so.1",
+];
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List every section (name, type, size) of an ELF64 file, e.g. one FEX still rejects
+    ElfSections {
+        /// ELF file to inspect.
+        #[arg(long)]
+        file: PathBuf,
+    },
+
+    /// Print the JSON Schema of the `--manifest` file, for validating it in CI
+    #[command(hide = true)]
+    EmitSchema,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct Manifest {
     fedora_version: String,
     repo_url: String,
@@ -49,12 +105,54 @@ struct Manifest {
     allow_abi_boundary: bool,
     strip_gnu_property: bool,
     downloaded_rpms: Vec<String>,
+    /// Failed `dnf download` attempts that were retried before the one that succeeded.
+    download_retries: u32,
     extracted_rpms: Vec<String>,
     skipped_rpms: Vec<SkippedRpm>,
     stripped_elf_count: usize,
+    #[serde(flatten)]
+    tree: TreeSizes,
+    comparison: Option<ManifestComparison>,
+    into: Option<IntoReport>,
 }
 
-#[derive(Serialize)]
+/// What `--into` extraction added on top of the existing directory.
+#[derive(Serialize, JsonSchema)]
+struct IntoReport {
+    dir: String,
+    preexisting_file_count: usize,
+    added_files: Vec<String>,
+}
+
+/// Byte totals of the staged tree (regular files only; symlinks are not counted).
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+#[serde(default)]
+struct TreeSizes {
+    total_bytes: u64,
+    /// Keyed by the first path component (`usr`, `etc`, ...); files at the root use `.`.
+    top_level_dir_bytes: BTreeMap<String, u64>,
+    /// Relative path -> size, so a later build can diff against this one.
+    file_bytes: BTreeMap<String, u64>,
+}
+
+/// The part of an earlier manifest that `--compare-manifest` reads. Manifests written before
+/// sizes were recorded compare as an empty tree.
+#[derive(Deserialize)]
+struct PreviousManifest {
+    #[serde(flatten)]
+    tree: TreeSizes,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ManifestComparison {
+    previous_manifest: String,
+    total_bytes_delta: i64,
+    top_level_dir_bytes_delta: BTreeMap<String, i64>,
+    added_files: Vec<String>,
+    removed_files: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
 struct SkippedRpm {
     rpm: String,
     reason: String,
@@ -63,6 +161,25 @@ struct SkippedRpm {
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(Commands::ElfSections { file }) = &cli.command {
+        print!("{}", elf_notes::format_elf_sections(file)?);
+        return Ok(());
+    }
+    if let Some(Commands::EmitSchema) = &cli.command {
+        let schema = serde_json::json!({ "manifest": schemars::schema_for!(Manifest) });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&schema).context("Serializing schema")?
+        );
+        return Ok(());
+    }
+
+    // Read the comparison baseline up front so a bad path fails before the download.
+    let previous_tree = match cli.compare_manifest.as_deref() {
+        Some(path) => Some((path, load_manifest_tree(path)?)),
+        None => None,
+    };
+
     // 1. Setup repo URL
     let repo_url = if cli.fedora_version == "rawhide" {
         "https://dl.fedoraproject.org/pub/fedora/linux/development/rawhide/Everything/x86_64/os/"
@@ -76,23 +193,48 @@ fn main() -> Result<()> {
 
     println!("Targeting Fedora: {} ({})", cli.fedora_version, repo_url);
 
+    if cli.check_only {
+        return check_only(&cli, &repo_url);
+    }
+
     // 2. Create temp dir
     let temp_dir = tempfile::Builder::new().prefix("fex-overlay-").tempdir()?;
     let work_dir = temp_dir.path();
-    let rootfs_dir = work_dir.join("rootfs");
-    std::fs::create_dir(&rootfs_dir)?;
+    let rootfs_dir = match &cli.into {
+        Some(dir) => {
+            if !dir.is_dir() {
+                anyhow::bail!("--into {} is not an existing directory", dir.display());
+            }
+            dir.clone()
+        }
+        None => {
+            let dir = work_dir.join("rootfs");
+            std::fs::create_dir(&dir)?;
+            dir
+        }
+    };
+    let preexisting_files = if cli.into.is_some() {
+        let files = list_relative_files(&rootfs_dir)?;
+        println!(
+            "Extracting into {} ({} files already present; existing files are kept)",
+            rootfs_dir.display(),
+            files.len()
+        );
+        files
+    } else {
+        BTreeSet::new()
+    };
 
     println!("Working in: {}", work_dir.display());
 
     // 3. Download RPMs (+ dependencies)
     let rpms_dir = work_dir.join("rpms");
-    let rpms = download_rpms_with_deps(&cli.packages, &repo_url, &rpms_dir)?;
+    let (rpms, download_retries) =
+        download_rpms_with_retries(&cli.packages, &repo_url, &rpms_dir, cli.download_retries)?;
 
     // 4. Extract RPMs into staging tree (deps overlays must not alter ABI boundary)
-    let deny_name_re = Regex::new(
-        r"^(glibc|glibc-common|glibc-minimal-langpack|glibc-langpack|gcc-libs|libgcc|libstdc\+\+|libgomp|libatomic|libasan|libubsan)-",
-    )
-    .context("Failed to compile denylist regex")?;
+    let deny_name_re =
+        Regex::new(ABI_BOUNDARY_NAME_RE).context("Failed to compile denylist regex")?;
 
     let mut extracted_rpms: Vec<String> = Vec::new();
     let mut skipped_rpms: Vec<SkippedRpm> = Vec::new();
@@ -129,8 +271,13 @@ fn main() -> Result<()> {
             }
         }
 
-        extract_rpm(&rpm_path, &rootfs_dir, cli.allow_abi_boundary)
-            .with_context(|| format!("Extracting {}", rpm_path.display()))?;
+        extract_rpm(
+            &rpm_path,
+            &rootfs_dir,
+            cli.allow_abi_boundary,
+            cli.into.is_some(),
+        )
+        .with_context(|| format!("Extracting {}", rpm_path.display()))?;
         extracted_rpms.push(rpm_path.display().to_string());
 
         // Some RPMs create read-only directories (e.g. 0555). Later RPMs may need
@@ -155,6 +302,36 @@ fn main() -> Result<()> {
     validate_staging_tree(&rootfs_dir, cli.allow_abi_boundary)
         .context("Staging tree failed invariants after sanitization")?;
 
+    let tree = measure_tree(&rootfs_dir).context("Measuring staged tree")?;
+    println!(
+        "Staged tree: {} bytes in {} files",
+        tree.total_bytes,
+        tree.file_bytes.len()
+    );
+    let into_report = cli.into.as_ref().map(|dir| {
+        let added_files: Vec<String> = tree
+            .file_bytes
+            .keys()
+            .filter(|f| !preexisting_files.contains(*f))
+            .cloned()
+            .collect();
+        println!(
+            "--into: {} files added, {} already present",
+            added_files.len(),
+            preexisting_files.len()
+        );
+        IntoReport {
+            dir: dir.display().to_string(),
+            preexisting_file_count: preexisting_files.len(),
+            added_files,
+        }
+    });
+    let comparison = previous_tree.map(|(path, prev)| {
+        let comparison = compare_trees(&tree, &prev, path);
+        print_comparison(&comparison);
+        comparison
+    });
+
     // 7. Pack EROFS
     println!("Packing EROFS image to: {}", cli.output.display());
     pack_erofs(&rootfs_dir, &cli.output)?;
@@ -169,9 +346,13 @@ fn main() -> Result<()> {
             allow_abi_boundary: cli.allow_abi_boundary,
             strip_gnu_property: cli.strip_gnu_property,
             downloaded_rpms,
+            download_retries,
             extracted_rpms,
             skipped_rpms,
             stripped_elf_count,
+            tree,
+            comparison,
+            into: into_report,
         };
         let json = serde_json::to_string_pretty(&manifest).context("Serializing manifest")?;
         std::fs::write(path, json)
@@ -188,22 +369,305 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn download_rpms_with_deps(
+fn measure_tree(root: &Path) -> Result<TreeSizes> {
+    let mut tree = TreeSizes::default();
+    walk_files(root, &mut |path| {
+        let len = std::fs::symlink_metadata(path)
+            .with_context(|| format!("symlink_metadata {}", path.display()))?
+            .len();
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        let top = if rel.components().count() > 1 {
+            rel.components()
+                .next()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .unwrap_or_else(|| ".".to_string())
+        } else {
+            ".".to_string()
+        };
+        tree.total_bytes += len;
+        *tree.top_level_dir_bytes.entry(top).or_default() += len;
+        tree.file_bytes
+            .insert(rel.to_string_lossy().into_owned(), len);
+        Ok(())
+    })?;
+    Ok(tree)
+}
+
+fn load_manifest_tree(path: &Path) -> Result<TreeSizes> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Reading manifest {}", path.display()))?;
+    let prev: PreviousManifest = serde_json::from_str(&text)
+        .with_context(|| format!("Parsing manifest {}", path.display()))?;
+    Ok(prev.tree)
+}
+
+fn list_relative_files(root: &Path) -> Result<BTreeSet<String>> {
+    let mut files = BTreeSet::new();
+    walk_files(root, &mut |path| {
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        files.insert(rel.to_string_lossy().into_owned());
+        Ok(())
+    })?;
+    Ok(files)
+}
+
+fn compare_trees(tree: &TreeSizes, prev: &TreeSizes, prev_path: &Path) -> ManifestComparison {
+    let mut dir_delta: BTreeMap<String, i64> = BTreeMap::new();
+    for (dir, bytes) in &tree.top_level_dir_bytes {
+        *dir_delta.entry(dir.clone()).or_default() += *bytes as i64;
+    }
+    for (dir, bytes) in &prev.top_level_dir_bytes {
+        *dir_delta.entry(dir.clone()).or_default() -= *bytes as i64;
+    }
+    dir_delta.retain(|_, d| *d != 0);
+
+    ManifestComparison {
+        previous_manifest: prev_path.display().to_string(),
+        total_bytes_delta: tree.total_bytes as i64 - prev.total_bytes as i64,
+        top_level_dir_bytes_delta: dir_delta,
+        added_files: tree
+            .file_bytes
+            .keys()
+            .filter(|f| !prev.file_bytes.contains_key(*f))
+            .cloned()
+            .collect(),
+        removed_files: prev
+            .file_bytes
+            .keys()
+            .filter(|f| !tree.file_bytes.contains_key(*f))
+            .cloned()
+            .collect(),
+    }
+}
+
+fn print_comparison(c: &ManifestComparison) {
+    const MAX_LISTED: usize = 50;
+    println!("Compared with {}:", c.previous_manifest);
+    println!("  total: {:+} bytes", c.total_bytes_delta);
+    for (dir, delta) in &c.top_level_dir_bytes_delta {
+        println!("  {dir}/: {delta:+} bytes");
+    }
+    for (label, files) in [("added", &c.added_files), ("removed", &c.removed_files)] {
+        println!("  {label} files: {}", files.len());
+        for f in files.iter().take(MAX_LISTED) {
+            println!("    {f}");
+        }
+        if files.len() > MAX_LISTED {
+            println!("    ... ({} more; see manifest)", files.len() - MAX_LISTED);
+        }
+    }
+}
+
+/// Runs `download_rpms_with_deps` up to `retries + 1` times, sleeping 1s, 2s, 4s, ... between
+/// attempts. Returns the RPMs and the number of retries it took.
+///
+/// dnf downloads the whole dependency closure in one invocation, so the retry unit is that
+/// invocation rather than a single RPM.
+fn download_rpms_with_retries(
     packages: &[String],
     repo_url: &str,
     destdir: &Path,
-) -> Result<Vec<PathBuf>> {
-    std::fs::create_dir_all(destdir).context("Failed to create RPM download directory")?;
+    retries: u32,
+) -> Result<(Vec<PathBuf>, u32)> {
+    let mut attempt = 0;
+    loop {
+        match download_rpms_with_deps(packages, repo_url, destdir) {
+            Ok(rpms) => return Ok((rpms, attempt)),
+            Err(e) if attempt < retries => {
+                let backoff = std::time::Duration::from_secs(1 << attempt.min(6));
+                attempt += 1;
+                println!(
+                    "Download failed (attempt {attempt}/{}): {e:#}; retrying in {}s",
+                    retries + 1,
+                    backoff.as_secs()
+                );
+                std::thread::sleep(backoff);
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("RPM download failed after {} attempts", attempt + 1))
+            }
+        }
+    }
+}
 
-    // DNF5 supports dependency-resolving downloads.
-    // We use --alldeps so the overlay doesn't accidentally rely on host-installed deps.
+/// `dnf` restricted to the target Fedora x86_64 repo.
+fn dnf_command(repo_url: &str) -> Command {
     let mut cmd = Command::new("dnf");
     cmd.arg(format!("--repofrompath=fedora-x86_64,{}", repo_url))
         .arg("--forcearch=x86_64")
         .arg("--assumeyes")
         .arg("--disablerepo=*")
-        .arg("--enablerepo=fedora-x86_64")
-        .arg("download")
+        .arg("--enablerepo=fedora-x86_64");
+    cmd
+}
+
+fn dnf_stdout(mut cmd: Command, what: &str) -> Result<String> {
+    let output = cmd
+        .output()
+        .with_context(|| format!("Failed to run dnf {what}"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "dnf {what} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `--check-only`: classify the dependency closure from repo metadata alone.
+///
+/// Uses the same name denylist as a build; the payload-path check is approximated by asking
+/// the repo which packages own `ABI_BOUNDARY_PATHS`, since the payload itself is not fetched.
+fn check_only(cli: &Cli, repo_url: &str) -> Result<()> {
+    let mut found = Vec::new();
+    let mut not_found = Vec::new();
+    for pkg in &cli.packages {
+        let mut cmd = dnf_command(repo_url);
+        cmd.args(["repoquery", "--arch=x86_64,noarch", "--qf", "%{name}\n"])
+            .arg(pkg);
+        if dnf_stdout(cmd, "repoquery")?.trim().is_empty() {
+            not_found.push(pkg.clone());
+        } else {
+            found.push(pkg.clone());
+        }
+    }
+
+    // Same resolution as the real download, but print URLs instead of fetching.
+    let mut would_download = Vec::new();
+    if !found.is_empty() {
+        let mut cmd = dnf_command(repo_url);
+        cmd.args([
+            "download",
+            "--arch=x86_64",
+            "--arch=noarch",
+            "--resolve",
+            "--alldeps",
+            "--url",
+        ])
+        .args(&found);
+        for line in dnf_stdout(cmd, "download --url")?.lines() {
+            let line = line.trim();
+            if line.ends_with(".rpm") {
+                if let Some(name) = line.rsplit('/').next() {
+                    would_download.push(name.to_string());
+                }
+            }
+        }
+    }
+    would_download.sort();
+    would_download.dedup();
+
+    let mut abi_owners = std::collections::BTreeSet::new();
+    if !cli.allow_abi_boundary {
+        // File-path queries need filelists metadata, which DNF5 only loads when asked to.
+        let mut cmd = dnf_command(repo_url);
+        cmd.arg("--setopt=optional_metadata_types=filelists")
+            .args(["repoquery", "--arch=x86_64,noarch", "--qf", "%{name}\n"])
+            .args(ABI_BOUNDARY_PATHS.iter().map(|p| format!("/{p}")));
+        abi_owners.extend(
+            dnf_stdout(cmd, "repoquery (ABI-boundary owners)")?
+                .lines()
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty()),
+        );
+        // A repo (or dnf) without filelists still answers the path query with nothing; that
+        // means "unknown" here, not "no owners".
+        if abi_owners.is_empty() && !would_download.is_empty() {
+            eprintln!(
+                "WARNING: no owners resolved for the ABI-boundary paths; would-skip-abi only \
+                 reflects the package-name denylist"
+            );
+        }
+    }
+
+    let deny_name_re =
+        Regex::new(ABI_BOUNDARY_NAME_RE).context("Failed to compile denylist regex")?;
+    let mut would_extract = Vec::new();
+    let mut would_skip_abi = Vec::new();
+    for rpm in &would_download {
+        let owner = abi_owners.iter().find(|name| rpm_file_has_name(rpm, name));
+        let reason = if cli.allow_abi_boundary {
+            None
+        } else if deny_name_re.is_match(rpm) {
+            Some("denylisted package family (ABI boundary)".to_string())
+        } else {
+            owner.map(|name| format!("{name} owns an ABI-boundary path"))
+        };
+        match reason {
+            Some(reason) => would_skip_abi.push(SkippedRpm {
+                rpm: rpm.clone(),
+                reason,
+            }),
+            None => would_extract.push(rpm.clone()),
+        }
+    }
+
+    println!("Plan:");
+    println!("  would-download: {}", would_download.len());
+    for rpm in &would_download {
+        println!("    {rpm}");
+    }
+    println!("  would-extract: {}", would_extract.len());
+    for rpm in &would_extract {
+        println!("    {rpm}");
+    }
+    println!("  would-skip-abi: {}", would_skip_abi.len());
+    for skip in &would_skip_abi {
+        println!("    {} ({})", skip.rpm, skip.reason);
+    }
+    println!("  not-found: {}", not_found.len());
+    for pkg in &not_found {
+        println!("    {pkg}");
+    }
+
+    let manifest = Manifest {
+        fedora_version: cli.fedora_version.clone(),
+        repo_url: repo_url.to_string(),
+        packages: cli.packages.clone(),
+        output: cli.output.display().to_string(),
+        allow_abi_boundary: cli.allow_abi_boundary,
+        strip_gnu_property: cli.strip_gnu_property,
+        downloaded_rpms: would_download,
+        download_retries: 0,
+        extracted_rpms: would_extract,
+        skipped_rpms: would_skip_abi,
+        stripped_elf_count: 0,
+        tree: TreeSizes::default(),
+        comparison: None,
+        into: None,
+    };
+    println!("Manifest (would write):");
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&manifest).context("Serializing manifest")?
+    );
+
+    if !not_found.is_empty() {
+        anyhow::bail!("packages not found: {}", not_found.join(", "));
+    }
+    Ok(())
+}
+
+/// Whether `rpm` (a `name-version-release.arch.rpm` file name) is a build of package `name`.
+fn rpm_file_has_name(rpm: &str, name: &str) -> bool {
+    rpm.strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('-'))
+        // What remains must be exactly `version-release.arch.rpm`.
+        .is_some_and(|rest| rest.rsplitn(3, '-').count() == 2)
+}
+
+fn download_rpms_with_deps(
+    packages: &[String],
+    repo_url: &str,
+    destdir: &Path,
+) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(destdir).context("Failed to create RPM download directory")?;
+
+    // DNF5 supports dependency-resolving downloads.
+    // We use --alldeps so the overlay doesn't accidentally rely on host-installed deps.
+    let mut cmd = dnf_command(repo_url);
+    cmd.arg("download")
         .arg("--arch=x86_64")
         .arg("--arch=noarch")
         .arg("--resolve")
@@ -245,7 +709,12 @@ fn download_rpms_with_deps(
     Ok(rpms)
 }
 
-fn extract_rpm(rpm_path: &Path, dest_dir: &Path, allow_abi_boundary: bool) -> Result<()> {
+fn extract_rpm(
+    rpm_path: &Path,
+    dest_dir: &Path,
+    allow_abi_boundary: bool,
+    keep_existing: bool,
+) -> Result<()> {
     // rpm2cpio <rpm> | bsdtar -xf - -C <dest>
     // We use bsdtar so we can ignore archive permissions; cpio tends to apply
     // restrictive directory modes (e.g. 0555) early, which can break extraction.
@@ -256,18 +725,54 @@ fn extract_rpm(rpm_path: &Path, dest_dir: &Path, allow_abi_boundary: bool) -> Re
         .spawn()
         .context("Failed to spawn rpm2cpio")?;
 
+    let bsdtar = bsdtar_extract_command(dest_dir, allow_abi_boundary, keep_existing)
+        .stdin(
+            rpm2cpio
+                .stdout
+                .take()
+                .context("rpm2cpio stdout was not piped")?,
+        )
+        .output()
+        .context("Failed to run bsdtar")?;
+
+    let rpm2cpio_status = rpm2cpio.wait().context("Failed to wait for rpm2cpio")?;
+
+    if !rpm2cpio_status.success() {
+        anyhow::bail!("rpm2cpio failed with status: {rpm2cpio_status}");
+    }
+
+    if !bsdtar.status.success() {
+        anyhow::bail!("bsdtar failed: {}", String::from_utf8_lossy(&bsdtar.stderr));
+    }
+
+    Ok(())
+}
+
+/// `bsdtar -xf - -C <dest_dir>` with the flags `extract_rpm` needs; reads the archive from stdin.
+fn bsdtar_extract_command(
+    dest_dir: &Path,
+    allow_abi_boundary: bool,
+    keep_existing: bool,
+) -> Command {
     let mut bsdtar_cmd = Command::new("bsdtar");
     bsdtar_cmd
         .arg("-xf")
         .arg("-")
         .arg("-C")
         .arg(dest_dir)
-        // Removes intervening directory symlinks instead of erroring.
-        // This prevents libarchive's secure-symlink guard from aborting extraction.
-        .arg("--unlink")
         .arg("--no-same-owner")
         .arg("--no-same-permissions");
 
+    if keep_existing {
+        // `--into`: never overwrite what an earlier extraction put there. `--unlink` would
+        // win over `--keep-old-files` (bsdtar replaces the file), so it must not be passed.
+        bsdtar_cmd.arg("--keep-old-files");
+    } else {
+        // Removes intervening directory symlinks instead of erroring.
+        // This prevents libarchive's secure-symlink guard from aborting extraction.
+        bsdtar_cmd.arg("--unlink");
+    }
+
     // Deps overlays should be library-focused and must not override base executables.
     // (e.g. AppRun uses #!/bin/bash; overriding bash can prevent the AppImage from starting.)
     if !allow_abi_boundary {
@@ -281,28 +786,7 @@ fn extract_rpm(rpm_path: &Path, dest_dir: &Path, allow_abi_boundary: bool) -> Re
             .arg("--exclude")
             .arg("./usr/sbin/*");
     }
-
-    let bsdtar = bsdtar_cmd
-        .stdin(
-            rpm2cpio
-                .stdout
-                .take()
-                .context("rpm2cpio stdout was not piped")?,
-        )
-        .output()
-        .context("Failed to run bsdtar")?;
-
-    let rpm2cpio_status = rpm2cpio.wait().context("Failed to wait for rpm2cpio")?;
-
-    if !rpm2cpio_status.success() {
-        anyhow::bail!("rpm2cpio failed with status: {rpm2cpio_status}");
-    }
-
-    if !bsdtar.status.success() {
-        anyhow::bail!("bsdtar failed: {}", String::from_utf8_lossy(&bsdtar.stderr));
-    }
-
-    Ok(())
+    bsdtar_cmd
 }
 
 fn rpm_forbidden_reason(rpm_path: &Path) -> Result<Option<String>> {
@@ -310,17 +794,6 @@ fn rpm_forbidden_reason(rpm_path: &Path) -> Result<Option<String>> {
     // not put it in a deps overlay.
     //
     // This is intentionally simple and explainable; it can be expanded later.
-    let forbidden_needles = [
-        "./lib64/ld-linux-x86-64.so.2",
-        "./usr/lib64/ld-linux-x86-64.so.2",
-        "./lib64/libc.so.6",
-        "./usr/lib64/libc.so.6",
-        "./lib64/libstdc++.so.6",
-        "./usr/lib64/libstdc++.so.6",
-        "./lib64/libgcc_s.so.1",
-        "./usr/lib64/libgcc_s.so.1",
-    ];
-
     let mut rpm2cpio = Command::new("rpm2cpio")
         .arg(rpm_path)
         .stdout(Stdio::piped())
@@ -354,13 +827,11 @@ fn rpm_forbidden_reason(rpm_path: &Path) -> Result<Option<String>> {
 
     let stdout = String::from_utf8_lossy(&list.stdout);
     for line in stdout.lines() {
-        for needle in forbidden_needles {
-            if line == needle {
-                return Ok(Some(format!(
-                    "payload contains ABI-boundary path: {}",
-                    needle.trim_start_matches("./")
-                )));
-            }
+        let Some(rel) = line.strip_prefix("./") else {
+            continue;
+        };
+        if ABI_BOUNDARY_PATHS.contains(&rel) {
+            return Ok(Some(format!("payload contains ABI-boundary path: {rel}")));
         }
     }
 
@@ -422,20 +893,9 @@ fn ensure_dirs_writable(root: &Path) -> Result<()> {
 }
 
 fn validate_staging_tree(root: &Path, allow_abi_boundary: bool) -> Result<()> {
-    let forbidden_paths = [
-        "lib64/ld-linux-x86-64.so.2",
-        "usr/lib64/ld-linux-x86-64.so.2",
-        "lib64/libc.so.6",
-        "usr/lib64/libc.so.6",
-        "lib64/libstdc++.so.6",
-        "usr/lib64/libstdc++.so.6",
-        "lib64/libgcc_s.so.1",
-        "usr/lib64/libgcc_s.so.1",
-    ];
-
     if !allow_abi_boundary {
         let mut found = Vec::new();
-        for rel in forbidden_paths {
+        for rel in ABI_BOUNDARY_PATHS {
             let p = root.join(rel);
             if p.exists() {
                 found.push(rel.to_string());
@@ -678,3 +1138,40 @@ fn elf_has_gnu_property_note(path: &Path) -> Result<bool> {
 
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn into_keeps_files_already_in_the_overlay() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src/usr/lib64");
+        let dest = tmp.path().join("dest");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::create_dir_all(dest.join("usr/lib64")).unwrap();
+        std::fs::write(src.join("libfoo.so.1"), "from rpm").unwrap();
+        std::fs::write(src.join("libbar.so.1"), "added").unwrap();
+        std::fs::write(dest.join("usr/lib64/libfoo.so.1"), "already there").unwrap();
+
+        let archive = tmp.path().join("payload.tar");
+        let status = Command::new("bsdtar")
+            .arg("-cf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(tmp.path().join("src"))
+            .arg("./usr")
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let output = bsdtar_extract_command(&dest, false, true)
+            .stdin(std::fs::File::open(&archive).unwrap())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        let read = |name: &str| std::fs::read_to_string(dest.join("usr/lib64").join(name)).unwrap();
+        assert_eq!(read("libfoo.so.1"), "already there");
+        assert_eq!(read("libbar.so.1"), "added");
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use schemars::JsonSchema;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
@@ -9,6 +10,9 @@ use std::process::Command;
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Write snapshot JSON to this path. If omitted, writes to stdout.
     #[arg(long)]
     output: Option<PathBuf>,
@@ -23,7 +27,14 @@ struct Cli {
     root: bool,
 }
 
-#[derive(Serialize)]
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Print the JSON Schema of the snapshot, for validating it in CI
+    #[command(hide = true)]
+    EmitSchema,
+}
+
+#[derive(Serialize, JsonSchema)]
 struct Snapshot {
     meta: Meta,
     os: OsInfo,
@@ -37,7 +48,7 @@ struct Snapshot {
     commands: Vec<CommandInfo>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct Meta {
     timestamp_utc: String,
     hostname: Option<String>,
@@ -45,12 +56,12 @@ struct Meta {
     kernel: Option<String>,
 }
 
-#[derive(Serialize, Default)]
+#[derive(Serialize, JsonSchema, Default)]
 struct OsInfo {
     os_release: BTreeMap<String, String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct RpmOstreeInfo {
     status: CommandInfo,
     db_diff: CommandInfo,
@@ -58,7 +69,7 @@ struct RpmOstreeInfo {
     overrides: Option<CommandInfo>,
 }
 
-#[derive(Serialize, Default)]
+#[derive(Serialize, JsonSchema, Default)]
 struct SystemdInfo {
     enabled_unit_files: Option<CommandInfo>,
     active_units: Option<CommandInfo>,
@@ -66,7 +77,7 @@ struct SystemdInfo {
     user_active_units: Option<CommandInfo>,
 }
 
-#[derive(Serialize, Default)]
+#[derive(Serialize, JsonSchema, Default)]
 struct NetworkInfo {
     iwd_enabled: Option<bool>,
     iwd_active: Option<bool>,
@@ -76,7 +87,7 @@ struct NetworkInfo {
     nm_wifi_backend_conf: Option<CommandInfo>,
 }
 
-#[derive(Serialize, Default)]
+#[derive(Serialize, JsonSchema, Default)]
 struct KeyboardInfo {
     keyd_installed: Option<bool>,
     keyd_enabled: Option<bool>,
@@ -84,30 +95,30 @@ struct KeyboardInfo {
     gnome_keybindings: Option<GnomeKeybindings>,
 }
 
-#[derive(Serialize, Default)]
+#[derive(Serialize, JsonSchema, Default)]
 struct GnomeKeybindings {
     wm_keybindings: Option<CommandInfo>,
     media_keys: Option<CommandInfo>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct UjustInfo {
     list: CommandInfo,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct ToolboxInfo {
     list: CommandInfo,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct FileInfo {
     path: String,
     exists: bool,
     sha256: Option<String>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, JsonSchema, Clone)]
 struct CommandInfo {
     argv: Vec<String>,
     status: Option<i32>,
@@ -119,6 +130,12 @@ struct CommandInfo {
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(Commands::EmitSchema) = cli.command {
+        let schema = serde_json::json!({ "snapshot": schemars::schema_for!(Snapshot) });
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
     if cli.root && !nix::unistd::Uid::effective().is_root() {
         anyhow::bail!("--root requires running as root (try sudo)");
     }
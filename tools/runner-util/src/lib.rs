@@ -0,0 +1,189 @@
+//! Non-ELF helpers both runners share when reporting on a guest run: Linux signal names,
+//! `mountinfo` and binfmt_misc parsing, the input file hashing they record for
+//! reproducibility, and which env keys they redact from artifacts.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// Lowercase hex SHA-256 of the file at `path`, read in fixed-size chunks so large
+/// AppImages and rootfs images don't have to fit in memory.
+pub fn hash_file(path: &Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 16];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .with_context(|| format!("read {}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Linux signal name for `sig` (e.g. `SIGSEGV` for 11).
+pub fn signal_name(sig: u32) -> String {
+    match sig {
+        1 => "SIGHUP".into(),
+        2 => "SIGINT".into(),
+        3 => "SIGQUIT".into(),
+        4 => "SIGILL".into(),
+        5 => "SIGTRAP".into(),
+        6 => "SIGABRT".into(),
+        7 => "SIGBUS".into(),
+        8 => "SIGFPE".into(),
+        9 => "SIGKILL".into(),
+        10 => "SIGUSR1".into(),
+        11 => "SIGSEGV".into(),
+        12 => "SIGUSR2".into(),
+        13 => "SIGPIPE".into(),
+        14 => "SIGALRM".into(),
+        15 => "SIGTERM".into(),
+        16 => "SIGSTKFLT".into(),
+        17 => "SIGCHLD".into(),
+        18 => "SIGCONT".into(),
+        19 => "SIGSTOP".into(),
+        20 => "SIGTSTP".into(),
+        21 => "SIGTTIN".into(),
+        22 => "SIGTTOU".into(),
+        23 => "SIGURG".into(),
+        24 => "SIGXCPU".into(),
+        25 => "SIGXFSZ".into(),
+        26 => "SIGVTALRM".into(),
+        27 => "SIGPROF".into(),
+        28 => "SIGWINCH".into(),
+        29 => "SIGIO".into(),
+        30 => "SIGPWR".into(),
+        31 => "SIGSYS".into(),
+        // Linux SIGRTMIN is typically 34; 32/33 are reserved by glibc/NPTL.
+        32 => "SIGRTMIN-2".into(),
+        33 => "SIGRTMIN-1".into(),
+        34..=64 => format!("SIGRTMIN+{}", sig - 34),
+        _ => format!("SIG{sig}"),
+    }
+}
+
+/// One line of `/proc/<pid>/mountinfo`, with octal escapes (`\040` etc.) decoded.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MountEntry {
+    pub mount_point: String,
+    pub fs_type: String,
+    pub source: String,
+    pub options: String,
+}
+
+/// Parse a `mountinfo` table; malformed lines are skipped.
+pub fn parse_mountinfo(text: &str) -> Vec<MountEntry> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // Optional fields end at the "-" separator; fs type and source follow it.
+            let sep = fields.iter().position(|f| *f == "-")?;
+            Some(MountEntry {
+                mount_point: unescape_mountinfo(fields.get(4)?),
+                fs_type: unescape_mountinfo(fields.get(sep + 1)?),
+                source: unescape_mountinfo(fields.get(sep + 2)?),
+                options: fields.get(5)?.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn unescape_mountinfo(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes
+            .get(i + 1..i + 4)
+            .filter(|_| bytes[i] == b'\\')
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u8::from_str_radix(d, 8).ok());
+        if let Some(b) = octal {
+            out.push(b);
+            i += 4;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The mount backing `path`: the deepest mount point containing it, preferring the later
+/// entry when one mount shadows another at the same point.
+pub fn mount_for_path<'a>(mounts: &'a [MountEntry], path: &Path) -> Option<&'a MountEntry> {
+    mounts
+        .iter()
+        .filter(|m| path.starts_with(&m.mount_point))
+        .max_by_key(|m| Path::new(&m.mount_point).components().count())
+}
+
+/// Where FEX registers itself with binfmt_misc for x86_64 ELFs.
+pub const FEX_BINFMT_PATH: &str = "/proc/sys/fs/binfmt_misc/FEX-x86_64";
+
+/// A `/proc/sys/fs/binfmt_misc/<name>` entry: whether it is enabled, the interpreter the
+/// kernel runs matching binaries with, and its flags (e.g. `POCF`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BinfmtEntry {
+    pub enabled: bool,
+    pub interpreter: Option<String>,
+    pub flags: Option<String>,
+}
+
+/// Parse the contents of a binfmt_misc entry file.
+pub fn parse_binfmt_entry(text: &str) -> BinfmtEntry {
+    let mut entry = BinfmtEntry::default();
+    for line in text.lines().map(str::trim) {
+        if line == "enabled" {
+            entry.enabled = true;
+        } else if let Some(v) = line.strip_prefix("interpreter ") {
+            entry.interpreter = Some(v.trim().to_string());
+        } else if let Some(v) = line.strip_prefix("flags:") {
+            entry.flags = Some(v.trim().to_string());
+        }
+    }
+    entry
+}
+
+/// Env key substrings (case-insensitive) whose values are redacted from run artifacts.
+pub const SENSITIVE_ENV_KEY_PATTERNS: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "KEY"];
+
+/// Whether `key`'s value should be redacted: listed in `redact` (`--redact`) or matching one
+/// of `SENSITIVE_ENV_KEY_PATTERNS`.
+pub fn is_sensitive_env_key(key: &str, redact: &[String]) -> bool {
+    let upper = key.to_ascii_uppercase();
+    redact.iter().any(|k| k == key) || SENSITIVE_ENV_KEY_PATTERNS.iter().any(|p| upper.contains(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_backing_mount_in_mountinfo() {
+        let text = "\
+22 1 0:21 / / rw,relatime - virtiofs rootfs rw
+30 22 0:25 / /tmp rw,nosuid shared:5 - tmpfs tmpfs rw
+31 22 0:26 / /run/muvm-host rw - virtiofs host rw
+32 31 0:27 / /run/muvm-host/my\\040runs rw - overlay overlay rw,lowerdir=/x
+";
+        let mounts = parse_mountinfo(text);
+        assert_eq!(mounts.len(), 4);
+        assert_eq!(mounts[3].mount_point, "/run/muvm-host/my runs");
+
+        let fs = |p: &str| mount_for_path(&mounts, Path::new(p)).map(|m| m.fs_type.as_str());
+        assert_eq!(fs("/tmp/edge-muvm-profile-1"), Some("tmpfs"));
+        assert_eq!(fs("/tmpfoo"), Some("virtiofs"));
+        assert_eq!(fs("/run/muvm-host/my runs/headless-1"), Some("overlay"));
+        assert_eq!(fs("/run/muvm-host/home"), Some("virtiofs"));
+    }
+}
@@ -1,8 +1,9 @@
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::Serialize;
 use x11rb::protocol::xproto::{ConnectionExt as _, QueryExtensionReply};
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct ExtInfo {
     name: String,
     present: bool,
@@ -11,7 +12,7 @@ struct ExtInfo {
     first_error: u8,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 struct Report {
     display: Option<String>,
     extensions: Vec<ExtInfo>,
@@ -28,6 +29,13 @@ fn to_info(name: &str, reply: &QueryExtensionReply) -> ExtInfo {
 }
 
 fn main() -> Result<()> {
+    // Hidden: print the JSON Schema of the report, for validating it in CI.
+    if std::env::args().nth(1).as_deref() == Some("emit-schema") {
+        let schema = serde_json::json!({ "report": schemars::schema_for!(Report) });
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
     let display = std::env::var("DISPLAY").ok();
 
     let (conn, _screen_num) = x11rb::connect(None).context("connect to X11")?;